@@ -0,0 +1,106 @@
+//! Parser throughput on captured backend outputs.
+//!
+//! The fixtures under `benches/fixtures/` are real command captures; each
+//! is tiled up to the scale of a full system (60k dpkg lines, a complete
+//! `pacman -Qi` dump) so a regression on large inputs cannot hide behind
+//! a fast small case. Run with `cargo bench`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+use pkgtool::actions::fuzzy_score;
+use pkgtool::package_managers::common;
+
+/// Tile a captured fixture until it is at least `lines` lines long.
+/// Parsers treat every line independently, so repetition does not change
+/// the code paths exercised, only the volume.
+fn amplify(fixture: &str, lines: usize) -> String {
+    let per_copy = fixture.lines().count().max(1);
+    fixture.repeat(lines.div_ceil(per_copy))
+}
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("{}/benches/fixtures/{name}", env!("CARGO_MANIFEST_DIR")))
+        .expect("bench fixture present")
+}
+
+/// One fixture-driven case: bench name, fixture file, target line count,
+/// and the parser under test (returning a count so nothing is optimized out).
+type ParserCase = (&'static str, &'static str, usize, fn(&str) -> usize);
+
+fn bench_parsers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parsers");
+    let cases: [ParserCase; 7] = [
+        ("dpkg_query_60k", "dpkg-query.txt", 60_000, |s| {
+            common::parse_dpkg_query(s).len()
+        }),
+        ("apt_upgradable_5k", "apt-upgradable.txt", 5_000, |s| {
+            common::parse_apt_upgradable(s).len()
+        }),
+        ("rpm_query_60k", "rpm-qa.txt", 60_000, |s| {
+            common::parse_rpm_query(s).len()
+        }),
+        ("pacman_qi_30k", "pacman-qi.txt", 30_000, |s| {
+            common::parse_pacman_query(s).len()
+        }),
+        ("pacman_search_10k", "pacman-search.txt", 10_000, |s| {
+            common::parse_pacman_search(s).len()
+        }),
+        ("checkupdates_5k", "checkupdates.txt", 5_000, |s| {
+            common::parse_checkupdates(s).len()
+        }),
+        ("brew_outdated_5k", "brew-outdated.txt", 5_000, |s| {
+            common::parse_brew_outdated(s).len()
+        }),
+    ];
+    for (name, file, lines, parse) in cases {
+        let input = amplify(&fixture(file), lines);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(name, |b| b.iter(|| parse(black_box(&input))));
+    }
+    group.finish();
+}
+
+fn bench_scoring(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scoring");
+
+    // Realistic version pairs: epochs, tildes, long Debian revisions.
+    let versions: Vec<(String, String)> = fixture("apt-upgradable.txt")
+        .lines()
+        .filter_map(|line| {
+            let new = line.split_whitespace().nth(1)?.to_string();
+            let current = line.rsplit_once("upgradable from: ")?.1.trim_end_matches(']');
+            Some((current.to_string(), new))
+        })
+        .collect();
+    assert!(!versions.is_empty());
+    group.bench_function("compare_versions", |b| {
+        use pkgtool::utils::version::{compare, VersionScheme};
+        b.iter(|| {
+            for (current, new) in &versions {
+                black_box(compare(VersionScheme::Deb, black_box(current), black_box(new)));
+            }
+        })
+    });
+
+    // Fuzzy-filtering a 60k-package list on a short query, as the command
+    // palette and package filter do on every keystroke.
+    let names: Vec<String> = amplify(&fixture("dpkg-query.txt"), 60_000)
+        .lines()
+        .filter_map(|line| Some(line.split('\t').next()?.to_string()))
+        .collect();
+    group.bench_function("fuzzy_score_60k", |b| {
+        b.iter(|| {
+            names
+                .iter()
+                .filter(|name| fuzzy_score(black_box("rip"), name).is_some())
+                .count()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parsers, bench_scoring);
+criterion_main!(benches);
@@ -0,0 +1,494 @@
+use crate::app::TabId;
+
+/// Everything the app can do, addressable by id.
+///
+/// Keybindings and the command palette both dispatch through this enum so
+/// every feature stays reachable even before it gets a dedicated key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    NextTab,
+    PreviousTab,
+    JumpTab(TabId),
+    Refresh,
+    UpdateSystem,
+    UpdateSecurity,
+    CleanCache,
+    ToggleOffline,
+    ToggleDryRun,
+    TestProxy,
+    PruneSnapshots,
+    ScanVulnerabilities,
+    ToggleIgnoredFindings,
+    ExportSecurityReport,
+    ShowThirdPartySources,
+    ShowSandboxPermissions,
+    CheckDependencyHealth,
+    BrowseOptionalDeps,
+    ShowOverlaps,
+    CheckRestarts,
+    ShowRestartPicker,
+    ShowHelp,
+    /// Open the input bar pre-filled with a command prefix.
+    Prompt(&'static str),
+}
+
+/// One entry of the action registry.
+pub struct ActionEntry {
+    #[allow(dead_code)] // addressed by id once keybinding overrides exist
+    pub id: &'static str,
+    pub title: &'static str,
+    /// Human-readable bound key, when one exists.
+    pub key: Option<&'static str>,
+    /// Input-bar syntax with a one-line example, for commands.
+    pub synopsis: Option<&'static str>,
+    /// One sentence for the help reference.
+    pub description: &'static str,
+    pub action: Action,
+}
+
+/// The full action registry, in palette display order.
+pub fn registry() -> Vec<ActionEntry> {
+    vec![
+        ActionEntry {
+            id: "tab.overview",
+            title: "Go to Overview tab",
+            key: Some("1"),
+            synopsis: None,
+            description: "Switch to the overview of all managers and counts.",
+            action: Action::JumpTab(TabId::Overview),
+        },
+        ActionEntry {
+            id: "tab.packages",
+            title: "Go to Packages tab",
+            key: Some("2"),
+            synopsis: None,
+            description: "Switch to the installed package list.",
+            action: Action::JumpTab(TabId::Packages),
+        },
+        ActionEntry {
+            id: "tab.updates",
+            title: "Go to Updates tab",
+            key: Some("3"),
+            synopsis: None,
+            description: "Switch to the pending updates list.",
+            action: Action::JumpTab(TabId::Updates),
+        },
+        ActionEntry {
+            id: "tab.search",
+            title: "Go to Search tab",
+            key: Some("4"),
+            synopsis: None,
+            description: "Switch to the search results list.",
+            action: Action::JumpTab(TabId::Search),
+        },
+        ActionEntry {
+            id: "tab.log",
+            title: "Go to Log tab",
+            key: Some("5"),
+            synopsis: None,
+            description: "Switch to the log of finished operations.",
+            action: Action::JumpTab(TabId::Log),
+        },
+        ActionEntry {
+            id: "tab.snapshots",
+            title: "Go to Snapshots tab",
+            key: Some("6"),
+            synopsis: Some("snapshots  (list, create, delete and restore snapshots)"),
+            description: "Switch to the system snapshots of the active backend.",
+            action: Action::JumpTab(TabId::Snapshots),
+        },
+        ActionEntry {
+            id: "tab.security",
+            title: "Go to Security tab",
+            key: Some("7"),
+            synopsis: None,
+            description: "Switch to the OSV vulnerability findings.",
+            action: Action::JumpTab(TabId::Security),
+        },
+        ActionEntry {
+            id: "tab.next",
+            title: "Next tab",
+            key: Some("Tab"),
+            synopsis: None,
+            description: "Cycle to the next tab.",
+            action: Action::NextTab,
+        },
+        ActionEntry {
+            id: "tab.previous",
+            title: "Previous tab",
+            key: Some("Shift+Tab"),
+            synopsis: None,
+            description: "Cycle to the previous tab.",
+            action: Action::PreviousTab,
+        },
+        ActionEntry {
+            id: "packages.refresh",
+            title: "Refresh package lists",
+            key: Some("r"),
+            synopsis: None,
+            description: "Reload installed packages and pending updates.",
+            action: Action::Refresh,
+        },
+        ActionEntry {
+            id: "system.update",
+            title: "Update system",
+            key: Some("u"),
+            synopsis: None,
+            description: "Refresh metadata and upgrade all packages.",
+            action: Action::UpdateSystem,
+        },
+        ActionEntry {
+            id: "system.update-security",
+            title: "Apply security updates only",
+            key: Some("S"),
+            synopsis: Some("update security  (only updates that fix vulnerabilities)"),
+            description: "Upgrade just the packages whose pending updates fix vulnerabilities.",
+            action: Action::UpdateSecurity,
+        },
+        ActionEntry {
+            id: "system.restart-check",
+            title: "Check reboot / service restarts",
+            key: None,
+            synopsis: Some("restart-check  (also runs after a system update)"),
+            description: "Probe for a pending reboot and services running outdated binaries.",
+            action: Action::CheckRestarts,
+        },
+        ActionEntry {
+            id: "system.restart-services",
+            title: "Restart outdated services...",
+            key: Some("R"),
+            synopsis: None,
+            description: "Pick services running outdated binaries and restart them via systemctl.",
+            action: Action::ShowRestartPicker,
+        },
+        ActionEntry {
+            id: "system.clean",
+            title: "Clean package cache",
+            key: Some("c"),
+            synopsis: None,
+            description: "Remove cached package files.",
+            action: Action::CleanCache,
+        },
+        ActionEntry {
+            id: "app.offline",
+            title: "Toggle offline mode",
+            key: None,
+            synopsis: Some("offline  (toggles; network operations are skipped)"),
+            description: "Skip network-touching operations and serve data from cache.",
+            action: Action::ToggleOffline,
+        },
+        ActionEntry {
+            id: "app.dry-run",
+            title: "Toggle dry-run mode",
+            key: None,
+            synopsis: Some("dry-run  (toggles; operations are simulated)"),
+            description: "Simulate mutating operations and show their plans without changing anything.",
+            action: Action::ToggleDryRun,
+        },
+        ActionEntry {
+            id: "app.proxy",
+            title: "Test proxy connectivity",
+            key: None,
+            synopsis: Some("proxy  (probes the configured proxies)"),
+            description: "Check that the configured or inherited proxy is reachable.",
+            action: Action::TestProxy,
+        },
+        ActionEntry {
+            id: "snapshots.create",
+            title: "Take a snapshot...",
+            key: None,
+            synopsis: Some("snapshot [description]  (e.g. snapshot before kernel test)"),
+            description: "Take a manual snapshot with an optional description.",
+            action: Action::Prompt("snapshot "),
+        },
+        ActionEntry {
+            id: "snapshots.prune",
+            title: "Prune old snapshots",
+            key: None,
+            synopsis: Some("prune-snapshots  (applies the retention policy after confirmation)"),
+            description: "Delete automatic snapshots beyond the configured count or age.",
+            action: Action::PruneSnapshots,
+        },
+        ActionEntry {
+            id: "snapshots.packages",
+            title: "Snapshot installed packages...",
+            key: None,
+            synopsis: Some("snapshot-packages <label>  (e.g. snapshot-packages before-distro-hop)"),
+            description: "Record every manager's installed list with versions into the state directory.",
+            action: Action::Prompt("snapshot-packages "),
+        },
+        ActionEntry {
+            id: "snapshots.restore-packages",
+            title: "Restore a package set...",
+            key: None,
+            synopsis: Some("restore-packages <label>  (diffs, then converges after confirmation)"),
+            description: "Install, remove and downgrade packages until the system matches a recorded set.",
+            action: Action::Prompt("restore-packages "),
+        },
+        ActionEntry {
+            id: "snapshots.note",
+            title: "Annotate the selected snapshot...",
+            key: None,
+            synopsis: Some("note [text]  (on the Snapshots tab; an empty note removes it)"),
+            description: "Attach a free-text note to the selected snapshot.",
+            action: Action::Prompt("note "),
+        },
+        ActionEntry {
+            id: "snapshots.filter",
+            title: "Filter snapshots...",
+            key: None,
+            synopsis: Some("filter [text]  (matches ids, triggers and notes; empty clears)"),
+            description: "Limit the Snapshots tab to entries matching the text.",
+            action: Action::Prompt("filter "),
+        },
+        ActionEntry {
+            id: "security.scan",
+            title: "Scan for vulnerabilities",
+            key: None,
+            synopsis: Some("scan  (queries OSV.dev; cached results are reused)"),
+            description: "Check installed packages against the OSV vulnerability database.",
+            action: Action::ScanVulnerabilities,
+        },
+        ActionEntry {
+            id: "security.ignore",
+            title: "Ignore a finding...",
+            key: None,
+            synopsis: Some("security ignore <id> [until=YYYY-MM-DD] <reason>  (undo: security unignore <id>)"),
+            description: "Acknowledge a finding so it leaves the counts; kept in security-ignores.json.",
+            action: Action::Prompt("security ignore "),
+        },
+        ActionEntry {
+            id: "security.export",
+            title: "Export security report",
+            key: Some("x"),
+            synopsis: None,
+            description: "Write the last scan as a self-contained HTML report.",
+            action: Action::ExportSecurityReport,
+        },
+        ActionEntry {
+            id: "security.sources",
+            title: "Third-party sources",
+            key: Some("o"),
+            synopsis: None,
+            description: "Review third-party package sources from the last scan.",
+            action: Action::ShowThirdPartySources,
+        },
+        ActionEntry {
+            id: "security.permissions",
+            title: "Sandbox permissions",
+            key: Some("p"),
+            synopsis: None,
+            description: "Review flatpak and snap app permissions from the last scan.",
+            action: Action::ShowSandboxPermissions,
+        },
+        ActionEntry {
+            id: "overview.health",
+            title: "Dependency health check",
+            key: Some("d"),
+            synopsis: None,
+            description: "Check for broken or unsatisfied dependencies and suggest repairs.",
+            action: Action::CheckDependencyHealth,
+        },
+        ActionEntry {
+            id: "overview.overlaps",
+            title: "Cross-manager overlaps",
+            key: Some("O"),
+            synopsis: None,
+            description: "Find packages installed through several managers and which wins on PATH.",
+            action: Action::ShowOverlaps,
+        },
+        ActionEntry {
+            id: "packages.optional-deps",
+            title: "Browse optional dependencies",
+            key: Some("O"),
+            synopsis: None,
+            description: "List the selected package's optional dependencies and install them.",
+            action: Action::BrowseOptionalDeps,
+        },
+        ActionEntry {
+            id: "security.show-ignored",
+            title: "Show ignored findings",
+            key: Some("i"),
+            synopsis: None,
+            description: "Toggle the Security tab between active and ignored findings.",
+            action: Action::ToggleIgnoredFindings,
+        },
+        ActionEntry {
+            id: "packages.search",
+            title: "Search packages...",
+            key: Some("/"),
+            synopsis: Some("search <query>  (e.g. search firefox)"),
+            description: "Search all scoped managers for a package.",
+            action: Action::Prompt("search "),
+        },
+        ActionEntry {
+            id: "packages.install",
+            title: "Install packages...",
+            key: None,
+            synopsis: Some("install <pkg...>  (e.g. install htop tmux)"),
+            description: "Install one or more packages.",
+            action: Action::Prompt("install "),
+        },
+        ActionEntry {
+            id: "packages.remove",
+            title: "Remove packages...",
+            key: None,
+            synopsis: Some("remove <pkg...>  (e.g. remove htop)"),
+            description: "Remove one or more packages.",
+            action: Action::Prompt("remove "),
+        },
+        ActionEntry {
+            id: "packages.verify-file",
+            title: "Verify package file...",
+            key: None,
+            synopsis: Some("verify-file <path>  (checks a downloaded file against repo metadata)"),
+            description: "Verify a downloaded package file's checksum or signature.",
+            action: Action::Prompt("verify-file "),
+        },
+        ActionEntry {
+            id: "packages.hold",
+            title: "Hold package...",
+            key: None,
+            synopsis: Some("hold <pkg>  (e.g. hold linux-image)"),
+            description: "Pin a package at its current version.",
+            action: Action::Prompt("hold "),
+        },
+        ActionEntry {
+            id: "packages.unhold",
+            title: "Unhold package...",
+            key: None,
+            synopsis: Some("unhold <pkg>  (e.g. unhold linux-image)"),
+            description: "Release a held package.",
+            action: Action::Prompt("unhold "),
+        },
+        ActionEntry {
+            id: "app.report",
+            title: "Export session report...",
+            key: None,
+            synopsis: Some("report [path]  (e.g. report ~/changes-%Y%m%d.md)"),
+            description: "Write a Markdown or JSON record of this session's operations.",
+            action: Action::Prompt("report "),
+        },
+        ActionEntry {
+            id: "help.show",
+            title: "Show help",
+            key: Some("?"),
+            synopsis: None,
+            description: "Open this searchable reference.",
+            action: Action::ShowHelp,
+        },
+        ActionEntry {
+            id: "app.quit",
+            title: "Quit",
+            key: Some("q"),
+            synopsis: None,
+            description: "Exit pkgtool.",
+            action: Action::Quit,
+        },
+    ]
+}
+
+/// Levenshtein distance between two short command words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The known input-bar command closest to `verb`, for "did you mean"
+/// suggestions. Only near misses (distance <= 2) are suggested.
+pub fn suggest_command(verb: &str) -> Option<&'static str> {
+    const COMMANDS: [&str; 29] = [
+        "search",
+        "install",
+        "remove",
+        "update",
+        "clean",
+        "hold",
+        "unhold",
+        "offline",
+        "dry-run",
+        "proxy",
+        "report",
+        "profile",
+        "snapshot",
+        "snapshots",
+        "snapshot-packages",
+        "restore-packages",
+        "prune-snapshots",
+        "note",
+        "filter",
+        "scan",
+        "security",
+        "restart-check",
+        "verify-file",
+        "why",
+        "consumers",
+        "cycles",
+        "compare",
+        "heavy",
+        "deps",
+    ];
+    COMMANDS
+        .into_iter()
+        .map(|command| (edit_distance(verb, command), command))
+        .min()
+        .filter(|&(distance, _)| distance <= 2)
+        .map(|(_, command)| command)
+}
+
+/// Case-insensitive subsequence match, returning a score (lower is better)
+/// when `needle` matches `haystack`.
+pub fn fuzzy_score(needle: &str, haystack: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    let haystack_lower = haystack.to_lowercase();
+    let mut score = 0;
+    let mut position = 0;
+    for c in needle.to_lowercase().chars() {
+        match haystack_lower[position..].find(c) {
+            Some(offset) => {
+                score += offset;
+                position += offset + c.len_utf8();
+            }
+            None => return None,
+        }
+    }
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matches_subsequences() {
+        assert!(fuzzy_score("uts", "Go to Updates tab").is_some());
+        assert!(fuzzy_score("xyz", "Go to Updates tab").is_none());
+    }
+
+    #[test]
+    fn suggests_near_misses_only() {
+        assert_eq!(suggest_command("isntall"), Some("install"));
+        assert_eq!(suggest_command("serach"), Some("search"));
+        assert_eq!(suggest_command("frobnicate"), None);
+    }
+
+    #[test]
+    fn fuzzy_prefers_tighter_matches() {
+        let tight = fuzzy_score("upd", "Update system").unwrap();
+        let loose = fuzzy_score("upd", "Un-pin directory").unwrap();
+        assert!(tight < loose);
+    }
+}
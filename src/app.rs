@@ -0,0 +1,6961 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind,
+};
+use futures::StreamExt;
+use ratatui::backend::Backend;
+use ratatui::layout::{Position, Rect};
+use ratatui::style::Style;
+use ratatui::widgets::ListState;
+use ratatui::Terminal;
+
+use crate::actions::{self, Action};
+use crate::config::Config;
+use crate::features::cache::MetadataCache;
+use crate::features::deps::DependencyManager;
+use crate::features::history::{Transaction, TransactionHistory};
+use crate::features::hooks;
+use crate::features::eol::{self, EolWarning};
+use crate::features::restart::{self, RestartReport};
+use crate::features::security::{Finding, IgnoreList, OriginGroup, SecurityAnalyzer, Severity};
+use crate::features::session::SessionState;
+use crate::features::prompts::{self, PromptRule};
+use crate::features::report::{ReportEvent, SessionReport};
+use crate::features::snapshots::SnapshotManager;
+use crate::features::watchlist::Watchlist;
+use crate::i18n;
+use crate::package_managers::{
+    detect_all, initialize_package_managers, Detection, InstallFootprint, OutputLine,
+    PackageDetails, PackageInfo, PackageManager, PackageUpdate,
+};
+use crate::theme::Theme;
+use crate::ui;
+use crate::utils::limiter::Limiter;
+use crate::utils::loadable::Loadable;
+use crate::utils::privilege::PrivilegeRunner;
+
+/// Identifies one tab of the UI.
+///
+/// Tabs are registered declaratively in `TabId::all`: adding a variant plus
+/// its `title`/`render` arms is all that is needed to grow the tab bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabId {
+    Overview,
+    Packages,
+    Updates,
+    Search,
+    Log,
+    Snapshots,
+    Security,
+}
+
+impl TabId {
+    /// Every tab, in display order.
+    pub fn all() -> Vec<TabId> {
+        vec![
+            TabId::Overview,
+            TabId::Packages,
+            TabId::Updates,
+            TabId::Search,
+            TabId::Log,
+            TabId::Snapshots,
+            TabId::Security,
+        ]
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            TabId::Overview => i18n::tr("tab.overview"),
+            TabId::Packages => i18n::tr("tab.packages"),
+            TabId::Updates => i18n::tr("tab.updates"),
+            TabId::Search => i18n::tr("tab.search"),
+            TabId::Log => i18n::tr("tab.log"),
+            TabId::Snapshots => i18n::tr("tab.snapshots"),
+            TabId::Security => i18n::tr("tab.security"),
+        }
+    }
+
+    /// The function that renders this tab's body.
+    pub fn render(&self) -> fn(&mut ratatui::Frame, &mut App, ratatui::layout::Rect) {
+        match self {
+            TabId::Overview => ui::draw_overview_tab,
+            TabId::Packages => ui::draw_packages_tab,
+            TabId::Updates => ui::draw_updates_tab,
+            TabId::Search => ui::draw_search_tab,
+            TabId::Log => ui::draw_log_tab,
+            TabId::Snapshots => ui::draw_snapshots_tab,
+            TabId::Security => ui::draw_security_tab,
+        }
+    }
+}
+
+/// How long the event loop waits for input before emitting a tick.
+const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// Default list-pane share of the list/details split, in percent.
+pub const DEFAULT_SPLIT_RATIO: u16 = 60;
+
+/// Characters used as row hints in quick-select mode, in assignment order.
+pub const HINT_CHARS: &str = "123456789abcdefghijklmnopqrstuvwxyz";
+
+/// How long the type-ahead buffer stays alive without a new keystroke.
+const TYPEAHEAD_TIMEOUT: Duration = Duration::from_millis(700);
+
+/// How often the session state is flushed to disk while running, so a
+/// crash loses at most this much view state.
+const SESSION_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Upper bound on retained input-bar commands.
+const COMMAND_HISTORY_CAP: usize = 100;
+
+/// Accumulates characters typed after the `'` leader to jump within a list.
+pub struct TypeAhead {
+    pub buffer: String,
+    last_key: Instant,
+}
+
+impl TypeAhead {
+    fn new() -> Self {
+        TypeAhead {
+            buffer: String::new(),
+            last_key: Instant::now(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.last_key.elapsed() > TYPEAHEAD_TIMEOUT
+    }
+
+    fn push(&mut self, c: char) {
+        if self.expired() {
+            self.buffer.clear();
+        }
+        self.buffer.push(c);
+        self.last_key = Instant::now();
+    }
+}
+
+/// State of the command palette overlay.
+pub struct Palette {
+    pub query: String,
+    /// Indices into the action registry matching the query, best first.
+    pub matches: Vec<usize>,
+    pub state: ListState,
+}
+
+impl Palette {
+    fn new() -> Self {
+        let mut palette = Palette {
+            query: String::new(),
+            matches: Vec::new(),
+            state: ListState::default(),
+        };
+        palette.refilter();
+        palette
+    }
+
+    /// Recompute `matches` from the current query.
+    fn refilter(&mut self) {
+        let registry = actions::registry();
+        let mut scored: Vec<(usize, usize)> = registry
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| actions::fuzzy_score(&self.query, entry.title).map(|s| (s, i)))
+            .collect();
+        scored.sort();
+        self.matches = scored.into_iter().map(|(_, i)| i).collect();
+        self.state
+            .select(if self.matches.is_empty() { None } else { Some(0) });
+    }
+}
+
+/// Order of the installed package list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortMode {
+    Name,
+    /// Most recently installed first; packages without a date sort last.
+    RecentlyInstalled,
+}
+
+impl SortMode {
+    pub fn toggle(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::RecentlyInstalled,
+            SortMode::RecentlyInstalled => SortMode::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "name",
+            SortMode::RecentlyInstalled => "recently installed",
+        }
+    }
+}
+
+/// Sort the installed list in place according to `mode`.
+fn sort_packages(packages: &mut [PackageInfo], mode: SortMode) {
+    match mode {
+        SortMode::Name => packages.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortMode::RecentlyInstalled => packages.sort_by(|a, b| {
+            match (a.install_date, b.install_date) {
+                (Some(a_date), Some(b_date)) => b_date.cmp(&a_date),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => a.name.cmp(&b.name),
+            }
+        }),
+    }
+}
+
+/// How much detail list rows show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViewDensity {
+    /// One line per package.
+    Compact,
+    /// Two lines per package: name/version plus a dimmed summary line.
+    Detailed,
+}
+
+impl ViewDensity {
+    pub fn toggle(self) -> Self {
+        match self {
+            ViewDensity::Compact => ViewDensity::Detailed,
+            ViewDensity::Detailed => ViewDensity::Compact,
+        }
+    }
+}
+
+/// State of the manager-scope popup: one checkbox row per registered backend.
+pub struct ScopePicker {
+    /// Manager ids in display order.
+    pub ids: Vec<String>,
+    pub state: ListState,
+}
+
+/// State of the origin picker popup: one row per origin plus "All".
+pub struct OriginPicker {
+    /// (origin, package count) pairs present in the installed list.
+    pub entries: Vec<(String, usize)>,
+    pub state: ListState,
+}
+
+/// State of the third-party sources popup on the Security tab: one row
+/// per source, expandable in place to its flags and packages.
+pub struct OriginRiskView {
+    pub groups: Vec<OriginGroup>,
+    /// Indices into `groups` currently expanded.
+    pub expanded: HashSet<usize>,
+    pub state: ListState,
+}
+
+impl OriginRiskView {
+    /// Display rows a group occupies: its header plus, when expanded,
+    /// one row per flag and per package.
+    fn rows_for(&self, index: usize, group: &OriginGroup) -> usize {
+        if self.expanded.contains(&index) {
+            1 + group.flags.len() + group.packages.len()
+        } else {
+            1
+        }
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.groups
+            .iter()
+            .enumerate()
+            .map(|(index, group)| self.rows_for(index, group))
+            .sum()
+    }
+
+    /// The group a display row belongs to.
+    pub fn group_at(&self, row: usize) -> Option<usize> {
+        let mut remaining = row;
+        for (index, group) in self.groups.iter().enumerate() {
+            let rows = self.rows_for(index, group);
+            if remaining < rows {
+                return Some(index);
+            }
+            remaining -= rows;
+        }
+        None
+    }
+}
+
+/// State of the dependency health popup: the aggregated broken or
+/// unsatisfied dependencies across the scoped managers.
+pub struct DepHealthView {
+    pub problems: Vec<crate::package_managers::DepProblem>,
+    pub state: ListState,
+}
+
+/// State of the optional-dependency browser (`O` on a package): the
+/// package's optdepends / weak deps, each with an installed flag, the
+/// uninstalled ones selectable for install.
+pub struct OptionalDepsView {
+    pub package: String,
+    pub entries: Vec<(crate::package_managers::OptionalDep, bool)>,
+    /// Indices of entries marked with Space for a batch install.
+    pub marked: HashSet<usize>,
+    pub state: ListState,
+}
+
+/// Provider picker shown when an install names a virtual package with
+/// more than one provider.
+pub struct ProviderPicker {
+    /// The virtual name being resolved.
+    pub name: String,
+    pub providers: Vec<PackageInfo>,
+    pub state: ListState,
+}
+
+/// One row of the cross-manager overlaps popup: group headers carry no
+/// action, entry rows remove their package through their manager.
+pub struct OverlapRow {
+    pub text: String,
+    /// `(manager, package)` for entry rows; `None` for headers.
+    pub target: Option<(String, String)>,
+    /// Whether this entry owns the first PATH hit for the group.
+    pub winner: bool,
+}
+
+/// State of the overlaps popup (`O` on Overview): packages installed
+/// through more than one manager, flattened into selectable rows.
+pub struct OverlapsView {
+    pub rows: Vec<OverlapRow>,
+    pub state: ListState,
+}
+
+/// One heavy single-root suggestion: `(manager, package, rendered line)`.
+type HeavyEntry = (String, String, String);
+
+/// State of the heavy single-root suggestions popup (`heavy` command):
+/// the largest non-explicit packages only one explicit package keeps
+/// installed. Advisory — Enter opens the why-installed view.
+pub struct HeavyDepsView {
+    pub entries: Vec<HeavyEntry>,
+    pub state: ListState,
+}
+
+/// State of the permission-audit popup on the Security tab: one row
+/// per sandboxed app, expandable in place to its full grant list.
+pub struct PermissionView {
+    pub apps: Vec<crate::features::security::AppPermissions>,
+    /// `flatpak override --show` lines, shown below the apps.
+    pub overrides: Vec<String>,
+    /// Indices into `apps` currently expanded.
+    pub expanded: HashSet<usize>,
+    pub state: ListState,
+}
+
+impl PermissionView {
+    /// Display rows an app occupies: its header plus, when expanded,
+    /// one row per granted permission.
+    fn rows_for(&self, index: usize) -> usize {
+        if self.expanded.contains(&index) {
+            1 + self.apps[index].permissions.len()
+        } else {
+            1
+        }
+    }
+
+    pub fn row_count(&self) -> usize {
+        (0..self.apps.len()).map(|index| self.rows_for(index)).sum()
+    }
+
+    /// The app a display row belongs to.
+    pub fn app_at(&self, row: usize) -> Option<usize> {
+        let mut remaining = row;
+        for index in 0..self.apps.len() {
+            let rows = self.rows_for(index);
+            if remaining < rows {
+                return Some(index);
+            }
+            remaining -= rows;
+        }
+        None
+    }
+}
+
+/// State of the finding-detail popup (Enter on a Security row): every
+/// finding on the selected package, shown one at a time with the cached
+/// advisory behind it.
+pub struct FindingDetailView {
+    pub findings: Vec<Finding>,
+    /// Index into `findings` of the one currently shown.
+    pub index: usize,
+    /// Cached OSV advisory for the current finding; distro-feed
+    /// findings have none and every extra field shows as unknown.
+    pub detail: Option<crate::features::security::VulnDetail>,
+    pub scroll: u16,
+}
+
+/// State of the service-restart popup: one checkbox row per service
+/// still running an outdated binary after updates.
+pub struct RestartPicker {
+    pub services: Vec<String>,
+    /// Indices into `services` marked for restart.
+    pub selected: HashSet<usize>,
+    pub state: ListState,
+}
+
+/// State of the first-run setup wizard, one screen per question.
+///
+/// Opens when no config file exists yet (or via `pkgtool setup`); finishing
+/// or skipping it writes the config file, so it never reappears on its own.
+pub struct SetupWizard {
+    /// Index into `SetupWizard::steps`.
+    pub step: usize,
+    /// Detection result per known backend, in display order.
+    pub detections: Vec<Detection>,
+    /// Whether each detected backend is enabled; parallel to `detections`.
+    pub enabled: Vec<bool>,
+    /// Index into `SetupWizard::THEMES`; previewed live while selected.
+    pub theme_choice: usize,
+    /// Index into `SetupWizard::CONFIRM_LEVELS`.
+    pub confirm: usize,
+    pub auto_refresh: bool,
+    /// Index into `SetupWizard::ESCALATIONS`.
+    pub escalation: usize,
+    /// Cursor within the managers step.
+    pub state: ListState,
+}
+
+impl SetupWizard {
+    pub const STEP_COUNT: usize = 5;
+    pub const THEMES: [&'static str; 2] = ["default", "no-color"];
+    /// Confirmation policy levels, in `Config::confirm` spelling.
+    pub const CONFIRM_LEVELS: [&'static str; 3] = ["always", "destructive-only", "never"];
+    pub const ESCALATIONS: [&'static str; 4] = ["sudo", "doas", "pkexec", "run0"];
+
+    pub fn steps() -> [&'static str; Self::STEP_COUNT] {
+        [
+            i18n::tr("setup.step.managers"),
+            i18n::tr("setup.step.theme"),
+            i18n::tr("setup.step.confirm"),
+            i18n::tr("setup.step.refresh"),
+            i18n::tr("setup.step.escalation"),
+        ]
+    }
+}
+
+/// Input handling mode: normal navigation or editing the input bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Editing,
+}
+
+/// Upper bound on retained live-output lines for one operation.
+const OPERATION_SCROLLBACK: usize = 2000;
+
+/// Bounded scrollback of live output from the running operation.
+#[derive(Default)]
+pub struct OperationLog {
+    pub lines: std::collections::VecDeque<OutputLine>,
+    /// Scroll position; `None` follows the tail.
+    pub scroll: Option<usize>,
+}
+
+impl OperationLog {
+    fn push(&mut self, line: OutputLine) {
+        if self.lines.len() == OPERATION_SCROLLBACK {
+            self.lines.pop_front();
+            if let Some(scroll) = self.scroll.as_mut() {
+                *scroll = scroll.saturating_sub(1);
+            }
+        }
+        self.lines.push_back(line);
+    }
+}
+
+/// How long an operation may stay silent before the stall dialog opens.
+const STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Quiet period after the last keystroke before a live search dispatches,
+/// so typing does not hammer the backends on every character.
+const LIVE_SEARCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// One finished live search: generation stamp, the query it ran, and the
+/// results or joined errors.
+type LiveSearchResult = (u64, String, std::result::Result<Vec<PackageInfo>, String>);
+
+/// A plain informational dialog, dismissed with any of Esc/Enter/q.
+pub struct MessageDialog {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// An interactive question detected in the operation output.
+pub struct PromptDialog {
+    pub question: String,
+    pub options: Vec<String>,
+    pub state: ListState,
+}
+
+/// A mutating operation held at the confirmation gate.
+pub enum PendingOperation {
+    Install(Vec<String>),
+    /// Install package files whose checksum failed verification. Never
+    /// skips the confirmation gate, whatever the policy says.
+    InstallUnverified(Vec<String>),
+    Remove(Vec<String>),
+    UpdateSystem,
+    /// Apply only the named updates, which fix vulnerabilities.
+    UpdateSecurity(Vec<String>),
+    CleanCache,
+    /// Restore the system to a snapshot. Never skips the confirmation
+    /// gate, whatever the policy says.
+    RestoreSnapshot(String),
+    /// Converge the system back onto a recorded package set. The plan is
+    /// computed up front so the confirmation can show it.
+    RestorePackages {
+        label: String,
+        steps: Vec<crate::features::snapshots::RestoreStep>,
+    },
+    /// Delete the snapshots the retention policy has condemned; the
+    /// candidate listing doubles as the dry run in the confirmation.
+    PruneSnapshots(Vec<crate::features::snapshots::PruneCandidate>),
+    /// Restart systemd units that run binaries updates have replaced.
+    RestartServices(Vec<String>),
+    /// Run a repair command a backend suggested for a broken
+    /// dependency, privileged.
+    RepairDependencies { manager: String, command: String },
+    /// Remove one package through one specific manager, for duplicates
+    /// several managers claim — the first-success loop in
+    /// `remove_packages` could hit the wrong copy.
+    RemoveFrom { manager: String, package: String },
+}
+
+impl PendingOperation {
+    /// Removals are the tier the "destructive-only" policy asks about;
+    /// the rest only add or replace and go through silently under it.
+    fn destructive(&self) -> bool {
+        matches!(
+            self,
+            PendingOperation::Remove(_)
+                | PendingOperation::InstallUnverified(_)
+                | PendingOperation::RestoreSnapshot(_)
+                | PendingOperation::RestorePackages { .. }
+                | PendingOperation::PruneSnapshots(_)
+                | PendingOperation::RemoveFrom { .. }
+        )
+    }
+
+    /// The question put to the user, e.g. "remove htop tmux?".
+    pub fn describe(&self) -> String {
+        match self {
+            PendingOperation::Install(packages) => format!("install {}?", packages.join(" ")),
+            PendingOperation::InstallUnverified(packages) => format!(
+                "CHECKSUM MISMATCH — {} does NOT match the repository. Install anyway?",
+                packages.join(" ")
+            ),
+            PendingOperation::Remove(packages) => format!("remove {}?", packages.join(" ")),
+            PendingOperation::UpdateSystem => "update the system?".to_string(),
+            PendingOperation::UpdateSecurity(packages) => {
+                format!("apply {} security update(s)?", packages.len())
+            }
+            PendingOperation::CleanCache => "clean the package caches?".to_string(),
+            PendingOperation::RestartServices(services) => {
+                format!("restart {}?", services.join(" "))
+            }
+            PendingOperation::RepairDependencies { manager, command } => {
+                format!("run `{command}` [{manager}] to repair dependencies?")
+            }
+            PendingOperation::RemoveFrom { manager, package } => {
+                format!("remove {package} [{manager}]?")
+            }
+            PendingOperation::RestoreSnapshot(id) => {
+                format!("restore the system to snapshot {id}?")
+            }
+            PendingOperation::RestorePackages { label, steps } => {
+                let plan: Vec<String> = steps
+                    .iter()
+                    .map(|step| {
+                        let mut changes: Vec<String> =
+                            step.install.iter().map(|name| format!("+{name}")).collect();
+                        changes.extend(step.remove.iter().map(|name| format!("-{name}")));
+                        changes.extend(
+                            step.version_changes
+                                .iter()
+                                .map(|(name, version)| format!("{name}->{version}")),
+                        );
+                        format!("{}: {}", step.manager, changes.join(" "))
+                    })
+                    .collect();
+                format!("apply package set {label} ({})?", plan.join("; "))
+            }
+            PendingOperation::PruneSnapshots(candidates) => {
+                let listing: Vec<String> = candidates
+                    .iter()
+                    .map(|candidate| {
+                        format!("{} ({})", candidate.snapshot.id, candidate.reason)
+                    })
+                    .collect();
+                let reclaim: u64 = candidates
+                    .iter()
+                    .filter_map(|candidate| candidate.snapshot.size_bytes)
+                    .sum();
+                let mut question = format!(
+                    "prune {} snapshot(s): {}?",
+                    candidates.len(),
+                    listing.join(", ")
+                );
+                if reclaim > 0 {
+                    question.push_str(&format!(
+                        " (~{} reclaimed)",
+                        crate::utils::format_size(reclaim)
+                    ));
+                }
+                question
+            }
+        }
+    }
+}
+
+/// The confirmation gate: one dialog in front of every mutating operation,
+/// opened only when the configured policy demands it.
+pub struct ConfirmPrompt {
+    pub operation: PendingOperation,
+    pub state: ListState,
+}
+
+impl ConfirmPrompt {
+    pub fn options() -> [&'static str; 2] {
+        [i18n::tr("confirm.proceed"), i18n::tr("confirm.abort")]
+    }
+}
+
+/// A package-set diff shown as a scrollable overlay (`D` on the
+/// Snapshots tab; `e` exports the Markdown rendering).
+pub struct DiffView {
+    pub title: String,
+    /// Display rows: manager headers carry no change kind, change rows do.
+    pub rows: Vec<(String, Option<crate::features::snapshots::ChangeKind>)>,
+    pub state: ListState,
+    /// The same diff rendered as Markdown.
+    pub markdown: String,
+    /// File `e` writes the Markdown to, in the working directory.
+    pub export_path: String,
+}
+
+/// Confirmation before cancelling a running privileged operation (Esc).
+pub struct CancelPrompt {
+    pub state: ListState,
+}
+
+impl CancelPrompt {
+    pub fn options() -> [&'static str; 2] {
+        [i18n::tr("cancel.keep"), i18n::tr("cancel.cancel")]
+    }
+}
+
+/// Offered when an operation has produced no output for a while.
+pub struct StallPrompt {
+    pub state: ListState,
+}
+
+impl StallPrompt {
+    pub fn options() -> [&'static str; 2] {
+        [i18n::tr("stall.wait"), i18n::tr("stall.abort")]
+    }
+}
+
+/// A privileged operation running in a background task.
+pub struct Operation {
+    pub description: String,
+    /// When the operation started, for the session report's durations.
+    started: Instant,
+    /// (manager, "name old -> new") pairs planned when the update began.
+    plan: Vec<(String, String)>,
+    /// Whether this operation only simulated; skips hooks and reloads.
+    dry_run: bool,
+    /// Per-manager results, in the order the managers were run.
+    handle: tokio::task::JoinHandle<Vec<(String, crate::error::Result<()>)>>,
+    /// Live output lines streamed by the backend.
+    output: tokio::sync::mpsc::UnboundedReceiver<OutputLine>,
+    /// Answers for interactive prompts, forwarded to the child's stdin.
+    answers: tokio::sync::mpsc::UnboundedSender<String>,
+    /// Fires when the user cancels the operation.
+    cancel: tokio_util::sync::CancellationToken,
+    /// When the operation last produced output, for stall detection.
+    last_output: Instant,
+    /// Pid of the privileged child, once the backend publishes it (0 while
+    /// unknown). Backends gain a way to publish this when command execution
+    /// is centralized; until then abort falls back to cancelling the task.
+    pid: Arc<AtomicU32>,
+}
+
+/// Choices offered when quitting while an operation is still running.
+pub struct QuitPrompt {
+    pub state: ListState,
+}
+
+impl QuitPrompt {
+    pub fn options() -> [&'static str; 3] {
+        [
+            i18n::tr("quit.wait"),
+            i18n::tr("quit.detach"),
+            i18n::tr("quit.abort"),
+        ]
+    }
+}
+
+/// Which pane key events are routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    List,
+    Details,
+    Input,
+    Dialog,
+}
+
+/// Screen areas of the focusable panes, captured during the last draw so
+/// mouse clicks can be mapped back to a pane.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PaneRects {
+    pub list: Rect,
+    pub details: Rect,
+    pub input: Rect,
+}
+
+/// Top-level application state for the TUI.
+pub struct App {
+    pub package_managers: HashMap<String, Arc<dyn PackageManager>>,
+    /// Registry ids in detection priority order (native manager first).
+    manager_order: Vec<String>,
+    pub history: TransactionHistory,
+    /// How root commands get their privileges, for user-facing advice;
+    /// the backends carry their own copy.
+    privilege: PrivilegeRunner,
+    /// Operations performed this session, for the exportable report.
+    pub report: SessionReport,
+    pub snapshots: SnapshotManager,
+    /// Pre snapshot of the transaction in flight, set by the snapshot
+    /// policy or the builtin snapshot hook and consumed by
+    /// `close_snapshot_pair`; its id goes into the transaction history.
+    snapshot_pre: Option<crate::features::snapshots::Snapshot>,
+    pub security: SecurityAnalyzer,
+    /// OSV scan results for the Security tab.
+    pub vulns: Loadable<crate::features::security::VulnReport>,
+    pub security_state: ListState,
+    /// Triage decisions: acknowledged findings that leave the counts.
+    pub security_ignores: IgnoreList,
+    /// Whether the Security tab shows the ignored findings instead of
+    /// the active ones (`i`).
+    pub show_ignored_findings: bool,
+    /// Whether the running release is at or near end of life, checked
+    /// at startup and again after each online scan refreshes the table.
+    pub eol: Option<EolWarning>,
+    /// Last post-update restart check: pending reboot and services
+    /// running outdated binaries. `None` until a check has run.
+    pub restart: Option<RestartReport>,
+    pub restart_picker: Option<RestartPicker>,
+    pub finding_detail: Option<FindingDetailView>,
+    /// The in-flight vulnerability scan, if any.
+    scan_task: Option<tokio::task::JoinHandle<()>>,
+    /// Progress of the running scan as (queried, total) packages.
+    pub scan_progress: Option<(usize, usize)>,
+    scan_progress_rx: Option<tokio::sync::mpsc::UnboundedReceiver<(usize, usize)>>,
+    /// Fingerprint of the installed set the running scan covers.
+    scan_fingerprint: u64,
+    /// Fingerprint the last finished (or persisted) scan described.
+    last_scan_fingerprint: Option<u64>,
+    /// When staleness was last considered, to keep the per-tick check cheap.
+    last_scan_check: Option<Instant>,
+    /// Result arriving from the scan task.
+    scan_rx: Option<
+        tokio::sync::mpsc::UnboundedReceiver<
+            std::result::Result<crate::features::security::VulnReport, String>,
+        >,
+    >,
+    pub deps: DependencyManager,
+    pub watchlist: Watchlist,
+    pub theme: Theme,
+
+    pub tabs: Vec<TabId>,
+    pub selected_tab: usize,
+    pub mode: Mode,
+    pub focus: Focus,
+    /// Focus to restore when the current dialog closes.
+    previous_focus: Focus,
+    pub pane_rects: PaneRects,
+    pub input: String,
+    /// Byte offset of the cursor within `input`, always on a char boundary.
+    pub input_cursor: usize,
+    pub show_help: bool,
+    /// Filter typed into the help reference with `/`; `None` when not filtering.
+    pub help_filter: Option<String>,
+    pub palette: Option<Palette>,
+    pub origin_picker: Option<OriginPicker>,
+    pub origin_risk: Option<OriginRiskView>,
+    pub permission_view: Option<PermissionView>,
+    pub dep_health: Option<DepHealthView>,
+    /// Problem count from the last dependency health check, for the
+    /// Overview summary.
+    pub dep_problem_count: Option<usize>,
+    /// When set, the installed list only shows packages from this origin.
+    pub origin_filter: Option<String>,
+    pub scope_picker: Option<ScopePicker>,
+    /// Manager ids that bulk operations (loads, search, update) consult.
+    pub enabled_managers: HashSet<String>,
+    /// The privileged operation currently running, if any.
+    pub operation: Option<Operation>,
+    pub operation_log: OperationLog,
+    pub quit_prompt: Option<QuitPrompt>,
+    pub setup: Option<SetupWizard>,
+    pub prompt_dialog: Option<PromptDialog>,
+    pub message_dialog: Option<MessageDialog>,
+    pub stall_prompt: Option<StallPrompt>,
+    pub cancel_prompt: Option<CancelPrompt>,
+    pub confirm_prompt: Option<ConfirmPrompt>,
+    /// Blast radius shown inside the removal confirmation, computed
+    /// when the prompt opens.
+    pub removal_impact: Option<crate::features::deps::RemovalImpact>,
+    /// Predicted conflicts shown inside the install confirmation,
+    /// computed when the prompt opens.
+    pub conflict_report: Option<crate::package_managers::ConflictReport>,
+    /// What the pending install would pull in, shown alongside the
+    /// predicted conflicts while the prompt is open.
+    pub install_footprint: Option<InstallFootprint>,
+    /// Footprint of the package in the details pane, when it is not
+    /// installed yet.
+    pub details_footprint: Option<InstallFootprint>,
+    /// Optional dependencies of the package in the details pane, with
+    /// an installed flag each; None when the backend cannot list them.
+    pub details_optional: Option<Vec<(crate::package_managers::OptionalDep, bool)>>,
+    /// The optional-dependency browser popup, when open.
+    pub optional_deps: Option<OptionalDepsView>,
+    /// The provider picker popup for an ambiguous virtual install.
+    pub provider_picker: Option<ProviderPicker>,
+    /// Virtual names the details-pane package provides, shown dimmed
+    /// under its dependency list.
+    pub details_provides: Option<Vec<String>>,
+    /// The cross-manager overlaps popup, when open.
+    pub overlaps_view: Option<OverlapsView>,
+    /// How many names the last overlap scan found installed through more
+    /// than one manager; `None` until a scan runs.
+    pub overlap_count: Option<usize>,
+    /// The heavy single-root suggestions popup, when open.
+    pub heavy_deps: Option<HeavyDepsView>,
+    /// Heavy suggestions keyed by a fingerprint of the installed set,
+    /// so repeat queries skip the graph walk until an install or
+    /// removal changes what is on the system.
+    heavy_cache: Option<(u64, Vec<HeavyEntry>)>,
+    /// Whether the extra essential-packages confirmation has been given.
+    impact_acknowledged: bool,
+    /// Snapshots of the active backend plus saved package sets, for the
+    /// Snapshots tab.
+    pub snapshot_list: Loadable<Vec<crate::features::snapshots::Snapshot>>,
+    pub snapshots_state: ListState,
+    /// Storage the snapshots occupy, refreshed with the list; `None`
+    /// until the first listing or when the backend reports nothing.
+    pub snapshot_usage: Option<crate::features::snapshots::SnapshotUsage>,
+    /// Labels of package sets marked with Space for diffing (at most two).
+    pub snapshot_marks: Vec<String>,
+    /// Text typed into the Snapshots-tab `/` filter, matched against
+    /// ids, triggers and notes; `None` shows everything.
+    pub snapshot_filter: Option<String>,
+    pub diff_view: Option<DiffView>,
+    /// A restore has been armed and only a reboot completes it; shown
+    /// as a persistent status-bar banner.
+    pub reboot_required: bool,
+    /// (manager id, package name) pairs currently held back from upgrades.
+    held: HashSet<(String, String)>,
+    /// Packages-tab filter limiting the list to held packages.
+    pub show_held_only: bool,
+    /// Provenance flags from the last survey: (manager, name) to why
+    /// the package's builder looks untrusted. None until surveyed.
+    pub provenance: Option<HashMap<(String, String), String>>,
+    /// Packages-tab filter limiting the list to flagged provenance.
+    pub show_unverified_only: bool,
+    /// Quick-select mode: visible rows carry one-key hints.
+    pub hint_mode: bool,
+    /// Absolute list indices the hints map to, filled during rendering.
+    pub hint_targets: Vec<usize>,
+    /// Patterns for recognizing interactive backend questions.
+    prompt_rules: Vec<PromptRule>,
+    /// Offline mode: network-touching operations are skipped or served
+    /// from cache. Shared with the auto-refresh task so toggling applies
+    /// immediately.
+    offline: Arc<AtomicBool>,
+    /// The loaded configuration; view-state fields are written back on exit.
+    pub config: Config,
+    /// Disk cache used to populate the UI before the first real load.
+    cache: MetadataCache,
+    /// Results pushed by the periodic background refresh task.
+    auto_refresh: Option<tokio::sync::mpsc::UnboundedReceiver<Vec<PackageUpdate>>>,
+    auto_refresh_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Query waiting out the live-search debounce, with its deadline.
+    live_search_pending: Option<(String, Instant)>,
+    /// Stamp for live-search requests; responses with an older stamp are
+    /// stale and dropped.
+    search_generation: u64,
+    /// The in-flight live search, aborted when a newer one dispatches.
+    live_search_task: Option<tokio::task::JoinHandle<()>>,
+    /// Results arriving from live-search tasks, stamped with generation
+    /// and query.
+    live_search_rx: Option<tokio::sync::mpsc::UnboundedReceiver<LiveSearchResult>>,
+    /// Serializes privileged backend commands, so the background refresh
+    /// never runs concurrently with a user-initiated operation.
+    op_lock: Arc<tokio::sync::Mutex<()>>,
+    /// Caps concurrent backend commands; see `utils::limiter`.
+    limiter: Limiter,
+    /// Completed operation output, shown on the Log tab.
+    pub log: Vec<String>,
+    pub log_state: ListState,
+    /// Commands entered in the input bar, oldest first; recalled with
+    /// Up/Down while editing and persisted with the session.
+    pub command_history: Vec<String>,
+    /// Position while cycling through `command_history`; `None` when the
+    /// input holds fresh text.
+    history_cursor: Option<usize>,
+    /// When the session state was last flushed to disk.
+    last_session_save: Instant,
+    pub typeahead: Option<TypeAhead>,
+    pub status_message: Option<String>,
+    pub should_quit: bool,
+    /// Set by every mutation path; the loop only redraws while it is set.
+    dirty: bool,
+    /// Frames actually rendered, for the debug overlay.
+    pub frames_rendered: u64,
+    pub debug_overlay: bool,
+    started: Instant,
+
+    pub overview_state: ListState,
+    pub packages: Loadable<Vec<PackageInfo>>,
+    pub package_state: ListState,
+    pub updates: Loadable<Vec<PackageUpdate>>,
+    pub updates_state: ListState,
+    pub search_results: Loadable<Vec<PackageInfo>>,
+    pub search_state: ListState,
+    pub details: Option<PackageDetails>,
+    /// Vertical scroll offset of the details pane.
+    pub details_scroll: u16,
+    /// Width of the list pane as a percentage of the split (details gets
+    /// the rest). Adjusted with `<`/`>` in 5% steps, reset with `=`.
+    pub split_ratio: u16,
+    /// Compact or detailed list rows, toggled with `v`.
+    pub density: ViewDensity,
+    /// Current order of the installed list, toggled with `s`.
+    pub sort_mode: SortMode,
+}
+
+impl App {
+    pub fn new(config: Config) -> Self {
+        let registry = initialize_package_managers(&config);
+        let manager_order: Vec<String> = registry.iter().map(|(id, _)| id.clone()).collect();
+        let package_managers: HashMap<String, Arc<dyn PackageManager>> =
+            registry.into_iter().collect();
+        let enabled_managers: HashSet<String> = if config.managers.is_empty() {
+            package_managers.keys().cloned().collect()
+        } else {
+            package_managers
+                .keys()
+                .filter(|id| config.managers.contains(id))
+                .cloned()
+                .collect()
+        };
+        let tabs = TabId::all();
+        let selected_tab = tabs
+            .iter()
+            .position(|tab| tab.title().eq_ignore_ascii_case(&config.default_tab))
+            .unwrap_or(0);
+        let mut app = App {
+            package_managers,
+            manager_order,
+            history: TransactionHistory::load(),
+            privilege: PrivilegeRunner::new(&config.escalation),
+            report: SessionReport::new(),
+            snapshots: SnapshotManager::new(
+                config.snapshots.clone(),
+                PrivilegeRunner::new(&config.escalation),
+            ),
+            snapshot_pre: None,
+            security: SecurityAnalyzer::new(config.security.clone()),
+            vulns: Loadable::NotLoaded,
+            security_state: ListState::default(),
+            security_ignores: IgnoreList::load(),
+            eol: eol::check(Utc::now().date_naive()),
+            restart: None,
+            restart_picker: None,
+            finding_detail: None,
+            scan_progress: None,
+            scan_progress_rx: None,
+            scan_fingerprint: 0,
+            last_scan_fingerprint: None,
+            last_scan_check: None,
+            show_ignored_findings: false,
+            scan_task: None,
+            scan_rx: None,
+            deps: DependencyManager::new(),
+            watchlist: Watchlist::load(),
+            theme: if crate::theme::no_color_requested() || config.theme == "no-color" {
+                Theme::no_color()
+            } else {
+                Theme::default()
+            },
+            tabs,
+            selected_tab,
+            mode: Mode::Normal,
+            focus: Focus::List,
+            previous_focus: Focus::List,
+            pane_rects: PaneRects::default(),
+            input: String::new(),
+            input_cursor: 0,
+            show_help: false,
+            help_filter: None,
+            palette: None,
+            origin_picker: None,
+            origin_risk: None,
+            permission_view: None,
+            dep_health: None,
+            dep_problem_count: None,
+            origin_filter: None,
+            scope_picker: None,
+            enabled_managers,
+            operation: None,
+            operation_log: OperationLog::default(),
+            quit_prompt: None,
+            setup: None,
+            prompt_dialog: None,
+            message_dialog: None,
+            stall_prompt: None,
+            cancel_prompt: None,
+            confirm_prompt: None,
+            removal_impact: None,
+            conflict_report: None,
+            install_footprint: None,
+            details_footprint: None,
+            details_optional: None,
+            optional_deps: None,
+            provider_picker: None,
+            details_provides: None,
+            overlaps_view: None,
+            overlap_count: None,
+            heavy_deps: None,
+            heavy_cache: None,
+            impact_acknowledged: false,
+            snapshot_list: Loadable::NotLoaded,
+            snapshots_state: ListState::default(),
+            snapshot_usage: None,
+            snapshot_marks: Vec::new(),
+            snapshot_filter: None,
+            diff_view: None,
+            reboot_required: false,
+            held: HashSet::new(),
+            show_held_only: false,
+            provenance: None,
+            show_unverified_only: false,
+            hint_mode: false,
+            hint_targets: Vec::new(),
+            prompt_rules: prompts::default_rules(),
+            log: Vec::new(),
+            log_state: ListState::default(),
+            command_history: Vec::new(),
+            history_cursor: None,
+            last_session_save: Instant::now(),
+            typeahead: None,
+            status_message: None,
+            should_quit: false,
+            dirty: true,
+            frames_rendered: 0,
+            debug_overlay: false,
+            started: Instant::now(),
+            overview_state: {
+                let mut state = ListState::default();
+                state.select(Some(0));
+                state
+            },
+            packages: Loadable::default(),
+            package_state: ListState::default(),
+            updates: Loadable::default(),
+            updates_state: ListState::default(),
+            search_results: Loadable::default(),
+            search_state: ListState::default(),
+            details: None,
+            details_scroll: 0,
+            split_ratio: config.split_ratio.clamp(20, 80),
+            density: if config.density == "detailed" {
+                ViewDensity::Detailed
+            } else {
+                ViewDensity::Compact
+            },
+            sort_mode: SortMode::Name,
+            offline: Arc::new(AtomicBool::new(config.offline)),
+            limiter: Limiter::new(config.network_concurrency, config.local_concurrency),
+            config,
+            cache: MetadataCache::new(),
+            auto_refresh: None,
+            auto_refresh_handle: None,
+            live_search_pending: None,
+            search_generation: 0,
+            live_search_task: None,
+            live_search_rx: None,
+            op_lock: Arc::new(tokio::sync::Mutex::new(())),
+        };
+        app.restore_session();
+        app
+    }
+
+    /// Apply the persisted session state, item by item as the config's
+    /// `session_restore` list allows.
+    fn restore_session(&mut self) {
+        let session = SessionState::load();
+        let restore_list = self.config.session_restore.clone();
+        let enabled = |what: &str| restore_list.iter().any(|item| item == what);
+        if enabled("tab") {
+            if let Some(index) = self
+                .tabs
+                .iter()
+                .position(|tab| tab.title().eq_ignore_ascii_case(&session.selected_tab))
+            {
+                self.selected_tab = index;
+            }
+        }
+        if enabled("filters") {
+            self.sort_mode = if session.sort_mode == SortMode::RecentlyInstalled.label() {
+                SortMode::RecentlyInstalled
+            } else {
+                SortMode::Name
+            };
+            self.origin_filter = session.origin_filter.clone();
+            self.show_held_only = session.show_held_only;
+        }
+        if enabled("split") && session.split_ratio > 0 {
+            self.split_ratio = session.split_ratio.clamp(20, 80);
+        }
+        if enabled("scroll") {
+            // Selections may point past the end until data arrives; the
+            // loads clamp them once the real lengths are known.
+            for tab in TabId::all() {
+                if let Some(&index) = session.selections.get(tab.title()) {
+                    self.state_for(tab).select(Some(index));
+                }
+            }
+        }
+        if enabled("history") {
+            self.command_history = session.command_history;
+        }
+    }
+
+    /// Snapshot the current view state and write it to `state.json`.
+    fn save_session(&mut self) {
+        let mut selections = HashMap::new();
+        for tab in TabId::all() {
+            if let Some(selected) = self.state_for(tab).selected() {
+                selections.insert(tab.title().to_string(), selected);
+            }
+        }
+        let session = SessionState {
+            selected_tab: self.current_tab().title().to_string(),
+            sort_mode: self.sort_mode.label().to_string(),
+            origin_filter: self.origin_filter.clone(),
+            show_held_only: self.show_held_only,
+            split_ratio: self.split_ratio,
+            selections,
+            command_history: self.command_history.clone(),
+        };
+        if let Err(err) = session.save() {
+            log::warn!("could not save session state: {err}");
+        }
+        self.last_session_save = Instant::now();
+    }
+
+    /// Pull restored selections back into range once real data is loaded,
+    /// so a selection on a package that no longer exists degrades to the
+    /// nearest remaining row.
+    fn clamp_selections(&mut self) {
+        for tab in TabId::all() {
+            let len = self.list_len(tab);
+            let state = self.state_for(tab);
+            match state.selected() {
+                Some(_) if len == 0 => state.select(None),
+                Some(selected) if selected >= len => state.select(Some(len - 1)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Main event loop.
+    ///
+    /// Selects over the async input stream and a tick interval, so input is
+    /// handled the moment it arrives while background results (operation
+    /// output, auto-refresh) surface within one tick without any keypress.
+    /// Redraws only while the dirty flag is set.
+    pub async fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> anyhow::Result<()> {
+        self.run_with_events(terminal, event::EventStream::new()).await
+    }
+
+    /// The loop itself, parameterized over the input stream so tests can
+    /// drive it with a scripted (or empty) stream instead of a terminal.
+    async fn run_with_events<B: Backend, S>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        mut events: S,
+    ) -> anyhow::Result<()>
+    where
+        S: futures::Stream<Item = std::io::Result<Event>> + Unpin,
+    {
+        self.populate_from_cache();
+        self.load_packages().await;
+        self.load_held().await;
+        self.spawn_auto_refresh();
+
+        let mut ticker = tokio::time::interval(TICK_RATE);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        while !self.should_quit {
+            self.drain_operation_output();
+            self.poll_operation().await;
+            self.poll_auto_refresh();
+            self.poll_live_search();
+            self.poll_vuln_scan();
+            self.maybe_auto_rescan();
+            self.drain_logger();
+            if self.dirty {
+                let frame_started = Instant::now();
+                terminal.draw(|frame| ui::draw(frame, self))?;
+                crate::utils::profile::record_frame(frame_started.elapsed());
+                self.frames_rendered += 1;
+                self.dirty = false;
+            }
+            tokio::select! {
+                maybe_event = events.next() => match maybe_event {
+                    Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                        self.handle_key(key).await;
+                        self.mark_dirty();
+                    }
+                    Some(Ok(Event::Paste(text))) => {
+                        self.handle_paste(&text);
+                        self.mark_dirty();
+                    }
+                    Some(Ok(Event::Mouse(mouse)))
+                        if mouse.kind == MouseEventKind::Down(MouseButton::Left) =>
+                    {
+                        self.focus_click(mouse.column, mouse.row);
+                        self.mark_dirty();
+                    }
+                    Some(Ok(Event::Resize(_, _))) => self.mark_dirty(),
+                    Some(Ok(_)) => {}
+                    // The input stream ending means the terminal went away.
+                    Some(Err(_)) | None => break,
+                },
+                _ = ticker.tick() => {
+                    if self.typeahead.as_ref().is_some_and(|t| t.expired()) {
+                        self.typeahead = None;
+                        self.mark_dirty();
+                    }
+                    // A running operation animates its overlay every tick.
+                    if self.operation.is_some() {
+                        self.mark_dirty();
+                    }
+                    if self.last_session_save.elapsed() >= SESSION_SAVE_INTERVAL {
+                        self.save_session();
+                    }
+                    self.dispatch_due_live_search();
+                }
+            }
+        }
+        if let Some(handle) = self.auto_refresh_handle.take() {
+            handle.abort();
+        }
+        // Only a clean quit persists view state; a vanished terminal skips
+        // it (the periodic save already covers most of the session).
+        if self.should_quit {
+            self.persist_config();
+            self.save_session();
+            if !self.config.report_path.is_empty() {
+                if let Err(err) = self.report.write(&self.config.report_path) {
+                    log::warn!("could not write session report: {err}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether offline mode is active.
+    pub fn offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// Flip offline mode at runtime; the new value is persisted with the
+    /// config on exit and picked up by the auto-refresh task immediately.
+    fn toggle_offline(&mut self) {
+        let offline = !self.offline();
+        self.offline.store(offline, Ordering::Relaxed);
+        self.config.offline = offline;
+        self.status_message = Some(if offline {
+            "offline mode on: network operations are skipped".to_string()
+        } else {
+            "offline mode off".to_string()
+        });
+        self.mark_dirty();
+    }
+
+    /// Probe the configured proxies and show the results, since a wrong
+    /// proxy otherwise only surfaces as opaque backend timeouts.
+    async fn test_proxy(&mut self) {
+        let settings = crate::utils::proxy::resolve(&self.config.proxy);
+        if !settings.configured() {
+            self.status_message = Some("no proxy configured".to_string());
+            return;
+        }
+        self.status_message = Some("testing proxy connectivity...".to_string());
+        let mut lines = Vec::new();
+        for (url, result) in settings.self_test().await {
+            lines.push(match result {
+                Ok(()) => format!("{url}  reachable"),
+                Err(err) => format!("{url}  unreachable: {err}"),
+            });
+        }
+        self.message_dialog = Some(MessageDialog {
+            title: "Proxy connectivity".to_string(),
+            lines,
+        });
+        self.mark_dirty();
+    }
+
+    /// Whether dry-run mode is active.
+    pub fn dry_run(&self) -> bool {
+        self.config.dry_run
+    }
+
+    /// Flip dry-run mode at runtime; the new value is persisted with the
+    /// config on exit. The tab bar carries a permanent indicator while on.
+    fn toggle_dry_run(&mut self) {
+        self.config.dry_run = !self.config.dry_run;
+        self.status_message = Some(if self.config.dry_run {
+            "dry-run mode on: operations are simulated, nothing is changed".to_string()
+        } else {
+            "dry-run mode off".to_string()
+        });
+        self.mark_dirty();
+    }
+
+    /// Write the session report, to `path` or the configured/default
+    /// location. An empty session produces no file, just a status note.
+    fn write_report(&mut self, path: Option<&str>) {
+        let template = path
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                if self.config.report_path.is_empty() {
+                    "pkgtool-report-%Y%m%d-%H%M%S.md".to_string()
+                } else {
+                    self.config.report_path.clone()
+                }
+            });
+        self.status_message = Some(match self.report.write(&template) {
+            Ok(Some(written)) => format!("report written to {}", written.display()),
+            Ok(None) => "nothing to report this session".to_string(),
+            Err(err) => err.to_string(),
+        });
+    }
+
+    /// Write the collected profiling samples as JSON, to `path` or a
+    /// timestamped file in the working directory.
+    fn dump_profile(&mut self, path: Option<&str>) {
+        let path = path
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                chrono::Local::now()
+                    .format("pkgtool-profile-%Y%m%d-%H%M%S.json")
+                    .to_string()
+            });
+        let snapshot = crate::utils::profile::snapshot();
+        let written = serde_json::to_string_pretty(&snapshot)
+            .map_err(crate::error::PkgError::from)
+            .and_then(|json| std::fs::write(&path, json).map_err(Into::into));
+        self.status_message = Some(match written {
+            Ok(()) => format!("profile written to {path}"),
+            Err(err) => err.to_string(),
+        });
+    }
+
+    /// Start the periodic updates check, when the config enables it.
+    ///
+    /// The task refreshes metadata and re-lists updates on a fixed interval,
+    /// holding the operation lock so it never overlaps a user-initiated
+    /// command, and backs off exponentially while backends keep failing.
+    fn spawn_auto_refresh(&mut self) {
+        let secs = self.config.auto_refresh_secs;
+        if secs == 0 {
+            return;
+        }
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .scope_ids()
+            .into_iter()
+            .filter_map(|id| self.package_managers.get(&id).cloned())
+            .collect();
+        let lock = self.op_lock.clone();
+        let limiter = self.limiter.clone();
+        let offline = self.offline.clone();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = tokio::spawn(async move {
+            let base = Duration::from_secs(secs);
+            let mut backoff = 1u32;
+            loop {
+                tokio::time::sleep(base * backoff).await;
+                if offline.load(Ordering::Relaxed) {
+                    continue;
+                }
+                // The refresh lock is shared with `pkgtool watch`; when
+                // another process is mid-refresh, skip this cycle instead
+                // of hitting the backends twice.
+                let Some(_refresh_lock) = crate::features::watch::try_refresh_lock() else {
+                    continue;
+                };
+                let mut updates = Vec::new();
+                let mut failed = false;
+                {
+                    let _guard = lock.lock().await;
+                    for manager in &managers {
+                        let refreshed = {
+                            let _permit = limiter.acquire(true).await;
+                            manager.refresh_metadata().await
+                        };
+                        if refreshed.is_err() {
+                            failed = true;
+                            continue;
+                        }
+                        let _permit = limiter
+                            .acquire(manager.network_operations().contains(&"list-updates"))
+                            .await;
+                        match manager.list_updates().await {
+                            Ok(mut list) => updates.append(&mut list),
+                            Err(_) => failed = true,
+                        }
+                    }
+                }
+                backoff = if failed { (backoff * 2).min(8) } else { 1 };
+                if !failed && tx.send(updates).is_err() {
+                    return;
+                }
+            }
+        });
+        self.auto_refresh = Some(rx);
+        self.auto_refresh_handle = Some(handle);
+    }
+
+    /// Pull entries written by the file logger into the Log tab, so backend
+    /// invocations and warnings show up next to operation output. Only the
+    /// Log tab redraws for them; other tabs pick them up on their next draw.
+    fn drain_logger(&mut self) {
+        let entries = crate::logging::drain_recent();
+        if entries.is_empty() {
+            return;
+        }
+        self.log.extend(entries);
+        if self.current_tab() == TabId::Log {
+            self.mark_dirty();
+        }
+    }
+
+    /// Apply the most recent background refresh result, if one arrived.
+    fn poll_auto_refresh(&mut self) {
+        let Some(rx) = self.auto_refresh.as_mut() else {
+            return;
+        };
+        let mut latest = None;
+        while let Ok(updates) = rx.try_recv() {
+            latest = Some(updates);
+        }
+        let Some(mut updates) = latest else {
+            return;
+        };
+        let previous = self.pending_updates().len();
+        updates.sort_by(|a, b| a.name.cmp(&b.name));
+        let watchlist = &self.watchlist;
+        updates.sort_by_key(|update| !watchlist.contains(&update.manager, &update.name));
+        if updates.len() > previous {
+            self.status_message = Some(format!("{} updates available", updates.len()));
+        }
+        if updates.len() != previous {
+            self.mark_dirty();
+        }
+        self.updates = Loadable::Loaded(updates);
+        if self.updates_state.selected().is_none() && !self.pending_updates().is_empty() {
+            self.updates_state.select(Some(0));
+        }
+    }
+
+    /// Write view-state toggles back to the config file on exit, so split
+    /// ratio and density survive restarts. Failures only produce a log line;
+    /// exiting matters more than persisting.
+    fn persist_config(&mut self) {
+        self.config.split_ratio = self.split_ratio;
+        self.config.density = match self.density {
+            ViewDensity::Compact => "compact".to_string(),
+            ViewDensity::Detailed => "detailed".to_string(),
+        };
+        if let Err(err) = self.config.save() {
+            log::warn!("could not save config: {err}");
+        }
+    }
+
+    /// Abort anything still running before the process exits; called on
+    /// signal-driven shutdown so child processes do not outlive the TUI.
+    pub async fn shutdown(&mut self) {
+        if let Some(handle) = self.auto_refresh_handle.take() {
+            handle.abort();
+        }
+        self.abort_operation().await;
+    }
+
+    /// Request a redraw on the next loop iteration.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Frames rendered per second since startup, for the debug overlay.
+    pub fn frame_rate(&self) -> f64 {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if elapsed > 0.0 {
+            self.frames_rendered as f64 / elapsed
+        } else {
+            0.0
+        }
+    }
+
+    /// Counts and approximate bytes of the main data structures, for the
+    /// profiling overlay: struct size times length plus string contents.
+    /// Close enough to spot growth; only computed while the overlay shows.
+    pub fn data_footprint(&self) -> Vec<(&'static str, usize, usize)> {
+        fn info_bytes(rows: &[PackageInfo]) -> usize {
+            std::mem::size_of_val(rows)
+                + rows
+                    .iter()
+                    .map(|p| {
+                        p.name.len()
+                            + p.version.len()
+                            + p.description.len()
+                            + p.manager.len()
+                            + p.origin.as_deref().map_or(0, str::len)
+                    })
+                    .sum::<usize>()
+        }
+        let packages = self.packages.value().map(Vec::as_slice).unwrap_or(&[]);
+        let search = self.search_results.value().map(Vec::as_slice).unwrap_or(&[]);
+        let updates = self.updates.value().map(Vec::as_slice).unwrap_or(&[]);
+        let updates_bytes = std::mem::size_of_val(updates)
+            + updates
+                .iter()
+                .map(|u| u.name.len() + u.current_version.len() + u.new_version.len() + u.manager.len())
+                .sum::<usize>();
+        let log_bytes = self.log.len() * std::mem::size_of::<String>()
+            + self.log.iter().map(String::len).sum::<usize>();
+        vec![
+            ("packages", packages.len(), info_bytes(packages)),
+            ("updates", updates.len(), updates_bytes),
+            ("search", search.len(), info_bytes(search)),
+            ("log", self.log.len(), log_bytes),
+        ]
+    }
+
+    /// Open the setup wizard, seeded from the current configuration.
+    pub fn open_setup_wizard(&mut self) {
+        let detections = detect_all();
+        let enabled = detections
+            .iter()
+            .map(|detection| {
+                detection.unavailable.is_none()
+                    && (self.config.managers.is_empty()
+                        || self.config.managers.contains(&detection.id.to_string()))
+            })
+            .collect();
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.setup = Some(SetupWizard {
+            step: 0,
+            detections,
+            enabled,
+            theme_choice: if self.config.theme == "no-color" { 1 } else { 0 },
+            confirm: SetupWizard::CONFIRM_LEVELS
+                .iter()
+                .position(|level| *level == self.config.confirm)
+                .unwrap_or(0),
+            auto_refresh: self.config.auto_refresh_secs > 0,
+            escalation: SetupWizard::ESCALATIONS
+                .iter()
+                .position(|tool| *tool == self.config.escalation)
+                .unwrap_or(0),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    fn handle_setup_key(&mut self, key: KeyEvent) {
+        let last_step = self
+            .setup
+            .as_ref()
+            .is_some_and(|wizard| wizard.step + 1 == SetupWizard::STEP_COUNT);
+        // Esc skips, accepting whatever is on screen; the file is written
+        // either way so the wizard does not reappear next launch.
+        if key.code == KeyCode::Esc || (key.code == KeyCode::Enter && last_step) {
+            self.finish_setup();
+            return;
+        }
+        let Some(wizard) = self.setup.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                wizard.step += 1;
+                wizard.state.select(Some(0));
+            }
+            KeyCode::Left | KeyCode::Backspace => {
+                wizard.step = wizard.step.saturating_sub(1);
+                wizard.state.select(Some(0));
+            }
+            KeyCode::Down | KeyCode::Up => {
+                let down = key.code == KeyCode::Down;
+                match wizard.step {
+                    0 => {
+                        let len = wizard.detections.len();
+                        let selected = wizard.state.selected().unwrap_or(0);
+                        let next = if down {
+                            (selected + 1).min(len.saturating_sub(1))
+                        } else {
+                            selected.saturating_sub(1)
+                        };
+                        wizard.state.select(Some(next));
+                    }
+                    1 => wizard.theme_choice = usize::from(down),
+                    2 => {
+                        let len = SetupWizard::CONFIRM_LEVELS.len();
+                        wizard.confirm = if down {
+                            (wizard.confirm + 1).min(len - 1)
+                        } else {
+                            wizard.confirm.saturating_sub(1)
+                        };
+                    }
+                    3 => wizard.auto_refresh = !down,
+                    4 => {
+                        let len = SetupWizard::ESCALATIONS.len();
+                        wizard.escalation = if down {
+                            (wizard.escalation + 1).min(len - 1)
+                        } else {
+                            wizard.escalation.saturating_sub(1)
+                        };
+                    }
+                    _ => {}
+                }
+            }
+            KeyCode::Char(' ') if wizard.step == 0 => {
+                if let Some(selected) = wizard.state.selected() {
+                    let usable = wizard
+                        .detections
+                        .get(selected)
+                        .is_some_and(|detection| detection.unavailable.is_none());
+                    if usable {
+                        wizard.enabled[selected] = !wizard.enabled[selected];
+                    }
+                }
+            }
+            _ => {}
+        }
+        // Live preview: the whole frame redraws in the chosen theme.
+        let preview = self
+            .setup
+            .as_ref()
+            .filter(|wizard| wizard.step == 1)
+            .map(|wizard| wizard.theme_choice);
+        if let Some(choice) = preview {
+            self.theme = if choice == 1 {
+                Theme::no_color()
+            } else {
+                Theme::default()
+            };
+        }
+    }
+
+    /// Write the wizard's answers to the config file and apply them.
+    fn finish_setup(&mut self) {
+        let Some(wizard) = self.setup.take() else {
+            return;
+        };
+        let chosen: Vec<String> = wizard
+            .detections
+            .iter()
+            .zip(&wizard.enabled)
+            .filter(|(detection, &enabled)| enabled && detection.unavailable.is_none())
+            .map(|(detection, _)| detection.id.to_string())
+            .collect();
+        let usable = wizard
+            .detections
+            .iter()
+            .filter(|detection| detection.unavailable.is_none())
+            .count();
+        // Everything enabled is stored as the empty list, so backends added
+        // later are picked up without editing the config.
+        self.config.managers = if chosen.len() == usable {
+            Vec::new()
+        } else {
+            chosen
+        };
+        self.config.theme = SetupWizard::THEMES[wizard.theme_choice].to_string();
+        self.theme = if wizard.theme_choice == 1 || crate::theme::no_color_requested() {
+            Theme::no_color()
+        } else {
+            Theme::default()
+        };
+        self.config.confirm = SetupWizard::CONFIRM_LEVELS[wizard.confirm].to_string();
+        self.config.auto_refresh_secs = if wizard.auto_refresh {
+            if self.config.auto_refresh_secs > 0 {
+                self.config.auto_refresh_secs
+            } else {
+                Config::default().auto_refresh_secs
+            }
+        } else {
+            0
+        };
+        self.config.escalation = SetupWizard::ESCALATIONS[wizard.escalation].to_string();
+        self.enabled_managers = self
+            .package_managers
+            .keys()
+            .filter(|id| self.config.managers.is_empty() || self.config.managers.contains(id))
+            .cloned()
+            .collect();
+        if let Err(err) = self.config.save() {
+            log::warn!("could not save config: {err}");
+        }
+        self.close_dialog();
+        self.status_message = Some(i18n::tr("setup.saved").to_string());
+    }
+
+    async fn handle_key(&mut self, key: KeyEvent) {
+        if self.setup.is_some() {
+            self.handle_setup_key(key);
+            return;
+        }
+        if self.show_help {
+            self.handle_help_key(key);
+            return;
+        }
+        if self.palette.is_some() {
+            self.handle_palette_key(key).await;
+            return;
+        }
+        if self.quit_prompt.is_some() {
+            self.handle_quit_prompt_key(key).await;
+            return;
+        }
+        if self.prompt_dialog.is_some() {
+            self.handle_prompt_dialog_key(key);
+            return;
+        }
+        if self.message_dialog.is_some() {
+            self.handle_message_dialog_key(key);
+            return;
+        }
+        if self.stall_prompt.is_some() {
+            self.handle_stall_prompt_key(key).await;
+            return;
+        }
+        if self.cancel_prompt.is_some() {
+            self.handle_cancel_prompt_key(key);
+            return;
+        }
+        if self.confirm_prompt.is_some() {
+            self.handle_confirm_prompt_key(key).await;
+            return;
+        }
+        if self.diff_view.is_some() {
+            self.handle_diff_view_key(key);
+            return;
+        }
+        if self.origin_picker.is_some() {
+            self.handle_origin_picker_key(key);
+            return;
+        }
+        if self.permission_view.is_some() {
+            self.handle_permission_view_key(key);
+            return;
+        }
+        if self.dep_health.is_some() {
+            self.handle_dep_health_key(key).await;
+            return;
+        }
+        if self.optional_deps.is_some() {
+            self.handle_optional_deps_key(key).await;
+            return;
+        }
+        if self.provider_picker.is_some() {
+            self.handle_provider_picker_key(key).await;
+            return;
+        }
+        if self.overlaps_view.is_some() {
+            self.handle_overlaps_key(key).await;
+            return;
+        }
+        if self.heavy_deps.is_some() {
+            self.handle_heavy_deps_key(key).await;
+            return;
+        }
+        if self.origin_risk.is_some() {
+            self.handle_origin_risk_key(key).await;
+            return;
+        }
+        if self.restart_picker.is_some() {
+            self.handle_restart_picker_key(key).await;
+            return;
+        }
+        if self.finding_detail.is_some() {
+            self.handle_finding_detail_key(key);
+            return;
+        }
+        if self.scope_picker.is_some() {
+            self.handle_scope_picker_key(key);
+            return;
+        }
+        match self.mode {
+            Mode::Normal => self.handle_normal_key(key).await,
+            Mode::Editing => self.handle_editing_key(key).await,
+        }
+    }
+
+    async fn handle_palette_key(&mut self, key: KeyEvent) {
+        let Some(palette) = self.palette.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.palette = None;
+                self.close_dialog();
+            }
+            KeyCode::Down => {
+                let len = palette.matches.len();
+                if len > 0 {
+                    let next = palette.state.selected().map_or(0, |i| (i + 1).min(len - 1));
+                    palette.state.select(Some(next));
+                }
+            }
+            KeyCode::Up => {
+                let previous = palette.state.selected().map_or(0, |i| i.saturating_sub(1));
+                palette.state.select(Some(previous));
+            }
+            KeyCode::Backspace => {
+                palette.query.pop();
+                palette.refilter();
+            }
+            KeyCode::Enter => {
+                let chosen = palette
+                    .state
+                    .selected()
+                    .and_then(|i| palette.matches.get(i).copied())
+                    .map(|i| actions::registry()[i].action);
+                self.palette = None;
+                self.close_dialog();
+                if let Some(action) = chosen {
+                    self.dispatch(action).await;
+                }
+            }
+            KeyCode::Char(c) => {
+                palette.query.push(c);
+                palette.refilter();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_scope_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = self.scope_picker.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter => {
+                self.scope_picker = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = picker.ids.len().saturating_sub(1);
+                let next = picker.state.selected().map_or(0, |i| (i + 1).min(last));
+                picker.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = picker.state.selected().map_or(0, |i| i.saturating_sub(1));
+                picker.state.select(Some(previous));
+            }
+            KeyCode::Char(' ') => {
+                let Some(id) = picker.state.selected().and_then(|i| picker.ids.get(i)) else {
+                    return;
+                };
+                // Never uncheck the last enabled manager: an empty scope
+                // would silently make every operation a no-op.
+                if self.enabled_managers.contains(id) {
+                    if self.enabled_managers.len() > 1 {
+                        self.enabled_managers.remove(id);
+                    }
+                } else {
+                    self.enabled_managers.insert(id.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the manager-scope popup listing every registered backend.
+    fn open_scope_picker(&mut self) {
+        let mut ids: Vec<String> = self.package_managers.keys().cloned().collect();
+        ids.sort();
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.scope_picker = Some(ScopePicker { ids, state });
+        self.open_dialog();
+    }
+
+    /// Bookmark or unbookmark the selected package on the watchlist.
+    fn toggle_watch(&mut self) {
+        let Some(package) = self.selected_package() else {
+            return;
+        };
+        let (manager, name) = (package.manager.clone(), package.name.clone());
+        match self.watchlist.toggle(&manager, &name) {
+            Ok(true) => self.status_message = Some(format!("watching {name}")),
+            Ok(false) => self.status_message = Some(format!("stopped watching {name}")),
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+        // Watched updates float to the top, so keep the order current.
+        if let Loadable::Loaded(updates) = &mut self.updates {
+            let watchlist = &self.watchlist;
+            updates.sort_by_key(|update| !watchlist.contains(&update.manager, &update.name));
+        }
+    }
+
+    /// Sorted ids of the managers in scope, for loops that also mutate App.
+    fn scope_ids(&self) -> Vec<String> {
+        self.manager_order
+            .iter()
+            .filter(|id| self.enabled_managers.contains(*id))
+            .cloned()
+            .collect()
+    }
+
+    /// Backends included in the current scope, for bulk operations.
+    fn scoped_managers(&self) -> impl Iterator<Item = &dyn PackageManager> {
+        self.package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.as_ref())
+    }
+
+    fn handle_origin_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = self.origin_picker.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.origin_picker = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                // Row 0 is "All origins"; entries follow.
+                let last = picker.entries.len();
+                let next = picker.state.selected().map_or(0, |i| (i + 1).min(last));
+                picker.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = picker.state.selected().map_or(0, |i| i.saturating_sub(1));
+                picker.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let chosen = picker.state.selected().and_then(|i| {
+                    if i == 0 {
+                        None // "All origins"
+                    } else {
+                        picker.entries.get(i - 1).map(|(origin, _)| origin.clone())
+                    }
+                });
+                self.origin_filter = chosen;
+                self.origin_picker = None;
+                self.close_dialog();
+                self.package_state.select(if self.installed_visible().is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the origin picker listing every origin in the installed list.
+    fn open_origin_picker(&mut self) {
+        let mut entries: Vec<(String, usize)> = Vec::new();
+        for package in self.installed() {
+            let origin = package.origin.clone().unwrap_or_else(|| "unknown".to_string());
+            match entries.iter_mut().find(|(o, _)| *o == origin) {
+                Some((_, count)) => *count += 1,
+                None => entries.push((origin, 1)),
+            }
+        }
+        entries.sort();
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.origin_picker = Some(OriginPicker { entries, state });
+        self.open_dialog();
+    }
+
+    async fn handle_origin_risk_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.origin_risk.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.origin_risk = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.row_count().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(index) = view.state.selected().and_then(|row| view.group_at(row)) else {
+                    return;
+                };
+                if !view.expanded.remove(&index) {
+                    view.expanded.insert(index);
+                }
+            }
+            // Jump to the Packages tab filtered to this source, where the
+            // repo files can be reviewed package by package; the status
+            // line says how to disable the source itself.
+            KeyCode::Char('g') => {
+                let Some(group) = view
+                    .state
+                    .selected()
+                    .and_then(|row| view.group_at(row))
+                    .and_then(|index| view.groups.get(index))
+                else {
+                    return;
+                };
+                let (origin, manager) = (group.origin.clone(), group.manager.clone());
+                self.origin_risk = None;
+                self.close_dialog();
+                self.origin_filter = Some(origin.clone());
+                self.jump_to(TabId::Packages).await;
+                self.package_state.select(if self.installed_visible().is_empty() {
+                    None
+                } else {
+                    Some(0)
+                });
+                let disable = match (manager.as_str(), origin.eq_ignore_ascii_case("aur")) {
+                    ("pacman", true) => "foreign packages; remove or rebuild them".to_string(),
+                    ("pacman", false) => format!("comment out [{origin}] in /etc/pacman.conf"),
+                    ("dnf", _) => format!("dnf config-manager --set-disabled {origin}"),
+                    _ => "remove its entry under /etc/apt/sources.list(.d)".to_string(),
+                };
+                self.status_message = Some(format!("showing packages from {origin} — to disable: {disable}"));
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the third-party sources popup from the last scan's report.
+    fn open_origin_risk(&mut self) {
+        let Some(report) = self.vulns.value() else {
+            self.status_message = Some("run a scan first (s) to survey package sources".to_string());
+            return;
+        };
+        if report.origins.is_empty() {
+            self.status_message = Some("no third-party package sources detected".to_string());
+            return;
+        }
+        let groups = report.origins.clone();
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.origin_risk = Some(OriginRiskView {
+            groups,
+            expanded: HashSet::new(),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    fn handle_permission_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.permission_view.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.permission_view = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.row_count().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(index) = view.state.selected().and_then(|row| view.app_at(row)) else {
+                    return;
+                };
+                if !view.expanded.remove(&index) {
+                    view.expanded.insert(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the sandbox permission popup from the last scan's report.
+    fn open_permission_view(&mut self) {
+        let Some(report) = self.vulns.value() else {
+            self.status_message =
+                Some("run a scan first (s) to audit sandbox permissions".to_string());
+            return;
+        };
+        if report.permissions.is_empty() {
+            self.status_message = Some("no flatpak or snap apps detected".to_string());
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.permission_view = Some(PermissionView {
+            apps: report.permissions.clone(),
+            overrides: report.flatpak_overrides.clone(),
+            expanded: HashSet::new(),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// Run every scoped manager's dependency consistency check and open
+    /// the health popup, which shows "no problems found" on a clean
+    /// system rather than nothing at all.
+    async fn open_dep_health(&mut self) {
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        let mut problems = Vec::new();
+        let mut failures = Vec::new();
+        for manager in managers {
+            match self.deps.problems(manager.as_ref()).await {
+                Ok(mut found) => problems.append(&mut found),
+                Err(crate::error::PkgError::Unsupported { .. }) => {}
+                Err(err) => failures.push(format!("{}: {err}", manager.id())),
+            }
+        }
+        self.dep_problem_count = Some(problems.len());
+        if !failures.is_empty() {
+            self.status_message = Some(format!("health check failed for {}", failures.join("; ")));
+        }
+        let mut state = ListState::default();
+        state.select((!problems.is_empty()).then_some(0));
+        self.dep_health = Some(DepHealthView { problems, state });
+        self.open_dialog();
+    }
+
+    async fn handle_dep_health_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.dep_health.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.dep_health = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.problems.len().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let Some(problem) = view
+                    .state
+                    .selected()
+                    .and_then(|index| view.problems.get(index))
+                else {
+                    return;
+                };
+                let Some(command) = problem.fix.clone() else {
+                    self.status_message =
+                        Some("no suggested fix for this problem".to_string());
+                    return;
+                };
+                let manager = problem.manager.clone();
+                self.dep_health = None;
+                self.close_dialog();
+                self.request_operation(PendingOperation::RepairDependencies { manager, command })
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the optional-dependency browser for the selected package.
+    async fn open_optional_deps(&mut self) {
+        let Some(package) = self.selected_package() else {
+            return;
+        };
+        let (manager_id, name) = (package.manager.clone(), package.name.clone());
+        let Some(entries) = self.optional_deps_with_markers(&manager_id, &name).await else {
+            self.status_message = Some(format!("no optional dependency data for {name}"));
+            return;
+        };
+        if entries.is_empty() {
+            self.status_message = Some(format!("{name} has no optional dependencies"));
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.optional_deps = Some(OptionalDepsView {
+            package: name,
+            entries,
+            marked: HashSet::new(),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// A package's optional dependencies, each flagged with whether it
+    /// is already on the system.
+    async fn optional_deps_with_markers(
+        &mut self,
+        manager_id: &str,
+        name: &str,
+    ) -> Option<Vec<(crate::package_managers::OptionalDep, bool)>> {
+        let manager = self.package_managers.get(manager_id).cloned()?;
+        let deps = manager.optional_dependencies(name).await.ok()?;
+        let installed: HashSet<&str> = self
+            .installed()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect();
+        Some(
+            deps.into_iter()
+                .map(|dep| {
+                    let present = installed.contains(dep.name.as_str());
+                    (dep, present)
+                })
+                .collect(),
+        )
+    }
+
+    /// Space marks uninstalled entries, Enter queues the marked set (or
+    /// the selected entry) for install through the usual confirmation.
+    async fn handle_optional_deps_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.optional_deps.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.optional_deps = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.entries.len().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Char(' ') => {
+                let Some(index) = view.state.selected() else {
+                    return;
+                };
+                if view.entries.get(index).is_some_and(|(_, present)| *present) {
+                    self.status_message = Some("already installed".to_string());
+                    return;
+                }
+                if !view.marked.remove(&index) {
+                    view.marked.insert(index);
+                }
+            }
+            KeyCode::Enter => {
+                let mut names: Vec<String> = view
+                    .marked
+                    .iter()
+                    .filter_map(|&index| view.entries.get(index))
+                    .map(|(dep, _)| dep.name.clone())
+                    .collect();
+                names.sort();
+                if names.is_empty() {
+                    match view.state.selected().and_then(|index| view.entries.get(index)) {
+                        Some((_, true)) => {
+                            self.status_message = Some("already installed".to_string());
+                            return;
+                        }
+                        Some((dep, false)) => names.push(dep.name.clone()),
+                        None => return,
+                    }
+                }
+                self.optional_deps = None;
+                self.close_dialog();
+                self.request_operation(PendingOperation::Install(names)).await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Scan the installed lists for tools present through more than one
+    /// manager and open the Overlaps popup (`O` on Overview).
+    async fn open_overlaps(&mut self) {
+        use crate::features::overlaps;
+        let packages = self.installed().to_vec();
+        let mut found = overlaps::find_overlaps(&packages);
+        overlaps::resolve_paths(&mut found).await;
+        self.overlap_count = Some(found.len());
+        if found.is_empty() {
+            self.status_message = Some("no cross-manager overlaps found".to_string());
+            return;
+        }
+        const DISTRO: [&str; 3] = ["pacman", "apt", "dnf"];
+        let mut rows = Vec::new();
+        for overlap in &found {
+            // Attribute the first PATH hit: ecosystem prefixes name the
+            // manager outright, distro paths like /usr/bin belong to the
+            // distro manager among the entries.
+            let winner: Option<String> = overlap.paths.first().map(|path| {
+                overlaps::manager_for_path(path)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| {
+                        overlap
+                            .entries
+                            .iter()
+                            .find(|entry| DISTRO.contains(&entry.manager.as_str()))
+                            .map(|entry| entry.manager.clone())
+                            .unwrap_or_default()
+                    })
+            });
+            let mut header = format!("{} — {} copies", overlap.name, overlap.entries.len());
+            if let Some(path) = overlap.paths.first() {
+                header.push_str(&format!(", {path} wins on PATH"));
+            }
+            rows.push(OverlapRow {
+                text: header,
+                target: None,
+                winner: false,
+            });
+            for entry in &overlap.entries {
+                let wins = winner.as_deref() == Some(entry.manager.as_str());
+                let note = if wins {
+                    " — wins on PATH"
+                } else if winner.is_some() {
+                    " — shadowed, consider removing"
+                } else {
+                    ""
+                };
+                rows.push(OverlapRow {
+                    text: format!(
+                        "  {} {} [{}]{note}",
+                        entry.package, entry.version, entry.manager
+                    ),
+                    target: Some((entry.manager.clone(), entry.package.clone())),
+                    winner: wins,
+                });
+            }
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.overlaps_view = Some(OverlapsView { rows, state });
+        self.open_dialog();
+    }
+
+    /// Enter removes the selected copy through the manager that owns
+    /// it; header rows only navigate.
+    async fn handle_overlaps_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.overlaps_view.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.overlaps_view = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.rows.len().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let Some(row) = view.state.selected().and_then(|index| view.rows.get(index))
+                else {
+                    return;
+                };
+                let Some((manager, package)) = row.target.clone() else {
+                    return;
+                };
+                if row.winner {
+                    self.status_message = Some(format!(
+                        "{package} wins on PATH; remove a shadowed copy instead"
+                    ));
+                    return;
+                }
+                self.overlaps_view = None;
+                self.close_dialog();
+                self.request_operation(PendingOperation::RemoveFrom { manager, package })
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Run a backend-suggested repair command privileged, then drop the
+    /// cached health report so the next check re-runs it.
+    async fn repair_dependencies(&mut self, manager: &str, command: &str) {
+        if self.dry_run() {
+            self.status_message = Some(format!("dry run: would run `{command}`"));
+            return;
+        }
+        let args: Vec<&str> = command.split_whitespace().collect();
+        let argv = self.privilege.wrap(&args);
+        match crate::package_managers::run_backend(manager, &argv).await {
+            Ok(_) => {
+                self.deps.invalidate();
+                self.dep_problem_count = None;
+                self.status_message =
+                    Some(format!("`{command}` finished — re-run the health check (d)"));
+            }
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+        self.mark_dirty();
+    }
+
+    async fn handle_restart_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = self.restart_picker.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.restart_picker = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = picker.services.len().saturating_sub(1);
+                let next = picker.state.selected().map_or(0, |i| (i + 1).min(last));
+                picker.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = picker.state.selected().map_or(0, |i| i.saturating_sub(1));
+                picker.state.select(Some(previous));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(index) = picker.state.selected() {
+                    if !picker.selected.remove(&index) {
+                        picker.selected.insert(index);
+                    }
+                }
+            }
+            KeyCode::Enter => {
+                let services: Vec<String> = picker
+                    .services
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| picker.selected.contains(index))
+                    .map(|(_, service)| service.clone())
+                    .collect();
+                if services.is_empty() {
+                    self.status_message =
+                        Some("space marks the services to restart".to_string());
+                    return;
+                }
+                self.restart_picker = None;
+                self.close_dialog();
+                self.request_operation(PendingOperation::RestartServices(services))
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Open the service-restart popup, running the check first when it
+    /// has not run yet this session.
+    async fn open_restart_picker(&mut self) {
+        if self.restart.is_none() {
+            self.status_message = Some("checking restart state...".to_string());
+            self.restart = Some(restart::check().await);
+        }
+        let report = self.restart.as_ref().expect("checked above");
+        if report.services.is_empty() {
+            self.status_message = Some(match &report.reboot {
+                Some(reason) => format!("no services to restart — reboot required: {reason}"),
+                None => "no services need restarting".to_string(),
+            });
+            return;
+        }
+        let services = report.services.clone();
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.restart_picker = Some(RestartPicker {
+            services,
+            selected: HashSet::new(),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// The finding under the cursor on the Security tab, skipping the
+    /// severity header rows the list interleaves.
+    fn selected_finding(&self) -> Option<Finding> {
+        let selected = self.security_state.selected()?;
+        let mut row = 0;
+        for (_, group) in self.security_buckets() {
+            if selected == row {
+                return None; // a severity header
+            }
+            row += 1;
+            for finding in group {
+                if selected == row {
+                    return Some((*finding).clone());
+                }
+                row += 1;
+            }
+        }
+        None
+    }
+
+    /// Open the advisory detail popup for the selected finding, with its
+    /// package's other findings reachable via n/p.
+    fn open_finding_detail(&mut self) {
+        let Some(finding) = self.selected_finding() else {
+            return;
+        };
+        let findings: Vec<Finding> = self
+            .visible_findings()
+            .into_iter()
+            .filter(|other| other.package == finding.package && other.manager == finding.manager)
+            .cloned()
+            .collect();
+        let index = findings
+            .iter()
+            .position(|other| other.id == finding.id)
+            .unwrap_or(0);
+        let detail = self.security.cached_detail(&finding.id);
+        self.finding_detail = Some(FindingDetailView {
+            findings,
+            index,
+            detail,
+            scroll: 0,
+        });
+        self.open_dialog();
+    }
+
+    fn handle_finding_detail_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.finding_detail.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.finding_detail = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => view.scroll = view.scroll.saturating_add(1),
+            KeyCode::Char('k') | KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            // n/p walk the package's findings without leaving the popup.
+            KeyCode::Char('n') | KeyCode::Char('p') => {
+                let last = view.findings.len().saturating_sub(1);
+                view.index = if key.code == KeyCode::Char('n') {
+                    (view.index + 1).min(last)
+                } else {
+                    view.index.saturating_sub(1)
+                };
+                view.scroll = 0;
+                let id = view.findings[view.index].id.clone();
+                view.detail = self.security.cached_detail(&id);
+            }
+            KeyCode::Char('y') => {
+                let Some(finding) = view.findings.get(view.index) else {
+                    return;
+                };
+                // The cached advisory's primary reference when there is
+                // one, else the id's issuing database.
+                let url = view
+                    .detail
+                    .as_ref()
+                    .and_then(|detail| detail.references.first().cloned())
+                    .unwrap_or_else(|| {
+                        crate::features::security::advisory_url(&finding.id)
+                    });
+                self.status_message = Some(match crate::utils::copy_to_clipboard(&url) {
+                    Ok(()) => format!("copied {url}"),
+                    Err(err) => format!("clipboard copy failed: {err}"),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Re-run the restart check and summarize the result in the status
+    /// line; the banner and popup pick the details up from the report.
+    async fn refresh_restart_state(&mut self) {
+        let report = restart::check().await;
+        self.status_message = Some(match (&report.reboot, report.services.len()) {
+            (Some(reason), 0) => format!("reboot required: {reason}"),
+            (Some(reason), count) => {
+                format!("reboot required: {reason}; {count} service(s) need restart (R)")
+            }
+            (None, 0) => "no reboot or service restarts needed".to_string(),
+            (None, count) => format!("{count} service(s) need restart (R)"),
+        });
+        self.restart = Some(report);
+        self.mark_dirty();
+    }
+
+    /// Execute an action from the registry.
+    pub async fn dispatch(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.request_quit(),
+            Action::NextTab => self.next_tab().await,
+            Action::PreviousTab => self.previous_tab().await,
+            Action::JumpTab(tab) => self.jump_to(tab).await,
+            Action::Refresh => {
+                self.load_packages().await;
+                self.load_updates().await;
+                self.load_held().await;
+            }
+            Action::UpdateSystem => self.request_operation(PendingOperation::UpdateSystem).await,
+            Action::UpdateSecurity => self.request_security_updates().await,
+            Action::CleanCache => self.request_operation(PendingOperation::CleanCache).await,
+            Action::ToggleOffline => self.toggle_offline(),
+            Action::ToggleDryRun => self.toggle_dry_run(),
+            Action::TestProxy => self.test_proxy().await,
+            Action::PruneSnapshots => self.request_prune_snapshots().await,
+            Action::ScanVulnerabilities => self.start_vuln_scan(),
+            Action::ToggleIgnoredFindings => {
+                self.show_ignored_findings = !self.show_ignored_findings;
+                self.security_state
+                    .select((!self.visible_findings().is_empty()).then_some(0));
+                self.jump_to(TabId::Security).await;
+                self.mark_dirty();
+            }
+            Action::ExportSecurityReport => self.export_security_report(),
+            Action::ShowThirdPartySources => {
+                self.jump_to(TabId::Security).await;
+                self.open_origin_risk();
+            }
+            Action::ShowSandboxPermissions => {
+                self.jump_to(TabId::Security).await;
+                self.open_permission_view();
+            }
+            Action::CheckDependencyHealth => {
+                self.jump_to(TabId::Overview).await;
+                self.open_dep_health().await;
+            }
+            Action::BrowseOptionalDeps => {
+                self.jump_to(TabId::Packages).await;
+                self.open_optional_deps().await;
+            }
+            Action::ShowOverlaps => {
+                self.jump_to(TabId::Overview).await;
+                self.open_overlaps().await;
+            }
+            Action::CheckRestarts => self.refresh_restart_state().await,
+            Action::ShowRestartPicker => self.open_restart_picker().await,
+            Action::ShowHelp => {
+                self.show_help = true;
+                self.open_dialog();
+            }
+            Action::Prompt(prefix) => {
+                self.mode = Mode::Editing;
+                self.focus = Focus::Input;
+                self.input = prefix.to_string();
+                self.input_cursor = self.input.len();
+            }
+        }
+    }
+
+    async fn handle_normal_key(&mut self, key: KeyEvent) {
+        // Quick-select consumes one keypress: a hint character jumps the
+        // selection, Esc (or `#` again) cancels, anything else falls through
+        // as a normal key. The hint targets were assigned by the last render.
+        if self.hint_mode {
+            self.hint_mode = false;
+            match key.code {
+                KeyCode::Char(c) if HINT_CHARS.contains(c) => {
+                    if let Some(position) = HINT_CHARS.chars().position(|hint| hint == c) {
+                        if let Some(&index) = self.hint_targets.get(position) {
+                            self.current_state().select(Some(index));
+                        }
+                    }
+                    return;
+                }
+                KeyCode::Esc | KeyCode::Char('#') => return,
+                _ => {} // navigation keys exit hint mode and still apply
+            }
+        }
+        // An active type-ahead captures character input until it is cleared.
+        if self.typeahead.is_some() {
+            match key.code {
+                KeyCode::Char(c) => {
+                    if let Some(typeahead) = self.typeahead.as_mut() {
+                        typeahead.push(c);
+                        let buffer = typeahead.buffer.clone();
+                        self.typeahead_jump(&buffer);
+                    }
+                    return;
+                }
+                KeyCode::Esc => {
+                    self.typeahead = None;
+                    return;
+                }
+                _ => self.typeahead = None, // navigation keys fall through
+            }
+        }
+        match key.code {
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.palette = Some(Palette::new());
+                self.open_dialog();
+            }
+            KeyCode::Right | KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.cycle_focus();
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.request_quit();
+            }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.clear_view_state();
+            }
+            KeyCode::Char('q') => self.request_quit(),
+            KeyCode::Tab => self.next_tab().await,
+            KeyCode::BackTab => self.previous_tab().await,
+            KeyCode::Char(c @ '1'..='9') => {
+                self.select_tab(c as usize - '1' as usize).await;
+            }
+            KeyCode::Char('j') | KeyCode::Down if self.focus == Focus::Details => {
+                self.details_scroll = self.details_scroll.saturating_add(1);
+            }
+            KeyCode::Char('k') | KeyCode::Up if self.focus == Focus::Details => {
+                self.details_scroll = self.details_scroll.saturating_sub(1);
+            }
+            KeyCode::Char('g') if self.focus == Focus::Details => self.details_scroll = 0,
+            KeyCode::Char('j') | KeyCode::Down => self.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => self.select_previous(),
+            KeyCode::Char('g') => self.select_first(),
+            KeyCode::Char('G') => self.select_last(),
+            KeyCode::PageUp if self.operation.is_some() => {
+                let page = 10;
+                let bottom = self.operation_log.lines.len();
+                let current = self.operation_log.scroll.unwrap_or(bottom);
+                self.operation_log.scroll = Some(current.saturating_sub(page));
+            }
+            KeyCode::PageDown if self.operation.is_some() => {
+                let page = 10;
+                let bottom = self.operation_log.lines.len();
+                let next = self.operation_log.scroll.unwrap_or(bottom) + page;
+                // Auto-follow re-engages once we are back at the bottom.
+                self.operation_log.scroll = if next >= bottom { None } else { Some(next) };
+            }
+            KeyCode::Char('J') | KeyCode::PageDown => {
+                self.details_scroll = self.details_scroll.saturating_add(1);
+            }
+            KeyCode::Char('K') | KeyCode::PageUp => {
+                self.details_scroll = self.details_scroll.saturating_sub(1);
+            }
+            KeyCode::F(12) => self.debug_overlay = !self.debug_overlay,
+            KeyCode::Char('v') => self.density = self.density.toggle(),
+            KeyCode::Char('s') if self.current_tab() == TabId::Security => {
+                self.start_vuln_scan();
+            }
+            KeyCode::Char('i') if self.current_tab() == TabId::Security => {
+                self.show_ignored_findings = !self.show_ignored_findings;
+                self.security_state
+                    .select((!self.visible_findings().is_empty()).then_some(0));
+                self.mark_dirty();
+            }
+            KeyCode::Char('x') if self.current_tab() == TabId::Security => {
+                self.export_security_report();
+            }
+            KeyCode::Char('o') if self.current_tab() == TabId::Security => {
+                self.open_origin_risk();
+            }
+            KeyCode::Char('p') if self.current_tab() == TabId::Security => {
+                self.open_permission_view();
+            }
+            KeyCode::Enter if self.current_tab() == TabId::Security => {
+                self.open_finding_detail();
+            }
+            KeyCode::Char('S') if self.current_tab() == TabId::Updates => {
+                self.request_security_updates().await;
+            }
+            KeyCode::Char('D') if self.current_tab() == TabId::Updates => {
+                if let Some(name) = self
+                    .updates_state
+                    .selected()
+                    .and_then(|index| self.pending_updates().get(index))
+                    .map(|update| update.name.clone())
+                {
+                    self.deps_diff(&name).await;
+                }
+            }
+            KeyCode::Char('n') if self.current_tab() == TabId::Snapshots => {
+                self.mode = Mode::Editing;
+                self.focus = Focus::Input;
+                self.input = "snapshot ".to_string();
+                self.input_cursor = self.input.len();
+            }
+            KeyCode::Char('d') | KeyCode::Delete
+                if self.current_tab() == TabId::Snapshots =>
+            {
+                self.delete_selected_snapshot().await;
+            }
+            KeyCode::Char('R') if self.current_tab() == TabId::Snapshots => {
+                self.request_snapshot_restore().await;
+            }
+            KeyCode::Char('p') if self.current_tab() == TabId::Snapshots => {
+                self.toggle_snapshot_pin().await;
+            }
+            KeyCode::Char('e') if self.current_tab() == TabId::Snapshots => {
+                if let Some(entry) = self.selected_snapshot_entry() {
+                    self.mode = Mode::Editing;
+                    self.focus = Focus::Input;
+                    self.input = format!("note {}", entry.note.unwrap_or_default());
+                    self.input_cursor = self.input.len();
+                }
+            }
+            KeyCode::Char('/') if self.current_tab() == TabId::Snapshots => {
+                self.mode = Mode::Editing;
+                self.focus = Focus::Input;
+                self.input = format!(
+                    "filter {}",
+                    self.snapshot_filter.clone().unwrap_or_default()
+                );
+                self.input_cursor = self.input.len();
+            }
+            KeyCode::Char(' ') if self.current_tab() == TabId::Snapshots => {
+                self.toggle_snapshot_mark();
+            }
+            KeyCode::Char('D') if self.current_tab() == TabId::Snapshots => {
+                self.open_snapshot_diff().await;
+            }
+            KeyCode::Char('d') if self.current_tab() == TabId::Overview => {
+                self.open_dep_health().await;
+            }
+            KeyCode::Char('O') if self.current_tab() == TabId::Overview => {
+                self.open_overlaps().await;
+            }
+            KeyCode::Char('o') if self.current_tab() == TabId::Packages => {
+                self.open_origin_picker();
+            }
+            KeyCode::Char('O') if self.current_tab() == TabId::Packages => {
+                self.open_optional_deps().await;
+            }
+            KeyCode::Char('#') if self.current_tab() == TabId::Packages => {
+                self.hint_mode = true;
+            }
+            KeyCode::Esc if self.operation.is_some() => {
+                let mut state = ListState::default();
+                state.select(Some(0));
+                self.cancel_prompt = Some(CancelPrompt { state });
+                self.open_dialog();
+            }
+            KeyCode::Char('H') if self.current_tab() == TabId::Packages => {
+                self.show_held_only = !self.show_held_only;
+            }
+            KeyCode::Char('V') if self.current_tab() == TabId::Packages => {
+                self.toggle_unverified_filter().await;
+            }
+            KeyCode::Char('w') if self.current_tab() == TabId::Packages => {
+                if let Some(package) = self.selected_package() {
+                    let (manager, name) = (package.manager.clone(), package.name.clone());
+                    self.explain_package(&manager, &name).await;
+                }
+            }
+            KeyCode::Char('m') => self.open_scope_picker(),
+            KeyCode::Char('b') => self.toggle_watch(),
+            KeyCode::Char('s') => {
+                self.sort_mode = self.sort_mode.toggle();
+                if let Loadable::Loaded(packages) = &mut self.packages {
+                    sort_packages(packages, self.sort_mode);
+                }
+                self.status_message = Some(format!("sorted by {}", self.sort_mode.label()));
+            }
+            KeyCode::Char('<') => {
+                self.split_ratio = (self.split_ratio - 5).max(20);
+            }
+            KeyCode::Char('>') => {
+                self.split_ratio = (self.split_ratio + 5).min(80);
+            }
+            KeyCode::Char('=') => self.split_ratio = DEFAULT_SPLIT_RATIO,
+            KeyCode::Char('?') => {
+                self.show_help = true;
+                self.open_dialog();
+            }
+            KeyCode::Char('\'') => self.typeahead = Some(TypeAhead::new()),
+            KeyCode::Char('/') => {
+                self.mode = Mode::Editing;
+                self.focus = Focus::Input;
+                self.input = "search ".to_string();
+                self.input_cursor = self.input.len();
+            }
+            KeyCode::Char(':') => {
+                self.mode = Mode::Editing;
+                self.focus = Focus::Input;
+                self.input.clear();
+                self.input_cursor = 0;
+            }
+            KeyCode::Char('r') => {
+                self.load_packages().await;
+                self.load_updates().await;
+                self.load_held().await;
+            }
+            KeyCode::Char('u') => self.request_operation(PendingOperation::UpdateSystem).await,
+            KeyCode::Char('c') => self.request_operation(PendingOperation::CleanCache).await,
+            KeyCode::Char('R') => self.open_restart_picker().await,
+            KeyCode::Enter if self.current_tab() == TabId::Overview => {
+                self.activate_overview_row().await;
+            }
+            KeyCode::Enter => self.load_selected_details().await,
+            _ => {}
+        }
+    }
+
+    async fn handle_editing_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.focus = Focus::List;
+                self.input.clear();
+                self.input_cursor = 0;
+                self.history_cursor = None;
+            }
+            KeyCode::Enter => {
+                let command = std::mem::take(&mut self.input);
+                self.input_cursor = 0;
+                self.mode = Mode::Normal;
+                self.focus = Focus::List;
+                self.history_cursor = None;
+                self.remember_command(&command);
+                self.run_command(&command).await;
+            }
+            KeyCode::Backspace => {
+                if let Some(prev) = self.prev_char_boundary() {
+                    self.input.remove(prev);
+                    self.input_cursor = prev;
+                }
+            }
+            KeyCode::Delete if self.input_cursor < self.input.len() => {
+                self.input.remove(self.input_cursor);
+            }
+            KeyCode::Left if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input_cursor = word_boundary_left(&self.input, self.input_cursor);
+            }
+            KeyCode::Right if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input_cursor = word_boundary_right(&self.input, self.input_cursor);
+            }
+            KeyCode::Left => {
+                if let Some(prev) = self.prev_char_boundary() {
+                    self.input_cursor = prev;
+                }
+            }
+            KeyCode::Right => {
+                if let Some(c) = self.input[self.input_cursor..].chars().next() {
+                    self.input_cursor += c.len_utf8();
+                }
+            }
+            KeyCode::Up => self.recall_previous(),
+            KeyCode::Down => self.recall_next(),
+            KeyCode::Home => self.input_cursor = 0,
+            KeyCode::End => self.input_cursor = self.input.len(),
+            KeyCode::Char(c) => {
+                self.input.insert(self.input_cursor, c);
+                self.input_cursor += c.len_utf8();
+            }
+            _ => {}
+        }
+        self.schedule_live_search();
+    }
+
+    /// Record an executed input-bar command, skipping blanks and immediate
+    /// repeats, bounded at `COMMAND_HISTORY_CAP`.
+    fn remember_command(&mut self, command: &str) {
+        if command.trim().is_empty() || self.command_history.last().map(String::as_str) == Some(command)
+        {
+            return;
+        }
+        self.command_history.push(command.to_string());
+        if self.command_history.len() > COMMAND_HISTORY_CAP {
+            self.command_history.remove(0);
+        }
+    }
+
+    /// Replace the input with the previous command history entry (Up).
+    fn recall_previous(&mut self) {
+        if self.command_history.is_empty() {
+            return;
+        }
+        let index = match self.history_cursor {
+            None => self.command_history.len() - 1,
+            Some(index) => index.saturating_sub(1),
+        };
+        self.history_cursor = Some(index);
+        self.input = self.command_history[index].clone();
+        self.input_cursor = self.input.len();
+    }
+
+    /// Step forward through the command history (Down); walking past the
+    /// newest entry clears the input again.
+    fn recall_next(&mut self) {
+        let Some(index) = self.history_cursor else {
+            return;
+        };
+        if index + 1 < self.command_history.len() {
+            self.history_cursor = Some(index + 1);
+            self.input = self.command_history[index + 1].clone();
+        } else {
+            self.history_cursor = None;
+            self.input.clear();
+        }
+        self.input_cursor = self.input.len();
+    }
+
+    /// Insert pasted text at the cursor in one operation, sanitized.
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.mode != Mode::Editing {
+            return;
+        }
+        let clean = sanitize_paste(text);
+        self.input.insert_str(self.input_cursor, &clean);
+        self.input_cursor += clean.len();
+    }
+
+    /// Byte offset of the char boundary before the cursor, if any.
+    fn prev_char_boundary(&self) -> Option<usize> {
+        self.input[..self.input_cursor]
+            .char_indices()
+            .next_back()
+            .map(|(i, _)| i)
+    }
+
+    /// Parse and execute a command typed into the input bar.
+    async fn run_command(&mut self, command: &str) {
+        let mut parts = command.split_whitespace();
+        let Some(verb) = parts.next() else { return };
+        let args: Vec<String> = parts.map(str::to_string).collect();
+
+        match verb {
+            "search" => {
+                let query = args.join(" ");
+                if !query.is_empty() {
+                    self.search_packages(&query).await;
+                    self.jump_to(TabId::Search).await;
+                }
+            }
+            "install" if !args.is_empty() => {
+                self.request_install(args).await;
+            }
+            "verify-file" if args.len() == 1 => self.verify_file_command(&args[0]).await,
+            "remove" if !args.is_empty() => {
+                self.request_operation(PendingOperation::Remove(args)).await;
+            }
+            "update" if matches!(args.first().map(String::as_str), Some("security" | "--security")) => {
+                self.request_security_updates().await;
+            }
+            "update" => self.request_operation(PendingOperation::UpdateSystem).await,
+            "clean" => self.request_operation(PendingOperation::CleanCache).await,
+            "offline" if args.is_empty() => self.toggle_offline(),
+            "dry-run" if args.is_empty() => self.toggle_dry_run(),
+            "proxy" if args.is_empty() => self.test_proxy().await,
+            "report" => self.write_report(args.first().map(String::as_str)),
+            "snapshots" if args.is_empty() => self.jump_to(TabId::Snapshots).await,
+            "scan" if args.is_empty() => {
+                self.start_vuln_scan();
+                self.jump_to(TabId::Security).await;
+            }
+            "security" => self.security_command(&args),
+            "restart-check" if args.is_empty() => self.refresh_restart_state().await,
+            "why" => {
+                let target = if args.is_empty() {
+                    self.selected_package()
+                        .map(|package| (package.manager.clone(), package.name.clone()))
+                } else {
+                    let name = args[0].to_string();
+                    self.installed()
+                        .iter()
+                        .find(|package| package.name == name)
+                        .map(|package| (package.manager.clone(), name.clone()))
+                };
+                match target {
+                    Some((manager, name)) => self.explain_package(&manager, &name).await,
+                    None => {
+                        self.status_message = Some(match args.first() {
+                            Some(name) => format!("{name} is not installed"),
+                            None => "usage: why <package>".to_string(),
+                        });
+                    }
+                }
+            }
+            "consumers" => {
+                let target = if args.is_empty() {
+                    self.selected_package().map(|package| package.name.clone())
+                } else {
+                    Some(args[0].to_string())
+                };
+                match target {
+                    Some(target) => self.show_consumers(&target).await,
+                    None => {
+                        self.status_message =
+                            Some("usage: consumers <package|libname>".to_string());
+                    }
+                }
+            }
+            "cycles" if args.is_empty() => self.show_cycles().await,
+            "heavy" if args.is_empty() => self.open_heavy_deps().await,
+            "deps" => match args.as_slice() {
+                [subcommand, name] if subcommand == "diff" => {
+                    let name = name.clone();
+                    self.deps_diff(&name).await;
+                }
+                _ => self.status_message = Some("usage: deps diff <package>".to_string()),
+            },
+            "compare" => match args.as_slice() {
+                [left, right] => {
+                    let (left, right) = (left.clone(), right.clone());
+                    self.compare_footprints(&left, &right).await;
+                }
+                _ => self.status_message = Some("usage: compare <pkg1> <pkg2>".to_string()),
+            },
+            "snapshot" => {
+                let description = if args.is_empty() {
+                    "manual".to_string()
+                } else {
+                    args.join(" ")
+                };
+                self.create_snapshot(&description).await;
+            }
+            "snapshot-packages" if !args.is_empty() => {
+                self.snapshot_packages(&args.join(" ")).await;
+            }
+            "restore-packages" if !args.is_empty() => {
+                self.request_package_restore(&args.join(" ")).await;
+            }
+            "prune-snapshots" if args.is_empty() => self.request_prune_snapshots().await,
+            "note" => self.set_snapshot_note(&args.join(" ")).await,
+            "filter" => {
+                self.snapshot_filter = Some(args.join(" ")).filter(|text| !text.is_empty());
+                self.snapshots_state
+                    .select((!self.filtered_snapshots().is_empty()).then_some(0));
+                self.mark_dirty();
+            }
+            "profile" if args.first().map(String::as_str) == Some("dump") => {
+                self.dump_profile(args.get(1).map(String::as_str));
+            }
+            "hold" if args.len() == 1 => self.hold_package(&args[0], true).await,
+            "unhold" if args.len() == 1 => self.hold_package(&args[0], false).await,
+            _ => {
+                self.status_message = Some(match actions::suggest_command(verb) {
+                    Some(suggestion) => {
+                        format!("unknown command: {verb} (did you mean `{suggestion}`?)")
+                    }
+                    None => format!("unknown command: {verb}"),
+                });
+            }
+        }
+    }
+
+    /// Keys inside the help reference: `/` starts filtering, typed characters
+    /// narrow the entry list, Esc backs out one level, anything else closes.
+    fn handle_help_key(&mut self, key: KeyEvent) {
+        match (&mut self.help_filter, key.code) {
+            (None, KeyCode::Char('/')) => self.help_filter = Some(String::new()),
+            (None, _) => {
+                self.show_help = false;
+                self.close_dialog();
+            }
+            (Some(filter), KeyCode::Char(c)) => filter.push(c),
+            (Some(filter), KeyCode::Backspace) => {
+                filter.pop();
+            }
+            (Some(_), KeyCode::Esc) => self.help_filter = None,
+            (Some(_), KeyCode::Enter) => {}
+            (Some(_), _) => {
+                self.help_filter = None;
+                self.show_help = false;
+                self.close_dialog();
+            }
+        }
+    }
+
+    /// Remember the current focus and hand it to a dialog overlay.
+    fn open_dialog(&mut self) {
+        if self.focus != Focus::Dialog {
+            self.previous_focus = self.focus;
+        }
+        self.focus = Focus::Dialog;
+    }
+
+    /// Restore the focus that was active before the dialog opened.
+    fn close_dialog(&mut self) {
+        self.focus = self.previous_focus;
+    }
+
+    /// Cycle focus between the list and the details pane (Ctrl+arrows).
+    fn cycle_focus(&mut self) {
+        self.focus = match self.focus {
+            Focus::List if self.current_tab() == TabId::Packages => Focus::Details,
+            _ => Focus::List,
+        };
+    }
+
+    /// Move focus to the pane under a mouse click, if it hit one.
+    fn focus_click(&mut self, column: u16, row: u16) {
+        if self.focus == Focus::Dialog {
+            return; // dialogs are modal
+        }
+        let position = Position::new(column, row);
+        if self.current_tab() == TabId::Packages && self.pane_rects.details.contains(position) {
+            self.focus = Focus::Details;
+        } else if self.pane_rects.list.contains(position) {
+            self.focus = Focus::List;
+        } else if self.mode == Mode::Editing && self.pane_rects.input.contains(position) {
+            self.focus = Focus::Input;
+        }
+    }
+
+    /// Border style for a pane: highlighted while it has focus.
+    pub fn pane_border(&self, pane: Focus) -> Style {
+        if self.focus == pane {
+            self.theme.border_focused
+        } else {
+            self.theme.border
+        }
+    }
+
+    /// Installed packages, or an empty slice while not loaded.
+    pub fn installed(&self) -> &[PackageInfo] {
+        self.packages.value().map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Installed packages after the origin filter, in list order.
+    pub fn installed_visible(&self) -> Vec<&PackageInfo> {
+        self.installed()
+            .iter()
+            .filter(|package| match &self.origin_filter {
+                None => true,
+                Some(origin) => package.origin.as_deref() == Some(origin.as_str()),
+            })
+            .filter(|package| !self.show_held_only || self.is_held(&package.manager, &package.name))
+            .filter(|package| {
+                !self.show_unverified_only
+                    || self.provenance_reason(&package.manager, &package.name).is_some()
+            })
+            .collect()
+    }
+
+    /// Answer "why is this installed?": explicitly, because something
+    /// explicit needs it (with the chains), or not at all — an orphan.
+    async fn explain_package(&mut self, manager_id: &str, name: &str) {
+        let Some(package) = self
+            .installed()
+            .iter()
+            .find(|package| package.manager == manager_id && package.name == name)
+            .cloned()
+        else {
+            self.status_message = Some(format!("{name} is not installed"));
+            return;
+        };
+        let mut lines = Vec::new();
+        if package.explicit == Some(true) {
+            lines.push(match package.install_date {
+                Some(date) => format!(
+                    "explicitly installed {}",
+                    crate::utils::relative_age(date)
+                ),
+                None => "explicitly installed".to_string(),
+            });
+        } else {
+            let explicit: HashSet<String> = self
+                .installed()
+                .iter()
+                .filter(|other| other.manager == manager_id && other.explicit == Some(true))
+                .map(|other| other.name.clone())
+                .collect();
+            let Some(manager) = self.package_managers.get(manager_id).cloned() else {
+                return;
+            };
+            self.status_message = Some(format!("tracing what needs {name}..."));
+            let chains = self
+                .deps
+                .why_chains(manager.as_ref(), name, &explicit, 3)
+                .await
+                .unwrap_or_default();
+            if !chains.is_empty() {
+                for chain in chains {
+                    // Chains arrive target-first, explicit root last.
+                    let parts = chain;
+                    let last = parts.len() - 1;
+                    let rendered: Vec<String> = parts
+                        .into_iter()
+                        .enumerate()
+                        .map(|(index, part)| {
+                            if index == last {
+                                format!("{part} (explicit)")
+                            } else {
+                                part
+                            }
+                        })
+                        .collect();
+                    lines.push(rendered.join(" \u{2190} "));
+                }
+            } else {
+                match self.deps.has_dependents(manager.as_ref(), name).await {
+                    Ok(false) => {
+                        lines.push("required by nothing and not explicitly installed".to_string());
+                        lines.push("an orphan — remove it with: remove ".to_string() + name);
+                    }
+                    Ok(true) => {
+                        lines.push(
+                            "required by other packages, but no chain reaches an explicitly installed one"
+                                .to_string(),
+                        );
+                    }
+                    Err(err) => {
+                        self.status_message = Some(format!("cannot trace {name}: {err}"));
+                        return;
+                    }
+                }
+            }
+        }
+        self.status_message = None;
+        self.message_dialog = Some(MessageDialog {
+            title: format!("Why {name}?"),
+            lines,
+        });
+        self.open_dialog();
+        self.mark_dirty();
+    }
+
+    /// List the installed packages that consume a package's shared
+    /// libraries, marking the ones whose binaries are running right now
+    /// (their processes map one of the libraries). A bare soname like
+    /// "libssl.so.3" resolves to its owning package first.
+    async fn show_consumers(&mut self, target: &str) {
+        let mut lines = Vec::new();
+        let mut name = target.to_string();
+        if target.contains(".so") && !self.installed().iter().any(|package| package.name == target)
+        {
+            let mut resolved = None;
+            for dir in ["/usr/lib", "/usr/lib64", "/lib", "/usr/lib/x86_64-linux-gnu"] {
+                let path = format!("{dir}/{target}");
+                if let Some(owner) = crate::package_managers::owner_of(&path).await {
+                    resolved = Some(owner);
+                    break;
+                }
+            }
+            match resolved {
+                Some(owner) => {
+                    lines.push(format!("{target} is provided by {owner}"));
+                    name = owner;
+                }
+                None => {
+                    self.status_message = Some(format!("cannot resolve {target} to a package"));
+                    return;
+                }
+            }
+        }
+        let Some(package) = self
+            .installed()
+            .iter()
+            .find(|package| package.name == name)
+            .cloned()
+        else {
+            self.status_message = Some(format!("{name} is not installed"));
+            return;
+        };
+        let Some(manager) = self.package_managers.get(&package.manager).cloned() else {
+            return;
+        };
+        self.status_message = Some(format!("looking up consumers of {name}..."));
+        let libs: HashSet<String> = manager
+            .installed_files(&name)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|path| {
+                path.rsplit('/')
+                    .next()
+                    .is_some_and(|file| file.contains(".so"))
+            })
+            .collect();
+        let sonames: HashSet<String> = libs
+            .iter()
+            .filter_map(|path| path.rsplit('/').next().map(str::to_string))
+            .collect();
+        let consumers = self
+            .deps
+            .dependents_of(manager.as_ref(), &name)
+            .await
+            .unwrap_or_default();
+        let running = if crate::utils::host::is_remote() {
+            Vec::new()
+        } else {
+            crate::features::deps::processes_mapping(&libs)
+        };
+        // Map running processes back to packages through their exe, so
+        // consumers can carry a [running] marker.
+        let mut running_owners: HashSet<String> = HashSet::new();
+        let mut seen_exes: HashSet<String> = HashSet::new();
+        for (_, _, exe) in &running {
+            if exe.is_empty() || !seen_exes.insert(exe.clone()) {
+                continue;
+            }
+            if let Some(owner) = crate::package_managers::owner_of(exe).await {
+                running_owners.insert(owner);
+            }
+        }
+        lines.push(if libs.is_empty() {
+            format!("{name} ships no shared libraries")
+        } else {
+            format!("{name} ships {} shared object file(s)", libs.len())
+        });
+        if consumers.is_empty() {
+            lines.push("no installed package declares a dependency on it".to_string());
+        }
+        // Checking which soname each consumer declares costs one query
+        // per consumer; past a screenful the markers are not worth it.
+        let annotate = consumers.len() <= 40 && !sonames.is_empty();
+        for consumer in &consumers {
+            let mut line = format!("  {consumer}");
+            if annotate {
+                let declared = self
+                    .deps
+                    .dependencies(manager.as_ref(), consumer)
+                    .await
+                    .unwrap_or_default();
+                let links = declared.iter().any(|dep| {
+                    let base = dep.split('=').next().unwrap_or(dep);
+                    base.contains(".so")
+                        && sonames
+                            .iter()
+                            .any(|soname| soname.starts_with(base) || base.starts_with(soname.as_str()))
+                });
+                if links {
+                    line.push_str(" — links its shared libraries");
+                }
+            }
+            if running_owners.contains(consumer) {
+                line.push_str(" [running]");
+            }
+            lines.push(line);
+        }
+        if !running.is_empty() {
+            let processes: Vec<String> = running
+                .iter()
+                .map(|(pid, comm, _)| format!("{comm} ({pid})"))
+                .collect();
+            lines.push(format!(
+                "mapped by running processes: {}",
+                processes.join(", ")
+            ));
+        }
+        self.status_message = None;
+        self.message_dialog = Some(MessageDialog {
+            title: format!("Consumers of {name}"),
+            lines,
+        });
+        self.open_dialog();
+        self.mark_dirty();
+    }
+
+    /// The `cycles` command: pull every scoped manager's whole edge
+    /// table in one query each, run the strongly-connected-component
+    /// pass, and report each dependency cycle with the edges that form
+    /// it. The report is also written to a file so it can be copied out
+    /// of the terminal. Cycles touching the distro's circular-by-design
+    /// base set are collapsed to their member list — still listed, just
+    /// not expanded edge by edge.
+    async fn show_cycles(&mut self) {
+        const BENIGN: [&str; 8] = [
+            "glibc",
+            "gcc-libs",
+            "filesystem",
+            "libc6",
+            "libgcc-s1",
+            "glibc-common",
+            "bash",
+            "systemd",
+        ];
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        self.status_message = Some("scanning for dependency cycles...".to_string());
+        let mut graph = crate::features::deps::DependencyManager::new();
+        let mut notes = Vec::new();
+        let mut scanned = 0usize;
+        for manager in managers {
+            match manager.all_dependency_edges().await {
+                Ok(table) => {
+                    scanned += 1;
+                    graph.absorb(manager.id(), table);
+                }
+                Err(crate::error::PkgError::Unsupported { .. }) => {
+                    notes.push(format!("{}: bulk listing unsupported; skipped", manager.id()));
+                }
+                Err(err) => notes.push(format!("{}: {err}", manager.id())),
+            }
+        }
+        self.status_message = None;
+        if scanned == 0 {
+            self.status_message = Some("no scoped manager can list its whole graph".to_string());
+            return;
+        }
+        let cycles = graph.cycles();
+        let mut lines = Vec::new();
+        if cycles.is_empty() {
+            lines.push("no dependency cycles found".to_string());
+        }
+        for cycle in &cycles {
+            let benign = cycle
+                .members
+                .iter()
+                .any(|member| BENIGN.contains(&member.as_str()));
+            if benign {
+                lines.push(format!(
+                    "cycle of {} packages (known-benign, collapsed): {}",
+                    cycle.members.len(),
+                    cycle.members.join(", ")
+                ));
+                continue;
+            }
+            lines.push(format!(
+                "cycle of {} packages: {}",
+                cycle.members.len(),
+                cycle.members.join(", ")
+            ));
+            for (from, to) in &cycle.edges {
+                lines.push(format!("  {from} -> {to}"));
+            }
+        }
+        if !cycles.is_empty() {
+            let path = chrono::Local::now()
+                .format("pkgtool-cycles-%Y%m%d-%H%M%S.txt")
+                .to_string();
+            lines.push(match std::fs::write(&path, lines.join("\n") + "\n") {
+                Ok(()) => format!("report written to {path}"),
+                Err(err) => format!("could not write {path}: {err}"),
+            });
+        }
+        lines.extend(notes);
+        self.message_dialog = Some(MessageDialog {
+            title: "Dependency cycles".to_string(),
+            lines,
+        });
+        self.open_dialog();
+        self.mark_dirty();
+    }
+
+    /// The `heavy` command: the largest non-explicit packages only one
+    /// explicit root keeps installed — candidates to reconsider, never
+    /// to auto-remove. The scan absorbs the bulk edge tables once and
+    /// is cached until the installed set changes.
+    async fn open_heavy_deps(&mut self) {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for package in self.installed() {
+            (&package.manager, &package.name, package.explicit).hash(&mut hasher);
+        }
+        let fingerprint = hasher.finish();
+        let entries = match &self.heavy_cache {
+            Some((cached, entries)) if *cached == fingerprint => entries.clone(),
+            _ => {
+                let Some(entries) = self.compute_heavy_deps().await else {
+                    return;
+                };
+                self.heavy_cache = Some((fingerprint, entries.clone()));
+                entries
+            }
+        };
+        if entries.is_empty() {
+            self.status_message =
+                Some("no heavy single-root dependencies found".to_string());
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.heavy_deps = Some(HeavyDepsView { entries, state });
+        self.open_dialog();
+    }
+
+    /// The scan behind `heavy`: every explicit root's required closure,
+    /// budget-capped, reduced to the largest packages reached from
+    /// exactly one root. `None` when no scoped manager can provide the
+    /// graph.
+    async fn compute_heavy_deps(&mut self) -> Option<Vec<HeavyEntry>> {
+        // Enough for a full desktop system's edges; a pathological graph
+        // truncates the answer instead of stalling the UI.
+        const STEP_BUDGET: usize = 500_000;
+        const LIMIT: usize = 15;
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        self.status_message = Some("weighing dependency subtrees...".to_string());
+        let mut graph = crate::features::deps::DependencyManager::new();
+        let mut failures = Vec::new();
+        let mut scanned = 0usize;
+        for manager in managers {
+            match manager.all_dependency_edges().await {
+                Ok(table) => {
+                    scanned += 1;
+                    graph.absorb(manager.id(), table);
+                }
+                Err(crate::error::PkgError::Unsupported { .. }) => {}
+                Err(err) => failures.push(format!("{}: {err}", manager.id())),
+            }
+        }
+        self.status_message = None;
+        if scanned == 0 {
+            self.status_message = Some("no scoped manager can list its whole graph".to_string());
+            return None;
+        }
+        if !failures.is_empty() {
+            self.status_message = Some(format!("graph incomplete: {}", failures.join("; ")));
+        }
+        let explicit: Vec<(String, String)> = self
+            .installed()
+            .iter()
+            .filter(|package| package.explicit == Some(true))
+            .map(|package| (package.manager.clone(), package.name.clone()))
+            .collect();
+        let sizes: HashMap<(String, String), u64> = self
+            .installed()
+            .iter()
+            .filter_map(|package| {
+                package
+                    .size
+                    .map(|size| ((package.manager.clone(), package.name.clone()), size))
+            })
+            .collect();
+        let mut singles: Vec<(crate::features::deps::SingleRootDep, u64)> = graph
+            .single_root_deps(&explicit, STEP_BUDGET)
+            .into_iter()
+            .filter_map(|single| {
+                let size = *sizes.get(&(single.manager.clone(), single.package.clone()))?;
+                Some((single, size))
+            })
+            .collect();
+        singles.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+        singles.truncate(LIMIT);
+        Some(
+            singles
+                .into_iter()
+                .map(|(single, size)| {
+                    let line = format!(
+                        "{} ({}) is only needed by {} [{}]",
+                        single.package,
+                        crate::utils::format_size(size),
+                        single.root,
+                        single.manager
+                    );
+                    (single.manager, single.package, line)
+                })
+                .collect(),
+        )
+    }
+
+    /// Advisory only: Enter traces why the selected package is
+    /// installed; nothing here removes anything.
+    async fn handle_heavy_deps_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.heavy_deps.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.heavy_deps = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.entries.len().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let Some((manager, package)) = view
+                    .state
+                    .selected()
+                    .and_then(|index| view.entries.get(index))
+                    .map(|(manager, package, _)| (manager.clone(), package.clone()))
+                else {
+                    return;
+                };
+                self.heavy_deps = None;
+                self.close_dialog();
+                self.explain_package(&manager, &package).await;
+            }
+            _ => {}
+        }
+    }
+
+    /// The `compare` command: two install footprints side by side, for
+    /// choosing the lighter of two candidates.
+    async fn compare_footprints(&mut self, left: &str, right: &str) {
+        self.status_message = Some(format!("computing footprints of {left} and {right}..."));
+        let mut footprints = Vec::new();
+        for name in [left, right] {
+            match self.compute_install_footprint(&[name.to_string()]).await {
+                Some(footprint) => footprints.push(footprint),
+                None => {
+                    self.status_message = Some(format!("could not compute a footprint for {name}"));
+                    return;
+                }
+            }
+        }
+        self.status_message = None;
+        let size = |bytes: Option<u64>| {
+            bytes.map_or_else(|| "unknown".to_string(), crate::utils::format_size)
+        };
+        let rows = [
+            ("".to_string(), left.to_string(), right.to_string()),
+            (
+                "new packages".to_string(),
+                footprints[0].packages.len().to_string(),
+                footprints[1].packages.len().to_string(),
+            ),
+            (
+                "download".to_string(),
+                size(footprints[0].download_bytes),
+                size(footprints[1].download_bytes),
+            ),
+            (
+                "installed".to_string(),
+                size(footprints[0].installed_bytes),
+                size(footprints[1].installed_bytes),
+            ),
+        ];
+        let width = rows.iter().map(|(_, a, _)| a.len()).max().unwrap_or(0);
+        let lines = rows
+            .iter()
+            .map(|(label, a, b)| format!("{label:<14} {a:<width$}   {b}"))
+            .collect();
+        self.message_dialog = Some(MessageDialog {
+            title: "Install footprints".to_string(),
+            lines,
+        });
+        self.open_dialog();
+        self.mark_dirty();
+    }
+
+    /// The `deps diff` command (`D` on an Updates row): what the
+    /// pending version of a package requires compared to the installed
+    /// one, so a major upgrade's new baggage is visible up front.
+    async fn deps_diff(&mut self, name: &str) {
+        let Some(package) = self
+            .installed()
+            .iter()
+            .find(|package| package.name == name)
+            .cloned()
+        else {
+            self.status_message = Some(format!("{name} is not installed"));
+            return;
+        };
+        let Some(candidate) = self
+            .pending_updates()
+            .iter()
+            .find(|update| update.name == name && update.manager == package.manager)
+            .map(|update| update.new_version.clone())
+        else {
+            self.status_message = Some(format!("no pending update for {name}"));
+            return;
+        };
+        let Some(manager) = self.package_managers.get(&package.manager).cloned() else {
+            return;
+        };
+        self.status_message = Some(format!(
+            "diffing dependencies of {name} {} -> {candidate}...",
+            package.version
+        ));
+        let current = match manager.version_dependencies(name, &package.version).await {
+            Ok(deps) => deps,
+            Err(err) => {
+                self.status_message =
+                    Some(format!("cannot read the installed dependency list: {err}"));
+                return;
+            }
+        };
+        let upcoming = match manager.version_dependencies(name, &candidate).await {
+            Ok(deps) => deps,
+            // Mirror lag: the update is announced but its metadata has
+            // not reached this mirror yet. Say so rather than diffing
+            // against nothing.
+            Err(crate::error::PkgError::NotFound(detail)) => {
+                self.status_message =
+                    Some(format!("candidate metadata unavailable: {detail}"));
+                return;
+            }
+            Err(err) => {
+                self.status_message = Some(err.to_string());
+                return;
+            }
+        };
+        self.status_message = None;
+        let sizes: HashMap<&str, u64> = self
+            .installed()
+            .iter()
+            .filter(|other| other.manager == package.manager)
+            .filter_map(|other| other.size.map(|size| (other.name.as_str(), size)))
+            .collect();
+        let annotate = |name: &str| match sizes.get(name) {
+            Some(size) => format!("{name} ({})", crate::utils::format_size(*size)),
+            None => name.to_string(),
+        };
+        let current_set: HashSet<&str> = current.iter().map(String::as_str).collect();
+        let upcoming_set: HashSet<&str> = upcoming.iter().map(String::as_str).collect();
+        let mut lines = Vec::new();
+        for dep in &upcoming {
+            if !current_set.contains(dep.as_str()) {
+                lines.push(format!("+ {}", annotate(dep)));
+            }
+        }
+        for dep in &current {
+            if !upcoming_set.contains(dep.as_str()) {
+                lines.push(format!("- {}", annotate(dep)));
+            }
+        }
+        if lines.is_empty() {
+            lines.push("no dependency changes".to_string());
+        }
+        self.message_dialog = Some(MessageDialog {
+            title: format!(
+                "Dependency diff: {name} {} -> {candidate}",
+                package.version
+            ),
+            lines,
+        });
+        self.open_dialog();
+        self.mark_dirty();
+    }
+
+    /// Why a package was flagged by the provenance survey, if it was.
+    pub fn provenance_reason(&self, manager: &str, name: &str) -> Option<&str> {
+        self.provenance
+            .as_ref()?
+            .get(&(manager.to_string(), name.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Toggle the unverified-packages filter, running the provenance
+    /// survey across the scoped managers on first use.
+    async fn toggle_unverified_filter(&mut self) {
+        if self.show_unverified_only {
+            self.show_unverified_only = false;
+            return;
+        }
+        if self.provenance.is_none() {
+            self.status_message = Some("surveying package provenance...".to_string());
+            let mut flagged = HashMap::new();
+            for id in self.scope_ids() {
+                let survey = crate::package_managers::provenance_survey(&id).await;
+                flagged.extend(
+                    survey
+                        .into_iter()
+                        .map(|(name, reason)| ((id.clone(), name), reason)),
+                );
+            }
+            self.provenance = Some(flagged);
+        }
+        self.show_unverified_only = true;
+        let count = self.installed_visible().len();
+        self.status_message = Some(format!(
+            "{count} package(s) without a trusted builder (V clears the filter)"
+        ));
+        self.package_state.select((count > 0).then_some(0));
+        self.mark_dirty();
+    }
+
+    pub fn is_held(&self, manager: &str, name: &str) -> bool {
+        self.held
+            .contains(&(manager.to_string(), name.to_string()))
+    }
+
+    /// Refresh the cached hold state from every scoped backend.
+    pub async fn load_held(&mut self) {
+        let mut held = HashSet::new();
+        for manager in self.scoped_managers() {
+            if let Ok(names) = manager.list_held().await {
+                let id = manager.id().to_string();
+                held.extend(names.into_iter().map(|name| (id.clone(), name)));
+            }
+        }
+        self.held = held;
+    }
+
+    /// Title of the packages pane: a breadcrumb of everything currently
+    /// shaping the view, so the list never looks filtered for no visible
+    /// reason. Trailing segments are dropped on narrow panes.
+    pub fn packages_breadcrumb(&self, max_width: usize) -> String {
+        let mut parts = vec![format!("Installed ({})", self.installed_visible().len())];
+        if let Some(origin) = &self.origin_filter {
+            parts.push(format!("origin:{origin}"));
+        }
+        if self.show_held_only {
+            parts.push("held-only".to_string());
+        }
+        if self.show_unverified_only {
+            parts.push("unverified-only".to_string());
+        }
+        if self.sort_mode != SortMode::Name {
+            parts.push(format!("sort:{}", self.sort_mode.label()));
+        }
+        if self.enabled_managers.len() != self.package_managers.len() {
+            let mut ids: Vec<&str> = self.enabled_managers.iter().map(String::as_str).collect();
+            ids.sort_unstable();
+            parts.push(format!("[{}]", ids.join(",")));
+        }
+        let mut crumb = parts.join(" \u{25b8} ");
+        while crumb.chars().count() > max_width && parts.len() > 1 {
+            parts.pop();
+            crumb = format!("{} \u{25b8} ...", parts.join(" \u{25b8} "));
+        }
+        crumb
+    }
+
+    /// Reset filter, sort and manager scope back to their defaults (Ctrl+L).
+    fn clear_view_state(&mut self) {
+        self.origin_filter = None;
+        self.show_held_only = false;
+        self.show_unverified_only = false;
+        self.sort_mode = SortMode::Name;
+        if let Loadable::Loaded(packages) = &mut self.packages {
+            sort_packages(packages, self.sort_mode);
+        }
+        self.enabled_managers = self.package_managers.keys().cloned().collect();
+        self.status_message = Some("view reset to defaults".to_string());
+    }
+
+    /// Pending updates, or an empty slice while not loaded.
+    pub fn pending_updates(&self) -> &[PackageUpdate] {
+        self.updates.value().map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The pending updates that fix vulnerabilities: flagged by the
+    /// backend (apt's `-security` suites, dnf's updateinfo) or matching
+    /// a finding of the last vulnerability scan.
+    pub fn security_updates(&self) -> Vec<&PackageUpdate> {
+        let today = Utc::now().date_naive();
+        let findings: Vec<&Finding> = self
+            .vulns
+            .value()
+            .map(|report| {
+                report
+                    .findings
+                    .iter()
+                    .filter(|finding| !self.security_ignores.is_ignored(&finding.id, today))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.pending_updates()
+            .iter()
+            .filter(|update| {
+                update.security
+                    || findings.iter().any(|finding| {
+                        finding.package == update.name && finding.manager == update.manager
+                    })
+            })
+            .collect()
+    }
+
+    /// The findings the Security tab currently shows: the active ones,
+    /// or — under the ignored filter — the triaged ones.
+    pub fn visible_findings(&self) -> Vec<&Finding> {
+        let today = Utc::now().date_naive();
+        self.vulns
+            .value()
+            .map(|report| {
+                report
+                    .findings
+                    .iter()
+                    .filter(|finding| {
+                        self.security_ignores.is_ignored(&finding.id, today)
+                            == self.show_ignored_findings
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Visible findings grouped into severity buckets, worst first. The
+    /// report is sorted by severity, so the groups come out in order.
+    pub fn security_buckets(&self) -> Vec<(Severity, Vec<&Finding>)> {
+        let mut buckets: Vec<(Severity, Vec<&Finding>)> = Vec::new();
+        for finding in self.visible_findings() {
+            match buckets.last_mut() {
+                Some((severity, group)) if *severity == finding.severity => group.push(finding),
+                _ => buckets.push((finding.severity, vec![finding])),
+            }
+        }
+        buckets
+    }
+
+    /// Search results, or an empty slice while not loaded.
+    pub fn search_hits(&self) -> &[PackageInfo] {
+        self.search_results.value().map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The tab currently being displayed.
+    pub fn current_tab(&self) -> TabId {
+        self.tabs[self.selected_tab]
+    }
+
+    pub async fn next_tab(&mut self) {
+        let next = (self.selected_tab + 1) % self.tabs.len();
+        self.select_tab(next).await;
+    }
+
+    pub async fn previous_tab(&mut self) {
+        let previous = (self.selected_tab + self.tabs.len() - 1) % self.tabs.len();
+        self.select_tab(previous).await;
+    }
+
+    /// Switch to a tab by index, lazily loading its data on first visit.
+    pub async fn select_tab(&mut self, index: usize) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        self.selected_tab = index;
+        if self.current_tab() == TabId::Updates && self.updates.is_not_loaded() {
+            self.load_updates().await;
+        }
+        if self.current_tab() == TabId::Snapshots && self.snapshot_list.is_not_loaded() {
+            self.load_snapshots().await;
+        }
+    }
+
+    /// Switch to a tab by id, if it is registered.
+    pub async fn jump_to(&mut self, tab: TabId) {
+        if let Some(index) = self.tabs.iter().position(|t| *t == tab) {
+            self.select_tab(index).await;
+        }
+    }
+
+    fn list_len(&self, tab: TabId) -> usize {
+        match tab {
+            TabId::Overview => self.overview_rows().len(),
+            TabId::Packages => self.installed_visible().len(),
+            TabId::Updates => self.pending_updates().len(),
+            TabId::Search => self.search_hits().len(),
+            TabId::Log => self.log.len(),
+            TabId::Snapshots => self.filtered_snapshots().len(),
+            // One row per finding plus a header row per severity bucket.
+            TabId::Security => self
+                .security_buckets()
+                .iter()
+                .map(|(_, group)| group.len() + 1)
+                .sum(),
+        }
+    }
+
+    fn current_list_len(&self) -> usize {
+        self.list_len(self.current_tab())
+    }
+
+    fn state_for(&mut self, tab: TabId) -> &mut ListState {
+        match tab {
+            TabId::Overview => &mut self.overview_state,
+            TabId::Packages => &mut self.package_state,
+            TabId::Updates => &mut self.updates_state,
+            TabId::Search => &mut self.search_state,
+            TabId::Log => &mut self.log_state,
+            TabId::Snapshots => &mut self.snapshots_state,
+            TabId::Security => &mut self.security_state,
+        }
+    }
+
+    fn current_state(&mut self) -> &mut ListState {
+        self.state_for(self.current_tab())
+    }
+
+    fn select_next(&mut self) {
+        let len = self.current_list_len();
+        if len == 0 {
+            return;
+        }
+        let state = self.current_state();
+        let next = state.selected().map_or(0, |i| (i + 1).min(len - 1));
+        state.select(Some(next));
+    }
+
+    fn select_previous(&mut self) {
+        if self.current_list_len() == 0 {
+            return;
+        }
+        let state = self.current_state();
+        let previous = state.selected().map_or(0, |i| i.saturating_sub(1));
+        state.select(Some(previous));
+    }
+
+    fn select_first(&mut self) {
+        if self.current_list_len() > 0 {
+            self.current_state().select(Some(0));
+        }
+    }
+
+    fn select_last(&mut self) {
+        let len = self.current_list_len();
+        if len > 0 {
+            self.current_state().select(Some(len - 1));
+        }
+    }
+
+    /// Names of the entries in the currently displayed list, in order.
+    fn current_names(&self) -> Vec<&str> {
+        match self.current_tab() {
+            TabId::Overview => Vec::new(),
+            TabId::Packages => self
+                .installed_visible()
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect(),
+            TabId::Updates => self
+                .pending_updates()
+                .iter()
+                .map(|u| u.name.as_str())
+                .collect(),
+            TabId::Search => self.search_hits().iter().map(|p| p.name.as_str()).collect(),
+            TabId::Log => Vec::new(),
+            TabId::Snapshots => Vec::new(),
+            TabId::Security => Vec::new(),
+        }
+    }
+
+    /// Jump the selection to the first entry whose name starts with `prefix`
+    /// (case-insensitive), scanning past the current selection and wrapping.
+    fn typeahead_jump(&mut self, prefix: &str) {
+        let prefix = prefix.to_lowercase();
+        let names = self.current_names();
+        if names.is_empty() || prefix.is_empty() {
+            return;
+        }
+        let start = self.current_state_ref().selected().unwrap_or(0);
+        let found = (0..names.len())
+            .map(|offset| (start + offset) % names.len())
+            .find(|&i| names[i].to_lowercase().starts_with(&prefix));
+        if let Some(index) = found {
+            self.current_state().select(Some(index));
+        }
+    }
+
+    /// Drop the type-ahead buffer once its timeout has passed.
+    pub fn expire_typeahead(&mut self) {
+        if self.typeahead.as_ref().is_some_and(TypeAhead::expired) {
+            self.typeahead = None;
+        }
+    }
+
+    fn current_state_ref(&self) -> &ListState {
+        match self.current_tab() {
+            TabId::Overview => &self.overview_state,
+            TabId::Packages => &self.package_state,
+            TabId::Updates => &self.updates_state,
+            TabId::Search => &self.search_state,
+            TabId::Log => &self.log_state,
+            TabId::Snapshots => &self.snapshots_state,
+            TabId::Security => &self.security_state,
+        }
+    }
+
+    /// The package currently under the cursor on the active tab, if any.
+    pub fn selected_package(&self) -> Option<&PackageInfo> {
+        match self.current_tab() {
+            TabId::Packages => self
+                .package_state
+                .selected()
+                .and_then(|i| self.installed_visible().get(i).copied()),
+            TabId::Search => self
+                .search_state
+                .selected()
+                .and_then(|i| self.search_hits().get(i)),
+            TabId::Updates | TabId::Overview | TabId::Log | TabId::Snapshots | TabId::Security => {
+                None
+            }
+        }
+    }
+
+    /// Summary rows for the Overview tab: text plus an optional tab to jump
+    /// to when the row is activated.
+    pub fn overview_rows(&self) -> Vec<(String, Option<TabId>)> {
+        let mut rows = Vec::new();
+        match &self.packages {
+            Loadable::NotLoaded | Loadable::Loading => {
+                rows.push(("Loading installed packages...".to_string(), None));
+            }
+            Loadable::Failed(err) => {
+                rows.push((format!("Package list failed: {err}"), Some(TabId::Packages)));
+            }
+            Loadable::Loaded(packages) => {
+                let mut counts: Vec<(String, usize)> = Vec::new();
+                for package in packages {
+                    match counts.iter_mut().find(|(m, _)| *m == package.manager) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((package.manager.clone(), 1)),
+                    }
+                }
+                counts.sort();
+                for (manager, count) in counts {
+                    rows.push((
+                        format!("{count} packages installed [{manager}]"),
+                        Some(TabId::Packages),
+                    ));
+                }
+            }
+        }
+        if self.updates.is_loaded() {
+            rows.push((
+                format!("{} updates pending", self.pending_updates().len()),
+                Some(TabId::Updates),
+            ));
+        } else {
+            rows.push((
+                "Updates not checked yet (Enter to check)".to_string(),
+                Some(TabId::Updates),
+            ));
+        }
+        match self.dep_problem_count {
+            Some(0) => rows.push(("dependency health: no problems found".to_string(), None)),
+            Some(count) => rows.push((
+                format!("dependency problems: {count} (d to inspect)"),
+                None,
+            )),
+            None => rows.push((
+                "dependency health not checked yet (d to check)".to_string(),
+                None,
+            )),
+        }
+        match self.overlap_count {
+            Some(0) => rows.push(("no cross-manager overlaps".to_string(), None)),
+            Some(count) => rows.push((
+                format!("cross-manager overlaps: {count} (O to inspect)"),
+                None,
+            )),
+            None => rows.push((
+                "overlaps not checked yet (O to check)".to_string(),
+                None,
+            )),
+        }
+        // A running `pkgtool watch` leaves its last count in the state
+        // file; show it while it is no older than two check intervals.
+        let watch_window = crate::features::watch::interval(&self.config).saturating_mul(2);
+        if let Some(state) = crate::features::watch::load_fresh(watch_window) {
+            rows.push((
+                format!(
+                    "watcher: {} updates ({} security), checked {}",
+                    state.total,
+                    state.security,
+                    crate::utils::relative_age(state.timestamp),
+                ),
+                Some(TabId::Updates),
+            ));
+        }
+        if let Some(usage) = &self.snapshot_usage {
+            let labeled = |label: &str, bytes: Option<u64>| {
+                bytes.map(|bytes| format!("{} {label}", crate::utils::format_size(bytes)))
+            };
+            let parts: Vec<String> = [
+                labeled("in snapshots", usage.used_bytes),
+                labeled("free", usage.free_bytes),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !parts.is_empty() {
+                rows.push((
+                    format!(
+                        "snapshots: {} [{}]",
+                        parts.join(", "),
+                        self.snapshots.backend_id()
+                    ),
+                    Some(TabId::Snapshots),
+                ));
+            }
+        }
+        let recent = self.history.entries().iter().rev().take(5);
+        for transaction in recent {
+            rows.push((
+                format!(
+                    "{}  {} {} [{}] {}",
+                    transaction.timestamp.format("%Y-%m-%d %H:%M"),
+                    transaction.action,
+                    transaction.packages.join(" "),
+                    transaction.manager,
+                    if transaction.success { "ok" } else { "failed" },
+                ),
+                None,
+            ));
+        }
+        rows
+    }
+
+    /// Activate the selected Overview row, jumping to its target tab.
+    async fn activate_overview_row(&mut self) {
+        let target = self
+            .overview_state
+            .selected()
+            .and_then(|i| self.overview_rows().get(i).and_then(|(_, tab)| *tab));
+        if let Some(tab) = target {
+            self.jump_to(tab).await;
+        }
+    }
+
+    /// Fill the package and updates views from the disk cache, so the UI
+    /// has content while the real load still runs. Stale or missing entries
+    /// simply leave the views in their not-loaded state.
+    fn populate_from_cache(&mut self) {
+        let mut packages: Vec<PackageInfo> = Vec::new();
+        let mut updates: Vec<PackageUpdate> = Vec::new();
+        // Offline, any cached data beats none; label it with its age.
+        let mut oldest: Option<chrono::DateTime<Utc>> = None;
+        for id in self.scope_ids() {
+            if self.offline() {
+                if let Some((mut cached, written)) =
+                    self.cache.load_stale::<Vec<PackageInfo>>(&id, "packages")
+                {
+                    packages.append(&mut cached);
+                    oldest = Some(oldest.map_or(written, |old| old.min(written)));
+                }
+                if let Some((mut cached, written)) =
+                    self.cache.load_stale::<Vec<PackageUpdate>>(&id, "updates")
+                {
+                    updates.append(&mut cached);
+                    oldest = Some(oldest.map_or(written, |old| old.min(written)));
+                }
+                continue;
+            }
+            if let Some(mut cached) = self.cache.load::<Vec<PackageInfo>>(&id, "packages") {
+                packages.append(&mut cached);
+            }
+            if let Some(mut cached) = self.cache.load::<Vec<PackageUpdate>>(&id, "updates") {
+                updates.append(&mut cached);
+            }
+        }
+        if !packages.is_empty() {
+            sort_packages(&mut packages, self.sort_mode);
+            self.status_message = Some(match oldest {
+                Some(written) => format!(
+                    "{} packages (cached, written {})",
+                    packages.len(),
+                    crate::utils::relative_age(written)
+                ),
+                None => format!("{} packages (cached)", packages.len()),
+            });
+            self.packages = Loadable::Loaded(packages);
+            if self.package_state.selected().is_none() {
+                self.package_state.select(Some(0));
+            }
+        }
+        if !updates.is_empty() {
+            updates.sort_by(|a, b| a.name.cmp(&b.name));
+            self.updates = Loadable::Loaded(updates);
+            if self.updates_state.selected().is_none() {
+                self.updates_state.select(Some(0));
+            }
+        }
+        // The last vulnerability report is cheap to show while a fresh scan
+        // (if one is due) runs in the background.
+        if let Some((report, fingerprint)) = crate::features::security::load_last_scan() {
+            self.security_state
+                .select((!report.findings.is_empty()).then_some(0));
+            self.vulns = Loadable::Loaded(report);
+            self.last_scan_fingerprint = Some(fingerprint);
+        }
+        self.clamp_selections();
+    }
+
+    /// Write per-manager slices of a loaded dataset back to the disk cache.
+    fn store_in_cache<T: serde::Serialize>(&self, dataset: &str, rows: &[T], manager_of: fn(&T) -> &str) {
+        for id in self.scope_ids() {
+            let slice: Vec<&T> = rows.iter().filter(|row| manager_of(row) == id).collect();
+            if let Err(err) = self.cache.store(&id, dataset, &slice) {
+                log::warn!("could not write {id} {dataset} cache: {err}");
+            }
+        }
+    }
+
+    /// Run one query against every scoped manager concurrently.
+    ///
+    /// Each call runs in its own task under a per-manager timeout, so one
+    /// hung backend delays nothing and blocks nobody. Results arrive tagged
+    /// with the manager id; errors are stringified for notification display.
+    async fn query_managers<T, Fut>(
+        &self,
+        operation: &'static str,
+        query: impl Fn(Arc<dyn PackageManager>) -> Fut,
+    ) -> Vec<(String, std::result::Result<Vec<T>, String>)>
+    where
+        Fut: std::future::Future<Output = crate::error::Result<Vec<T>>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let timeout = Duration::from_secs(self.config.manager_timeout_secs.max(1));
+        let mut blocked = Vec::new();
+        let mut tasks = tokio::task::JoinSet::new();
+        for id in self.scope_ids() {
+            let Some(manager) = self.package_managers.get(&id).cloned() else {
+                continue;
+            };
+            if self.offline() && manager.network_operations().contains(&operation) {
+                // Explain instead of timing out against a missing network.
+                blocked.push((id, Err("disabled by offline mode".to_string())));
+                continue;
+            }
+            let network_bound = manager.network_operations().contains(&operation);
+            let limiter = self.limiter.clone();
+            let call = query(manager);
+            tasks.spawn(async move {
+                // Acquire before the timeout starts, so waiting for a slot
+                // is never mistaken for a slow backend.
+                let _permit = limiter.acquire(network_bound).await;
+                match tokio::time::timeout(timeout, call).await {
+                    Ok(Ok(rows)) => (id, Ok(rows)),
+                    Ok(Err(err)) => (id, Err(err.to_string())),
+                    Err(_) => (id, Err("timed out".to_string())),
+                }
+            });
+        }
+        let mut results = blocked;
+        while let Some(Ok(result)) = tasks.join_next().await {
+            results.push(result);
+        }
+        results
+    }
+
+    pub async fn load_packages(&mut self) {
+        self.packages = Loadable::Loading;
+        let mut packages = Vec::new();
+        let mut errors = Vec::new();
+        for (id, result) in self
+            .query_managers("list", |manager| async move { manager.list_installed().await })
+            .await
+        {
+            match result {
+                Ok(mut list) => packages.append(&mut list),
+                Err(err) => errors.push(format!("{id}: {err}")),
+            }
+        }
+        if packages.is_empty() && !errors.is_empty() {
+            self.packages = Loadable::Failed(errors.join("; "));
+            return;
+        }
+        sort_packages(&mut packages, self.sort_mode);
+        self.status_message = Some(if errors.is_empty() {
+            format!("{} packages installed", packages.len())
+        } else {
+            errors.join("; ")
+        });
+        self.store_in_cache("packages", &packages, |pkg| &pkg.manager);
+        self.packages = Loadable::Loaded(packages);
+        if self.package_state.selected().is_none() && !self.installed().is_empty() {
+            self.package_state.select(Some(0));
+        }
+        self.clamp_selections();
+    }
+
+    pub async fn load_updates(&mut self) {
+        self.updates = Loadable::Loading;
+        let mut updates = Vec::new();
+        let mut errors = Vec::new();
+        for (id, result) in self
+            .query_managers("list-updates", |manager| async move { manager.list_updates().await })
+            .await
+        {
+            match result {
+                Ok(mut list) => updates.append(&mut list),
+                Err(err) => errors.push(format!("{id}: {err}")),
+            }
+        }
+        if updates.is_empty() && !errors.is_empty() {
+            self.updates = Loadable::Failed(errors.join("; "));
+            return;
+        }
+        if !errors.is_empty() {
+            self.status_message = Some(errors.join("; "));
+        }
+        updates.sort_by(|a, b| a.name.cmp(&b.name));
+        // Watched packages get priority placement, and a toast when one of
+        // them has a new version available.
+        let watchlist = &self.watchlist;
+        updates.sort_by_key(|update| !watchlist.contains(&update.manager, &update.name));
+        if let Some(watched) = updates
+            .iter()
+            .find(|update| watchlist.contains(&update.manager, &update.name))
+        {
+            self.status_message = Some(format!(
+                "watched: {} {} available",
+                watched.name, watched.new_version
+            ));
+        }
+        self.store_in_cache("updates", &updates, |update| &update.manager);
+        self.updates = Loadable::Loaded(updates);
+        if self.updates_state.selected().is_none() && !self.pending_updates().is_empty() {
+            self.updates_state.select(Some(0));
+        }
+        self.clamp_selections();
+    }
+
+    /// Queue a live search for the query in the input bar, restarting the
+    /// debounce window. Called on every edit; does nothing unless the config
+    /// opts in and the input holds a search command.
+    fn schedule_live_search(&mut self) {
+        if !self.config.live_search || self.mode != Mode::Editing {
+            self.live_search_pending = None;
+            return;
+        }
+        let query = self
+            .input
+            .strip_prefix("search ")
+            .map(str::trim)
+            .unwrap_or("");
+        if query.is_empty() {
+            self.live_search_pending = None;
+            return;
+        }
+        self.live_search_pending = Some((query.to_string(), Instant::now() + LIVE_SEARCH_DEBOUNCE));
+    }
+
+    /// Dispatch the pending live search once its debounce window has passed.
+    fn dispatch_due_live_search(&mut self) {
+        let due = self
+            .live_search_pending
+            .as_ref()
+            .is_some_and(|(_, deadline)| Instant::now() >= *deadline);
+        if !due {
+            return;
+        }
+        let (query, _) = self.live_search_pending.take().expect("checked above");
+        self.dispatch_live_search(query);
+    }
+
+    /// Run a live search in the background, aborting any in-flight one. The
+    /// result comes back stamped, so a slow response for an old query can
+    /// never overwrite a newer one.
+    fn dispatch_live_search(&mut self, query: String) {
+        self.search_generation += 1;
+        let generation = self.search_generation;
+        if let Some(task) = self.live_search_task.take() {
+            task.abort();
+        }
+        let timeout = Duration::from_secs(self.config.manager_timeout_secs.max(1));
+        let offline = self.offline();
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .scope_ids()
+            .into_iter()
+            .filter_map(|id| self.package_managers.get(&id).cloned())
+            .filter(|manager| !(offline && manager.network_operations().contains(&"search")))
+            .collect();
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.live_search_rx = Some(rx);
+        self.search_results = Loadable::Loading;
+        let limiter = self.limiter.clone();
+        self.live_search_task = Some(tokio::spawn(async move {
+            let mut results = Vec::new();
+            let mut errors = Vec::new();
+            for manager in managers {
+                let _permit = limiter
+                    .acquire(manager.network_operations().contains(&"search"))
+                    .await;
+                match tokio::time::timeout(timeout, manager.search(&query)).await {
+                    Ok(Ok(mut list)) => {
+                        // A purely virtual name matches nothing itself;
+                        // surface its providers instead of an empty list.
+                        if list.is_empty() {
+                            if let Ok(Ok(mut providers)) =
+                                tokio::time::timeout(timeout, manager.providers(&query)).await
+                            {
+                                list.append(&mut providers);
+                            }
+                        }
+                        results.append(&mut list);
+                    }
+                    Ok(Err(err)) => errors.push(format!("{}: {err}", manager.id())),
+                    Err(_) => errors.push(format!("{}: timed out", manager.id())),
+                }
+            }
+            let outcome = if results.is_empty() && !errors.is_empty() {
+                Err(errors.join("; "))
+            } else {
+                Ok(results)
+            };
+            let _ = tx.send((generation, query, outcome));
+        }));
+    }
+
+    /// Apply a finished live search, dropping it when it is stale: an older
+    /// generation, or a query the input no longer shows.
+    fn poll_live_search(&mut self) {
+        let Some(rx) = self.live_search_rx.as_mut() else {
+            return;
+        };
+        let mut latest = None;
+        while let Ok(result) = rx.try_recv() {
+            latest = Some(result);
+        }
+        let Some((generation, query, outcome)) = latest else {
+            return;
+        };
+        if generation != self.search_generation {
+            return;
+        }
+        let current = self
+            .input
+            .strip_prefix("search ")
+            .map(str::trim)
+            .unwrap_or("");
+        if self.mode == Mode::Editing && current != query {
+            return;
+        }
+        match outcome {
+            Ok(results) => {
+                self.search_state
+                    .select(if results.is_empty() { None } else { Some(0) });
+                self.status_message =
+                    Some(format!("{} results for \"{query}\"", results.len()));
+                self.search_results = Loadable::Loaded(results);
+            }
+            Err(errors) => self.search_results = Loadable::Failed(errors),
+        }
+        if self.current_tab() == TabId::Search {
+            self.mark_dirty();
+        }
+    }
+
+    /// Scan the installed packages against OSV.dev in the background. The
+    /// analyzer caches responses on disk, so a re-scan only queries
+    /// packages whose versions changed since the last run.
+    fn start_vuln_scan(&mut self) {
+        if self.offline() {
+            self.status_message = Some("offline mode: vulnerability scan skipped".to_string());
+            return;
+        }
+        let Some(packages) = self.packages.value() else {
+            self.status_message = Some("load packages first (r)".to_string());
+            return;
+        };
+        if self.scan_task.is_some() {
+            self.status_message = Some("a scan is already running".to_string());
+            return;
+        }
+        let analyzer = self.security.clone();
+        let packages = packages.clone();
+        self.scan_fingerprint = crate::features::security::fingerprint(&packages);
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let (progress_tx, progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        self.scan_rx = Some(rx);
+        self.scan_progress = None;
+        self.scan_progress_rx = Some(progress_rx);
+        self.vulns = Loadable::Loading;
+        self.security_state.select(None);
+        self.scan_task = Some(tokio::spawn(async move {
+            // Refresh the EOL date table while the network is in use
+            // anyway; the scan outcome does not depend on it.
+            let _ = eol::refresh_cache().await;
+            let outcome = analyzer
+                .scan_with_progress(&packages, Some(progress_tx))
+                .await
+                .map_err(|err| err.to_string());
+            let _ = tx.send(outcome);
+        }));
+        self.mark_dirty();
+    }
+
+    /// Start a background re-scan when the last report has aged past the
+    /// configured interval or no longer matches the installed set.
+    /// Checked once a minute; never stacks onto a running scan.
+    fn maybe_auto_rescan(&mut self) {
+        const CHECK_EVERY: std::time::Duration = std::time::Duration::from_secs(60);
+        if self
+            .last_scan_check
+            .is_some_and(|checked| checked.elapsed() < CHECK_EVERY)
+        {
+            return;
+        }
+        self.last_scan_check = Some(Instant::now());
+        if self.offline() || self.scan_task.is_some() {
+            return;
+        }
+        let Some(packages) = self.packages.value() else {
+            return;
+        };
+        let stale = match self.vulns.value() {
+            None => !matches!(self.vulns, Loadable::Failed(_)),
+            Some(report) => {
+                let age = Utc::now().signed_duration_since(report.generated);
+                age > chrono::Duration::hours(
+                    self.config.security.scan_interval_hours.max(1) as i64,
+                ) || self.last_scan_fingerprint
+                    != Some(crate::features::security::fingerprint(packages))
+            }
+        };
+        if stale {
+            self.start_vuln_scan();
+        }
+    }
+
+    /// Apply a finished vulnerability scan.
+    fn poll_vuln_scan(&mut self) {
+        if let Some(progress_rx) = self.scan_progress_rx.as_mut() {
+            let mut latest = None;
+            while let Ok(counts) = progress_rx.try_recv() {
+                latest = Some(counts);
+            }
+            if latest.is_some() && latest != self.scan_progress {
+                self.scan_progress = latest;
+                if self.current_tab() == TabId::Security {
+                    self.mark_dirty();
+                }
+            }
+        }
+        let Some(rx) = self.scan_rx.as_mut() else {
+            return;
+        };
+        let Ok(outcome) = rx.try_recv() else {
+            return;
+        };
+        self.scan_rx = None;
+        self.scan_task = None;
+        self.scan_progress = None;
+        self.scan_progress_rx = None;
+        match outcome {
+            Ok(report) => {
+                self.security_state
+                    .select((!report.findings.is_empty()).then_some(0));
+                let today = Utc::now().date_naive();
+                let ignored = report
+                    .findings
+                    .iter()
+                    .filter(|finding| self.security_ignores.is_ignored(&finding.id, today))
+                    .count();
+                let mut message = format!(
+                    "{} vulnerabilities across {} scanned packages",
+                    report.findings.len() - ignored,
+                    report.scanned
+                );
+                if ignored > 0 {
+                    message.push_str(&format!(" ({ignored} ignored)"));
+                }
+                self.status_message = Some(message);
+                crate::features::security::save_last_scan(&report, self.scan_fingerprint);
+                self.last_scan_fingerprint = Some(self.scan_fingerprint);
+                self.vulns = Loadable::Loaded(report);
+                // The scan may have refreshed the EOL table on disk.
+                self.eol = eol::check(today);
+            }
+            Err(err) => self.vulns = Loadable::Failed(err),
+        }
+        if self.current_tab() == TabId::Security {
+            self.mark_dirty();
+        }
+    }
+
+    pub async fn search_packages(&mut self, query: &str) {
+        // A manual search supersedes any in-flight live one.
+        self.search_generation += 1;
+        if let Some(task) = self.live_search_task.take() {
+            task.abort();
+        }
+        self.search_results = Loadable::Loading;
+        let mut results = Vec::new();
+        let mut errors = Vec::new();
+        let needle = query.to_string();
+        for (id, result) in self
+            .query_managers("search", move |manager| {
+                let needle = needle.clone();
+                async move { manager.search(&needle).await }
+            })
+            .await
+        {
+            match result {
+                Ok(mut list) => results.append(&mut list),
+                Err(err) => errors.push(format!("{id}: {err}")),
+            }
+        }
+        // A purely virtual name matches nothing itself; resolve its
+        // providers instead of reporting an empty search.
+        if results.is_empty() && errors.is_empty() {
+            if let Some(mut providers) = self.virtual_providers(query).await {
+                results.append(&mut providers);
+            }
+        }
+        if results.is_empty() && !errors.is_empty() {
+            self.search_results = Loadable::Failed(errors.join("; "));
+            return;
+        }
+        self.search_state
+            .select(if results.is_empty() { None } else { Some(0) });
+        self.status_message = Some(if errors.is_empty() {
+            format!("{} results for \"{}\"", results.len(), query)
+        } else {
+            errors.join("; ")
+        });
+        self.search_results = Loadable::Loaded(results);
+    }
+
+    async fn load_selected_details(&mut self) {
+        let Some(package) = self.selected_package() else {
+            return;
+        };
+        let (manager_id, name) = (package.manager.clone(), package.name.clone());
+        let already_installed = package.installed;
+        // The footprint only means something for packages not on the
+        // system yet; installed ones show their own size instead.
+        self.details_footprint = if already_installed {
+            None
+        } else {
+            self.compute_install_footprint(std::slice::from_ref(&name))
+                .await
+        };
+        self.details_optional = self.optional_deps_with_markers(&manager_id, &name).await;
+        self.details_provides = match self.package_managers.get(&manager_id).cloned() {
+            Some(manager) => manager.dependency_edges(&name).await.ok().map(|edges| {
+                edges
+                    .into_iter()
+                    .filter(|edge| edge.kind == crate::package_managers::DepKind::Provides)
+                    .map(|edge| edge.name)
+                    .collect()
+            }),
+            None => None,
+        };
+        let Some(manager) = self.package_managers.get(&manager_id) else {
+            return;
+        };
+        match manager.details(&name).await {
+            Ok(details) => {
+                if let Err(err) = self.cache.store(&manager_id, &format!("details-{name}"), &details)
+                {
+                    log::warn!("could not write details cache: {err}");
+                }
+                self.details = Some(details);
+                self.details_scroll = 0;
+            }
+            Err(err) => {
+                // A stale detail view beats an error toast when offline.
+                match self.cache.load(&manager_id, &format!("details-{name}")) {
+                    Some(details) => {
+                        self.details = Some(details);
+                        self.details_scroll = 0;
+                        self.status_message = Some("details from cache (backend failed)".to_string());
+                    }
+                    None => self.status_message = Some(err.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Dispatch one hook entry: `builtin:` names run inside pkgtool,
+    /// anything else is an executable path run by `hooks::run_script`.
+    async fn run_hook(
+        &mut self,
+        hook: &str,
+        action: &str,
+        manager: &str,
+        packages: &[String],
+    ) -> crate::error::Result<()> {
+        match hook.strip_prefix(hooks::BUILTIN_PREFIX) {
+            Some("snapshot") => {
+                // The policy snapshot may already cover this transaction.
+                if self.snapshot_pre.is_none() {
+                    let description = format!("{action} {}", packages.join(" "));
+                    self.snapshot_pre = Some(
+                        self.snapshots
+                            .pre_transaction(description.trim_end())
+                            .await?,
+                    );
+                }
+                Ok(())
+            }
+            Some(other) => Err(crate::error::PkgError::Config {
+                path: "hooks".to_string(),
+                detail: format!("unknown builtin hook \"{other}\""),
+            }),
+            None => {
+                hooks::run_script(hook, self.config.hooks.timeout_secs, action, manager, packages)
+                    .await
+            }
+        }
+    }
+
+    /// Run the pre-hooks for `action`. A failing hook means the operation
+    /// must not proceed: its stderr opens in a dialog and `false` comes back.
+    async fn run_pre_hooks(&mut self, action: &str, manager: &str, packages: &[String]) -> bool {
+        for hook in self.config.hooks.pre(action).to_vec() {
+            if let Err(err) = self.run_hook(&hook, action, manager, packages).await {
+                let detail = match &err {
+                    crate::error::PkgError::CommandFailed { stderr, .. } if !stderr.is_empty() => {
+                        stderr.clone()
+                    }
+                    other => other.to_string(),
+                };
+                self.message_dialog = Some(MessageDialog {
+                    title: format!(" {action} aborted by {hook} "),
+                    lines: detail.lines().map(str::to_string).collect(),
+                });
+                self.open_dialog();
+                self.mark_dirty();
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Run the post-hooks for `action`; failures only produce a warning.
+    async fn run_post_hooks(&mut self, action: &str, manager: &str, packages: &[String]) {
+        for hook in self.config.hooks.post(action).to_vec() {
+            if let Err(err) = self.run_hook(&hook, action, manager, packages).await {
+                log::warn!(target: "pkgtool::hooks", "post-{action} hook failed: {err}");
+                self.status_message = Some(format!("post-{action} hook {hook} failed"));
+            }
+        }
+    }
+
+    /// Whether the snapshot policy covers a transaction touching
+    /// `changes` packages under `action`. Unrecognized values behave
+    /// like "never": nobody wants surprise subvolumes from a typo.
+    fn snapshot_policy_applies(&self, action: &str, changes: usize) -> bool {
+        // rpm-ostree creates a deployment as part of the transaction,
+        // so a pre-transaction snapshot has nothing to add there.
+        if !self.snapshots.snapshots_before_transactions() {
+            return false;
+        }
+        match self.config.snapshots.auto.as_str() {
+            "always" => true,
+            "upgrades-only" => action == "update",
+            "over-threshold" => changes > self.config.snapshots.threshold,
+            _ => false,
+        }
+    }
+
+    /// Whether confirming `operation` will take an automatic snapshot
+    /// first, so the confirmation dialog can say so.
+    pub fn operation_takes_snapshot(&self, operation: &PendingOperation) -> bool {
+        match operation {
+            PendingOperation::Install(packages)
+            | PendingOperation::InstallUnverified(packages) => {
+                self.snapshot_policy_applies("install", packages.len())
+            }
+            PendingOperation::Remove(packages) => {
+                self.snapshot_policy_applies("remove", packages.len())
+            }
+            PendingOperation::RemoveFrom { .. } => self.snapshot_policy_applies("remove", 1),
+            PendingOperation::UpdateSystem => self.snapshot_policy_applies(
+                "update",
+                self.updates.value().map(Vec::len).unwrap_or(0),
+            ),
+            PendingOperation::UpdateSecurity(packages) => {
+                self.snapshot_policy_applies("update", packages.len())
+            }
+            PendingOperation::CleanCache
+            | PendingOperation::RestoreSnapshot(_)
+            | PendingOperation::RestorePackages { .. }
+            | PendingOperation::PruneSnapshots(_)
+            | PendingOperation::RestartServices(_)
+            | PendingOperation::RepairDependencies { .. } => false,
+        }
+    }
+
+    /// Take the policy-driven snapshot in front of a transaction.
+    /// `false` means the snapshot failed and the configured on_failure
+    /// says the operation must not proceed.
+    async fn auto_snapshot(&mut self, action: &str, packages: &[String], changes: usize) -> bool {
+        if !self.snapshot_policy_applies(action, changes) {
+            return true;
+        }
+        // A snapshot onto a nearly-full volume tends to fail mid-write
+        // (or, on LVM, to invalidate itself soon after), so warn before
+        // taking it; the snapshot itself still goes ahead.
+        let min_free = self.config.snapshots.min_free_mb * 1024 * 1024;
+        if min_free > 0 {
+            let free = match self.snapshots.snapshot_usage().await {
+                Ok(usage) => usage.free_bytes,
+                Err(_) => None,
+            };
+            if let Some(free) = free.filter(|free| *free < min_free) {
+                self.status_message = Some(format!(
+                    "snapshot volume low on space: only {} free",
+                    crate::utils::format_size(free)
+                ));
+                self.mark_dirty();
+            }
+        }
+        let description = format!("{action} {}", packages.join(" "));
+        match self.snapshots.pre_transaction(description.trim_end()).await {
+            Ok(snapshot) => {
+                self.snapshot_pre = Some(snapshot);
+                self.auto_prune_snapshots().await;
+                true
+            }
+            Err(err) if self.config.snapshots.on_failure == "warn" => {
+                log::warn!(target: "pkgtool::snapshots", "pre-{action} snapshot failed: {err}");
+                self.status_message = Some(format!("snapshot failed ({err}); continuing"));
+                true
+            }
+            Err(err) => {
+                self.message_dialog = Some(MessageDialog {
+                    title: format!(" {action} aborted: snapshot failed "),
+                    lines: err.to_string().lines().map(str::to_string).collect(),
+                });
+                self.open_dialog();
+                self.mark_dirty();
+                false
+            }
+        }
+    }
+
+    /// The snapshot the most recent transaction references, which the
+    /// retention policy never deletes.
+    fn protected_snapshot(&self) -> Option<String> {
+        self.history
+            .entries()
+            .last()
+            .and_then(|transaction| transaction.snapshot.clone())
+    }
+
+    /// Silent retention pass after each automatic snapshot. The
+    /// on-demand `prune-snapshots` command confirms; this one only logs.
+    async fn auto_prune_snapshots(&mut self) {
+        let Ok(list) = self.snapshots.list().await else {
+            return;
+        };
+        let protected = self.protected_snapshot();
+        let candidates = crate::features::snapshots::prune_candidates(
+            &self.config.snapshots,
+            &list,
+            protected.as_deref(),
+            Utc::now(),
+        );
+        for candidate in &candidates {
+            match self.snapshots.delete(&candidate.snapshot.id).await {
+                Ok(()) => log::info!(
+                    target: "pkgtool::snapshots",
+                    "pruned snapshot {} ({})",
+                    candidate.snapshot.id,
+                    candidate.reason
+                ),
+                Err(err) => log::warn!(
+                    target: "pkgtool::snapshots",
+                    "prune of {} failed: {err}",
+                    candidate.snapshot.id
+                ),
+            }
+        }
+    }
+
+    /// Compute prune candidates and put the listing in front of the user
+    /// (the `prune-snapshots` command); describe() doubles as the dry run.
+    async fn request_prune_snapshots(&mut self) {
+        let list = match self.snapshots.list().await {
+            Ok(list) => list,
+            Err(err) => {
+                self.status_message = Some(err.to_string());
+                return;
+            }
+        };
+        let protected = self.protected_snapshot();
+        let candidates = crate::features::snapshots::prune_candidates(
+            &self.config.snapshots,
+            &list,
+            protected.as_deref(),
+            Utc::now(),
+        );
+        if candidates.is_empty() {
+            self.status_message = Some("no snapshots to prune".to_string());
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.confirm_prompt = Some(ConfirmPrompt {
+            operation: PendingOperation::PruneSnapshots(candidates),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// Delete the confirmed prune candidates, reporting the count and —
+    /// where the backend reports sizes — the space reclaimed.
+    async fn prune_snapshots(&mut self, candidates: &[crate::features::snapshots::PruneCandidate]) {
+        let mut deleted = 0usize;
+        let mut reclaimed = 0u64;
+        let mut failures = Vec::new();
+        for candidate in candidates {
+            match self.snapshots.delete(&candidate.snapshot.id).await {
+                Ok(()) => {
+                    deleted += 1;
+                    reclaimed += candidate.snapshot.size_bytes.unwrap_or(0);
+                }
+                Err(err) => failures.push(err.to_string()),
+            }
+        }
+        let mut message = format!("pruned {deleted} snapshot(s)");
+        if reclaimed > 0 {
+            message.push_str(&format!(", ~{} reclaimed", crate::utils::format_size(reclaimed)));
+        }
+        if !failures.is_empty() {
+            message.push_str(&format!("; {}", failures.join("; ")));
+        }
+        self.status_message = Some(message);
+        self.load_snapshots().await;
+    }
+
+    /// Close the pre/post snapshot pair around the transaction that just
+    /// finished. A failed or cancelled transaction still gets its post
+    /// snapshot — marked as failed — so the pair brackets the transaction
+    /// either way. Like post-hooks, failures here only warn.
+    async fn close_snapshot_pair(&mut self, action: &str, packages: &[String], success: bool) {
+        let Some(pre) = self.snapshot_pre.take() else {
+            return;
+        };
+        let outcome = if success { "" } else { " (failed)" };
+        let description = format!("{action} {}", packages.join(" "));
+        let description = format!("{}{outcome}", description.trim_end());
+        if let Err(err) = self.snapshots.post_transaction(&pre, &description).await {
+            log::warn!(target: "pkgtool::hooks", "post snapshot failed: {err}");
+            self.status_message = Some("post snapshot failed".to_string());
+        }
+    }
+
+    /// Export the last scan as a self-contained HTML report (`x` on the
+    /// Security tab); the CLI's `security-report` covers the other
+    /// formats.
+    fn export_security_report(&mut self) {
+        use crate::features::security::{SecurityReportExport, SystemInfo};
+        let Some(report) = self.vulns.value() else {
+            self.status_message = Some("no scan to export; run one first (s)".to_string());
+            return;
+        };
+        let export = SecurityReportExport::new(report, SystemInfo::collect());
+        let path = chrono::Local::now()
+            .format("pkgtool-security-%Y%m%d-%H%M%S.html")
+            .to_string();
+        self.status_message = Some(match std::fs::write(&path, export.to_html()) {
+            Ok(()) => format!("security report written to {path}"),
+            Err(err) => format!("could not write {path}: {err}"),
+        });
+    }
+
+    /// The `security` triage command: `ignore <id> [until=YYYY-MM-DD]
+    /// <reason>` acknowledges a finding so it leaves the counts,
+    /// `unignore <id>` revives it, and the bare form documents the file
+    /// the command edits.
+    fn security_command(&mut self, args: &[String]) {
+        match args.first().map(String::as_str) {
+            Some("ignore") if args.len() >= 2 => {
+                let id = &args[1];
+                let mut rest = &args[2..];
+                let mut until = None;
+                if let Some(date) = rest.first().and_then(|arg| arg.strip_prefix("until=")) {
+                    match chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                        Ok(parsed) => until = Some(parsed),
+                        Err(_) => {
+                            self.status_message =
+                                Some(format!("bad date {date}; expected until=YYYY-MM-DD"));
+                            return;
+                        }
+                    }
+                    rest = &rest[1..];
+                }
+                let reason = rest.join(" ");
+                if reason.is_empty() {
+                    self.status_message = Some(
+                        "a reason is required: security ignore <id> [until=YYYY-MM-DD] <reason>"
+                            .to_string(),
+                    );
+                    return;
+                }
+                self.status_message = Some(match self.security_ignores.ignore(id, &reason, until) {
+                    Ok(()) => match until {
+                        Some(date) => format!("{id} ignored until {date} (i shows ignored)"),
+                        None => format!("{id} ignored (i shows ignored)"),
+                    },
+                    Err(err) => format!("could not save the ignore list: {err}"),
+                });
+                self.mark_dirty();
+            }
+            Some("unignore") if args.len() == 2 => {
+                self.status_message = Some(match self.security_ignores.unignore(&args[1]) {
+                    Ok(true) => format!("{} is active again", args[1]),
+                    Ok(false) => format!("{} was not ignored", args[1]),
+                    Err(err) => format!("could not save the ignore list: {err}"),
+                });
+                self.mark_dirty();
+            }
+            _ => {
+                self.status_message = Some(
+                    "security ignore <id> [until=YYYY-MM-DD] <reason> | security unignore <id> \
+                     — entries {id, reason, until?, added} in security-ignores.json"
+                        .to_string(),
+                );
+            }
+        }
+    }
+
+    /// Check one package file against repository metadata and report the
+    /// verdict in the status line (the `verify-file` command).
+    async fn verify_file_command(&mut self, path: &str) {
+        use crate::features::verify::{self, Verification};
+        self.status_message = Some(match verify::verify_file(path).await {
+            Ok(Verification::Matched { source }) => format!("{path}: checksum OK ({source})"),
+            Ok(Verification::Mismatched { source, detail }) => {
+                format!("{path}: CHECKSUM MISMATCH against {source} — {detail}")
+            }
+            Ok(Verification::Unverifiable(reason)) => {
+                format!("{path}: unable to verify — {reason}")
+            }
+            Err(err) => format!("verify-file failed: {err}"),
+        });
+    }
+
+    /// Providers of `name` when it is virtual. None means the name is a
+    /// real package (it provides itself), nothing resolves it, or no
+    /// manager supports the query — all cases where the install should
+    /// proceed with the name as given.
+    async fn virtual_providers(&mut self, name: &str) -> Option<Vec<PackageInfo>> {
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        for manager in managers {
+            let Ok(providers) = manager.providers(name).await else {
+                continue;
+            };
+            if providers.is_empty() {
+                continue;
+            }
+            if providers.iter().any(|provider| provider.name == name) {
+                return None;
+            }
+            return Some(providers);
+        }
+        None
+    }
+
+    /// Enter installs the chosen provider of a virtual name.
+    async fn handle_provider_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = self.provider_picker.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.provider_picker = None;
+                self.close_dialog();
+                self.status_message = Some("aborted".to_string());
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = picker.providers.len().saturating_sub(1);
+                let next = picker.state.selected().map_or(0, |i| (i + 1).min(last));
+                picker.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = picker.state.selected().map_or(0, |i| i.saturating_sub(1));
+                picker.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let Some(provider) = picker
+                    .state
+                    .selected()
+                    .and_then(|index| picker.providers.get(index))
+                else {
+                    return;
+                };
+                let chosen = provider.name.clone();
+                self.provider_picker = None;
+                self.close_dialog();
+                self.request_operation(PendingOperation::Install(vec![chosen]))
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Gate an install request on checksum verification. Arguments that
+    /// name local package files are checked first; a mismatch blocks the
+    /// install behind an explicit, unskippable confirmation, while clean
+    /// and unverifiable files fall through to the normal gate.
+    async fn request_install(&mut self, mut packages: Vec<String>) {
+        use crate::features::verify::{self, Verification};
+        // Resolve a single virtual name before the backend chokes on
+        // it: one provider substitutes silently, several open the
+        // picker. Multi-package installs pass through untouched.
+        if packages.len() == 1 && !Path::new(packages[0].as_str()).is_file() {
+            if let Some(providers) = self.virtual_providers(&packages[0]).await {
+                if providers.len() == 1 {
+                    self.status_message = Some(format!(
+                        "{} is provided by {}",
+                        packages[0], providers[0].name
+                    ));
+                    packages = vec![providers[0].name.clone()];
+                } else {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.provider_picker = Some(ProviderPicker {
+                        name: packages.remove(0),
+                        providers,
+                        state,
+                    });
+                    self.open_dialog();
+                    return;
+                }
+            }
+        }
+        let mut mismatches = Vec::new();
+        for package in packages.iter().filter(|name| Path::new(name.as_str()).is_file()) {
+            match verify::verify_file(package).await {
+                Ok(Verification::Matched { source }) => {
+                    self.log
+                        .push(format!("{package}: checksum OK ({source})"));
+                }
+                Ok(Verification::Mismatched { source, detail }) => {
+                    self.log.push(format!(
+                        "{package}: CHECKSUM MISMATCH against {source} — {detail}"
+                    ));
+                    mismatches.push(package.clone());
+                }
+                Ok(Verification::Unverifiable(reason)) => {
+                    self.log
+                        .push(format!("{package}: unable to verify — {reason}"));
+                }
+                Err(err) => {
+                    self.status_message = Some(format!("verification failed: {err}"));
+                    return;
+                }
+            }
+        }
+        if mismatches.is_empty() {
+            self.request_operation(PendingOperation::Install(packages)).await;
+            return;
+        }
+        self.status_message = Some(format!(
+            "checksum mismatch: {} — confirm to install anyway",
+            mismatches.join(" ")
+        ));
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.confirm_prompt = Some(ConfirmPrompt {
+            operation: PendingOperation::InstallUnverified(packages),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// The single gate in front of every mutating operation. Runs it
+    /// immediately when the policy does not require asking — or when dry
+    /// run is on, since a simulation has nothing to confirm — and opens
+    /// the confirmation dialog otherwise.
+    async fn request_operation(&mut self, operation: PendingOperation) {
+        if self.dry_run() || !self.config.requires_confirmation(operation.destructive()) {
+            self.execute_operation(operation).await;
+            return;
+        }
+        if let PendingOperation::Remove(packages) = &operation {
+            self.removal_impact = Some(self.compute_removal_impact(&packages.clone()).await);
+            self.impact_acknowledged = false;
+        }
+        if let PendingOperation::Install(packages) = &operation {
+            let packages = packages.clone();
+            self.conflict_report = Some(self.predict_install_conflicts(&packages).await);
+            self.install_footprint = self.compute_install_footprint(&packages).await;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.confirm_prompt = Some(ConfirmPrompt { operation, state });
+        self.open_dialog();
+    }
+
+    /// Ask every enabled manager to predict conflicts for an install.
+    /// Managers without the check are skipped silently; other failures
+    /// become notes so the dialog can say the check was incomplete.
+    async fn predict_install_conflicts(
+        &mut self,
+        packages: &[String],
+    ) -> crate::package_managers::ConflictReport {
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        let mut report = crate::package_managers::ConflictReport::default();
+        for manager in managers {
+            match manager.predict_conflicts(packages).await {
+                Ok(mut part) => {
+                    report.conflicts.append(&mut part.conflicts);
+                    report.notes.append(&mut part.notes);
+                }
+                Err(crate::error::PkgError::Unsupported { .. }) => {}
+                Err(err) => report.notes.push(format!("{}: {err}", manager.id())),
+            }
+        }
+        report
+    }
+
+    /// What installing `packages` would really cost. The first enabled
+    /// manager whose simulate-install plan resolves wins; managers
+    /// without one fall back to the dependency closure minus the
+    /// installed set, which yields a count but no sizes.
+    async fn compute_install_footprint(&mut self, packages: &[String]) -> Option<InstallFootprint> {
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .package_managers
+            .iter()
+            .filter(|(id, _)| self.enabled_managers.contains(*id))
+            .map(|(_, manager)| manager.clone())
+            .collect();
+        let installed: HashSet<(String, String)> = self
+            .installed()
+            .iter()
+            .map(|package| (package.manager.clone(), package.name.clone()))
+            .collect();
+        for manager in managers {
+            match manager.install_footprint(packages).await {
+                Ok(footprint) => return Some(footprint),
+                Err(crate::error::PkgError::Unsupported { .. }) => {}
+                Err(_) => continue,
+            }
+            // Closure fallback for backends without a plan query.
+            let mut new_packages = Vec::new();
+            let mut resolved = true;
+            for name in packages {
+                let Ok(closure) = self.deps.closure(manager.as_ref(), name).await else {
+                    resolved = false;
+                    break;
+                };
+                for dep in std::iter::once(name.clone()).chain(closure) {
+                    let key = (manager.id().to_string(), dep.clone());
+                    if !installed.contains(&key) && !new_packages.contains(&dep) {
+                        new_packages.push(dep);
+                    }
+                }
+            }
+            if resolved {
+                return Some(InstallFootprint {
+                    packages: new_packages,
+                    ..Default::default()
+                });
+            }
+        }
+        None
+    }
+
+    /// Everything a removal would drag along, across the managers the
+    /// named packages belong to, plus whether the backends' simulated
+    /// removal agrees it can be done.
+    async fn compute_removal_impact(
+        &mut self,
+        packages: &[String],
+    ) -> crate::features::deps::RemovalImpact {
+        let mut by_manager: HashMap<String, Vec<String>> = HashMap::new();
+        for name in packages {
+            let Some(id) = self
+                .installed()
+                .iter()
+                .find(|package| &package.name == name)
+                .map(|package| package.manager.clone())
+            else {
+                continue;
+            };
+            by_manager.entry(id).or_default().push(name.clone());
+        }
+        let mut impact = crate::features::deps::RemovalImpact {
+            tree: Vec::new(),
+            affected: 0,
+            freed_bytes: 0,
+            essential: Vec::new(),
+            simulation_error: None,
+        };
+        for (id, names) in by_manager {
+            let Some(manager) = self.package_managers.get(&id).cloned() else {
+                continue;
+            };
+            let sizes: HashMap<String, u64> = self
+                .installed()
+                .iter()
+                .filter(|package| package.manager == id)
+                .filter_map(|package| package.size.map(|size| (package.name.clone(), size)))
+                .collect();
+            let essential: HashSet<String> = manager
+                .essential_packages()
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            let part = self
+                .deps
+                .removal_impact(manager.as_ref(), &names, &sizes, &essential)
+                .await;
+            impact.tree.extend(part.tree);
+            impact.affected += part.affected;
+            impact.freed_bytes += part.freed_bytes;
+            impact.essential.extend(part.essential);
+            if impact.simulation_error.is_none() {
+                impact.simulation_error =
+                    manager.remove(&names, true).await.err().map(|err| err.to_string());
+            }
+        }
+        impact
+    }
+
+    async fn execute_operation(&mut self, operation: PendingOperation) {
+        match operation {
+            PendingOperation::Install(packages)
+            | PendingOperation::InstallUnverified(packages) => {
+                self.install_packages(&packages).await;
+            }
+            PendingOperation::Remove(packages) => self.remove_packages(&packages).await,
+            PendingOperation::UpdateSystem => self.start_update_system().await,
+            PendingOperation::UpdateSecurity(packages) => {
+                self.apply_security_updates(&packages).await;
+            }
+            PendingOperation::CleanCache => self.clean_cache().await,
+            PendingOperation::RestoreSnapshot(id) => self.restore_snapshot(&id).await,
+            PendingOperation::RestorePackages { label, steps } => {
+                self.apply_package_set(&label, &steps).await;
+            }
+            PendingOperation::PruneSnapshots(candidates) => {
+                self.prune_snapshots(&candidates).await;
+            }
+            PendingOperation::RestartServices(services) => {
+                self.restart_services(&services).await;
+            }
+            PendingOperation::RepairDependencies { manager, command } => {
+                self.repair_dependencies(&manager, &command).await;
+            }
+            PendingOperation::RemoveFrom { manager, package } => {
+                self.remove_from(&manager, &package).await;
+            }
+        }
+    }
+
+    /// Restart the given units through privilege escalation, then trim
+    /// them from the report so the banner count stays honest.
+    async fn restart_services(&mut self, services: &[String]) {
+        if self.dry_run() {
+            self.status_message =
+                Some(format!("dry run: would restart {}", services.join(" ")));
+            return;
+        }
+        let mut args: Vec<&str> = vec!["systemctl", "restart"];
+        args.extend(services.iter().map(String::as_str));
+        let argv = self.privilege.wrap(&args);
+        match crate::package_managers::run_backend("restart", &argv).await {
+            Ok(_) => {
+                if let Some(report) = self.restart.as_mut() {
+                    report.services.retain(|service| !services.contains(service));
+                }
+                self.status_message = Some(format!("restarted {}", services.join(" ")));
+            }
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+        self.mark_dirty();
+    }
+
+    /// Run a confirmed snapshot restore. An immediate restore refreshes
+    /// everything the restored state invalidates; one that waits for a
+    /// reboot must say so instead of pretending it finished.
+    async fn restore_snapshot(&mut self, id: &str) {
+        use crate::features::snapshots::RestoreOutcome;
+        match self.snapshots.restore(id).await {
+            Ok(RestoreOutcome::Complete) => {
+                self.status_message = Some(format!("restored snapshot {id}"));
+                self.deps.invalidate();
+                self.load_packages().await;
+                self.load_updates().await;
+                self.load_snapshots().await;
+            }
+            Ok(RestoreOutcome::RebootRequired) => {
+                self.reboot_required = true;
+                self.status_message = Some(format!("snapshot {id} armed for restore"));
+                self.load_snapshots().await;
+            }
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+    }
+
+    async fn install_packages(&mut self, packages: &[String]) {
+        let dry_run = self.dry_run();
+        let action = if dry_run { "install (dry run)" } else { "install" };
+        if !dry_run && !self.auto_snapshot("install", packages, packages.len()).await {
+            return;
+        }
+        let managers: Vec<Arc<dyn PackageManager>> =
+            self.package_managers.values().cloned().collect();
+        for manager in managers {
+            if self.offline() && manager.network_operations().contains(&"install") {
+                self.status_message =
+                    Some(format!("install ({}) disabled by offline mode", manager.id()));
+                continue;
+            }
+            // Hooks can mutate the system (snapshots, scripts), so a dry
+            // run skips them along with the operation itself.
+            if !dry_run && !self.run_pre_hooks("install", manager.id(), packages).await {
+                self.close_snapshot_pair("install", packages, false).await;
+                return;
+            }
+            let attempt_started = Instant::now();
+            let result = manager.install(packages, dry_run).await;
+            let success = result.is_ok();
+            let snapshot = self.snapshot_pre.as_ref().map(|pre| pre.id.clone());
+            self.close_snapshot_pair("install", packages, success).await;
+            self.report.record(ReportEvent {
+                timestamp: Utc::now(),
+                manager: manager.id().to_string(),
+                action: action.to_string(),
+                packages: packages.to_vec(),
+                success,
+                duration_secs: attempt_started.elapsed().as_secs_f64(),
+            });
+            let _ = self.history.record(Transaction {
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                manager: manager.id().to_string(),
+                packages: packages.to_vec(),
+                success,
+                snapshot,
+            });
+            match result {
+                Ok(()) if dry_run => {
+                    self.status_message =
+                        Some(format!("install (dry run): {}", packages.join(" ")));
+                    return;
+                }
+                Ok(()) => {
+                    self.run_post_hooks("install", manager.id(), packages).await;
+                    self.status_message = Some(format!("installed: {}", packages.join(" ")));
+                    self.load_packages().await;
+                    return;
+                }
+                Err(err) => self.status_message = Some(err.to_string()),
+            }
+        }
+    }
+
+    async fn remove_packages(&mut self, packages: &[String]) {
+        let dry_run = self.dry_run();
+        let action = if dry_run { "remove (dry run)" } else { "remove" };
+        if !dry_run && !self.auto_snapshot("remove", packages, packages.len()).await {
+            return;
+        }
+        let managers: Vec<Arc<dyn PackageManager>> =
+            self.package_managers.values().cloned().collect();
+        for manager in managers {
+            if self.offline() && manager.network_operations().contains(&"remove") {
+                self.status_message =
+                    Some(format!("remove ({}) disabled by offline mode", manager.id()));
+                continue;
+            }
+            if !dry_run && !self.run_pre_hooks("remove", manager.id(), packages).await {
+                self.close_snapshot_pair("remove", packages, false).await;
+                return;
+            }
+            let attempt_started = Instant::now();
+            let result = manager.remove(packages, dry_run).await;
+            let success = result.is_ok();
+            let snapshot = self.snapshot_pre.as_ref().map(|pre| pre.id.clone());
+            self.close_snapshot_pair("remove", packages, success).await;
+            self.report.record(ReportEvent {
+                timestamp: Utc::now(),
+                manager: manager.id().to_string(),
+                action: action.to_string(),
+                packages: packages.to_vec(),
+                success,
+                duration_secs: attempt_started.elapsed().as_secs_f64(),
+            });
+            let _ = self.history.record(Transaction {
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                manager: manager.id().to_string(),
+                packages: packages.to_vec(),
+                success,
+                snapshot,
+            });
+            match result {
+                Ok(()) if dry_run => {
+                    self.status_message =
+                        Some(format!("remove (dry run): {}", packages.join(" ")));
+                    return;
+                }
+                Ok(()) => {
+                    self.run_post_hooks("remove", manager.id(), packages).await;
+                    self.status_message = Some(format!("removed: {}", packages.join(" ")));
+                    self.load_packages().await;
+                    return;
+                }
+                Err(err) => self.status_message = Some(err.to_string()),
+            }
+        }
+    }
+
+    /// Remove one package through the named manager only, bypassing the
+    /// first-success loop above. Used for cross-manager duplicates,
+    /// where every enabled manager knows a package by that name.
+    async fn remove_from(&mut self, id: &str, package: &str) {
+        let Some(manager) = self.package_managers.get(id).cloned() else {
+            self.status_message = Some(format!("no such manager: {id}"));
+            return;
+        };
+        if self.offline() && manager.network_operations().contains(&"remove") {
+            self.status_message = Some(format!("remove ({id}) disabled by offline mode"));
+            return;
+        }
+        let packages = [package.to_string()];
+        let dry_run = self.dry_run();
+        let action = if dry_run { "remove (dry run)" } else { "remove" };
+        if !dry_run && !self.auto_snapshot("remove", &packages, 1).await {
+            return;
+        }
+        if !dry_run && !self.run_pre_hooks("remove", id, &packages).await {
+            self.close_snapshot_pair("remove", &packages, false).await;
+            return;
+        }
+        let attempt_started = Instant::now();
+        let result = manager.remove(&packages, dry_run).await;
+        let success = result.is_ok();
+        let snapshot = self.snapshot_pre.as_ref().map(|pre| pre.id.clone());
+        self.close_snapshot_pair("remove", &packages, success).await;
+        self.report.record(ReportEvent {
+            timestamp: Utc::now(),
+            manager: id.to_string(),
+            action: action.to_string(),
+            packages: packages.to_vec(),
+            success,
+            duration_secs: attempt_started.elapsed().as_secs_f64(),
+        });
+        let _ = self.history.record(Transaction {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            manager: id.to_string(),
+            packages: packages.to_vec(),
+            success,
+            snapshot,
+        });
+        match result {
+            Ok(()) if dry_run => {
+                self.status_message = Some(format!("remove (dry run): {package} [{id}]"));
+            }
+            Ok(()) => {
+                self.run_post_hooks("remove", id, &packages).await;
+                self.status_message = Some(format!("removed: {package} [{id}]"));
+                self.load_packages().await;
+            }
+            Err(err) => self.status_message = Some(err.to_string()),
+        }
+    }
+
+    /// Ask to apply only the updates that fix vulnerabilities. When the
+    /// whole subset belongs to pacman, which forbids partial upgrades,
+    /// nothing is offered — the rows stay highlighted instead.
+    pub async fn request_security_updates(&mut self) {
+        let names: Vec<String> = self
+            .security_updates()
+            .iter()
+            .map(|update| update.name.clone())
+            .collect();
+        if names.is_empty() {
+            self.status_message = Some("no pending security updates".to_string());
+            return;
+        }
+        if self
+            .security_updates()
+            .iter()
+            .all(|update| update.manager == "pacman")
+        {
+            self.status_message = Some(
+                "pacman does not support partial upgrades; run a full update (u)".to_string(),
+            );
+            return;
+        }
+        self.request_operation(PendingOperation::UpdateSecurity(names))
+            .await;
+    }
+
+    /// Apply the named security updates, grouped by the manager whose
+    /// pending list claims them.
+    async fn apply_security_updates(&mut self, packages: &[String]) {
+        let dry_run = self.dry_run();
+        let action = if dry_run {
+            "security update (dry run)"
+        } else {
+            "security update"
+        };
+        if !dry_run && !self.auto_snapshot("update", packages, packages.len()).await {
+            return;
+        }
+        let mut by_manager: std::collections::BTreeMap<String, Vec<String>> = Default::default();
+        for update in self.security_updates() {
+            if packages.contains(&update.name) {
+                by_manager
+                    .entry(update.manager.clone())
+                    .or_default()
+                    .push(update.name.clone());
+            }
+        }
+        let mut applied: Vec<String> = Vec::new();
+        let mut errors: Vec<String> = Vec::new();
+        for (id, names) in by_manager {
+            let Some(manager) = self.package_managers.get(&id).cloned() else {
+                continue;
+            };
+            if self.offline() && manager.network_operations().contains(&"update") {
+                errors.push(format!("{id}: security update disabled by offline mode"));
+                continue;
+            }
+            if !dry_run && !self.run_pre_hooks("update", &id, &names).await {
+                self.close_snapshot_pair("update", packages, false).await;
+                return;
+            }
+            let attempt_started = Instant::now();
+            let result = manager.update_security(&names, dry_run).await;
+            let success = result.is_ok();
+            let snapshot = self.snapshot_pre.as_ref().map(|pre| pre.id.clone());
+            self.report.record(ReportEvent {
+                timestamp: Utc::now(),
+                manager: id.clone(),
+                action: action.to_string(),
+                packages: names.clone(),
+                success,
+                duration_secs: attempt_started.elapsed().as_secs_f64(),
+            });
+            let _ = self.history.record(Transaction {
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                manager: id.clone(),
+                packages: names.clone(),
+                success,
+                snapshot,
+            });
+            match result {
+                Ok(()) => {
+                    if !dry_run {
+                        self.run_post_hooks("update", &id, &names).await;
+                    }
+                    applied.extend(names);
+                }
+                Err(err) => errors.push(format!("{id}: {err}")),
+            }
+        }
+        let success = errors.is_empty();
+        self.close_snapshot_pair("update", packages, success).await;
+        self.status_message = Some(if !success {
+            errors.join("; ")
+        } else if dry_run {
+            format!("security update (dry run): {}", applied.join(" "))
+        } else {
+            format!("applied security updates: {}", applied.join(" "))
+        });
+        if success && !dry_run {
+            self.load_updates().await;
+            self.load_packages().await;
+        }
+    }
+
+    /// Kick off a system update in a background task so the UI stays
+    /// responsive and quitting can be intercepted while it runs.
+    pub async fn start_update_system(&mut self) {
+        if self.operation.is_some() {
+            self.status_message = Some("an operation is already running".to_string());
+            return;
+        }
+        let offline = self.offline();
+        let managers: Vec<Arc<dyn PackageManager>> = self
+            .scope_ids()
+            .into_iter()
+            .filter_map(|id| self.package_managers.get(&id).cloned())
+            .filter(|manager| !(offline && manager.network_operations().contains(&"update")))
+            .collect();
+        if managers.is_empty() {
+            self.status_message = Some("system update is disabled by offline mode".to_string());
+            return;
+        }
+        let dry_run = self.dry_run();
+        if !dry_run {
+            // Whatever the filesystem offers, there is always a "what did
+            // I have before" record of the package state.
+            let label = format!("pre-update-{}", Utc::now().format("%Y%m%d-%H%M%S"));
+            self.snapshot_packages(&label).await;
+        }
+        let pending = self.updates.value().map(Vec::len).unwrap_or(0);
+        if !dry_run && !self.auto_snapshot("update", &[], pending).await {
+            return;
+        }
+        let scope = self.scope_ids().join(",");
+        if !dry_run && !self.run_pre_hooks("update", &scope, &[]).await {
+            self.close_snapshot_pair("update", &[], false).await;
+            return;
+        }
+        let pid = Arc::new(AtomicU32::new(0));
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let (answer_tx, answer_rx) = tokio::sync::mpsc::unbounded_channel();
+        let answer_rx = Arc::new(tokio::sync::Mutex::new(answer_rx));
+        let lock = self.op_lock.clone();
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let task_cancel = cancel.clone();
+        let handle = tokio::spawn(async move {
+            let _guard = lock.lock().await;
+            let mut results = Vec::new();
+            for manager in managers {
+                let id = manager.id().to_string();
+                let result = manager
+                    .update_system_streaming(
+                        tx.clone(),
+                        answer_rx.clone(),
+                        task_cancel.clone(),
+                        dry_run,
+                    )
+                    .await;
+                let failed = result.is_err();
+                results.push((id, result));
+                if failed {
+                    break;
+                }
+            }
+            results
+        });
+        let plan: Vec<(String, String)> = self
+            .pending_updates()
+            .iter()
+            .map(|update| {
+                (
+                    update.manager.clone(),
+                    format!(
+                        "{} {} -> {}",
+                        update.name, update.current_version, update.new_version
+                    ),
+                )
+            })
+            .collect();
+        self.operation_log = OperationLog::default();
+        self.operation = Some(Operation {
+            description: if dry_run {
+                "system update (dry run)".to_string()
+            } else {
+                "system update".to_string()
+            },
+            started: Instant::now(),
+            plan,
+            dry_run,
+            handle,
+            output: rx,
+            answers: answer_tx,
+            cancel,
+            pid,
+            last_output: Instant::now(),
+        });
+        self.status_message = Some(if dry_run {
+            "simulating system update...".to_string()
+        } else {
+            "updating system...".to_string()
+        });
+    }
+
+    /// Move any pending live-output lines into the operation scrollback,
+    /// watching for known interactive prompts along the way.
+    fn drain_operation_output(&mut self) {
+        let scope = self.scope_ids();
+        let Some(operation) = self.operation.as_mut() else {
+            return;
+        };
+        let mut lines = Vec::new();
+        while let Ok(line) = operation.output.try_recv() {
+            lines.push(line);
+        }
+        if !lines.is_empty() {
+            operation.last_output = Instant::now();
+            self.mark_dirty();
+        }
+        let mut prompt = None;
+        for line in lines {
+            if prompt.is_none() && self.prompt_dialog.is_none() {
+                if let Some(rule) = prompts::match_prompt(&self.prompt_rules, &scope, &line.text) {
+                    prompt = Some(PromptDialog {
+                        question: line.text.clone(),
+                        options: rule.options.iter().map(|o| o.to_string()).collect(),
+                        state: {
+                            let mut state = ListState::default();
+                            state.select(Some(0));
+                            state
+                        },
+                    });
+                }
+            }
+            self.operation_log.push(line);
+        }
+        if let Some(dialog) = prompt {
+            self.prompt_dialog = Some(dialog);
+            self.open_dialog();
+        }
+        // An operation that has gone quiet may be stuck on a prompt we do
+        // not recognize; offer a way out.
+        let stalled = self
+            .operation
+            .as_ref()
+            .is_some_and(|operation| operation.last_output.elapsed() > STALL_TIMEOUT);
+        if stalled && self.prompt_dialog.is_none() && self.stall_prompt.is_none() {
+            let mut state = ListState::default();
+            state.select(Some(0));
+            self.stall_prompt = Some(StallPrompt { state });
+            self.open_dialog();
+            self.mark_dirty();
+        }
+    }
+
+    /// Harvest a finished background operation: record history, refresh the
+    /// package data and surface the outcome.
+    async fn poll_operation(&mut self) {
+        let finished = self
+            .operation
+            .as_ref()
+            .is_some_and(|operation| operation.handle.is_finished());
+        if !finished {
+            return;
+        }
+        let Some(mut operation) = self.operation.take() else {
+            return;
+        };
+        while let Ok(line) = operation.output.try_recv() {
+            self.operation_log.push(line);
+        }
+        self.log.push(format!(
+            "==> {} ({})",
+            operation.description,
+            Utc::now().format("%Y-%m-%d %H:%M:%S")
+        ));
+        self.log
+            .extend(self.operation_log.lines.drain(..).map(|line| line.text));
+        self.operation_log = OperationLog::default();
+        let results = match operation.handle.await {
+            Ok(results) => results,
+            Err(_) => {
+                self.status_message = Some(format!("{} aborted", operation.description));
+                self.mark_dirty();
+                return;
+            }
+        };
+        let action = if operation.dry_run {
+            "update (dry run)"
+        } else {
+            "update"
+        };
+        let mut error = None;
+        let mut cancelled = false;
+        let snapshot = self.snapshot_pre.as_ref().map(|pre| pre.id.clone());
+        for (manager, result) in results {
+            let success = result.is_ok();
+            self.report.record(ReportEvent {
+                timestamp: Utc::now(),
+                manager: manager.clone(),
+                action: action.to_string(),
+                packages: operation
+                    .plan
+                    .iter()
+                    .filter(|(id, _)| id == &manager)
+                    .map(|(_, change)| change.clone())
+                    .collect(),
+                success,
+                duration_secs: operation.started.elapsed().as_secs_f64(),
+            });
+            let _ = self.history.record(Transaction {
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                manager,
+                packages: Vec::new(),
+                success,
+                snapshot: snapshot.clone(),
+            });
+            match result {
+                Err(crate::error::PkgError::Cancelled) => cancelled = true,
+                Err(err) => error = Some(err.to_string()),
+                Ok(()) => {}
+            }
+        }
+        self.close_snapshot_pair("update", &[], !cancelled && error.is_none())
+            .await;
+        if !cancelled && error.is_none() && !operation.dry_run {
+            let scope = self.scope_ids().join(",");
+            self.run_post_hooks("update", &scope, &[]).await;
+        }
+        if !operation.dry_run {
+            self.deps.invalidate();
+            self.load_packages().await;
+            self.load_updates().await;
+        }
+        // A finished update may leave the kernel or running services
+        // outdated; the banner and `R` pick the result up.
+        if !cancelled && error.is_none() && !operation.dry_run
+            && operation.description.starts_with("system update")
+        {
+            self.restart = Some(restart::check().await);
+        }
+        self.status_message = Some(if cancelled {
+            format!("{} cancelled", operation.description)
+        } else if let Some(error) = error {
+            // sudo/doas run with -n because a tty prompt would be invisible
+            // under the alternate screen; turn that refusal into advice.
+            // polkit-based tools (pkexec, run0) prompt through their own
+            // agent, so they never need it.
+            if self.privilege.prompts_on_tty() && error.contains("password is required") {
+                format!("{error} — authenticate in a terminal first (e.g. `sudo -v`)")
+            } else {
+                error
+            }
+        } else if operation.dry_run {
+            "system update simulated; nothing was changed".to_string()
+        } else {
+            i18n::tr("status.updated").to_string()
+        });
+        self.mark_dirty();
+    }
+
+    /// Quit immediately when idle; ask what to do with a running operation.
+    fn request_quit(&mut self) {
+        if self.operation.is_none() {
+            self.should_quit = true;
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.quit_prompt = Some(QuitPrompt { state });
+        self.open_dialog();
+    }
+
+    async fn handle_quit_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.quit_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.quit_prompt = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = QuitPrompt::options().len() - 1;
+                let next = prompt.state.selected().map_or(0, |i| (i + 1).min(last));
+                prompt.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = prompt.state.selected().map_or(0, |i| i.saturating_sub(1));
+                prompt.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let choice = prompt.state.selected().unwrap_or(0);
+                self.quit_prompt = None;
+                self.close_dialog();
+                match choice {
+                    0 => {} // wait: keep running, dialog closed
+                    1 => self.should_quit = true, // detach: child keeps running
+                    _ => {
+                        self.abort_operation().await;
+                        self.should_quit = true;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_prompt_dialog_key(&mut self, key: KeyEvent) {
+        let Some(dialog) = self.prompt_dialog.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.prompt_dialog = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = dialog.options.len().saturating_sub(1);
+                let next = dialog.state.selected().map_or(0, |i| (i + 1).min(last));
+                dialog.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = dialog.state.selected().map_or(0, |i| i.saturating_sub(1));
+                dialog.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let choice = dialog.state.selected().unwrap_or(0);
+                let answer = dialog.options[choice].clone();
+                self.prompt_dialog = None;
+                self.close_dialog();
+                if let Some(operation) = self.operation.as_ref() {
+                    let _ = operation.answers.send(answer);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_message_dialog_key(&mut self, key: KeyEvent) {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+            self.message_dialog = None;
+            self.close_dialog();
+        }
+    }
+
+    /// Keys in the cancel-confirmation dialog; cancelling a privileged
+    /// operation mid-transaction is risky, hence the extra step.
+    fn handle_cancel_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.cancel_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.cancel_prompt = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = CancelPrompt::options().len() - 1;
+                let next = prompt.state.selected().map_or(0, |i| (i + 1).min(last));
+                prompt.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = prompt.state.selected().map_or(0, |i| i.saturating_sub(1));
+                prompt.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let choice = prompt.state.selected().unwrap_or(0);
+                self.cancel_prompt = None;
+                self.close_dialog();
+                if choice == 1 {
+                    if let Some(operation) = self.operation.as_ref() {
+                        operation.cancel.cancel();
+                        self.status_message = Some("cancelling...".to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Keys in the confirmation gate; Esc aborts, Enter runs the choice.
+    async fn handle_confirm_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.confirm_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.confirm_prompt = None;
+                self.removal_impact = None;
+                self.conflict_report = None;
+                self.install_footprint = None;
+                self.close_dialog();
+                self.status_message = Some("aborted".to_string());
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = ConfirmPrompt::options().len() - 1;
+                let next = prompt.state.selected().map_or(0, |i| (i + 1).min(last));
+                prompt.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = prompt.state.selected().map_or(0, |i| i.saturating_sub(1));
+                prompt.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let proceed = prompt.state.selected() == Some(0);
+                // Essential packages in the blast radius are a hard
+                // stop: the first proceed only arms the second one.
+                if proceed
+                    && !self.impact_acknowledged
+                    && self
+                        .removal_impact
+                        .as_ref()
+                        .is_some_and(|impact| !impact.essential.is_empty())
+                {
+                    self.impact_acknowledged = true;
+                    self.status_message = Some(
+                        "essential packages would be affected — confirm once more to proceed"
+                            .to_string(),
+                    );
+                    self.mark_dirty();
+                    return;
+                }
+                let Some(prompt) = self.confirm_prompt.take() else {
+                    return;
+                };
+                self.removal_impact = None;
+                self.conflict_report = None;
+                self.install_footprint = None;
+                self.close_dialog();
+                if proceed {
+                    self.execute_operation(prompt.operation).await;
+                } else {
+                    self.status_message = Some("aborted".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// (Re)list snapshots for the Snapshots tab, keeping the selection
+    /// in bounds across deletes.
+    pub async fn load_snapshots(&mut self) {
+        let sets: Vec<crate::features::snapshots::Snapshot> =
+            crate::features::snapshots::list_package_sets()
+                .iter()
+                .map(|set| set.as_snapshot())
+                .collect();
+        // Package sets share the list with filesystem snapshots; on a
+        // system without a working backend they are all there is, so a
+        // backend failure must not hide them.
+        let mut entries = match self.snapshots.list().await {
+            Ok(entries) => entries,
+            Err(err) if sets.is_empty() => {
+                self.snapshot_list = Loadable::Failed(err.to_string());
+                self.mark_dirty();
+                return;
+            }
+            Err(_) => Vec::new(),
+        };
+        // Usage rides along with every listing; backends that cannot
+        // report it return empty numbers, which render as nothing.
+        self.snapshot_usage = self.snapshots.snapshot_usage().await.ok();
+        if let Some(usage) = &self.snapshot_usage {
+            for entry in &mut entries {
+                if entry.size_bytes.is_none() {
+                    entry.size_bytes = usage
+                        .per_snapshot
+                        .iter()
+                        .find(|(id, _)| *id == entry.id)
+                        .map(|(_, bytes)| *bytes);
+                }
+            }
+        }
+        entries.extend(sets);
+        // Sidecar notes apply uniformly — backend snapshots and package
+        // sets alike; snapper's native notes are already in place.
+        let notes = crate::features::snapshots::sidecar_notes();
+        for entry in &mut entries {
+            if entry.note.is_none() {
+                entry.note = notes.get(&entry.id).cloned();
+            }
+        }
+        let selected = self
+            .snapshots_state
+            .selected()
+            .map(|i| i.min(entries.len().saturating_sub(1)));
+        self.snapshots_state
+            .select(if entries.is_empty() { None } else { selected.or(Some(0)) });
+        self.snapshot_list = Loadable::Loaded(entries);
+        self.mark_dirty();
+    }
+
+    /// The snapshot list after the `/` filter, in listing order; ids,
+    /// triggers and notes all take part in the match.
+    pub fn filtered_snapshots(&self) -> Vec<&crate::features::snapshots::Snapshot> {
+        let entries = self.snapshot_list.value().map(Vec::as_slice).unwrap_or(&[]);
+        let Some(filter) = &self.snapshot_filter else {
+            return entries.iter().collect();
+        };
+        let needle = filter.to_lowercase();
+        entries
+            .iter()
+            .filter(|snapshot| {
+                snapshot.id.to_lowercase().contains(&needle)
+                    || snapshot.trigger.to_lowercase().contains(&needle)
+                    || snapshot
+                        .note
+                        .as_deref()
+                        .is_some_and(|note| note.to_lowercase().contains(&needle))
+            })
+            .collect()
+    }
+
+    /// The entry selected on the Snapshots tab, in filtered order.
+    fn selected_snapshot_entry(&self) -> Option<crate::features::snapshots::Snapshot> {
+        let entries = self.filtered_snapshots();
+        self.snapshots_state
+            .selected()
+            .and_then(|i| entries.get(i))
+            .map(|snapshot| (*snapshot).clone())
+    }
+
+    /// Take a manual snapshot (the `snapshot` command and the `n` key).
+    async fn create_snapshot(&mut self, description: &str) {
+        self.status_message = Some(match self.snapshots.create(description).await {
+            Ok(snapshot) => format!("snapshot {} created", snapshot.id),
+            Err(err) => err.to_string(),
+        });
+        self.load_snapshots().await;
+    }
+
+    async fn delete_selected_snapshot(&mut self) {
+        let Some(entry) = self.selected_snapshot_entry() else {
+            return;
+        };
+        let id = &entry.id;
+        self.status_message = Some(if entry.kind == "packages" {
+            match crate::features::snapshots::delete_package_set(id) {
+                Ok(()) => {
+                    let _ = crate::features::snapshots::set_sidecar_note(id, "");
+                    format!("package set {id} deleted")
+                }
+                Err(err) => err.to_string(),
+            }
+        } else {
+            match self.snapshots.delete(id).await {
+                Ok(()) => format!("snapshot {id} deleted"),
+                Err(err) => err.to_string(),
+            }
+        });
+        self.snapshot_marks.retain(|label| label != id);
+        self.load_snapshots().await;
+    }
+
+    /// Pin or unpin the selected deployment so cleanup keeps it; only
+    /// the rpm-ostree backend supports this, everywhere else it reports
+    /// as much.
+    async fn toggle_snapshot_pin(&mut self) {
+        let Some(entry) = self.selected_snapshot_entry() else {
+            return;
+        };
+        self.status_message = Some(match self.snapshots.toggle_pin(&entry.id).await {
+            Ok(()) => format!("deployment {} pin toggled", entry.id),
+            Err(err) => err.to_string(),
+        });
+        self.load_snapshots().await;
+    }
+
+    /// Attach a note to the selected snapshot (the `note` command and
+    /// the `e` key); an empty note removes the existing one.
+    async fn set_snapshot_note(&mut self, note: &str) {
+        let Some(entry) = self.selected_snapshot_entry() else {
+            self.status_message = Some("select a snapshot to annotate first".to_string());
+            return;
+        };
+        // Package sets never reach a backend; their notes always live
+        // in the sidecar.
+        let result = if entry.kind == "packages" {
+            crate::features::snapshots::set_sidecar_note(&entry.id, note.trim())
+        } else {
+            self.snapshots.set_note(&entry.id, note.trim()).await
+        };
+        self.status_message = Some(match result {
+            Ok(()) if note.trim().is_empty() => format!("note removed from {}", entry.id),
+            Ok(()) => format!("note saved on {}", entry.id),
+            Err(err) => err.to_string(),
+        });
+        self.load_snapshots().await;
+    }
+
+    /// Mark or unmark the selected package set for diffing; marking a
+    /// third replaces the older of the two.
+    fn toggle_snapshot_mark(&mut self) {
+        let Some(entry) = self.selected_snapshot_entry() else {
+            return;
+        };
+        if entry.kind != "packages" {
+            self.status_message = Some("only package sets can be diffed".to_string());
+            return;
+        }
+        if let Some(position) = self.snapshot_marks.iter().position(|label| *label == entry.id) {
+            self.snapshot_marks.remove(position);
+        } else {
+            if self.snapshot_marks.len() == 2 {
+                self.snapshot_marks.remove(0);
+            }
+            self.snapshot_marks.push(entry.id);
+        }
+        self.mark_dirty();
+    }
+
+    /// Open the diff overlay: two marked package sets against each other,
+    /// or the marked/selected one against the current installed state.
+    async fn open_snapshot_diff(&mut self) {
+        use crate::features::snapshots::{self as snapshots, ChangeKind};
+
+        let load = |label: &str| snapshots::load_package_set(label);
+        let (older, newer) = if self.snapshot_marks.len() == 2 {
+            let (a, b) = (load(&self.snapshot_marks[0]), load(&self.snapshot_marks[1]));
+            match (a, b) {
+                (Ok(a), Ok(b)) if a.created <= b.created => (a, b),
+                (Ok(a), Ok(b)) => (b, a),
+                (Err(err), _) | (_, Err(err)) => {
+                    self.status_message = Some(err.to_string());
+                    return;
+                }
+            }
+        } else {
+            let label = self.snapshot_marks.first().cloned().or_else(|| {
+                self.selected_snapshot_entry()
+                    .filter(|entry| entry.kind == "packages")
+                    .map(|entry| entry.id)
+            });
+            let Some(label) = label else {
+                self.status_message =
+                    Some("mark package sets with Space (or select one) to diff".to_string());
+                return;
+            };
+            let set = match load(&label) {
+                Ok(set) => set,
+                Err(err) => {
+                    self.status_message = Some(err.to_string());
+                    return;
+                }
+            };
+            if self.packages.is_not_loaded() {
+                self.load_packages().await;
+            }
+            let current = snapshots::capture_package_set("current", self.installed());
+            (set, current)
+        };
+
+        let changes = snapshots::diff_package_sets(&older, &newer);
+        if changes.is_empty() {
+            self.status_message = Some(format!(
+                "no package changes between {} and {}",
+                older.label, newer.label
+            ));
+            return;
+        }
+        let mut rows: Vec<(String, Option<ChangeKind>)> = Vec::new();
+        let mut current_manager = "";
+        for change in &changes {
+            if change.manager != current_manager {
+                rows.push((format!("{}:", change.manager), None));
+                current_manager = &change.manager;
+            }
+            let text = match change.kind {
+                ChangeKind::Added => format!("  + {} {}", change.name, change.to),
+                ChangeKind::Removed => format!("  - {} (was {})", change.name, change.from),
+                ChangeKind::Upgraded => {
+                    format!("  ↑ {} {} -> {}", change.name, change.from, change.to)
+                }
+                ChangeKind::Downgraded => {
+                    format!("  ↓ {} {} -> {}", change.name, change.from, change.to)
+                }
+                ChangeKind::MarkChanged => {
+                    format!("  ± {} {} -> {}", change.name, change.from, change.to)
+                }
+            };
+            rows.push((text, Some(change.kind)));
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.diff_view = Some(DiffView {
+            title: format!(" {} -> {} ", older.label, newer.label),
+            rows,
+            state,
+            markdown: snapshots::diff_markdown(&older.label, &newer.label, &changes),
+            export_path: format!(
+                "pkgtool-diff-{}-vs-{}.md",
+                older.label.replace(' ', "-"),
+                newer.label.replace(' ', "-")
+            ),
+        });
+        self.open_dialog();
+    }
+
+    fn handle_diff_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.diff_view.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.diff_view = None;
+                self.close_dialog();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = view.rows.len().saturating_sub(1);
+                let next = view.state.selected().map_or(0, |i| (i + 1).min(last));
+                view.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = view.state.selected().map_or(0, |i| i.saturating_sub(1));
+                view.state.select(Some(previous));
+            }
+            KeyCode::Char('e') => {
+                self.status_message =
+                    Some(match std::fs::write(&view.export_path, &view.markdown) {
+                        Ok(()) => format!("diff written to {}", view.export_path),
+                        Err(err) => err.to_string(),
+                    });
+            }
+            _ => {}
+        }
+    }
+
+    /// Record every manager's installed list under `label` (the
+    /// `snapshot-packages` command; also runs before each system update).
+    async fn snapshot_packages(&mut self, label: &str) {
+        if self.packages.is_not_loaded() {
+            self.load_packages().await;
+        }
+        let set = crate::features::snapshots::capture_package_set(label, self.installed());
+        let total: usize = set.managers.values().map(Vec::len).sum();
+        self.status_message = Some(match crate::features::snapshots::save_package_set(&set) {
+            Ok(_) => format!("package set {label} saved ({total} packages)"),
+            Err(err) => err.to_string(),
+        });
+    }
+
+    /// Diff a recorded package set against the current state and put the
+    /// resulting plan in front of the user. Like a snapshot restore, this
+    /// never skips the confirmation gate.
+    async fn request_package_restore(&mut self, label: &str) {
+        if self.packages.is_not_loaded() {
+            self.load_packages().await;
+        }
+        let set = match crate::features::snapshots::load_package_set(label) {
+            Ok(set) => set,
+            Err(err) => {
+                self.status_message = Some(err.to_string());
+                return;
+            }
+        };
+        let steps = crate::features::snapshots::diff_package_set(&set, self.installed());
+        if steps.is_empty() {
+            self.status_message = Some(format!("package set {label} already matches the system"));
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.confirm_prompt = Some(ConfirmPrompt {
+            operation: PendingOperation::RestorePackages {
+                label: label.to_string(),
+                steps,
+            },
+            state,
+        });
+        self.open_dialog();
+    }
+
+    /// Run a confirmed package-set restore: per manager, install what is
+    /// missing, remove what is extra and pin recorded versions where the
+    /// backend supports it.
+    async fn apply_package_set(
+        &mut self,
+        label: &str,
+        steps: &[crate::features::snapshots::RestoreStep],
+    ) {
+        let dry_run = self.dry_run();
+        let action = if dry_run {
+            "restore-packages (dry run)"
+        } else {
+            "restore-packages"
+        };
+        let mut failures = Vec::new();
+        for step in steps {
+            let Some(manager) = self.package_managers.get(&step.manager).cloned() else {
+                failures.push(format!("{}: manager not available", step.manager));
+                continue;
+            };
+            if self.offline() && manager.network_operations().contains(&"install") {
+                failures.push(format!("{}: skipped by offline mode", step.manager));
+                continue;
+            }
+            if !step.install.is_empty() {
+                if let Err(err) = manager.install(&step.install, dry_run).await {
+                    failures.push(err.to_string());
+                }
+            }
+            if !step.remove.is_empty() {
+                if let Err(err) = manager.remove(&step.remove, dry_run).await {
+                    failures.push(err.to_string());
+                }
+            }
+            for (name, version) in &step.version_changes {
+                if let Err(err) = manager.install_version(name, version, dry_run).await {
+                    failures.push(err.to_string());
+                }
+            }
+            let packages: Vec<String> = step
+                .install
+                .iter()
+                .chain(step.remove.iter())
+                .cloned()
+                .chain(step.version_changes.iter().map(|(name, _)| name.clone()))
+                .collect();
+            let _ = self.history.record(Transaction {
+                timestamp: Utc::now(),
+                action: action.to_string(),
+                manager: step.manager.clone(),
+                packages,
+                success: failures.is_empty(),
+                snapshot: None,
+            });
+        }
+        self.status_message = Some(if failures.is_empty() {
+            format!("package set {label} {}", if dry_run { "plan resolved (dry run)" } else { "applied" })
+        } else {
+            format!("package set {label}: {}", failures.join("; "))
+        });
+        if !dry_run {
+            self.deps.invalidate();
+            self.load_packages().await;
+            self.load_updates().await;
+        }
+    }
+
+    /// Start the restore flow for the selected snapshot. Restore is
+    /// always dangerous, so the confirmation gate opens directly —
+    /// bypassing the "never" policy — and quotes the backend's own
+    /// explanation of what will happen. Package-set entries route to the
+    /// diff-driven package restore instead.
+    async fn request_snapshot_restore(&mut self) {
+        let Some(entry) = self.selected_snapshot_entry() else {
+            return;
+        };
+        if entry.kind == "packages" {
+            self.request_package_restore(&entry.id).await;
+            return;
+        }
+        let mut state = ListState::default();
+        state.select(Some(0));
+        self.confirm_prompt = Some(ConfirmPrompt {
+            operation: PendingOperation::RestoreSnapshot(entry.id),
+            state,
+        });
+        self.open_dialog();
+    }
+
+    async fn handle_stall_prompt_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.stall_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.stall_prompt = None;
+                self.close_dialog();
+                self.reset_stall_timer();
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                let last = StallPrompt::options().len() - 1;
+                let next = prompt.state.selected().map_or(0, |i| (i + 1).min(last));
+                prompt.state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                let previous = prompt.state.selected().map_or(0, |i| i.saturating_sub(1));
+                prompt.state.select(Some(previous));
+            }
+            KeyCode::Enter => {
+                let choice = prompt.state.selected().unwrap_or(0);
+                self.stall_prompt = None;
+                self.close_dialog();
+                if choice == 0 {
+                    self.reset_stall_timer();
+                } else {
+                    self.abort_operation().await;
+                    self.status_message = Some("operation aborted".to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Give a silent operation another full timeout window before asking again.
+    fn reset_stall_timer(&mut self) {
+        if let Some(operation) = self.operation.as_mut() {
+            operation.last_output = Instant::now();
+        }
+    }
+
+    /// Interrupt the running operation and give it a moment to clean up.
+    async fn abort_operation(&mut self) {
+        let Some(operation) = self.operation.take() else {
+            return;
+        };
+        let pid = operation.pid.load(Ordering::Relaxed);
+        if pid != 0 {
+            // SIGINT lets apt/dpkg roll back cleanly where possible.
+            let _ = tokio::process::Command::new("kill")
+                .args(["-INT", &pid.to_string()])
+                .status()
+                .await;
+            let _ = tokio::time::timeout(Duration::from_secs(3), operation.handle).await;
+        } else {
+            operation.handle.abort();
+            let _ = operation.handle.await;
+        }
+    }
+
+    async fn clean_cache(&mut self) {
+        for id in self.scope_ids() {
+            let Some(manager) = self.package_managers.get(&id) else {
+                continue;
+            };
+            if let Err(err) = manager.clean_cache().await {
+                self.status_message = Some(err.to_string());
+                return;
+            }
+        }
+        self.status_message = Some("cache cleaned".to_string());
+    }
+
+    async fn hold_package(&mut self, package: &str, hold: bool) {
+        let managers: Vec<_> = self.package_managers.values().cloned().collect();
+        for manager in managers {
+            let result = if hold {
+                manager.hold(package).await
+            } else {
+                manager.unhold(package).await
+            };
+            match result {
+                Ok(()) => {
+                    self.status_message = Some(format!(
+                        "{} {}",
+                        if hold { "held" } else { "unheld" },
+                        package
+                    ));
+                    self.load_held().await;
+                    return;
+                }
+                Err(err) => self.status_message = Some(err.to_string()),
+            }
+        }
+    }
+}
+
+/// Move left from `cursor` to the start of the previous word.
+fn word_boundary_left(input: &str, cursor: usize) -> usize {
+    let before = &input[..cursor];
+    let trimmed = before.trim_end_matches(' ');
+    trimmed
+        .rfind(' ')
+        .map(|i| i + 1)
+        .unwrap_or(0)
+}
+
+/// Move right from `cursor` to the start of the next word (or the end).
+fn word_boundary_right(input: &str, cursor: usize) -> usize {
+    let after = &input[cursor..];
+    let skipped_word = after.trim_start_matches(|c| c != ' ');
+    let skipped_spaces = skipped_word.trim_start_matches(' ');
+    input.len() - skipped_spaces.len()
+}
+
+/// Clean up pasted text for single-line input: newlines become spaces and
+/// all other control characters (tabs, ANSI escape fragments, ...) are
+/// dropped.
+fn sanitize_paste(text: &str) -> String {
+    let mut clean = String::with_capacity(text.len());
+    let mut in_escape = false;
+    for c in text.replace("\r\n", "\n").chars() {
+        if in_escape {
+            // Swallow a CSI/OSC-style sequence up to its terminating letter.
+            if c.is_ascii_alphabetic() || c == '\u{7}' {
+                in_escape = false;
+            }
+            continue;
+        }
+        match c {
+            '\u{1b}' => in_escape = true,
+            '\n' | '\r' => clean.push(' '),
+            '\t' => clean.push(' '),
+            c if c.is_control() => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_converts_newlines_to_spaces() {
+        assert_eq!(sanitize_paste("htop\ntmux\nvim"), "htop tmux vim");
+    }
+
+    #[test]
+    fn paste_handles_crlf() {
+        assert_eq!(sanitize_paste("htop\r\ntmux\r\n"), "htop tmux ");
+    }
+
+    #[test]
+    fn paste_converts_tabs() {
+        assert_eq!(sanitize_paste("htop\ttmux"), "htop tmux");
+    }
+
+    #[test]
+    fn paste_strips_ansi_escape_fragments() {
+        assert_eq!(sanitize_paste("\u{1b}[31mhtop\u{1b}[0m tmux"), "htop tmux");
+    }
+
+    #[test]
+    fn paste_keeps_multibyte_text() {
+        assert_eq!(sanitize_paste("p\u{e4}ckage \u{4e2d}\u{6587}"), "p\u{e4}ckage \u{4e2d}\u{6587}");
+    }
+
+    #[tokio::test]
+    async fn background_results_reach_the_ui_without_input() {
+        let mut app = App::new(crate::config::Config::default());
+        app.package_managers = HashMap::new();
+        app.enabled_managers.clear();
+        app.config.auto_refresh_secs = 0;
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        app.auto_refresh = Some(rx);
+        tx.send(vec![PackageUpdate {
+            name: "htop".to_string(),
+            current_version: "3.2.1".to_string(),
+            new_version: "3.3.0".to_string(),
+            manager: "apt".to_string(),
+            security: false,
+        }])
+        .unwrap();
+
+        let backend = ratatui::backend::TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+        // An empty input stream ends the loop after one pass, which is all
+        // the background message needs to reach the view.
+        app.run_with_events(&mut terminal, futures::stream::empty())
+            .await
+            .unwrap();
+
+        assert_eq!(app.pending_updates().len(), 1);
+        assert!(app.frames_rendered > 0, "the UI never redrew");
+    }
+
+    #[test]
+    fn recently_installed_sorts_undated_packages_last() {
+        let package = |name: &str, days_ago: Option<i64>| PackageInfo {
+            name: name.to_string(),
+            version: String::new(),
+            description: String::new(),
+            manager: "apt".to_string(),
+            installed: true,
+            size: None,
+            install_date: days_ago.map(|d| Utc::now() - chrono::Duration::days(d)),
+            origin: None,
+            explicit: None,
+        };
+        let mut packages = vec![
+            package("undated", None),
+            package("old", Some(30)),
+            package("new", Some(1)),
+        ];
+        sort_packages(&mut packages, SortMode::RecentlyInstalled);
+        let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["new", "old", "undated"]);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        let input = "install htop tmux";
+        assert_eq!(word_boundary_left(input, 12), 8);
+        assert_eq!(word_boundary_left(input, 8), 0);
+        assert_eq!(word_boundary_right(input, 0), 8);
+        assert_eq!(word_boundary_right(input, 8), 13);
+        assert_eq!(word_boundary_right(input, 13), input.len());
+    }
+}
@@ -0,0 +1,850 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::error::{PkgError, Result};
+use crate::features::cache::MetadataCache;
+use crate::package_managers::{initialize_package_managers, PackageManager};
+
+/// Command-line interface. Without a subcommand, pkgtool starts the TUI;
+/// with one, it runs the operation directly and exits, so the same binary
+/// works from shell scripts.
+#[derive(Parser)]
+#[command(name = "pkgtool", version, about = "TUI and CLI for system package managers")]
+pub struct Cli {
+    /// Restrict operations to one backend ("apt", "pacman", ...).
+    #[arg(long, global = true)]
+    pub manager: Option<String>,
+
+    /// Assume yes: skip confirmation prompts.
+    #[arg(long, short = 'y', global = true)]
+    pub yes: bool,
+
+    /// Disable colors (NO_COLOR is honored too).
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Emit machine-readable JSON instead of tables.
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Emit newline-delimited JSON, one object per line (implies --json).
+    #[arg(long = "json-lines", global = true)]
+    pub json_lines: bool,
+
+    /// Log at trace level, overriding the configured log_level.
+    #[arg(long, global = true)]
+    pub debug: bool,
+
+    /// Manage a remote machine over ssh, e.g. "admin@server".
+    #[arg(long, global = true, value_name = "USER@HOST")]
+    pub host: Option<String>,
+
+    /// Skip network-touching operations (refresh, search, install).
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Start with the performance overlay visible (same as F12).
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Simulate: resolve and show transactions, but change nothing.
+    #[arg(long = "dry-run", global = true)]
+    pub dry_run: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Search all scoped managers for a package.
+    Search { query: String },
+    /// Install one or more packages.
+    Install {
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+    /// Remove one or more packages.
+    Remove {
+        #[arg(required = true)]
+        packages: Vec<String>,
+    },
+    /// Refresh metadata and upgrade all packages.
+    Update {
+        /// Apply only the updates that fix vulnerabilities. Managers
+        /// without partial upgrades (pacman) refuse and are skipped.
+        #[arg(long)]
+        security: bool,
+    },
+    /// List pending updates.
+    ListUpdates,
+    /// Scan for vulnerabilities and print a compliance report to stdout.
+    SecurityReport {
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+        format: ReportFormat,
+    },
+    /// Re-run the first-launch setup wizard in the TUI.
+    Setup,
+    /// Emit a completion script for the given shell.
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print package-name suggestions from the disk cache. Called by the
+    /// generated completion scripts; not meant for interactive use.
+    #[command(name = "__complete", hide = true)]
+    Complete {
+        /// The subcommand being completed ("install", "remove", ...).
+        verb: String,
+        /// What the user has typed so far.
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+    /// Dependency graph tools.
+    Deps {
+        #[command(subcommand)]
+        command: DepsCommand,
+    },
+    /// Watch for updates in the background and send desktop notifications.
+    Watch,
+    /// Count pending updates and exit 0 (none), 100 (some) or 1 (error).
+    CheckUpdates {
+        /// Print nothing; the exit code carries the answer.
+        #[arg(long)]
+        quiet: bool,
+        /// Skip the metadata refresh and count against the current cache.
+        #[arg(long)]
+        no_refresh: bool,
+        /// Print a per-manager breakdown instead of one total.
+        #[arg(long)]
+        verbose: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DepsCommand {
+    /// Export the dependency graph from a root package to DOT or JSON.
+    Export {
+        /// Root package; omit with --all.
+        package: Option<String>,
+        /// Export from every explicitly installed package instead.
+        #[arg(long, conflicts_with = "package")]
+        all: bool,
+        /// Maximum dependency depth from each root (unlimited without it).
+        #[arg(long, value_name = "N")]
+        depth: Option<usize>,
+        /// Output format.
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+        /// Write to a file instead of stdout.
+        #[arg(long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// Export past the node-count safety limit anyway.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Formats `deps export` can emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+/// Formats `security-report` can emit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    Json,
+    Html,
+    Md,
+}
+
+/// Upper bound per backend for check-updates, so a missing network fails
+/// fast instead of hanging a status bar.
+const CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Process exit codes. Clap exits with `EXIT_USAGE` on its own for parse
+/// failures; the remaining codes are ours. Scripts rely on these values.
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+/// Some packages in a batch succeeded and some failed.
+const EXIT_PARTIAL: i32 = 4;
+/// check-updates found pending updates (the apt/dnf convention).
+const EXIT_UPDATES_AVAILABLE: i32 = 100;
+
+/// How subcommand results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    /// Human-readable tables.
+    Human,
+    /// One JSON array/object for the whole result set.
+    Json,
+    /// One JSON object per line, for streaming consumers.
+    JsonLines,
+}
+
+impl OutputMode {
+    fn json(self) -> bool {
+        self != OutputMode::Human
+    }
+}
+
+/// One install/remove outcome, as emitted in JSON mode. The field names and
+/// status values are a stable interface for scripts.
+#[derive(Debug, Serialize)]
+struct OperationResult {
+    package: String,
+    /// "installed", "removed" or "failed"; "would-install" and
+    /// "would-remove" under --dry-run.
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run one CLI subcommand to completion. Returns the process exit code:
+/// 0 on success, 1 on failure, 4 when only part of a batch failed, 100
+/// when check-updates found pending updates (usage errors exit 2 via clap).
+pub async fn run(cli: Cli, config: Config) -> i32 {
+    // The completion commands never touch the backends: `completions` only
+    // prints a script, and `__complete` runs inside the user's shell where
+    // anything slower than the disk cache would be felt on every keystroke.
+    match &cli.command {
+        Some(Command::Completions { shell }) => {
+            emit_completions(*shell);
+            return EXIT_OK;
+        }
+        Some(Command::Complete { verb, prefix }) => {
+            complete(verb, prefix);
+            return EXIT_OK;
+        }
+        _ => {}
+    }
+    let offline = config.offline;
+    let dry_run = config.dry_run;
+    let managers = match scoped_managers(&cli, &config) {
+        Ok(managers) => managers,
+        Err(message) => {
+            eprintln!("pkgtool: {message}");
+            return EXIT_FAILURE;
+        }
+    };
+    let mode = if cli.json_lines {
+        OutputMode::JsonLines
+    } else if cli.json {
+        OutputMode::Json
+    } else {
+        OutputMode::Human
+    };
+    let command = cli.command.expect("run requires a subcommand");
+    if let Command::CheckUpdates {
+        quiet,
+        no_refresh,
+        verbose,
+    } = command
+    {
+        return check_updates(&managers, quiet, no_refresh || offline, verbose).await;
+    }
+    if matches!(command, Command::Watch) {
+        return crate::features::watch::run(&managers, &config).await;
+    }
+    let result = match command {
+        Command::Search { query } => search(&managers, &query, mode, offline).await.map(|()| EXIT_OK),
+        // --yes and a policy that does not require asking both settle the
+        // question up front; `operate` only prompts when it stays open.
+        Command::Install { packages } => {
+            let yes = cli.yes || !config.requires_confirmation(false);
+            operate(&managers, &packages, yes, true, mode, offline, dry_run).await
+        }
+        Command::Remove { packages } => {
+            let yes = cli.yes || !config.requires_confirmation(true);
+            operate(&managers, &packages, yes, false, mode, offline, dry_run).await
+        }
+        Command::Update { security } => {
+            let yes = cli.yes || !config.requires_confirmation(false);
+            update(&managers, security, yes, offline, dry_run).await
+        }
+        Command::ListUpdates => list_updates(&managers, mode).await.map(|()| EXIT_OK),
+        Command::SecurityReport { format } => security_report(&managers, &config, format)
+            .await
+            .map(|()| EXIT_OK),
+        Command::Deps {
+            command:
+                DepsCommand::Export {
+                    package,
+                    all,
+                    depth,
+                    format,
+                    output,
+                    force,
+                },
+        } => deps_export(&managers, package, all, depth, format, output, force).await,
+        Command::Watch
+        | Command::CheckUpdates { .. }
+        | Command::Completions { .. }
+        | Command::Complete { .. } => {
+            unreachable!("handled above")
+        }
+        Command::Setup => unreachable!("handled in main"),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("pkgtool: {err}");
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// Shell shims appended to the generated scripts so package-name arguments
+/// complete dynamically through `pkgtool __complete`.
+const BASH_DYNAMIC: &str = r#"
+# Dynamic package-name completion backed by pkgtool's metadata cache.
+_pkgtool_dynamic() {
+    _pkgtool "$@"
+    case "${COMP_WORDS[1]}" in
+        install|remove|hold|unhold)
+            if [[ ${COMP_WORDS[COMP_CWORD]} != -* ]]; then
+                COMPREPLY+=( $(pkgtool __complete "${COMP_WORDS[1]}" "${COMP_WORDS[COMP_CWORD]}" 2>/dev/null) )
+            fi ;;
+    esac
+}
+complete -F _pkgtool_dynamic -o nosort -o bashdefault -o default pkgtool
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+# Dynamic package-name completion backed by pkgtool's metadata cache.
+_pkgtool_dynamic() {
+    _pkgtool "$@"
+    case $words[2] in
+        install|remove|hold|unhold)
+            if [[ $words[CURRENT] != -* ]]; then
+                compadd -- ${(f)"$(pkgtool __complete $words[2] $words[CURRENT] 2>/dev/null)"}
+            fi ;;
+    esac
+}
+compdef _pkgtool_dynamic pkgtool
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+# Dynamic package-name completion backed by pkgtool's metadata cache.
+complete -c pkgtool -n "__fish_seen_subcommand_from install remove hold unhold" -f     -a "(pkgtool __complete (commandline -opc)[2] (commandline -ct) 2>/dev/null)"
+"#;
+
+/// Print the static completion script for `shell`, with the dynamic shim
+/// appended for the shells that support one.
+fn emit_completions(shell: clap_complete::Shell) {
+    let mut stdout = std::io::stdout();
+    clap_complete::generate(shell, &mut Cli::command(), "pkgtool", &mut stdout);
+    match shell {
+        clap_complete::Shell::Bash => print!("{BASH_DYNAMIC}"),
+        clap_complete::Shell::Zsh => print!("{ZSH_DYNAMIC}"),
+        clap_complete::Shell::Fish => print!("{FISH_DYNAMIC}"),
+        _ => {}
+    }
+}
+
+/// Print package-name suggestions for `verb` starting with `prefix`, one
+/// per line. Reads only the disk cache so it returns within a shell's
+/// latency budget; an absent cache means no suggestions, never an error.
+fn complete(verb: &str, prefix: &str) {
+    let datasets: &[&str] = match verb {
+        // No cache of the full available set exists, so install completes
+        // from what the cache does know: installed names and pending updates.
+        "install" => &["packages", "updates"],
+        "remove" | "hold" | "unhold" => &["packages"],
+        _ => return,
+    };
+    let cache = MetadataCache::new();
+    let mut names: Vec<String> = datasets
+        .iter()
+        .flat_map(|dataset| cache.cached_names(dataset))
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort();
+    names.dedup();
+    for name in names {
+        println!("{name}");
+    }
+}
+
+/// The detected managers, narrowed by `--manager` and the config scope.
+fn scoped_managers(
+    cli: &Cli,
+    config: &Config,
+) -> std::result::Result<Vec<Arc<dyn PackageManager>>, String> {
+    let registry = initialize_package_managers(config);
+    if registry.is_empty() {
+        return Err("no supported package manager detected".to_string());
+    }
+    let managers: Vec<Arc<dyn PackageManager>> = registry
+        .into_iter()
+        .filter(|(id, _)| match &cli.manager {
+            Some(wanted) => id == wanted,
+            None => config.managers.is_empty() || config.managers.contains(id),
+        })
+        .map(|(_, manager)| manager)
+        .collect();
+    if managers.is_empty() {
+        return Err(match &cli.manager {
+            Some(wanted) => format!("unknown or unavailable manager: {wanted}"),
+            None => "config leaves no manager enabled".to_string(),
+        });
+    }
+    Ok(managers)
+}
+
+async fn search(
+    managers: &[Arc<dyn PackageManager>],
+    query: &str,
+    mode: OutputMode,
+    offline: bool,
+) -> Result<()> {
+    let mut all = Vec::new();
+    for manager in managers {
+        if offline && manager.network_operations().contains(&"search") {
+            eprintln!("pkgtool: {}: search disabled by offline mode", manager.id());
+            continue;
+        }
+        for pkg in manager.search(query).await? {
+            match mode {
+                OutputMode::JsonLines => println!("{}", serde_json::to_string(&pkg)?),
+                OutputMode::Json => all.push(pkg),
+                OutputMode::Human => {
+                    all.push(pkg.clone());
+                    println!(
+                        "{:<32} {:<20} [{}] {}",
+                        pkg.name,
+                        pkg.version,
+                        pkg.manager,
+                        pkg.description.lines().next().unwrap_or("")
+                    );
+                }
+            }
+        }
+    }
+    match mode {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&all)?),
+        OutputMode::Human if all.is_empty() => println!("no results for \"{query}\""),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Upgrade the system, or with `security` only the updates that fix
+/// vulnerabilities. Each manager fails on its own, so one refusing the
+/// security subset (pacman) does not stop the others.
+async fn update(
+    managers: &[Arc<dyn PackageManager>],
+    security: bool,
+    yes: bool,
+    offline: bool,
+    dry_run: bool,
+) -> Result<i32> {
+    let verb = if security {
+        "apply security updates"
+    } else {
+        "update the system"
+    };
+    if !dry_run && !yes && !confirm(&format!("{verb}?"))? {
+        println!("aborted");
+        return Ok(EXIT_OK);
+    }
+    let mut attempted = 0usize;
+    let mut failures = 0usize;
+    for manager in managers {
+        if offline && manager.network_operations().contains(&"update") {
+            eprintln!("pkgtool: {}: update disabled by offline mode", manager.id());
+            continue;
+        }
+        attempted += 1;
+        let result = if security {
+            let names: Vec<String> = manager
+                .list_updates()
+                .await?
+                .into_iter()
+                .filter(|update| update.security)
+                .map(|update| update.name)
+                .collect();
+            if names.is_empty() {
+                println!("{}: no pending security updates", manager.id());
+                continue;
+            }
+            manager.update_security(&names, dry_run).await
+        } else {
+            manager.update_system(dry_run).await
+        };
+        match result {
+            Ok(()) => println!(
+                "{}: {}",
+                manager.id(),
+                if dry_run { "simulated" } else { "done" }
+            ),
+            Err(err) => {
+                eprintln!("pkgtool: {}: {err}", manager.id());
+                failures += 1;
+            }
+        }
+    }
+    Ok(if failures == 0 {
+        EXIT_OK
+    } else if failures == attempted {
+        EXIT_FAILURE
+    } else {
+        EXIT_PARTIAL
+    })
+}
+
+async fn list_updates(managers: &[Arc<dyn PackageManager>], mode: OutputMode) -> Result<()> {
+    let mut all = Vec::new();
+    for manager in managers {
+        for update in manager.list_updates().await? {
+            match mode {
+                OutputMode::JsonLines => println!("{}", serde_json::to_string(&update)?),
+                OutputMode::Json => all.push(update),
+                OutputMode::Human => {
+                    println!(
+                        "{:<32} {:<20} -> {:<20} [{}]",
+                        update.name, update.current_version, update.new_version, update.manager
+                    );
+                    all.push(update);
+                }
+            }
+        }
+    }
+    match mode {
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(&all)?),
+        OutputMode::Human if all.is_empty() => println!("all packages are up to date"),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Scan the scoped managers' packages and print the compliance artifact
+/// to stdout. Uses the same analyzer and on-disk cache as the TUI, so a
+/// recent scan makes this nearly free.
+async fn security_report(
+    managers: &[Arc<dyn PackageManager>],
+    config: &Config,
+    format: ReportFormat,
+) -> Result<()> {
+    use crate::features::security::{SecurityAnalyzer, SecurityReportExport, SystemInfo};
+    let mut packages = Vec::new();
+    for manager in managers {
+        packages.extend(manager.list_installed().await?);
+    }
+    let analyzer = SecurityAnalyzer::new(config.security.clone());
+    let report = analyzer.scan(&packages).await?;
+    let export = SecurityReportExport::new(&report, SystemInfo::collect());
+    let rendered = match format {
+        ReportFormat::Json => export.to_json()?,
+        ReportFormat::Html => export.to_html(),
+        ReportFormat::Md => export.to_markdown(),
+    };
+    print!("{rendered}");
+    Ok(())
+}
+
+/// Hard ceiling for `deps export` without --force, to keep an `--all`
+/// run on a full system from writing a surprise multi-megabyte file.
+const EXPORT_NODE_LIMIT: usize = 2000;
+
+/// Walk the dependency graph from a root package (or the explicit set
+/// with --all) and write it as Graphviz DOT or a JSON adjacency list.
+async fn deps_export(
+    managers: &[Arc<dyn PackageManager>],
+    package: Option<String>,
+    all: bool,
+    depth: Option<usize>,
+    format: GraphFormat,
+    output: Option<std::path::PathBuf>,
+    force: bool,
+) -> Result<i32> {
+    use crate::features::deps::{DependencyManager, GraphExport};
+    if package.is_none() && !all {
+        eprintln!("pkgtool: deps export needs a package or --all");
+        return Ok(EXIT_USAGE);
+    }
+    let max_nodes = if force {
+        usize::MAX
+    } else {
+        // One past the limit so "exactly at the limit" still exports.
+        EXPORT_NODE_LIMIT + 1
+    };
+    let mut deps = DependencyManager::new();
+    let mut graph = GraphExport {
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    for manager in managers {
+        let installed = manager.list_installed().await?;
+        let explicit: std::collections::HashSet<String> = installed
+            .iter()
+            .filter(|pkg| pkg.explicit == Some(true))
+            .map(|pkg| pkg.name.clone())
+            .collect();
+        let roots: Vec<String> = match &package {
+            Some(name) => {
+                if !installed.iter().any(|pkg| &pkg.name == name) {
+                    continue;
+                }
+                vec![name.clone()]
+            }
+            None => {
+                let mut roots: Vec<String> = explicit.iter().cloned().collect();
+                roots.sort();
+                roots
+            }
+        };
+        if roots.is_empty() {
+            continue;
+        }
+        let budget = max_nodes.saturating_sub(graph.nodes.len());
+        let mut part = deps
+            .export_graph(manager.as_ref(), &roots, &explicit, depth, budget)
+            .await?;
+        graph.nodes.append(&mut part.nodes);
+        graph.edges.append(&mut part.edges);
+    }
+    if graph.nodes.is_empty() {
+        eprintln!(
+            "pkgtool: {}",
+            match &package {
+                Some(name) => format!("{name} is not installed"),
+                None => "no manager reported explicitly installed packages".to_string(),
+            }
+        );
+        return Ok(EXIT_FAILURE);
+    }
+    if !force && graph.nodes.len() > EXPORT_NODE_LIMIT {
+        eprintln!(
+            "pkgtool: graph exceeds {EXPORT_NODE_LIMIT} nodes; re-run with --force to export it anyway"
+        );
+        return Ok(EXIT_FAILURE);
+    }
+    let rendered = match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph)? + "\n",
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)?;
+            eprintln!(
+                "pkgtool: wrote {} node(s) and {} edge(s) to {}",
+                graph.nodes.len(),
+                graph.edges.len(),
+                path.display()
+            );
+        }
+        None => print!("{rendered}"),
+    }
+    Ok(EXIT_OK)
+}
+
+/// Count pending updates across the scoped managers.
+///
+/// Exit codes follow the apt/dnf convention for update probes: 0 when
+/// everything is current, 100 when updates exist, 1 on any error. Designed
+/// for status bars, so it never touches the terminal beyond plain prints.
+async fn check_updates(
+    managers: &[Arc<dyn PackageManager>],
+    quiet: bool,
+    no_refresh: bool,
+    verbose: bool,
+) -> i32 {
+    let mut total = 0usize;
+    let mut security_total = 0usize;
+    for manager in managers {
+        if !no_refresh {
+            match tokio::time::timeout(CHECK_TIMEOUT, manager.refresh_metadata()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => {
+                    eprintln!("pkgtool: {}: {err}", manager.id());
+                    return EXIT_FAILURE;
+                }
+                Err(_) => {
+                    eprintln!("pkgtool: {}: refresh timed out", manager.id());
+                    return EXIT_FAILURE;
+                }
+            }
+        }
+        match tokio::time::timeout(CHECK_TIMEOUT, manager.list_updates()).await {
+            Ok(Ok(updates)) => {
+                let security = updates.iter().filter(|update| update.security).count();
+                if verbose && !quiet {
+                    println!("{}: {} ({security} security)", manager.id(), updates.len());
+                }
+                total += updates.len();
+                security_total += security;
+            }
+            Ok(Err(err)) => {
+                eprintln!("pkgtool: {}: {err}", manager.id());
+                return EXIT_FAILURE;
+            }
+            Err(_) => {
+                eprintln!("pkgtool: {}: update check timed out", manager.id());
+                return EXIT_FAILURE;
+            }
+        }
+    }
+    if !quiet && !verbose {
+        // The bare total stays first so status bars keep their number.
+        if security_total > 0 {
+            println!("{total} ({security_total} security)");
+        } else {
+            println!("{total}");
+        }
+    }
+    if total == 0 {
+        EXIT_OK
+    } else {
+        EXIT_UPDATES_AVAILABLE
+    }
+}
+
+/// Install or remove `packages`, trying managers in the TUI's fallback
+/// order. Packages are attempted one at a time so a bad name does not sink
+/// the whole batch; a package rejected by one manager is retried with the
+/// next. Returns `EXIT_OK`, `EXIT_PARTIAL` or `EXIT_FAILURE` depending on
+/// how much of the batch went through, and summarizes the failures with
+/// their reasons on stderr.
+async fn operate(
+    managers: &[Arc<dyn PackageManager>],
+    packages: &[String],
+    yes: bool,
+    install: bool,
+    mode: OutputMode,
+    offline: bool,
+    dry_run: bool,
+) -> Result<i32> {
+    let verb = if install { "install" } else { "remove" };
+    // A dry run changes nothing, so there is nothing to confirm.
+    if mode.json() && !dry_run {
+        // JSON consumers cannot answer an interactive prompt; the caller
+        // decides up front or the command refuses to run.
+        if !yes {
+            eprintln!("pkgtool: {verb} with --json requires --yes");
+            return Ok(EXIT_USAGE);
+        }
+    } else if !mode.json() && !dry_run && !yes && !confirm(&format!("{verb} {}?", packages.join(" ")))? {
+        println!("aborted");
+        return Ok(EXIT_OK);
+    }
+    // Why each still-pending package last failed; cleared on success.
+    let mut failures: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+    let mut pending: Vec<String> = packages.to_vec();
+    for manager in managers {
+        if pending.is_empty() {
+            break;
+        }
+        if offline && manager.network_operations().contains(&verb) {
+            let reason = PkgError::Unsupported {
+                manager: manager.id().to_string(),
+                operation: format!("{verb} disabled by offline mode"),
+            };
+            for package in &pending {
+                failures.insert(package.clone(), reason.to_string());
+            }
+            continue;
+        }
+        let results = if install {
+            manager.install_each(&pending, dry_run).await
+        } else {
+            manager.remove_each(&pending, dry_run).await
+        };
+        pending = Vec::new();
+        for (package, result) in results {
+            match result {
+                Ok(()) => {
+                    failures.remove(&package);
+                }
+                Err(err) => {
+                    failures.insert(package.clone(), err.to_string());
+                    pending.push(package);
+                }
+            }
+        }
+    }
+    let results: Vec<OperationResult> = packages
+        .iter()
+        .map(|package| match failures.get(package) {
+            Some(reason) => OperationResult {
+                package: package.clone(),
+                status: "failed",
+                error: Some(reason.clone()),
+            },
+            None => OperationResult {
+                package: package.clone(),
+                status: match (install, dry_run) {
+                    (true, false) => "installed",
+                    (false, false) => "removed",
+                    (true, true) => "would-install",
+                    (false, true) => "would-remove",
+                },
+                error: None,
+            },
+        })
+        .collect();
+    emit_operation_results(&results, mode)?;
+    if !failures.is_empty() {
+        let summary: Vec<String> = results
+            .iter()
+            .filter_map(|result| {
+                let reason = result.error.as_ref()?;
+                Some(format!("{}: {reason}", result.package))
+            })
+            .collect();
+        eprintln!(
+            "pkgtool: failed to {verb} {} of {} package(s): {}",
+            failures.len(),
+            packages.len(),
+            summary.join("; ")
+        );
+    }
+    Ok(if failures.is_empty() {
+        EXIT_OK
+    } else if failures.len() == packages.len() {
+        EXIT_FAILURE
+    } else {
+        EXIT_PARTIAL
+    })
+}
+
+/// Write the per-package outcomes in the requested format. Failures are
+/// kept out of Human stdout; they go to the stderr summary instead.
+fn emit_operation_results(results: &[OperationResult], mode: OutputMode) -> Result<()> {
+    match mode {
+        OutputMode::Human => {
+            if let Some(first) = results.iter().find(|result| result.error.is_none()) {
+                let succeeded: Vec<&str> = results
+                    .iter()
+                    .filter(|result| result.error.is_none())
+                    .map(|result| result.package.as_str())
+                    .collect();
+                println!("{}: {}", first.status, succeeded.join(" "));
+            }
+        }
+        OutputMode::Json => println!("{}", serde_json::to_string_pretty(results)?),
+        OutputMode::JsonLines => {
+            for result in results {
+                println!("{}", serde_json::to_string(result)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal; defaults to no.
+fn confirm(question: &str) -> Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
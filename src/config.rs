@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PkgError, Result};
+
+/// User configuration, read from `config.toml` in the platform config
+/// directory (`$XDG_CONFIG_HOME/pkgtool/` on Linux).
+///
+/// Every field has a default, so a missing file or a file setting only a few
+/// keys both work. Unknown keys are ignored to stay forward compatible.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Manager ids to query; empty means every detected manager.
+    pub managers: Vec<String>,
+    /// Manager ids to register even when distro detection would skip them.
+    pub force_managers: Vec<String>,
+    /// Manager ids never to register, overriding detection.
+    pub disabled_managers: Vec<String>,
+    /// Color theme: "default" or "no-color".
+    pub theme: String,
+    /// UI language: a tag like "en" or "es", or "auto" to follow LANG.
+    pub locale: String,
+    /// When to ask before a mutating operation: "always",
+    /// "destructive-only" (removals only) or "never". `--yes` overrides.
+    pub confirm: String,
+    /// Update search results while typing (debounced); off by default
+    /// because it is wasteful over slow managers.
+    pub live_search: bool,
+    /// Skip network-touching operations and serve data from cache.
+    pub offline: bool,
+    /// Simulate mutating operations: show transaction plans, change nothing.
+    pub dry_run: bool,
+    /// Seconds between automatic refreshes; 0 disables them.
+    pub auto_refresh_secs: u64,
+    /// `pkgtool watch` notifies only when security updates are pending.
+    pub notify_security_only: bool,
+    /// Per-manager timeout for list/search queries, in seconds.
+    pub manager_timeout_secs: u64,
+    /// How many network-heavy backend operations (refresh, remote search)
+    /// may run at once across all managers.
+    pub network_concurrency: usize,
+    /// How many local queries (installed lists, details) may run at once.
+    pub local_concurrency: usize,
+    /// Whether j/k/g/G style navigation is active.
+    pub vim_keys: bool,
+    /// Tab shown at startup: "overview", "packages", "updates", "search" or "log".
+    pub default_tab: String,
+    /// List-pane share of the list/details split, in percent (20-80).
+    pub split_ratio: u16,
+    /// List row density: "compact" or "detailed".
+    pub density: String,
+    /// Log file verbosity: "off", "error", "warn", "info", "debug" or "trace".
+    pub log_level: String,
+    /// Privilege escalation tool for root operations: "sudo", "doas",
+    /// "pkexec" or "run0". Falls back to whichever is installed.
+    pub escalation: String,
+    /// What to do when launched through sudo: "adapt" (resolve config and
+    /// cache against the invoking user's home), "warn" (keep root's paths
+    /// but say so first) or "allow" (continue silently). A plain root
+    /// login, e.g. in a container, always behaves like "allow".
+    pub root_behavior: String,
+    /// Where to write the session report on exit; empty disables it.
+    /// Supports strftime placeholders and a leading `~`; a `.json`
+    /// extension selects JSON instead of Markdown.
+    pub report_path: String,
+    /// View state restored at startup: any of "tab", "filters", "scroll",
+    /// "history" and "split". Remove items for a fresh view every launch.
+    pub session_restore: Vec<String>,
+    /// Keybinding overrides, action id to key (e.g. `"system.update" = "U"`).
+    pub keybindings: HashMap<String, String>,
+    /// Remote host to manage over ssh; an empty host means this machine.
+    pub remote: crate::utils::host::RemoteConfig,
+    /// Snapshot locations (btrfs) and size (LVM).
+    pub snapshots: crate::features::snapshots::SnapshotConfig,
+    /// Proxy for backend commands; empty fields follow $http_proxy et al.
+    pub proxy: crate::utils::proxy::ProxyConfig,
+    /// OSV vulnerability scan: response cache lifetime.
+    pub security: crate::features::security::SecurityConfig,
+    /// Extra backends driven by external commands, keyed by manager id.
+    pub plugins: HashMap<String, crate::package_managers::plugin::PluginConfig>,
+    /// Scripts run around install/remove/update operations.
+    pub hooks: crate::features::hooks::HooksConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            managers: Vec::new(),
+            force_managers: Vec::new(),
+            disabled_managers: Vec::new(),
+            theme: "default".to_string(),
+            locale: "auto".to_string(),
+            confirm: "always".to_string(),
+            live_search: false,
+            offline: false,
+            dry_run: false,
+            auto_refresh_secs: 30 * 60,
+            notify_security_only: false,
+            manager_timeout_secs: 15,
+            network_concurrency: 2,
+            local_concurrency: 4,
+            vim_keys: true,
+            default_tab: "overview".to_string(),
+            split_ratio: crate::app::DEFAULT_SPLIT_RATIO,
+            density: "compact".to_string(),
+            log_level: "info".to_string(),
+            escalation: "sudo".to_string(),
+            root_behavior: "adapt".to_string(),
+            report_path: String::new(),
+            session_restore: ["tab", "filters", "scroll", "history", "split"]
+                .map(str::to_string)
+                .to_vec(),
+            keybindings: HashMap::new(),
+            remote: crate::utils::host::RemoteConfig::default(),
+            snapshots: crate::features::snapshots::SnapshotConfig::default(),
+            proxy: crate::utils::proxy::ProxyConfig::default(),
+            security: crate::features::security::SecurityConfig::default(),
+            plugins: HashMap::new(),
+            hooks: crate::features::hooks::HooksConfig::default(),
+        }
+    }
+}
+
+/// Header written above the values so a generated file documents itself.
+const FILE_HEADER: &str = "\
+# pkgtool configuration.
+#
+# managers            manager ids to query; empty means every detected one
+# force_managers      register these ids even when detection would skip them
+# disabled_managers   never register these ids, overriding detection
+# theme               \"default\" or \"no-color\"
+# locale              UI language tag (\"en\", \"es\") or \"auto\" to follow LANG
+# confirm             ask first: \"always\", \"destructive-only\" (removals) or \"never\"
+# live_search         update search results while typing (debounced)
+# offline             skip network-touching operations, serve from cache
+# dry_run             simulate mutating operations; nothing is changed
+# auto_refresh_secs   seconds between automatic refreshes; 0 disables them
+# notify_security_only `pkgtool watch` notifies only for security updates
+# manager_timeout_secs per-manager timeout for list/search queries
+# network_concurrency  concurrent network-heavy backend operations (refresh/search)
+# local_concurrency    concurrent local queries (installed lists, details)
+# vim_keys            j/k/g/G style navigation
+# default_tab         \"overview\", \"packages\", \"updates\", \"search\" or \"log\"
+# split_ratio         list-pane share of the list/details split, in percent
+# density             \"compact\" or \"detailed\"
+# log_level           log file verbosity; \"off\" to \"trace\" (--debug overrides)
+# escalation          privilege escalation tool: \"sudo\", \"doas\", \"pkexec\" or \"run0\"
+# root_behavior       under sudo: \"adapt\" (invoking user's paths), \"warn\" or \"allow\"
+# report_path         session report written on exit (strftime placeholders; empty = off)
+# session_restore     view state restored at startup; remove items for a fresh view
+# [keybindings]       action id to key, e.g. \"system.update\" = \"U\"
+# [remote]            manage another machine over ssh: host, user, ssh_options
+# [snapshots]         locations and size, plus the automatic-snapshot policy:
+#                     auto (\"always\"/\"upgrades-only\"/\"over-threshold\"/\"never\"),
+#                     threshold and on_failure (\"abort\" or \"warn\"), and
+#                     retention: keep_last and keep_days (0 disables a rule),
+#                     and min_free_mb to warn before snapshotting a full volume
+# [proxy]             http/https/no_proxy overrides; empty follows the environment
+# [security]          vulnerability scan: cache_ttl_hours, scan_interval_hours and
+#                     engine (auto/builtin/arch-audit/debsecan)
+# [plugins.<id>]      external backend: command templates plus a \"format\"
+# [hooks]             pre_/post_ install/remove/update script lists, timeout_secs
+
+";
+
+impl Config {
+    /// Whether the policy requires asking before an operation — the one
+    /// decision point for every confirmation, TUI dialog and CLI prompt
+    /// alike. Unrecognized values err on the side of asking.
+    pub fn requires_confirmation(&self, destructive: bool) -> bool {
+        match self.confirm.as_str() {
+            "never" => false,
+            "destructive-only" => destructive,
+            _ => true,
+        }
+    }
+
+    /// Whether a config file exists; its absence marks a first run.
+    pub fn exists() -> bool {
+        config_path().exists()
+    }
+
+    /// Load the configuration, falling back to defaults when the file does
+    /// not exist. A malformed file is a hard error: the message names the
+    /// offending line and field rather than silently using defaults.
+    pub fn load() -> Result<Config> {
+        let path = config_path();
+        let data = match std::fs::read_to_string(&path) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(err.into()),
+        };
+        toml::from_str(&data).map_err(|err| PkgError::Config {
+            path: path.display().to_string(),
+            detail: err.to_string(),
+        })
+    }
+
+    /// Write the configuration back, creating a commented file on first save.
+    pub fn save(&self) -> Result<()> {
+        let path = config_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let values = toml::to_string_pretty(self).map_err(|err| PkgError::Config {
+            path: path.display().to_string(),
+            detail: err.to_string(),
+        })?;
+        std::fs::write(&path, format!("{FILE_HEADER}{values}"))?;
+        Ok(())
+    }
+}
+
+fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("config.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file_fills_in_defaults() {
+        let config: Config = toml::from_str("split_ratio = 70\ndensity = \"detailed\"").unwrap();
+        assert_eq!(config.split_ratio, 70);
+        assert_eq!(config.density, "detailed");
+        assert_eq!(config.theme, "default");
+        assert_eq!(config.confirm, "always");
+    }
+
+    #[test]
+    fn confirmation_policy_levels() {
+        let policy = |level: &str| Config {
+            confirm: level.to_string(),
+            ..Config::default()
+        };
+        assert!(policy("always").requires_confirmation(false));
+        assert!(policy("destructive-only").requires_confirmation(true));
+        assert!(!policy("destructive-only").requires_confirmation(false));
+        assert!(!policy("never").requires_confirmation(true));
+        // A typo in the config must not silence the prompts.
+        assert!(policy("destuctive-only").requires_confirmation(false));
+    }
+
+    #[test]
+    fn malformed_file_reports_the_location() {
+        let err = toml::from_str::<Config>("split_ratio = \"wide\"").unwrap_err();
+        assert!(err.to_string().contains("split_ratio"));
+    }
+}
@@ -0,0 +1,48 @@
+use std::io;
+
+use thiserror::Error;
+
+/// Errors produced by package manager backends and the surrounding tooling.
+#[allow(dead_code)] // some variants are only built by not-yet-registered backends
+#[derive(Debug, Error)]
+pub enum PkgError {
+    /// A backend command exited with a non-zero status.
+    #[error("command `{command}` failed with status {status}: {stderr}")]
+    CommandFailed {
+        command: String,
+        status: i32,
+        stderr: String,
+    },
+
+    /// The requested package could not be found by the backend.
+    #[error("package not found: {0}")]
+    NotFound(String),
+
+    /// Output from a backend command could not be parsed.
+    #[error("failed to parse {source_desc} output: {detail}")]
+    Parse { source_desc: String, detail: String },
+
+    /// The backend does not support the requested operation.
+    #[error("operation not supported by {manager}: {operation}")]
+    Unsupported { manager: String, operation: String },
+
+    /// The configuration file could not be read or parsed.
+    #[error("config file {path}: {detail}")]
+    Config { path: String, detail: String },
+
+    /// The operation was cancelled by the user.
+    #[error("operation cancelled")]
+    Cancelled,
+
+    /// No usable package manager was detected on the system.
+    #[error("no supported package manager detected")]
+    NoManager,
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, PkgError>;
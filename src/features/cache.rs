@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// How long an entry stays usable when the manager's database mtime cannot
+/// be checked (e.g. homebrew, which has no single database file).
+const FALLBACK_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// On-disk cache of package metadata between sessions.
+///
+/// One JSON file per manager and dataset under the platform cache directory
+/// (`$XDG_CACHE_HOME/pkgtool/` on Linux). Entries carry the mtime of the
+/// manager's own database at write time, so staleness detection is one
+/// `stat` call instead of re-running list commands. A corrupted file is
+/// discarded and rebuilt on the next store; it never aborts startup.
+pub struct MetadataCache {
+    dir: PathBuf,
+}
+
+/// A cached dataset plus the freshness metadata it was written with.
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    timestamp: DateTime<Utc>,
+    /// Seconds-since-epoch mtime of the manager's database when written.
+    db_mtime: Option<u64>,
+    data: T,
+}
+
+impl MetadataCache {
+    pub fn new() -> Self {
+        let dir = dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("pkgtool");
+        MetadataCache { dir }
+    }
+
+    /// Load a dataset if a fresh entry exists; `None` means the caller
+    /// should fall back to querying the backend.
+    pub fn load<T: DeserializeOwned>(&self, manager: &str, dataset: &str) -> Option<T> {
+        // Once the ssh connection to a remote host has dropped, the host
+        // may have moved on without us; nothing cached counts as fresh.
+        if crate::utils::host::connection_severed() {
+            return None;
+        }
+        let envelope: Option<Envelope<T>> = self.read(manager, dataset);
+        let fresh = envelope.as_ref().is_some_and(|envelope| {
+            match (database_mtime(manager), envelope.db_mtime) {
+                (Some(current), Some(stored)) => current == stored,
+                _ => {
+                    let age = Utc::now().signed_duration_since(envelope.timestamp);
+                    age.to_std().is_ok_and(|age| age < FALLBACK_TTL)
+                }
+            }
+        });
+        crate::utils::profile::record_cache(fresh);
+        fresh.then(|| envelope.unwrap().data)
+    }
+
+    /// Load a dataset regardless of freshness, with the time it was written.
+    /// Offline mode prefers stale data over nothing, labeled with its age.
+    pub fn load_stale<T: DeserializeOwned>(
+        &self,
+        manager: &str,
+        dataset: &str,
+    ) -> Option<(T, DateTime<Utc>)> {
+        let envelope: Envelope<T> = self.read(manager, dataset)?;
+        Some((envelope.data, envelope.timestamp))
+    }
+
+    fn read<T: DeserializeOwned>(&self, manager: &str, dataset: &str) -> Option<Envelope<T>> {
+        let path = self.path(manager, dataset);
+        let data = std::fs::read_to_string(&path).ok()?;
+        match serde_json::from_str(&data) {
+            Ok(envelope) => Some(envelope),
+            Err(_) => {
+                // Corrupted cache: throw it away and rebuild on next store.
+                let _ = std::fs::remove_file(&path);
+                None
+            }
+        }
+    }
+
+    /// Write a dataset, stamping it with the manager's current database
+    /// mtime so later loads can detect staleness.
+    pub fn store<T: Serialize>(&self, manager: &str, dataset: &str, data: &T) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let envelope = Envelope {
+            timestamp: Utc::now(),
+            db_mtime: database_mtime(manager),
+            data,
+        };
+        let json = serde_json::to_string(&envelope)?;
+        std::fs::write(self.path(manager, dataset), json)?;
+        Ok(())
+    }
+
+    /// Package names across every manager's cached copy of `dataset`,
+    /// fresh or stale, for shell completion. An absent or unreadable cache
+    /// yields an empty list rather than an error.
+    pub fn cached_names(&self, dataset: &str) -> Vec<String> {
+        let suffix = format!("-{dataset}.json");
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut names = Vec::new();
+        for entry in entries.flatten() {
+            if !entry.file_name().to_string_lossy().ends_with(&suffix) {
+                continue;
+            }
+            let Ok(data) = std::fs::read_to_string(entry.path()) else {
+                continue;
+            };
+            let Ok(envelope) = serde_json::from_str::<Envelope<Vec<serde_json::Value>>>(&data)
+            else {
+                continue;
+            };
+            names.extend(
+                envelope
+                    .data
+                    .iter()
+                    .filter_map(|row| Some(row.get("name")?.as_str()?.to_string())),
+            );
+        }
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn path(&self, manager: &str, dataset: &str) -> PathBuf {
+        self.dir.join(format!("{manager}-{dataset}.json"))
+    }
+}
+
+impl Default for MetadataCache {
+    fn default() -> Self {
+        MetadataCache::new()
+    }
+}
+
+/// mtime of the file that changes whenever a manager's database does.
+fn database_mtime(manager: &str) -> Option<u64> {
+    // A remote host's database files are not visible locally; fall back to
+    // the age-based TTL rather than stat-ing this machine's files.
+    if crate::utils::host::is_remote() {
+        return None;
+    }
+    let path = match manager {
+        "apt" => "/var/lib/dpkg/status",
+        "pacman" => "/var/lib/pacman/local",
+        "dnf" => "/var/lib/rpm/rpmdb.sqlite",
+        _ => return None,
+    };
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|mtime| mtime.as_secs())
+}
@@ -0,0 +1,289 @@
+//! End-of-life detection for the running distribution release.
+//!
+//! A release past its EOL date gets no security updates, which quietly
+//! defeats everything the Security tab reports. The dates come from a
+//! bundled table, overlaid with a cached copy fetched from
+//! endoflife.date when a scan runs online — the bundled data keeps the
+//! check working offline and the overlay keeps it from going stale.
+//! Rolling distributions have no EOL and are exempt.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::error::Result;
+
+/// How close to the EOL date the warning starts, in days.
+const NEAR_DAYS: i64 = 60;
+
+/// Release lifecycle API; `{product}.json` lists one entry per cycle.
+const ENDOFLIFE_API: &str = "https://endoflife.date/api";
+
+/// EOL dates shipped with the binary. Approximate dates are acceptable
+/// here: the fetched overlay corrects them, and a warning that is off
+/// by days still beats silence about a dead release.
+const BUNDLED: &str = r#"[
+    {"id": "debian", "release": "10", "eol": "2022-09-10"},
+    {"id": "debian", "release": "11", "eol": "2024-08-14"},
+    {"id": "debian", "release": "12", "eol": "2026-06-10"},
+    {"id": "debian", "release": "13", "eol": "2028-06-10"},
+    {"id": "ubuntu", "release": "20.04", "eol": "2025-05-29"},
+    {"id": "ubuntu", "release": "22.04", "eol": "2027-06-01"},
+    {"id": "ubuntu", "release": "23.10", "eol": "2024-07-11"},
+    {"id": "ubuntu", "release": "24.04", "eol": "2029-05-31"},
+    {"id": "ubuntu", "release": "24.10", "eol": "2025-07-10"},
+    {"id": "ubuntu", "release": "25.04", "eol": "2026-01-31"},
+    {"id": "fedora", "release": "39", "eol": "2024-11-26"},
+    {"id": "fedora", "release": "40", "eol": "2025-05-13"},
+    {"id": "fedora", "release": "41", "eol": "2025-12-15"},
+    {"id": "fedora", "release": "42", "eol": "2026-05-13"},
+    {"id": "opensuse-leap", "release": "15.4", "eol": "2023-12-07"},
+    {"id": "opensuse-leap", "release": "15.5", "eol": "2024-12-31"},
+    {"id": "opensuse-leap", "release": "15.6", "eol": "2025-12-31"}
+]"#;
+
+/// One release's end-of-life date.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EolEntry {
+    /// os-release `ID` the entry applies to.
+    pub id: String,
+    /// os-release `VERSION_ID` of the release.
+    pub release: String,
+    pub eol: NaiveDate,
+}
+
+/// The full date table: bundled entries with the fetched overlay on top.
+pub struct EolTable {
+    entries: Vec<EolEntry>,
+}
+
+impl EolTable {
+    /// The table as shipped; the bundled JSON is trusted to parse, and a
+    /// test pins that it does.
+    pub fn bundled() -> Self {
+        EolTable {
+            entries: serde_json::from_str(BUNDLED).expect("bundled EOL table parses"),
+        }
+    }
+
+    /// Bundled entries with any cached fetch overlaid, newest data
+    /// winning per (id, release). A missing or unreadable cache leaves
+    /// the bundled table as is.
+    pub fn load() -> Self {
+        let mut table = Self::bundled();
+        if let Ok(data) = std::fs::read_to_string(cache_path()) {
+            if let Ok(fetched) = serde_json::from_str::<Vec<EolEntry>>(&data) {
+                for entry in fetched {
+                    table.upsert(entry);
+                }
+            }
+        }
+        table
+    }
+
+    fn upsert(&mut self, entry: EolEntry) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.id == entry.id && existing.release == entry.release)
+        {
+            Some(existing) => existing.eol = entry.eol,
+            None => self.entries.push(entry),
+        }
+    }
+
+    /// The EOL date for a release, or `None` when the table does not
+    /// know it — an unknown release warrants no warning.
+    pub fn eol_for(&self, id: &str, version_id: &str) -> Option<NaiveDate> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id && entry.release == version_id)
+            .map(|entry| entry.eol)
+    }
+}
+
+/// The warning the UI shows: which release, when it dies (or died), and
+/// how many days remain — negative once the date has passed.
+#[derive(Debug, Clone)]
+pub struct EolWarning {
+    /// Human-readable release name, e.g. "Ubuntu 20.04.6 LTS".
+    pub release: String,
+    pub eol: NaiveDate,
+    pub days_left: i64,
+}
+
+impl EolWarning {
+    /// One line for banners: past tense once dead, a countdown before.
+    pub fn describe(&self) -> String {
+        if self.days_left < 0 {
+            format!(
+                "{} reached end of life on {} — no more security updates",
+                self.release, self.eol
+            )
+        } else {
+            format!(
+                "{} reaches end of life in {} day(s) ({})",
+                self.release, self.days_left, self.eol
+            )
+        }
+    }
+}
+
+/// Evaluate the managed host's release against the loaded table.
+pub fn check(today: NaiveDate) -> Option<EolWarning> {
+    let content = crate::utils::host::read_file("/etc/os-release").ok()?;
+    evaluate(&content, &EolTable::load(), today)
+}
+
+/// The check proper, separated from I/O so fixtures can drive it.
+fn evaluate(os_release: &str, table: &EolTable, today: NaiveDate) -> Option<EolWarning> {
+    let field = |key: &str| -> Option<String> {
+        os_release.lines().find_map(|line| {
+            line.strip_prefix(key)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|value| value.trim().trim_matches('"').to_string())
+        })
+    };
+    let id = field("ID")?;
+    // Rolling releases never expire; no VERSION_ID means the same.
+    if matches!(
+        id.as_str(),
+        "arch" | "artix" | "manjaro" | "gentoo" | "void" | "opensuse-tumbleweed"
+    ) {
+        return None;
+    }
+    let version_id = field("VERSION_ID")?;
+    let eol = table.eol_for(&id, &version_id)?;
+    let days_left = (eol - today).num_days();
+    (days_left <= NEAR_DAYS).then(|| EolWarning {
+        release: field("PRETTY_NAME").unwrap_or_else(|| format!("{id} {version_id}")),
+        eol,
+        days_left,
+    })
+}
+
+/// Fetch current dates from endoflife.date and cache them for `load`.
+/// Best-effort per product; returns how many entries were written. The
+/// caller decides whether the network may be used at all.
+pub async fn refresh_cache() -> Result<usize> {
+    let mut entries: Vec<EolEntry> = Vec::new();
+    for (id, product) in [
+        ("debian", "debian"),
+        ("ubuntu", "ubuntu"),
+        ("fedora", "fedora"),
+        ("opensuse-leap", "opensuse"),
+    ] {
+        let argv: Vec<String> = [
+            "curl",
+            "-sS",
+            "--fail",
+            "--max-time",
+            "30",
+            &format!("{ENDOFLIFE_API}/{product}.json"),
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        if let Ok(output) = crate::package_managers::run_backend("eol", &argv).await {
+            entries.extend(parse_endoflife(id, &output));
+        }
+    }
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&entries)?)?;
+    Ok(entries.len())
+}
+
+/// Parse one endoflife.date product listing. The `eol` field is a date
+/// string for finished cycles and `false` for ones without a date yet;
+/// only dated cycles become entries.
+fn parse_endoflife(id: &str, output: &str) -> Vec<EolEntry> {
+    #[derive(Deserialize)]
+    struct Cycle {
+        cycle: String,
+        eol: serde_json::Value,
+    }
+    serde_json::from_str::<Vec<Cycle>>(output)
+        .map(|cycles| {
+            cycles
+                .into_iter()
+                .filter_map(|cycle| {
+                    let eol = cycle.eol.as_str()?.parse().ok()?;
+                    Some(EolEntry {
+                        id: id.to_string(),
+                        release: cycle.cycle,
+                        eol,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("eol.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn the_bundled_table_parses_and_answers() {
+        let table = EolTable::bundled();
+        assert_eq!(table.eol_for("ubuntu", "20.04"), Some(date("2025-05-29")));
+        assert_eq!(table.eol_for("ubuntu", "99.04"), None);
+    }
+
+    #[test]
+    fn past_and_near_eol_releases_warn_but_current_ones_do_not() {
+        let table = EolTable::bundled();
+        let os_release = "ID=ubuntu\nVERSION_ID=\"20.04\"\nPRETTY_NAME=\"Ubuntu 20.04.6 LTS\"\n";
+        let warning = evaluate(os_release, &table, date("2026-01-01")).unwrap();
+        assert_eq!(warning.release, "Ubuntu 20.04.6 LTS");
+        assert!(warning.days_left < 0);
+        assert!(warning.describe().contains("no more security updates"));
+
+        // 2027-04-15 is 47 days before the 22.04 date: inside the window.
+        let os_release = "ID=ubuntu\nVERSION_ID=\"22.04\"\n";
+        let warning = evaluate(os_release, &table, date("2027-04-15")).unwrap();
+        assert_eq!(warning.days_left, 47);
+        assert!(warning.describe().contains("in 47 day(s)"));
+        assert!(evaluate(os_release, &table, date("2026-01-01")).is_none());
+    }
+
+    #[test]
+    fn rolling_and_unknown_releases_are_exempt() {
+        let table = EolTable::bundled();
+        assert!(evaluate("ID=arch\nBUILD_ID=rolling\n", &table, date("2099-01-01")).is_none());
+        assert!(evaluate("ID=slackware\nVERSION_ID=\"15.0\"\n", &table, date("2099-01-01")).is_none());
+    }
+
+    #[test]
+    fn fetched_cycles_overlay_the_bundled_dates() {
+        let mut table = EolTable::bundled();
+        let output = r#"[
+            {"cycle": "20.04", "eol": "2025-04-02", "lts": true},
+            {"cycle": "26.04", "eol": "2031-04-25"},
+            {"cycle": "devel", "eol": false}
+        ]"#;
+        for entry in parse_endoflife("ubuntu", output) {
+            table.upsert(entry);
+        }
+        assert_eq!(table.eol_for("ubuntu", "20.04"), Some(date("2025-04-02")));
+        assert_eq!(table.eol_for("ubuntu", "26.04"), Some(date("2031-04-25")));
+        assert_eq!(table.eol_for("ubuntu", "devel"), None);
+    }
+}
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// A single recorded package operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transaction {
+    pub timestamp: DateTime<Utc>,
+    /// Operation kind: "install", "remove", "update", "clean", ...
+    pub action: String,
+    pub manager: String,
+    pub packages: Vec<String>,
+    pub success: bool,
+    /// Id of the snapshot taken just before this transaction, when the
+    /// snapshot policy or a hook took one.
+    #[serde(default)]
+    pub snapshot: Option<String>,
+}
+
+/// Append-only log of package operations, persisted as JSON.
+pub struct TransactionHistory {
+    path: PathBuf,
+    entries: Vec<Transaction>,
+}
+
+impl TransactionHistory {
+    /// Load existing history, or start empty when the file is absent or unreadable.
+    pub fn load() -> Self {
+        let path = history_path();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        TransactionHistory { path, entries }
+    }
+
+    pub fn entries(&self) -> &[Transaction] {
+        &self.entries
+    }
+
+    /// Record an operation and flush to disk.
+    pub fn record(&mut self, transaction: Transaction) -> Result<()> {
+        self.entries.push(transaction);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, data)?;
+        Ok(())
+    }
+}
+
+fn history_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".pkg_tool")
+        .join("history.json")
+}
@@ -0,0 +1,143 @@
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::error::{PkgError, Result};
+
+/// Marker for hooks implemented inside pkgtool rather than as a script.
+/// `builtin:snapshot` creates a system snapshot through the snapshot
+/// backend, proving the hook interface works for built-in features too.
+pub const BUILTIN_PREFIX: &str = "builtin:";
+
+/// Hook scripts run around package operations, declared under `[hooks]`
+/// in the config file.
+///
+/// Each entry is the path of an executable (or a `builtin:` name), run with
+/// the operation described in environment variables: `PKGTOOL_ACTION`
+/// ("install", "remove" or "update"), `PKGTOOL_MANAGER` and
+/// `PKGTOOL_PACKAGES` (space-separated). A pre-hook failing aborts the
+/// operation; a post-hook failing only produces a warning.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub pre_install: Vec<String>,
+    pub post_install: Vec<String>,
+    pub pre_remove: Vec<String>,
+    pub post_remove: Vec<String>,
+    pub pre_update: Vec<String>,
+    pub post_update: Vec<String>,
+    /// Seconds a single hook may run before it is killed.
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        HooksConfig {
+            pre_install: Vec::new(),
+            post_install: Vec::new(),
+            pre_remove: Vec::new(),
+            post_remove: Vec::new(),
+            pre_update: Vec::new(),
+            post_update: Vec::new(),
+            timeout_secs: 60,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Hooks to run before `action`.
+    pub fn pre(&self, action: &str) -> &[String] {
+        match action {
+            "install" => &self.pre_install,
+            "remove" => &self.pre_remove,
+            "update" => &self.pre_update,
+            _ => &[],
+        }
+    }
+
+    /// Hooks to run after `action`.
+    pub fn post(&self, action: &str) -> &[String] {
+        match action {
+            "install" => &self.post_install,
+            "remove" => &self.post_remove,
+            "update" => &self.post_update,
+            _ => &[],
+        }
+    }
+}
+
+/// Run one hook script with the operation described in the environment.
+///
+/// The script's output goes to the log (and so to the Log tab) line by
+/// line. A non-zero exit or a timeout is an error carrying the script's
+/// stderr, so pre-hook callers can show it.
+pub async fn run_script(
+    script: &str,
+    timeout_secs: u64,
+    action: &str,
+    manager: &str,
+    packages: &[String],
+) -> Result<()> {
+    let started = std::time::Instant::now();
+    let run = Command::new(script)
+        .env("PKGTOOL_ACTION", action)
+        .env("PKGTOOL_MANAGER", manager)
+        .env("PKGTOOL_PACKAGES", packages.join(" "))
+        .output();
+    let output = match tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), run).await
+    {
+        Ok(output) => output?,
+        Err(_) => {
+            return Err(PkgError::CommandFailed {
+                command: script.to_string(),
+                status: -1,
+                stderr: format!("hook timed out after {timeout_secs}s"),
+            })
+        }
+    };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        log::info!(target: "pkgtool::hooks", "{script}: {line}");
+    }
+    for line in String::from_utf8_lossy(&output.stderr).lines() {
+        log::warn!(target: "pkgtool::hooks", "{script}: {line}");
+    }
+    crate::logging::invocation("hooks", script, started.elapsed(), output.status.code().unwrap_or(-1));
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(PkgError::CommandFailed {
+            command: script.to_string(),
+            status: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_map_to_their_hook_lists() {
+        let config = HooksConfig {
+            pre_update: vec!["/usr/local/bin/snapshot.sh".to_string()],
+            ..HooksConfig::default()
+        };
+        assert_eq!(config.pre("update"), ["/usr/local/bin/snapshot.sh"]);
+        assert!(config.post("update").is_empty());
+        assert!(config.pre("search").is_empty());
+    }
+
+    #[tokio::test]
+    async fn failing_hook_carries_its_stderr() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join("pkgtool-hook-test.sh");
+        std::fs::write(&path, "#!/bin/sh\necho \"no space for $PKGTOOL_ACTION\" >&2\nexit 1\n")
+            .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let err = run_script(path.to_str().unwrap(), 5, "update", "apt", &[])
+            .await
+            .unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert!(err.to_string().contains("no space for update"));
+    }
+}
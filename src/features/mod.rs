@@ -0,0 +1,15 @@
+pub mod cache;
+pub mod deps;
+pub mod eol;
+pub mod history;
+pub mod hooks;
+pub mod overlaps;
+pub mod prompts;
+pub mod report;
+pub mod restart;
+pub mod security;
+pub mod session;
+pub mod snapshots;
+pub mod verify;
+pub mod watch;
+pub mod watchlist;
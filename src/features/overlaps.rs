@@ -0,0 +1,211 @@
+//! Cross-manager duplicate detection: the same tool installed through
+//! several managers at once, each shadowing the others on PATH
+//! differently.
+//!
+//! Grouping is by normalized name, so ecosystem renames still join
+//! ("python3-requests" from apt is pip's "requests"). PATH resolution
+//! is a plain `which -a` over the tool's binary name, local hosts only;
+//! the winner is whichever entry owns the first hit.
+
+use std::collections::HashMap;
+
+use crate::package_managers::PackageInfo;
+
+/// Ecosystem renames that plain prefix stripping cannot catch.
+const RENAMES: [(&str, &str); 6] = [
+    ("fd-find", "fd"),
+    ("gnupg2", "gnupg"),
+    ("node-typescript", "typescript"),
+    ("golang-go", "go"),
+    ("python3-pip", "pip"),
+    ("libssl-dev", "openssl"),
+];
+
+/// Executables known by a different name than their package.
+const BINARIES: [(&str, &str); 4] = [
+    ("ripgrep", "rg"),
+    ("difftastic", "difft"),
+    ("bottom", "btm"),
+    ("tealdeer", "tldr"),
+];
+
+/// Install prefixes that identify the owning ecosystem of a resolved
+/// binary; distro paths like /usr/bin are attributed by the caller,
+/// which knows which distro manager is enabled.
+const PREFIXES: [(&str, &str); 5] = [
+    ("/snap/", "snap"),
+    ("/var/lib/flatpak/", "flatpak"),
+    (".cargo/bin/", "cargo"),
+    ("/opt/homebrew/", "brew"),
+    ("/home/linuxbrew/", "brew"),
+];
+
+/// One package involved in an overlap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverlapEntry {
+    pub manager: String,
+    pub package: String,
+    pub version: String,
+}
+
+/// One normalized name installed through more than one manager.
+#[derive(Debug, Clone)]
+pub struct Overlap {
+    /// The normalized name the entries joined on.
+    pub name: String,
+    pub entries: Vec<OverlapEntry>,
+    /// Everything `which -a` found for the binary, in PATH order; empty
+    /// for library packages and on remote hosts.
+    pub paths: Vec<String>,
+}
+
+/// Canonical cross-ecosystem name: the rename table first, then the
+/// common language-library prefixes the distros add.
+pub fn normalize(name: &str) -> String {
+    let lower = name.to_lowercase();
+    if let Some((_, canonical)) = RENAMES.iter().find(|(from, _)| *from == lower) {
+        return canonical.to_string();
+    }
+    for prefix in ["python3-", "python-", "rust-", "node-", "golang-", "perl-", "ruby-"] {
+        if let Some(rest) = lower.strip_prefix(prefix) {
+            return rest.to_string();
+        }
+    }
+    // Debian's perl convention: libjson-perl is perl's "json".
+    if let Some(rest) = lower
+        .strip_prefix("lib")
+        .and_then(|rest| rest.strip_suffix("-perl"))
+    {
+        return rest.to_string();
+    }
+    lower
+}
+
+/// The executable a tool is looked up by, when it differs from the
+/// canonical name.
+pub fn binary_name(canonical: &str) -> &str {
+    BINARIES
+        .iter()
+        .find(|(name, _)| *name == canonical)
+        .map(|(_, binary)| *binary)
+        .unwrap_or(canonical)
+}
+
+/// The ecosystem a resolved binary path belongs to, when its prefix
+/// gives it away; None for distro paths like /usr/bin.
+pub fn manager_for_path(path: &str) -> Option<&'static str> {
+    PREFIXES
+        .iter()
+        .find(|(prefix, _)| path.contains(prefix))
+        .map(|(_, manager)| *manager)
+}
+
+/// Join the installed lists on normalized names and keep the groups
+/// spanning more than one manager.
+pub fn find_overlaps(packages: &[PackageInfo]) -> Vec<Overlap> {
+    let mut groups: HashMap<String, Vec<OverlapEntry>> = HashMap::new();
+    for package in packages {
+        groups
+            .entry(normalize(&package.name))
+            .or_default()
+            .push(OverlapEntry {
+                manager: package.manager.clone(),
+                package: package.name.clone(),
+                version: package.version.clone(),
+            });
+    }
+    let mut overlaps: Vec<Overlap> = groups
+        .into_iter()
+        .filter(|(_, entries)| {
+            let mut managers: Vec<&str> =
+                entries.iter().map(|entry| entry.manager.as_str()).collect();
+            managers.sort();
+            managers.dedup();
+            managers.len() > 1
+        })
+        .map(|(name, mut entries)| {
+            entries.sort_by(|a, b| (&a.manager, &a.package).cmp(&(&b.manager, &b.package)));
+            Overlap {
+                name,
+                entries,
+                paths: Vec::new(),
+            }
+        })
+        .collect();
+    overlaps.sort_by(|a, b| a.name.cmp(&b.name));
+    overlaps
+}
+
+/// Fill in each overlap's PATH resolution; skipped on remote hosts,
+/// whose PATH is not ours to inspect.
+pub async fn resolve_paths(overlaps: &mut [Overlap]) {
+    if crate::utils::host::is_remote() {
+        return;
+    }
+    for overlap in overlaps.iter_mut() {
+        let Ok(output) = tokio::process::Command::new("which")
+            .args(["-a", binary_name(&overlap.name)])
+            .output()
+            .await
+        else {
+            continue;
+        };
+        if output.status.success() {
+            overlap.paths = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, version: &str, manager: &str) -> PackageInfo {
+        PackageInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            manager: manager.to_string(),
+            installed: true,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        }
+    }
+
+    #[test]
+    fn normalization_joins_renames_and_distro_prefixes() {
+        assert_eq!(normalize("python3-requests"), "requests");
+        assert_eq!(normalize("libjson-perl"), "json");
+        assert_eq!(normalize("fd-find"), "fd");
+        assert_eq!(normalize("Ripgrep"), "ripgrep");
+        assert_eq!(binary_name("ripgrep"), "rg");
+        assert_eq!(manager_for_path("/snap/bin/rg"), Some("snap"));
+        assert_eq!(manager_for_path("/usr/bin/rg"), None);
+    }
+
+    #[test]
+    fn overlaps_need_two_managers_not_two_packages() {
+        let packages = [
+            package("ripgrep", "14.1", "pacman"),
+            package("ripgrep", "13.0", "brew"),
+            package("python3-requests", "2.31", "apt"),
+            package("requests", "2.32", "pip"),
+            // Two pacman packages normalizing alike are not an overlap.
+            package("perl-json", "4.10", "pacman"),
+            package("json", "1.0", "pacman"),
+            package("htop", "3.3", "pacman"),
+        ];
+        let overlaps = find_overlaps(&packages);
+        let names: Vec<&str> = overlaps.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["requests", "ripgrep"]);
+        assert_eq!(overlaps[1].entries.len(), 2);
+        assert_eq!(overlaps[1].entries[0].manager, "brew");
+    }
+}
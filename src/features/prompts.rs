@@ -0,0 +1,60 @@
+use regex::Regex;
+
+/// A known interactive question a backend may ask mid-operation.
+///
+/// When a streamed output line matches `pattern`, the UI shows the question
+/// with `options` and writes the chosen answer to the child's stdin.
+pub struct PromptRule {
+    /// Manager id this rule applies to ("apt", "pacman", ...).
+    pub manager: &'static str,
+    pub pattern: Regex,
+    /// Answers offered to the user, written verbatim to stdin.
+    pub options: Vec<&'static str>,
+}
+
+/// The built-in prompt table. Kept as a function so a config file can extend
+/// or replace it later.
+pub fn default_rules() -> Vec<PromptRule> {
+    let rule = |manager, pattern: &str, options: Vec<&'static str>| PromptRule {
+        manager,
+        pattern: Regex::new(pattern).expect("built-in prompt pattern"),
+        options,
+    };
+    vec![
+        // dpkg conffile question: "*** foo.conf (Y/I/N/O/D/Z) ?"
+        rule("apt", r"\(Y/I/N/O/D/Z\)", vec!["N", "Y", "I", "O", "D", "Z"]),
+        rule("apt", r"Do you want to continue\?", vec!["y", "n"]),
+        rule(
+            "pacman",
+            r"Replace .+ with .+\? \[Y/n\]",
+            vec!["y", "n"],
+        ),
+        rule("pacman", r"Proceed with installation\?", vec!["y", "n"]),
+        rule("dnf", r"Is this ok \[y/N\]", vec!["n", "y"]),
+    ]
+}
+
+/// Find the first rule for any of `managers` matching `line`.
+pub fn match_prompt<'r>(
+    rules: &'r [PromptRule],
+    managers: &[String],
+    line: &str,
+) -> Option<&'r PromptRule> {
+    rules
+        .iter()
+        .find(|rule| managers.iter().any(|m| m == rule.manager) && rule.pattern.is_match(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_conffile_prompt_for_apt_only() {
+        let rules = default_rules();
+        let managers = vec!["apt".to_string()];
+        let line = "*** sshd_config (Y/I/N/O/D/Z) [default=N] ?";
+        assert!(match_prompt(&rules, &managers, line).is_some());
+        assert!(match_prompt(&rules, &["pacman".to_string()], line).is_none());
+    }
+}
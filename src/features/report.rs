@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::error::Result;
+
+/// One operation performed during this session, as it appears in the report.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportEvent {
+    pub timestamp: DateTime<Utc>,
+    pub manager: String,
+    /// Operation kind: "install", "remove" or "update".
+    pub action: String,
+    /// Affected packages; update entries carry "name old -> new" where the
+    /// versions were known when the operation started.
+    pub packages: Vec<String>,
+    pub success: bool,
+    pub duration_secs: f64,
+}
+
+/// Everything pkgtool did this session, for a change-management record.
+///
+/// Fed from the same operation outcomes that reach the Log tab and the
+/// transaction history; written out on demand (`report` command) or on exit
+/// when the config sets a `report_path`.
+#[derive(Debug, Serialize)]
+pub struct SessionReport {
+    pub started: DateTime<Utc>,
+    pub events: Vec<ReportEvent>,
+}
+
+impl SessionReport {
+    pub fn new() -> Self {
+        SessionReport {
+            started: Utc::now(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, event: ReportEvent) {
+        self.events.push(event);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Write the report to `template`, expanded with strftime placeholders
+    /// and a leading `~`. A `.json` extension selects JSON, anything else
+    /// Markdown. Returns the path written; an empty session writes nothing.
+    pub fn write(&self, template: &str) -> Result<Option<PathBuf>> {
+        if self.is_empty() {
+            return Ok(None);
+        }
+        let path = expand_path(template);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::to_string_pretty(self)?
+        } else {
+            self.render_markdown()
+        };
+        std::fs::write(&path, contents)?;
+        Ok(Some(path))
+    }
+
+    fn render_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "# pkgtool session report\n\nSession started {}.\n\n",
+            self.started.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        out.push_str("| Time | Manager | Action | Packages | Outcome | Duration |\n");
+        out.push_str("|------|---------|--------|----------|---------|----------|\n");
+        for event in &self.events {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.1}s |\n",
+                event.timestamp.format("%H:%M:%S"),
+                event.manager,
+                event.action,
+                event.packages.join(", "),
+                if event.success { "ok" } else { "failed" },
+                event.duration_secs,
+            ));
+        }
+        out
+    }
+}
+
+impl Default for SessionReport {
+    fn default() -> Self {
+        SessionReport::new()
+    }
+}
+
+/// Expand a leading `~` and strftime placeholders against the current time.
+fn expand_path(template: &str) -> PathBuf {
+    let expanded = Utc::now().format(template).to_string();
+    match expanded.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(rest),
+        None => PathBuf::from(expanded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> ReportEvent {
+        ReportEvent {
+            timestamp: Utc::now(),
+            manager: "apt".to_string(),
+            action: "update".to_string(),
+            packages: vec!["ripgrep 14.0.0 -> 14.1.0".to_string()],
+            success: true,
+            duration_secs: 12.3,
+        }
+    }
+
+    #[test]
+    fn markdown_lists_every_event() {
+        let mut report = SessionReport::new();
+        report.record(event());
+        let markdown = report.render_markdown();
+        assert!(markdown.contains("| apt | update | ripgrep 14.0.0 -> 14.1.0 | ok | 12.3s |"));
+    }
+
+    #[test]
+    fn empty_session_writes_no_file() {
+        let report = SessionReport::new();
+        let path = report
+            .write("/nonexistent-dir/pkgtool-report-%Y.md")
+            .unwrap();
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn placeholders_expand_against_the_clock() {
+        let path = expand_path("report-%Y.md");
+        let year = Utc::now().format("%Y").to_string();
+        assert_eq!(path, PathBuf::from(format!("report-{year}.md")));
+    }
+}
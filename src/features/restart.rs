@@ -0,0 +1,194 @@
+//! Post-update restart detection: whether the running kernel is older
+//! than the installed one, and which services are still running
+//! binaries that updates have since replaced.
+//!
+//! Every probe is distro-specific and best-effort: Debian's
+//! reboot-required marker and `needrestart`, dnf's `needs-restarting`,
+//! and a kernel-version comparison on Arch, where nothing else records
+//! the state. A missing tool or unreadable file contributes nothing.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::PkgError;
+use crate::package_managers::{binary_exists, run_backend};
+
+/// What the post-update check found.
+#[derive(Debug, Clone, Default)]
+pub struct RestartReport {
+    /// Why a reboot is needed ("kernel 6.6.8 → 6.6.9"), when it is.
+    pub reboot: Option<String>,
+    /// systemd units running outdated binaries, ready for
+    /// `systemctl restart`.
+    pub services: Vec<String>,
+    pub checked: DateTime<Utc>,
+}
+
+/// Probe every applicable source on the managed host. Later, more
+/// specific sources refine earlier ones: the Debian marker file only
+/// says "reboot", needrestart names the kernel versions.
+pub async fn check() -> RestartReport {
+    let mut reboot: Option<String> = None;
+    let mut services: Vec<String> = Vec::new();
+
+    // Debian: apt hooks drop a marker file, with the packages that
+    // asked for the reboot next to it.
+    if crate::utils::host::read_file("/var/run/reboot-required").is_ok() {
+        let packages = crate::utils::host::read_file("/var/run/reboot-required.pkgs")
+            .map(|content| {
+                let names: Vec<&str> = content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                names.join(", ")
+            })
+            .unwrap_or_default();
+        reboot = Some(if packages.is_empty() {
+            "system packages were updated".to_string()
+        } else {
+            format!("updated: {packages}")
+        });
+    }
+    if binary_exists("needrestart") {
+        if let Ok(output) = run("needrestart", "-b").await {
+            let (kernel, found) = parse_needrestart(&output);
+            if let Some((current, expected)) = kernel {
+                reboot = Some(format!("kernel {current} → {expected}"));
+            }
+            services.extend(found);
+        }
+    }
+    if binary_exists("dnf") {
+        // Exit 1 is the tool's way of saying "reboot needed"; anything
+        // else it might fail with is not an answer.
+        if let Err(PkgError::CommandFailed { status: 1, .. }) =
+            run("dnf needs-restarting", "-r").await
+        {
+            reboot
+                .get_or_insert_with(|| "core libraries or services were updated".to_string());
+        }
+        if let Ok(output) = run("dnf needs-restarting", "-s").await {
+            services.extend(parse_needs_restarting(&output));
+        }
+    }
+    if binary_exists("pacman") && reboot.is_none() {
+        let running = run("uname", "-r").await;
+        let installed = run("pacman -Q", "linux").await;
+        if let (Ok(running), Ok(installed)) = (running, installed) {
+            reboot = kernel_mismatch(running.trim(), installed.trim());
+        }
+    }
+    services.sort();
+    services.dedup();
+    RestartReport {
+        reboot,
+        services,
+        checked: Utc::now(),
+    }
+}
+
+async fn run(command: &str, arg: &str) -> crate::error::Result<String> {
+    let mut argv: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+    argv.push(arg.to_string());
+    run_backend("restart", &argv).await
+}
+
+/// Parse `needrestart -b` batch output: the kernel state (KSTA above 1
+/// means the running kernel is outdated) with its current and expected
+/// versions, and one SVC line per service to restart.
+fn parse_needrestart(output: &str) -> (Option<(String, String)>, Vec<String>) {
+    let mut current = None;
+    let mut expected = None;
+    let mut state = 1u8;
+    let mut services = Vec::new();
+    for line in output.lines() {
+        if let Some(value) = line.strip_prefix("NEEDRESTART-KCUR:") {
+            current = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("NEEDRESTART-KEXP:") {
+            expected = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("NEEDRESTART-KSTA:") {
+            state = value.trim().parse().unwrap_or(1);
+        } else if let Some(value) = line.strip_prefix("NEEDRESTART-SVC:") {
+            let service = value.trim();
+            if !service.is_empty() {
+                services.push(service.to_string());
+            }
+        }
+    }
+    let kernel = (state > 1)
+        .then_some(())
+        .and(current.zip(expected))
+        .filter(|(current, expected)| current != expected);
+    (kernel, services)
+}
+
+/// Parse `dnf needs-restarting -s`: one unit per line, sometimes with
+/// trailing commentary the newer versions add.
+fn parse_needs_restarting(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|unit| unit.ends_with(".service"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compare the running kernel against the installed `linux` package.
+/// Arch writes "6.6.9.arch1-1" in the package version but the kernel
+/// reports "6.6.9-arch1-1", so the comparison ignores separator
+/// differences. `query` is `pacman -Q linux` output: "linux <version>".
+fn kernel_mismatch(running: &str, query: &str) -> Option<String> {
+    let installed = query.split_whitespace().nth(1)?;
+    let normalize = |version: &str| -> String {
+        version
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '.' })
+            .collect()
+    };
+    (normalize(running) != normalize(installed))
+        .then(|| format!("kernel {running} → {installed}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needrestart_batch_output_yields_kernel_and_services() {
+        let output = "NEEDRESTART-VER: 3.6\n\
+                      NEEDRESTART-KCUR: 6.1.0-17-amd64\n\
+                      NEEDRESTART-KEXP: 6.1.0-18-amd64\n\
+                      NEEDRESTART-KSTA: 3\n\
+                      NEEDRESTART-SVC: cron.service\n\
+                      NEEDRESTART-SVC: ssh.service\n";
+        let (kernel, services) = parse_needrestart(output);
+        assert_eq!(
+            kernel,
+            Some(("6.1.0-17-amd64".to_string(), "6.1.0-18-amd64".to_string()))
+        );
+        assert_eq!(services, ["cron.service", "ssh.service"]);
+
+        // KSTA 1 means the running kernel is current.
+        let (kernel, services) =
+            parse_needrestart("NEEDRESTART-KCUR: 6.1.0-18\nNEEDRESTART-KEXP: 6.1.0-18\nNEEDRESTART-KSTA: 1\n");
+        assert_eq!(kernel, None);
+        assert!(services.is_empty());
+    }
+
+    #[test]
+    fn needs_restarting_keeps_only_service_units() {
+        let output = "crond.service\nsystemd-journald.service\n1234 : /usr/bin/foo\n";
+        assert_eq!(
+            parse_needs_restarting(output),
+            ["crond.service", "systemd-journald.service"]
+        );
+    }
+
+    #[test]
+    fn kernel_comparison_ignores_separator_spelling() {
+        // Same kernel, different separators: not a mismatch.
+        assert_eq!(kernel_mismatch("6.6.9-arch1-1", "linux 6.6.9.arch1-1"), None);
+        let detail = kernel_mismatch("6.6.8-arch1-1", "linux 6.6.9.arch1-1").unwrap();
+        assert_eq!(detail, "kernel 6.6.8-arch1-1 → 6.6.9.arch1-1");
+    }
+}
@@ -0,0 +1,2981 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PkgError, Result};
+use crate::package_managers::PackageInfo;
+
+/// Base URL of the OSV.dev REST API.
+const OSV_API: &str = "https://api.osv.dev/v1";
+
+/// How many packages go into one querybatch call. OSV accepts up to a
+/// thousand, but smaller batches keep the request argv and the responses
+/// at a manageable size.
+const BATCH_SIZE: usize = 100;
+
+/// Pause between consecutive API calls, staying well inside OSV's
+/// public rate limits.
+const REQUEST_PAUSE: Duration = Duration::from_millis(250);
+
+/// Debian's security tracker export, covering DSA and DLA advisories.
+const DEBIAN_TRACKER: &str = "https://security-tracker.debian.org/tracker/data/json";
+
+/// Arch's security tracker export: one entry per AVG advisory group.
+const ARCH_TRACKER: &str = "https://security.archlinux.org/json";
+
+/// AUR RPC endpoint, used to compare installed foreign packages against
+/// the versions the AUR currently carries.
+const AUR_RPC: &str = "https://aur.archlinux.org/rpc/";
+
+/// Days before a signing key's expiry at which it is worth flagging.
+const KEY_EXPIRY_WARN_DAYS: i64 = 30;
+
+/// Settings for the vulnerability scan, from the `[security]` config
+/// section.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityConfig {
+    /// Hours a cached OSV response stays valid. Within the window a
+    /// re-scan only queries packages whose version changed.
+    pub cache_ttl_hours: u64,
+    /// Hours before the last persisted scan counts as stale and a
+    /// background re-scan starts on its own.
+    pub scan_interval_hours: u64,
+    /// Advisory engine for distro packages. "auto" prefers a locally
+    /// installed `arch-audit` or `debsecan` over the built-in feed
+    /// parsers, "builtin" never uses them, and naming one requires it.
+    pub engine: String,
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        SecurityConfig {
+            cache_ttl_hours: 24,
+            scan_interval_hours: 24,
+            engine: "auto".to_string(),
+        }
+    }
+}
+
+/// Coarse severity buckets, ordered worst-first so sorting a report
+/// ascending puts the most urgent findings on top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    /// The advisory names no severity; common for Debian DSAs.
+    Unknown,
+}
+
+impl Severity {
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Critical => "CRITICAL",
+            Severity::High => "HIGH",
+            Severity::Medium => "MEDIUM",
+            Severity::Low => "LOW",
+            Severity::Unknown => "unknown",
+        }
+    }
+
+    /// The bucket for a CVSS v3 base score, per the spec's qualitative
+    /// rating scale.
+    pub fn from_score(score: f32) -> Severity {
+        if score >= 9.0 {
+            Severity::Critical
+        } else if score >= 7.0 {
+            Severity::High
+        } else if score >= 4.0 {
+            Severity::Medium
+        } else {
+            Severity::Low
+        }
+    }
+
+    /// Map the severity words the various OSV databases use onto the
+    /// buckets; anything unrecognized stays `Unknown` rather than
+    /// guessing.
+    fn parse(text: &str) -> Severity {
+        match text.to_uppercase().as_str() {
+            "CRITICAL" => Severity::Critical,
+            "HIGH" | "IMPORTANT" => Severity::High,
+            "MEDIUM" | "MODERATE" => Severity::Medium,
+            "LOW" | "NEGLIGIBLE" => Severity::Low,
+            _ => Severity::Unknown,
+        }
+    }
+}
+
+/// A security finding attached to an installed package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Finding {
+    pub package: String,
+    pub manager: String,
+    pub installed_version: String,
+    /// Advisory or CVE identifier.
+    pub id: String,
+    pub summary: String,
+    pub severity: Severity,
+    /// Normalized CVSS v3 base score, when the advisory carries a
+    /// vector; the severity bucket is derived from it where present.
+    #[serde(default)]
+    pub score: Option<f32>,
+    /// Lowest version that fixes the issue, when the advisory names one.
+    pub fixed_version: Option<String>,
+    /// Which source asserted this: "osv", "debian", "arch" or "fedora".
+    pub source: String,
+}
+
+/// Everything one scan produced, sorted by severity then package.
+/// Serializable so the last report survives a restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VulnReport {
+    pub findings: Vec<Finding>,
+    /// Packages whose manager maps onto an OSV ecosystem.
+    pub scanned: usize,
+    /// Packages OSV has no data for (homebrew, plugins without a
+    /// recognized ecosystem).
+    pub skipped: usize,
+    /// Sources that could not be reached or parsed, one message each.
+    /// A partial report is still a report; these say what it is missing.
+    pub source_errors: Vec<String>,
+    /// Problems with repository signing and keyring state, gathered
+    /// locally alongside the scan.
+    pub signatures: Vec<SignatureFinding>,
+    /// Third-party package sources and what was installed from each,
+    /// gathered locally alongside the scan.
+    pub origins: Vec<OriginGroup>,
+    /// Sandboxed apps and their granted permissions, gathered locally
+    /// alongside the scan.
+    #[serde(default)]
+    pub permissions: Vec<AppPermissions>,
+    /// `flatpak override --show` verbatim: tightenings already in
+    /// place, for display next to the permission audit.
+    #[serde(default)]
+    pub flatpak_overrides: Vec<String>,
+    /// Unexpected privileged binaries from the setuid/capability walk,
+    /// gathered locally alongside the scan.
+    #[serde(default)]
+    pub setuid: Vec<SetuidFinding>,
+    /// Which engines contributed findings, e.g. "arch-audit, osv".
+    /// Empty in reports persisted by older versions.
+    #[serde(default)]
+    pub engine: String,
+    pub generated: DateTime<Utc>,
+}
+
+/// One acknowledged finding. `until` lets an ignore expire on its own —
+/// a temporary risk acceptance that resurfaces instead of being
+/// forgotten.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IgnoreEntry {
+    /// Advisory or CVE identifier, exactly as the report shows it.
+    pub id: String,
+    /// Why the finding is acceptable; required so the file explains
+    /// itself.
+    pub reason: String,
+    /// Last day the ignore applies; absent means indefinitely.
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+    pub added: DateTime<Utc>,
+}
+
+/// Triage decisions, persisted as `security-ignores.json` in the state
+/// directory: a JSON array of `{id, reason, until?, added}` entries. The
+/// `security ignore` command edits it through this type rather than the
+/// user editing by hand, so a typo cannot silently discard the list.
+pub struct IgnoreList {
+    path: PathBuf,
+    entries: Vec<IgnoreEntry>,
+}
+
+impl IgnoreList {
+    /// Load the existing triage decisions, or start empty when the file
+    /// is absent or unreadable.
+    pub fn load() -> Self {
+        let path = ignore_path();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        IgnoreList { path, entries }
+    }
+
+    /// Whether `id` is ignored today: listed and not past its expiry.
+    pub fn is_ignored(&self, id: &str, today: NaiveDate) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.id == id && entry.until.is_none_or(|until| today <= until))
+    }
+
+    /// The triage entry for `id`, so the ignored view can show the
+    /// reason next to the finding.
+    pub fn get(&self, id: &str) -> Option<&IgnoreEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Record (or replace) an ignore and persist it.
+    pub fn ignore(&mut self, id: &str, reason: &str, until: Option<NaiveDate>) -> Result<()> {
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.push(IgnoreEntry {
+            id: id.to_string(),
+            reason: reason.to_string(),
+            until,
+            added: Utc::now(),
+        });
+        self.save()
+    }
+
+    /// Drop an ignore; false means `id` was not listed.
+    pub fn unignore(&mut self, id: &str) -> Result<bool> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        let removed = self.entries.len() != before;
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, data)?;
+        Ok(())
+    }
+}
+
+fn ignore_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("security-ignores.json")
+}
+
+/// One third-party package source and everything installed from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OriginGroup {
+    /// Origin label as the manager reports it: a repo id, an apt suite,
+    /// or "AUR" for foreign pacman packages.
+    pub origin: String,
+    pub manager: String,
+    /// Installed (name, version) pairs from this source.
+    pub packages: Vec<(String, String)>,
+    /// Weak-configuration notes parsed from the repo files: disabled
+    /// signing, plain-http URLs, or (for the AUR) the inherent lack of
+    /// a repository at all.
+    pub flags: Vec<String>,
+    /// Packages whose installed version differs from what the source
+    /// currently carries, as (name, installed, current). Only filled
+    /// for the AUR, where the RPC makes the comparison cheap.
+    pub behind: Vec<(String, String, String)>,
+}
+
+/// Permissions one sandboxed app holds, from `flatpak info
+/// --show-permissions` or `snap connections`, with the high-risk
+/// subset called out separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppPermissions {
+    pub app: String,
+    /// "flatpak" or "snap".
+    pub manager: String,
+    /// Every granted permission as one short `key=value` (flatpak) or
+    /// interface name (snap).
+    pub permissions: Vec<String>,
+    /// The subset worth a second look: host filesystem access, all
+    /// devices, system-bus talk names, snap's broad interfaces.
+    pub flagged: Vec<String>,
+}
+
+/// One privileged binary the filesystem walk turned up: a setuid or
+/// setgid file, or one carrying file capabilities.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetuidFinding {
+    pub path: String,
+    /// "setuid", "setgid", or the capability set getcap printed.
+    pub kind: String,
+    /// Package owning the file; `None` is the worst case — a
+    /// privileged binary no manager accounts for.
+    pub package: Option<String>,
+    pub severity: Severity,
+}
+
+/// A stable hash of the installed set, one (manager, name, version)
+/// triple per package, so a persisted report can tell whether it still
+/// describes this system. Order-insensitive. The hasher's keys are
+/// fixed, so values compare across runs; a toolchain upgrade changing
+/// the algorithm costs at most one spurious re-scan.
+pub fn fingerprint(packages: &[PackageInfo]) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut triples: Vec<(&str, &str, &str)> = packages
+        .iter()
+        .map(|package| {
+            (
+                package.manager.as_str(),
+                package.name.as_str(),
+                package.version.as_str(),
+            )
+        })
+        .collect();
+    triples.sort_unstable();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    triples.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The report as persisted between runs, with the fingerprint of the
+/// installed set it described.
+#[derive(Serialize, Deserialize)]
+struct PersistedScan {
+    fingerprint: u64,
+    report: VulnReport,
+}
+
+/// Persist a finished scan so the next launch starts from it instead of
+/// an empty tab. Best-effort, like the OSV cache.
+pub fn save_last_scan(report: &VulnReport, fingerprint: u64) {
+    let path = last_scan_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let persisted = PersistedScan {
+        fingerprint,
+        report: report.clone(),
+    };
+    if let Ok(data) = serde_json::to_string(&persisted) {
+        let _ = std::fs::write(&path, data);
+    }
+}
+
+/// The persisted report and its fingerprint, if a readable one exists.
+pub fn load_last_scan() -> Option<(VulnReport, u64)> {
+    let data = std::fs::read_to_string(last_scan_path()).ok()?;
+    let persisted: PersistedScan = serde_json::from_str(&data).ok()?;
+    Some((persisted.report, persisted.fingerprint))
+}
+
+fn last_scan_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("last-scan.json")
+}
+
+/// One problem with repository signature verification or keyring state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureFinding {
+    pub severity: Severity,
+    /// What is wrong and why it matters, one line.
+    pub detail: String,
+    /// Where the setting lives: a file, a `file:line`, or a keyring.
+    pub location: String,
+}
+
+/// The OSV ecosystem for a manager id; `None` means OSV cannot answer
+/// for packages from that manager.
+fn ecosystem(manager: &str) -> Option<&'static str> {
+    match manager {
+        "apt" => Some("Debian"),
+        "pacman" => Some("Arch"),
+        "pip" | "pip3" => Some("PyPI"),
+        "cargo" => Some("crates.io"),
+        "npm" => Some("npm"),
+        _ => None,
+    }
+}
+
+/// Cache key for one package at one version; a version bump naturally
+/// invalidates the entry.
+fn query_key(ecosystem: &str, name: &str, version: &str) -> String {
+    format!("{ecosystem}/{name}/{version}")
+}
+
+/// On-disk cache of OSV responses, one JSON file in the cache directory.
+/// Batch answers are keyed by package+version, advisory details by id,
+/// so re-scans and overlapping packages never re-query the API.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OsvCache {
+    #[serde(default)]
+    queries: BTreeMap<String, CachedQuery>,
+    #[serde(default)]
+    vulns: BTreeMap<String, CachedVuln>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedQuery {
+    timestamp: DateTime<Utc>,
+    /// Ids of the advisories affecting the package at that version.
+    ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedVuln {
+    timestamp: DateTime<Utc>,
+    detail: VulnDetail,
+}
+
+/// The slice of an OSV advisory pkgtool keeps. Also what the
+/// finding-detail popup shows; entries cached before a field existed
+/// default to empty and render as unknown.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VulnDetail {
+    pub id: String,
+    pub summary: String,
+    pub severity: Severity,
+    /// CVSS v3 base score computed from the advisory's vector, if any.
+    #[serde(default)]
+    pub score: Option<f32>,
+    /// The CVSS v3 vector the score came from, verbatim.
+    #[serde(default)]
+    pub vector: Option<String>,
+    /// Publication timestamp as the advisory states it.
+    #[serde(default)]
+    pub published: Option<String>,
+    /// Reference URLs, the marked advisory link first.
+    #[serde(default)]
+    pub references: Vec<String>,
+    /// (ecosystem, package name, fixed version) per affected entry.
+    pub fixed: Vec<(String, String, Option<String>)>,
+    /// Other ids for the same issue (typically the CVE), used to
+    /// deduplicate against distro advisories.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl VulnDetail {
+    fn fixed_for(&self, ecosystem: &str, name: &str) -> Option<String> {
+        self.fixed
+            .iter()
+            .find(|(eco, package, _)| eco == ecosystem && package == name)
+            .and_then(|(_, _, fixed)| fixed.clone())
+    }
+}
+
+/// Inspects installed packages for known security problems by querying
+/// the OSV.dev database.
+///
+/// Scans are incremental: every batch answer and advisory is cached on
+/// disk for the configured TTL, so a re-scan only touches the network
+/// for packages that changed version since the last one.
+#[derive(Clone)]
+pub struct SecurityAnalyzer {
+    config: SecurityConfig,
+    cache_path: PathBuf,
+}
+
+impl SecurityAnalyzer {
+    pub fn new(config: SecurityConfig) -> Self {
+        let cache_path = dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("pkgtool")
+            .join("osv.json");
+        SecurityAnalyzer { config, cache_path }
+    }
+
+    /// Assemble a report from every applicable source: the distro's own
+    /// advisory feed for apt, pacman and dnf packages, plus OSV for
+    /// everything with a known ecosystem. Each source fails on its own —
+    /// an unreachable feed lands in `source_errors` rather than blanking
+    /// the report; only all sources failing is a hard error.
+    pub async fn scan(&self, packages: &[PackageInfo]) -> Result<VulnReport> {
+        self.scan_with_progress(packages, None).await
+    }
+
+    /// `scan` with a progress channel: `(queried, total)` counts arrive
+    /// as the OSV pass works through the installed set, cache hits
+    /// first. Every batch answer lands in the on-disk cache as it
+    /// arrives, so a scan cut short by quitting resumes from where the
+    /// cached answers end.
+    pub async fn scan_with_progress(
+        &self,
+        packages: &[PackageInfo],
+        progress: Option<tokio::sync::mpsc::UnboundedSender<(usize, usize)>>,
+    ) -> Result<VulnReport> {
+        if !crate::package_managers::binary_exists("curl") {
+            return Err(PkgError::Unsupported {
+                manager: "security".to_string(),
+                operation: "the vulnerability scan needs curl installed".to_string(),
+            });
+        }
+
+        let installed = |manager: &str| -> BTreeMap<&str, &str> {
+            packages
+                .iter()
+                .filter(|package| package.manager == manager)
+                .map(|package| (package.name.as_str(), package.version.as_str()))
+                .collect()
+        };
+        let mut findings = Vec::new();
+        let mut source_errors = Vec::new();
+        let mut engines: Vec<String> = Vec::new();
+        let mut attempted = 0;
+
+        // Distro feeds first: they are authoritative for their own
+        // packages, so their findings win the CVE dedup against OSV.
+        // A locally installed arch-audit or debsecan replaces the
+        // matching feed when the configured engine allows it.
+        let feeds: [(&str, BTreeMap<&str, &str>); 3] = [
+            ("debian", installed("apt")),
+            ("arch", installed("pacman")),
+            ("fedora", installed("dnf")),
+        ];
+        for (source, packages) in &feeds {
+            if packages.is_empty() {
+                continue;
+            }
+            attempted += 1;
+            let external = self.external_engine(source);
+            let mut via = external.unwrap_or(source);
+            let mut fetched = match external {
+                Some("arch-audit") => arch_audit_findings(packages).await,
+                Some(_) => debsecan_findings(packages).await,
+                None => builtin_feed(source, packages).await,
+            };
+            if let (Err(err), Some(tool)) = (&fetched, external) {
+                source_errors.push(format!("{tool}: {err} (using the {source} feed)"));
+                via = source;
+                fetched = builtin_feed(source, packages).await;
+            }
+            match fetched {
+                Ok(mut list) => {
+                    engines.push(via.to_string());
+                    findings.append(&mut list);
+                }
+                Err(err) => source_errors.push(format!("{source}: {err}")),
+            }
+        }
+
+        // OSV covers the rest (and the same distros again, generically);
+        // findings a distro feed already asserted are dropped by CVE id.
+        let asserted: BTreeSet<(String, String)> = findings
+            .iter()
+            .map(|finding| (finding.package.clone(), finding.id.clone()))
+            .collect();
+        attempted += 1;
+        let (scanned, skipped) = match self
+            .osv_findings(packages, &asserted, progress.as_ref())
+            .await
+        {
+            Ok((mut list, scanned, skipped)) => {
+                engines.push("osv".to_string());
+                findings.append(&mut list);
+                (scanned, skipped)
+            }
+            Err(err) => {
+                source_errors.push(format!("osv: {err}"));
+                (0, 0)
+            }
+        };
+        if !source_errors.is_empty() && source_errors.len() == attempted {
+            return Err(PkgError::Parse {
+                source_desc: "security feeds".to_string(),
+                detail: source_errors.join("; "),
+            });
+        }
+
+        findings.sort_by(|a, b| {
+            (a.severity, &a.package, &a.id).cmp(&(b.severity, &b.package, &b.id))
+        });
+        Ok(VulnReport {
+            findings,
+            scanned,
+            skipped,
+            source_errors,
+            signatures: self.signature_report().await,
+            origins: self.origin_report(packages).await,
+            permissions: self.permission_report().await,
+            flatpak_overrides: self.flatpak_overrides().await,
+            setuid: self.setuid_report().await,
+            engine: engines.join(", "),
+            generated: Utc::now(),
+        })
+    }
+
+    /// Which external tool, if any, should handle a feed's packages:
+    /// the matching one when the configured engine names it, or when
+    /// "auto" finds it installed. "builtin" and mismatches get `None`.
+    fn external_engine(&self, source: &str) -> Option<&'static str> {
+        let tool = match source {
+            "arch" => "arch-audit",
+            "debian" => "debsecan",
+            _ => return None,
+        };
+        match self.config.engine.as_str() {
+            "auto" => crate::package_managers::binary_exists(tool).then_some(tool),
+            name if name == tool => Some(tool),
+            _ => None,
+        }
+    }
+
+    /// Group installed packages by third-party origin and annotate each
+    /// source with weak-configuration flags from its repo files. AUR
+    /// groups additionally compare installed versions against the AUR's
+    /// current ones. Best-effort throughout: a package without an
+    /// origin, an unreadable file or an unreachable RPC contributes
+    /// nothing.
+    pub async fn origin_report(&self, packages: &[PackageInfo]) -> Vec<OriginGroup> {
+        let mut groups: Vec<OriginGroup> = Vec::new();
+        for package in packages {
+            let Some(origin) = package.origin.as_deref() else {
+                continue;
+            };
+            if crate::package_managers::is_official_origin(&package.manager, origin) {
+                continue;
+            }
+            let entry = (package.name.clone(), package.version.clone());
+            match groups
+                .iter_mut()
+                .find(|group| group.origin == origin && group.manager == package.manager)
+            {
+                Some(group) => group.packages.push(entry),
+                None => groups.push(OriginGroup {
+                    origin: origin.to_string(),
+                    manager: package.manager.clone(),
+                    packages: vec![entry],
+                    flags: Vec::new(),
+                    behind: Vec::new(),
+                }),
+            }
+        }
+        groups.sort_by(|a, b| (&a.manager, &a.origin).cmp(&(&b.manager, &b.origin)));
+        for group in &mut groups {
+            let files = repo_files(&group.manager);
+            group.flags = origin_flags(&group.manager, &group.origin, &files);
+            if group.manager == "pacman" && group.origin.eq_ignore_ascii_case("aur") {
+                let names: Vec<&str> = group
+                    .packages
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect();
+                let current = aur_versions(&names).await;
+                group.behind = group
+                    .packages
+                    .iter()
+                    .filter_map(|(name, installed)| {
+                        let aur = current.get(name.as_str())?;
+                        // Strictly newer: a local VCS build that ran
+                        // ahead of the AUR pkgver is not behind.
+                        let behind = crate::utils::version::compare(
+                            crate::utils::version::VersionScheme::Rpm,
+                            aur,
+                            installed,
+                        ) == std::cmp::Ordering::Greater;
+                        behind.then(|| (name.clone(), installed.clone(), aur.clone()))
+                    })
+                    .collect();
+            }
+        }
+        groups
+    }
+
+    /// Audit the permissions of installed flatpak and snap apps.
+    /// Best-effort like the other local surveys: a missing binary or a
+    /// failing command contributes nothing.
+    pub async fn permission_report(&self) -> Vec<AppPermissions> {
+        let mut apps = Vec::new();
+        if crate::package_managers::binary_exists("flatpak") {
+            if let Ok(list) = run_local("flatpak list --app --columns=application").await {
+                for app in list.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                    let Ok(output) =
+                        run_local(&format!("flatpak info --show-permissions {app}")).await
+                    else {
+                        continue;
+                    };
+                    let permissions = parse_flatpak_permissions(&output);
+                    let flagged = flag_flatpak_permissions(&permissions);
+                    apps.push(AppPermissions {
+                        app: app.to_string(),
+                        manager: "flatpak".to_string(),
+                        permissions,
+                        flagged,
+                    });
+                }
+            }
+        }
+        if crate::package_managers::binary_exists("snap") {
+            if let Ok(output) = run_local("snap connections").await {
+                for (app, interfaces) in parse_snap_connections(&output) {
+                    let flagged = interfaces
+                        .iter()
+                        .filter(|interface| SNAP_RISKY.contains(&interface.as_str()))
+                        .cloned()
+                        .collect();
+                    apps.push(AppPermissions {
+                        app,
+                        manager: "snap".to_string(),
+                        permissions: interfaces,
+                        flagged,
+                    });
+                }
+            }
+        }
+        apps.sort_by(|a, b| (&a.manager, &a.app).cmp(&(&b.manager, &b.app)));
+        apps
+    }
+
+    /// The user's global flatpak overrides, verbatim, so the permission
+    /// audit can show what has already been tightened. Empty when
+    /// flatpak is absent or nothing was overridden.
+    pub async fn flatpak_overrides(&self) -> Vec<String> {
+        if !crate::package_managers::binary_exists("flatpak") {
+            return Vec::new();
+        }
+        match run_local("flatpak override --show").await {
+            Ok(output) => output
+                .lines()
+                .map(str::trim_end)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Walk the standard binary directories for setuid/setgid files and
+    /// file capabilities, attribute each to its owning package, and keep
+    /// the unexpected ones. Runs inside the scan task, off the UI
+    /// thread; unreadable directories and files contribute nothing. The
+    /// per-file owner lookups are the slow part, so they are cached by
+    /// path and mtime — an unchanged binary costs nothing on a re-scan.
+    pub async fn setuid_report(&self) -> Vec<SetuidFinding> {
+        let mut privileged: Vec<(String, String, i64)> = Vec::new();
+        for dir in SETUID_DIRS {
+            for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if !metadata.is_file() {
+                    continue;
+                }
+                use std::os::unix::fs::MetadataExt;
+                let kind = if metadata.mode() & 0o4000 != 0 {
+                    "setuid"
+                } else if metadata.mode() & 0o2000 != 0 {
+                    "setgid"
+                } else {
+                    continue;
+                };
+                privileged.push((
+                    entry.path().to_string_lossy().into_owned(),
+                    kind.to_string(),
+                    metadata.mtime(),
+                ));
+            }
+        }
+        if crate::package_managers::binary_exists("getcap") {
+            for dir in SETUID_DIRS {
+                let Ok(output) = run_local(&format!("getcap -r {dir}")).await else {
+                    continue;
+                };
+                for (path, caps) in parse_getcap(&output) {
+                    let mtime = std::fs::metadata(&path)
+                        .map(|metadata| {
+                            use std::os::unix::fs::MetadataExt;
+                            metadata.mtime()
+                        })
+                        .unwrap_or_default();
+                    privileged.push((path, caps, mtime));
+                }
+            }
+        }
+        privileged.sort();
+        privileged.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+
+        let mut cache = load_setuid_cache();
+        let mut findings = Vec::new();
+        for (path, kind, mtime) in privileged {
+            let package = match cache.get(&path).filter(|entry| entry.mtime == mtime) {
+                Some(entry) => entry.package.clone(),
+                None => {
+                    let package = crate::package_managers::owner_of(&path).await;
+                    cache.insert(
+                        path.clone(),
+                        SetuidCacheEntry {
+                            mtime,
+                            package: package.clone(),
+                        },
+                    );
+                    package
+                }
+            };
+            if let Some(severity) = classify_privileged(&kind, package.as_deref()) {
+                findings.push(SetuidFinding {
+                    path,
+                    kind,
+                    package,
+                    severity,
+                });
+            }
+        }
+        save_setuid_cache(&cache);
+        findings.sort_by(|a, b| (a.severity, &a.path).cmp(&(b.severity, &b.path)));
+        findings
+    }
+
+    /// Survey repository signing settings and keyring health. Every
+    /// check is independent and best-effort: an unreadable file or a
+    /// missing tool contributes nothing rather than an error.
+    pub async fn signature_report(&self) -> Vec<SignatureFinding> {
+        let mut findings = Vec::new();
+        let today = Utc::now().date_naive();
+        if let Ok(content) = crate::utils::host::read_file("/etc/apt/sources.list") {
+            findings.extend(parse_apt_list("/etc/apt/sources.list", &content));
+        }
+        for entry in std::fs::read_dir("/etc/apt/sources.list.d")
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let path = entry.path();
+            let label = path.display().to_string();
+            let Ok(content) = crate::utils::host::read_file(&label) else {
+                continue;
+            };
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("list") => findings.extend(parse_apt_list(&label, &content)),
+                Some("sources") => findings.extend(parse_apt_deb822(&label, &content)),
+                _ => {}
+            }
+        }
+        if let Ok(content) = crate::utils::host::read_file("/etc/pacman.conf") {
+            findings.extend(parse_pacman_conf("/etc/pacman.conf", &content));
+        }
+        for entry in std::fs::read_dir("/etc/yum.repos.d")
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("repo") {
+                continue;
+            }
+            let label = path.display().to_string();
+            if let Ok(content) = crate::utils::host::read_file(&label) {
+                findings.extend(parse_dnf_repos(&label, &content));
+            }
+        }
+        for (binary, argv, keyring) in [
+            ("apt-key", &["apt-key", "list"][..], "apt keyring"),
+            (
+                "pacman-key",
+                &["pacman-key", "--list-keys"][..],
+                "pacman keyring",
+            ),
+        ] {
+            if !crate::package_managers::binary_exists(binary) {
+                continue;
+            }
+            let argv: Vec<String> = argv.iter().map(|arg| arg.to_string()).collect();
+            if let Ok(output) = crate::package_managers::run_backend("security", &argv).await {
+                findings.extend(parse_key_listing(keyring, &output, today));
+            }
+        }
+        findings.sort_by_key(|finding| finding.severity);
+        findings
+    }
+
+    /// Query OSV for every package with a known ecosystem, skipping
+    /// findings a distro feed already `asserted` as (package, CVE). A
+    /// network failure aborts with the error; everything fetched before
+    /// it stays cached.
+    async fn osv_findings(
+        &self,
+        packages: &[PackageInfo],
+        asserted: &BTreeSet<(String, String)>,
+        progress: Option<&tokio::sync::mpsc::UnboundedSender<(usize, usize)>>,
+    ) -> Result<(Vec<Finding>, usize, usize)> {
+        let mut cache = self.load_cache();
+        let ttl = chrono::Duration::hours(self.config.cache_ttl_hours.max(1) as i64);
+        let now = Utc::now();
+        let fresh = |timestamp: DateTime<Utc>| now.signed_duration_since(timestamp) < ttl;
+
+        let mut scanned: Vec<(&'static str, &PackageInfo)> = Vec::new();
+        let mut skipped = 0;
+        for package in packages {
+            match ecosystem(&package.manager) {
+                Some(eco) => scanned.push((eco, package)),
+                None => skipped += 1,
+            }
+        }
+
+        // Incremental pass: only packages without a fresh cached answer
+        // go out over the network, in batches.
+        let stale: Vec<&(&'static str, &PackageInfo)> = scanned
+            .iter()
+            .filter(|(eco, package)| {
+                let key = query_key(eco, &package.name, &package.version);
+                !cache
+                    .queries
+                    .get(&key)
+                    .is_some_and(|entry| fresh(entry.timestamp))
+            })
+            .collect();
+        let mut queried = scanned.len() - stale.len();
+        if let Some(progress) = progress {
+            let _ = progress.send((queried, scanned.len()));
+        }
+        for chunk in stale.chunks(BATCH_SIZE) {
+            let body = batch_body(chunk);
+            let response = post_json(&format!("{OSV_API}/querybatch"), &body).await?;
+            let results = parse_batch_response(&response)?;
+            if results.len() != chunk.len() {
+                return Err(PkgError::Parse {
+                    source_desc: "osv querybatch".to_string(),
+                    detail: format!("{} answers for {} queries", results.len(), chunk.len()),
+                });
+            }
+            for ((eco, package), ids) in chunk.iter().zip(results) {
+                let key = query_key(eco, &package.name, &package.version);
+                cache.queries.insert(key, CachedQuery { timestamp: now, ids });
+            }
+            self.store_cache(&cache);
+            queried += chunk.len();
+            if let Some(progress) = progress {
+                let _ = progress.send((queried, scanned.len()));
+            }
+            tokio::time::sleep(REQUEST_PAUSE).await;
+        }
+
+        // Advisory details, again only the ones not cached yet.
+        let mut needed: BTreeSet<String> = BTreeSet::new();
+        for (eco, package) in &scanned {
+            let key = query_key(eco, &package.name, &package.version);
+            if let Some(entry) = cache.queries.get(&key) {
+                for id in &entry.ids {
+                    if !cache.vulns.get(id).is_some_and(|entry| fresh(entry.timestamp)) {
+                        needed.insert(id.clone());
+                    }
+                }
+            }
+        }
+        for id in needed {
+            let response = get_json(&format!("{OSV_API}/vulns/{id}")).await?;
+            let detail = parse_vuln(&response)?;
+            cache.vulns.insert(id, CachedVuln { timestamp: now, detail });
+            self.store_cache(&cache);
+            tokio::time::sleep(REQUEST_PAUSE).await;
+        }
+
+        let mut findings = Vec::new();
+        for (eco, package) in &scanned {
+            let key = query_key(eco, &package.name, &package.version);
+            let Some(entry) = cache.queries.get(&key) else {
+                continue;
+            };
+            for id in &entry.ids {
+                let Some(cached) = cache.vulns.get(id) else {
+                    continue;
+                };
+                let covered = |id: &String| asserted.contains(&(package.name.clone(), id.clone()));
+                if covered(&cached.detail.id) || cached.detail.aliases.iter().any(covered) {
+                    continue;
+                }
+                findings.push(Finding {
+                    package: package.name.clone(),
+                    manager: package.manager.clone(),
+                    installed_version: package.version.clone(),
+                    id: cached.detail.id.clone(),
+                    summary: cached.detail.summary.clone(),
+                    severity: cached.detail.severity,
+                    score: cached.detail.score,
+                    fixed_version: cached.detail.fixed_for(eco, &package.name),
+                    source: "osv".to_string(),
+                });
+            }
+        }
+        Ok((findings, scanned.len(), skipped))
+    }
+
+    /// The cached OSV advisory behind a finding, for the detail popup.
+    /// Distro-feed findings have no cached advisory and yield `None`.
+    pub fn cached_detail(&self, id: &str) -> Option<VulnDetail> {
+        self.load_cache().vulns.remove(id).map(|cached| cached.detail)
+    }
+
+    /// A corrupted or missing cache file just means a cold scan.
+    fn load_cache(&self) -> OsvCache {
+        std::fs::read_to_string(&self.cache_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Written after every network round trip, so an aborted scan keeps
+    /// what it already fetched.
+    fn store_cache(&self, cache: &OsvCache) {
+        if let Some(parent) = self.cache_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Ok(data) = serde_json::to_string(cache) {
+            let _ = std::fs::write(&self.cache_path, data);
+        }
+    }
+}
+
+impl Default for SecurityAnalyzer {
+    fn default() -> Self {
+        SecurityAnalyzer::new(SecurityConfig::default())
+    }
+}
+
+/// Hostname, distro and kernel of the scanned system, so an exported
+/// report says which machine it describes.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub hostname: String,
+    pub distro: String,
+    pub kernel: String,
+}
+
+impl SystemInfo {
+    /// Best-effort collection through the host abstraction, so a remote
+    /// session reports the remote machine. An unreadable source reads
+    /// "unknown" rather than failing the export.
+    pub fn collect() -> Self {
+        let read = |path: &str| {
+            crate::utils::host::read_file(path)
+                .ok()
+                .map(|content| content.trim().to_string())
+                .filter(|content| !content.is_empty())
+        };
+        SystemInfo {
+            hostname: read("/etc/hostname").unwrap_or_else(|| "unknown".to_string()),
+            distro: read("/etc/os-release")
+                .as_deref()
+                .and_then(parse_os_release)
+                .unwrap_or_else(|| "unknown".to_string()),
+            kernel: read("/proc/sys/kernel/osrelease").unwrap_or_else(|| "unknown".to_string()),
+        }
+    }
+}
+
+/// The PRETTY_NAME of an os-release file, unquoted.
+fn parse_os_release(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        line.strip_prefix("PRETTY_NAME=")
+            .map(|value| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// The exportable artifact of one scan (`security-report`, `x` on the
+/// Security tab).
+///
+/// The JSON layout is a stable interface for compliance tooling:
+/// `schema_version` only moves when an existing field changes meaning,
+/// additions keep it, and a test pins the shape.
+#[derive(Debug, Serialize)]
+pub struct SecurityReportExport {
+    pub schema_version: u32,
+    pub generated: DateTime<Utc>,
+    pub system: SystemInfo,
+    pub summary: ReportSummary,
+    pub findings: Vec<ReportFinding>,
+}
+
+/// Finding counts per severity bucket plus the scan coverage.
+#[derive(Debug, Serialize)]
+pub struct ReportSummary {
+    pub critical: usize,
+    pub high: usize,
+    pub medium: usize,
+    pub low: usize,
+    pub unknown: usize,
+    pub scanned: usize,
+    pub skipped: usize,
+}
+
+/// One finding in the export; `severity` is the lowercase bucket name.
+#[derive(Debug, Serialize)]
+pub struct ReportFinding {
+    pub package: String,
+    pub manager: String,
+    pub installed_version: String,
+    pub id: String,
+    pub severity: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_version: Option<String>,
+    pub source: String,
+    pub advisory_url: String,
+}
+
+/// Where to read the full advisory for an id, routed to the database
+/// that issued it; OSV serves everything else (GHSA, PYSEC, RUSTSEC...).
+pub fn advisory_url(id: &str) -> String {
+    if id.starts_with("CVE-") {
+        format!("https://www.cve.org/CVERecord?id={id}")
+    } else if id.starts_with("DSA-") || id.starts_with("DLA-") {
+        format!("https://security-tracker.debian.org/tracker/{id}")
+    } else if id.starts_with("AVG-") || id.starts_with("ASA-") {
+        format!("https://security.archlinux.org/{id}")
+    } else if id.starts_with("FEDORA-") {
+        format!("https://bodhi.fedoraproject.org/updates/{id}")
+    } else {
+        format!("https://osv.dev/vulnerability/{id}")
+    }
+}
+
+impl SecurityReportExport {
+    /// Snapshot `report` into the export schema.
+    pub fn new(report: &VulnReport, system: SystemInfo) -> Self {
+        let count = |severity: Severity| {
+            report
+                .findings
+                .iter()
+                .filter(|finding| finding.severity == severity)
+                .count()
+        };
+        SecurityReportExport {
+            schema_version: 1,
+            generated: report.generated,
+            system,
+            summary: ReportSummary {
+                critical: count(Severity::Critical),
+                high: count(Severity::High),
+                medium: count(Severity::Medium),
+                low: count(Severity::Low),
+                unknown: count(Severity::Unknown),
+                scanned: report.scanned,
+                skipped: report.skipped,
+            },
+            findings: report
+                .findings
+                .iter()
+                .map(|finding| ReportFinding {
+                    package: finding.package.clone(),
+                    manager: finding.manager.clone(),
+                    installed_version: finding.installed_version.clone(),
+                    id: finding.id.clone(),
+                    severity: finding.severity.label().to_lowercase(),
+                    score: finding.score,
+                    summary: finding.summary.clone(),
+                    fixed_version: finding.fixed_version.clone(),
+                    source: finding.source.clone(),
+                    advisory_url: advisory_url(&finding.id),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!(
+            "# pkgtool security report\n\n{} — {} — kernel {}\n\nScanned {} packages \
+             ({} skipped) on {}.\n\n",
+            self.system.hostname,
+            self.system.distro,
+            self.system.kernel,
+            self.summary.scanned,
+            self.summary.skipped,
+            self.generated.format("%Y-%m-%d %H:%M:%S UTC"),
+        ));
+        out.push_str(&format!(
+            "Critical: {} | High: {} | Medium: {} | Low: {} | Unknown: {}\n\n",
+            self.summary.critical,
+            self.summary.high,
+            self.summary.medium,
+            self.summary.low,
+            self.summary.unknown,
+        ));
+        out.push_str("| Severity | Score | Package | Installed | Advisory | Fixed | Source |\n");
+        out.push_str("|----------|-------|---------|-----------|----------|-------|--------|\n");
+        for finding in &self.findings {
+            out.push_str(&format!(
+                "| {} | {} | {} [{}] | {} | [{}]({}) | {} | {} |\n",
+                finding.severity,
+                finding
+                    .score
+                    .map(|score| format!("{score:.1}"))
+                    .unwrap_or_else(|| "—".to_string()),
+                finding.package,
+                finding.manager,
+                finding.installed_version,
+                finding.id,
+                finding.advisory_url,
+                finding.fixed_version.as_deref().unwrap_or("—"),
+                finding.source,
+            ));
+        }
+        out
+    }
+
+    /// A single self-contained HTML file: inline CSS, no external
+    /// assets, severity color-coded rows.
+    pub fn to_html(&self) -> String {
+        let mut rows = String::new();
+        for finding in &self.findings {
+            rows.push_str(&format!(
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{} [{}]</td><td>{}</td>\
+                 <td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                finding.severity,
+                escape_html(&finding.severity),
+                finding
+                    .score
+                    .map(|score| format!("{score:.1}"))
+                    .unwrap_or_else(|| "—".to_string()),
+                escape_html(&finding.package),
+                escape_html(&finding.manager),
+                escape_html(&finding.installed_version),
+                escape_html(&finding.advisory_url),
+                escape_html(&finding.id),
+                escape_html(finding.fixed_version.as_deref().unwrap_or("—")),
+                escape_html(&finding.source),
+                escape_html(&finding.summary),
+            ));
+        }
+        let meta = format!(
+            "{} — {} — kernel {} — generated {}",
+            escape_html(&self.system.hostname),
+            escape_html(&self.system.distro),
+            escape_html(&self.system.kernel),
+            self.generated.format("%Y-%m-%d %H:%M:%S UTC"),
+        );
+        let summary = format!(
+            "{} scanned, {} skipped — critical {} / high {} / medium {} / low {} / unknown {}",
+            self.summary.scanned,
+            self.summary.skipped,
+            self.summary.critical,
+            self.summary.high,
+            self.summary.medium,
+            self.summary.low,
+            self.summary.unknown,
+        );
+        HTML_TEMPLATE
+            .replace("__META__", &meta)
+            .replace("__SUMMARY__", &summary)
+            .replace("__ROWS__", &rows)
+    }
+}
+
+/// The template `to_html` fills in; everything inline so the file stands
+/// alone.
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>pkgtool security report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+.meta { color: #555; font-size: 0.9em; }
+table { border-collapse: collapse; width: 100%; margin-top: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; font-size: 0.9em; }
+th { background: #f0f0f0; }
+tr.critical td:first-child { color: #fff; background: #7b1fa2; }
+tr.high td:first-child { color: #fff; background: #c62828; }
+tr.medium td:first-child { background: #f9a825; }
+tr.low td:first-child { background: #e0e0e0; }
+tr.unknown td:first-child { color: #777; }
+</style>
+</head>
+<body>
+<h1>pkgtool security report</h1>
+<p class="meta">__META__</p>
+<p class="meta">__SUMMARY__</p>
+<table>
+<tr><th>Severity</th><th>Score</th><th>Package</th><th>Installed</th>
+<th>Advisory</th><th>Fixed</th><th>Source</th><th>Summary</th></tr>
+__ROWS__
+</table>
+</body>
+</html>
+"#;
+
+/// Minimal HTML escaping for text and attribute positions.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The querybatch request body for one chunk of packages.
+fn batch_body(chunk: &[&(&'static str, &PackageInfo)]) -> String {
+    let queries: Vec<serde_json::Value> = chunk
+        .iter()
+        .map(|(eco, package)| {
+            serde_json::json!({
+                "package": { "ecosystem": eco, "name": package.name },
+                "version": package.version,
+            })
+        })
+        .collect();
+    serde_json::json!({ "queries": queries }).to_string()
+}
+
+/// Parse a querybatch response: one result per query, in order, each
+/// carrying the ids of matching advisories (OSV returns only ids and
+/// modification times here; details come from `/vulns/<id>`).
+fn parse_batch_response(output: &str) -> Result<Vec<Vec<String>>> {
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(default)]
+        results: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(default)]
+        vulns: Vec<Vuln>,
+    }
+    #[derive(Deserialize)]
+    struct Vuln {
+        id: String,
+    }
+    let response: Response = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "osv querybatch".to_string(),
+        detail: err.to_string(),
+    })?;
+    Ok(response
+        .results
+        .into_iter()
+        .map(|entry| entry.vulns.into_iter().map(|vuln| vuln.id).collect())
+        .collect())
+}
+
+/// Parse one advisory from `/vulns/<id>` down to what the report needs:
+/// a summary line, a severity bucket and the fixed version per affected
+/// package.
+fn parse_vuln(output: &str) -> Result<VulnDetail> {
+    #[derive(Deserialize)]
+    struct Advisory {
+        id: String,
+        #[serde(default)]
+        summary: String,
+        #[serde(default)]
+        details: String,
+        #[serde(default)]
+        severity: Vec<SeverityEntry>,
+        #[serde(default)]
+        aliases: Vec<String>,
+        #[serde(default)]
+        affected: Vec<Affected>,
+        #[serde(default)]
+        references: Vec<Reference>,
+        #[serde(default)]
+        published: String,
+        #[serde(default)]
+        database_specific: serde_json::Value,
+    }
+    #[derive(Deserialize)]
+    struct Reference {
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        url: String,
+    }
+    #[derive(Deserialize)]
+    struct SeverityEntry {
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        score: String,
+    }
+    #[derive(Deserialize)]
+    struct Affected {
+        #[serde(default)]
+        package: Package,
+        #[serde(default)]
+        ranges: Vec<Range>,
+    }
+    #[derive(Default, Deserialize)]
+    struct Package {
+        #[serde(default)]
+        ecosystem: String,
+        #[serde(default)]
+        name: String,
+    }
+    #[derive(Deserialize)]
+    struct Range {
+        #[serde(default)]
+        events: Vec<BTreeMap<String, String>>,
+    }
+    let advisory: Advisory = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "osv advisory".to_string(),
+        detail: err.to_string(),
+    })?;
+    // A complete CVSS v3 vector is the preferred signal: its computed
+    // base score normalizes across databases. Qualitative words — GHSA's
+    // database_specific, Ubuntu's severity score field — are the
+    // fallback; an advisory with neither stays Unknown.
+    let score = advisory
+        .severity
+        .iter()
+        .filter(|entry| entry.kind.is_empty() || entry.kind.starts_with("CVSS_V3"))
+        .filter_map(|entry| cvss_v3_base_score(&entry.score))
+        .reduce(f32::max);
+    let mut severity = match score {
+        Some(score) => Severity::from_score(score),
+        None => advisory
+            .database_specific
+            .get("severity")
+            .and_then(|value| value.as_str())
+            .map(Severity::parse)
+            .unwrap_or(Severity::Unknown),
+    };
+    if severity == Severity::Unknown {
+        severity = advisory
+            .severity
+            .iter()
+            .map(|entry| Severity::parse(&entry.score))
+            .find(|parsed| *parsed != Severity::Unknown)
+            .unwrap_or(Severity::Unknown);
+    }
+    let vector = advisory
+        .severity
+        .iter()
+        .filter(|entry| entry.kind.is_empty() || entry.kind.starts_with("CVSS_V3"))
+        .find(|entry| cvss_v3_base_score(&entry.score).is_some())
+        .map(|entry| entry.score.clone());
+    let summary = if advisory.summary.is_empty() {
+        advisory.details.lines().next().unwrap_or("").to_string()
+    } else {
+        advisory.summary
+    };
+    // The ADVISORY-typed reference is the canonical write-up; keep it
+    // first so "the primary link" is well defined.
+    let mut references: Vec<String> = Vec::new();
+    for reference in &advisory.references {
+        if reference.kind == "ADVISORY" && !reference.url.is_empty() {
+            references.push(reference.url.clone());
+        }
+    }
+    for reference in &advisory.references {
+        if reference.kind != "ADVISORY" && !reference.url.is_empty() {
+            references.push(reference.url.clone());
+        }
+    }
+    let fixed = advisory
+        .affected
+        .into_iter()
+        .map(|affected| {
+            let fixed = affected
+                .ranges
+                .iter()
+                .flat_map(|range| &range.events)
+                .find_map(|event| event.get("fixed").cloned());
+            (affected.package.ecosystem, affected.package.name, fixed)
+        })
+        .collect();
+    Ok(VulnDetail {
+        id: advisory.id,
+        summary,
+        severity,
+        score,
+        vector,
+        published: (!advisory.published.is_empty()).then_some(advisory.published),
+        references,
+        fixed,
+        aliases: advisory.aliases,
+    })
+}
+
+/// Base score of a CVSS v3.x vector string, per the specification's
+/// base metric equations. `None` unless every base metric is present,
+/// so a truncated vector never produces a score by accident.
+pub fn cvss_v3_base_score(vector: &str) -> Option<f32> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: BTreeMap<&str, &str> = parts
+        .filter_map(|part| part.split_once(':'))
+        .collect();
+    let weight = |key: &str, table: &[(&str, f64)]| -> Option<f64> {
+        let value = metrics.get(key)?;
+        table.iter().find(|(name, _)| name == value).map(|(_, w)| *w)
+    };
+    let changed = *metrics.get("S")? == "C";
+    let av = weight("AV", &[("N", 0.85), ("A", 0.62), ("L", 0.55), ("P", 0.2)])?;
+    let ac = weight("AC", &[("L", 0.77), ("H", 0.44)])?;
+    let pr = if changed {
+        weight("PR", &[("N", 0.85), ("L", 0.68), ("H", 0.5)])?
+    } else {
+        weight("PR", &[("N", 0.85), ("L", 0.62), ("H", 0.27)])?
+    };
+    let ui = weight("UI", &[("N", 0.85), ("R", 0.62)])?;
+    let cia = [("H", 0.56), ("L", 0.22), ("N", 0.0)];
+    let iss = 1.0
+        - (1.0 - weight("C", &cia)?) * (1.0 - weight("I", &cia)?) * (1.0 - weight("A", &cia)?);
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * av * ac * pr * ui;
+    let raw = if changed {
+        (1.08 * (impact + exploitability)).min(10.0)
+    } else {
+        (impact + exploitability).min(10.0)
+    };
+    Some(roundup(raw))
+}
+
+/// The spec's Roundup: the smallest one-decimal number not less than
+/// the input, computed over integers so float drift cannot round the
+/// wrong way.
+fn roundup(value: f64) -> f32 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        (scaled as f64 / 100_000.0) as f32
+    } else {
+        ((scaled / 10_000) + 1) as f32 / 10.0
+    }
+}
+
+/// The built-in feed for one distro source; the fallback when no
+/// external engine applies or the preferred one fails.
+async fn builtin_feed(source: &str, installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    match source {
+        "debian" => debian_advisories(installed).await,
+        "arch" => arch_advisories(installed).await,
+        _ => fedora_advisories(installed).await,
+    }
+}
+
+/// Findings from a locally installed `arch-audit`, which already
+/// matched the tracker data against the local database.
+async fn arch_audit_findings(installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    let argv: Vec<String> = ["arch-audit", "--json"]
+        .iter()
+        .map(|word| word.to_string())
+        .collect();
+    let output = crate::package_managers::run_backend("security", &argv).await?;
+    parse_arch_audit(&output, installed)
+}
+
+/// Parse `arch-audit --json`: issue objects naming the affected
+/// package, the CVEs, a severity and (when available) the fixed
+/// version. Both the bare-array and the `{"packages": [...]}` framings
+/// that different versions emit are accepted.
+fn parse_arch_audit(output: &str, installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(default, alias = "pkgname")]
+        name: String,
+        #[serde(default, alias = "issues")]
+        cves: Vec<String>,
+        #[serde(default)]
+        severity: String,
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        fixed: Option<String>,
+    }
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Framing {
+        Bare(Vec<Entry>),
+        Wrapped { packages: Vec<Entry> },
+    }
+    let entries = match serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "arch-audit".to_string(),
+        detail: err.to_string(),
+    })? {
+        Framing::Bare(entries) => entries,
+        Framing::Wrapped { packages } => packages,
+    };
+    let mut findings = Vec::new();
+    for entry in &entries {
+        let Some(version) = installed.get(entry.name.as_str()) else {
+            continue;
+        };
+        for id in &entry.cves {
+            findings.push(Finding {
+                package: entry.name.clone(),
+                manager: "pacman".to_string(),
+                installed_version: version.to_string(),
+                id: id.clone(),
+                summary: entry.kind.clone(),
+                severity: Severity::parse(&entry.severity),
+                score: None,
+                fixed_version: entry.fixed.clone(),
+                source: "arch-audit".to_string(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Findings from a locally installed `debsecan`, which already matched
+/// the Debian tracker against the local package state.
+async fn debsecan_findings(installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    let argv: Vec<String> = vec!["debsecan".to_string()];
+    let output = crate::package_managers::run_backend("security", &argv).await?;
+    Ok(parse_debsecan(&output, installed))
+}
+
+/// Parse debsecan's default output: one `CVE-... package (notes)` line
+/// per open issue, the notes carrying the urgency. Lines that do not
+/// fit the shape or name a package that is not installed are dropped.
+fn parse_debsecan(output: &str, installed: &BTreeMap<&str, &str>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    for line in output.lines() {
+        let mut words = line.split_whitespace();
+        let (Some(id), Some(package)) = (words.next(), words.next()) else {
+            continue;
+        };
+        if !id.starts_with("CVE-") {
+            continue;
+        }
+        let Some(version) = installed.get(package) else {
+            continue;
+        };
+        let notes = line
+            .split_once('(')
+            .and_then(|(_, rest)| rest.strip_suffix(')'))
+            .unwrap_or("");
+        let severity = if notes.contains("high urgency") {
+            Severity::High
+        } else if notes.contains("medium urgency") {
+            Severity::Medium
+        } else if notes.contains("low urgency") {
+            Severity::Low
+        } else {
+            Severity::Unknown
+        };
+        findings.push(Finding {
+            package: package.to_string(),
+            manager: "apt".to_string(),
+            installed_version: version.to_string(),
+            id: id.to_string(),
+            summary: notes.to_string(),
+            severity,
+            score: None,
+            fixed_version: None,
+            source: "debsecan".to_string(),
+        });
+    }
+    findings
+}
+
+/// Findings from Debian's security tracker for the installed apt
+/// packages.
+async fn debian_advisories(installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    let output = get_json(DEBIAN_TRACKER).await?;
+    parse_debian_tracker(&output, installed)
+}
+
+/// Parse the Debian tracker export: a map of source package to CVE to
+/// per-release status. An issue counts while any release reports it
+/// open; a release that resolved it supplies the fixed version.
+fn parse_debian_tracker(output: &str, installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(default)]
+        description: String,
+        #[serde(default)]
+        releases: BTreeMap<String, Release>,
+    }
+    #[derive(Deserialize)]
+    struct Release {
+        #[serde(default)]
+        status: String,
+        #[serde(default)]
+        fixed_version: Option<String>,
+        #[serde(default)]
+        urgency: String,
+    }
+    let tracker: BTreeMap<String, BTreeMap<String, Entry>> = serde_json::from_str(output)
+        .map_err(|err| PkgError::Parse {
+            source_desc: "debian security tracker".to_string(),
+            detail: err.to_string(),
+        })?;
+    let mut findings = Vec::new();
+    for (package, issues) in &tracker {
+        let Some(version) = installed.get(package.as_str()) else {
+            continue;
+        };
+        for (id, entry) in issues {
+            let open: Vec<&Release> = entry
+                .releases
+                .values()
+                .filter(|release| matches!(release.status.as_str(), "open" | "undetermined"))
+                .collect();
+            if open.is_empty() {
+                continue;
+            }
+            let severity = open
+                .iter()
+                .map(|release| Severity::parse(&release.urgency))
+                .min()
+                .unwrap_or(Severity::Unknown);
+            let fixed_version = entry
+                .releases
+                .values()
+                .find_map(|release| release.fixed_version.clone())
+                .filter(|fixed| fixed != "0");
+            findings.push(Finding {
+                package: package.clone(),
+                manager: "apt".to_string(),
+                installed_version: version.to_string(),
+                id: id.clone(),
+                summary: entry.description.clone(),
+                severity,
+                score: None,
+                fixed_version,
+                source: "debian".to_string(),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Findings from the Arch security tracker for the installed pacman
+/// packages.
+async fn arch_advisories(installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    let output = get_json(ARCH_TRACKER).await?;
+    parse_arch_advisories(&output, installed)
+}
+
+/// Parse the Arch tracker export: an array of AVG groups, each naming
+/// its packages, CVEs and fixed version. One finding per installed
+/// package per CVE, so the dedup against OSV works by id.
+fn parse_arch_advisories(output: &str, installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    #[derive(Deserialize)]
+    struct Group {
+        name: String,
+        #[serde(default)]
+        packages: Vec<String>,
+        #[serde(default)]
+        status: String,
+        #[serde(default)]
+        severity: String,
+        #[serde(default, rename = "type")]
+        kind: String,
+        #[serde(default)]
+        fixed: Option<String>,
+        #[serde(default)]
+        issues: Vec<String>,
+    }
+    let groups: Vec<Group> = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "arch security tracker".to_string(),
+        detail: err.to_string(),
+    })?;
+    let mut findings = Vec::new();
+    for group in &groups {
+        if !group.status.starts_with("Vulnerable") {
+            continue;
+        }
+        for package in &group.packages {
+            let Some(version) = installed.get(package.as_str()) else {
+                continue;
+            };
+            for id in &group.issues {
+                findings.push(Finding {
+                    package: package.clone(),
+                    manager: "pacman".to_string(),
+                    installed_version: version.to_string(),
+                    id: id.clone(),
+                    summary: format!("{} ({})", group.kind, group.name),
+                    severity: Severity::parse(&group.severity),
+                    score: None,
+                    fixed_version: group.fixed.clone(),
+                    source: "arch".to_string(),
+                });
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Findings from dnf's updateinfo for the installed dnf packages. This
+/// one needs no network of its own: dnf reads the repo metadata it
+/// already has.
+async fn fedora_advisories(installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    let argv: Vec<String> = ["dnf", "updateinfo", "list", "--security", "--json"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let output = crate::package_managers::run_backend("security", &argv).await?;
+    parse_fedora_updateinfo(&output, installed)
+}
+
+/// Parse `dnf updateinfo list --security --json`: an array of pending
+/// advisories, each naming the nevra that carries the fix.
+fn parse_fedora_updateinfo(output: &str, installed: &BTreeMap<&str, &str>) -> Result<Vec<Finding>> {
+    #[derive(Deserialize)]
+    struct Advisory {
+        name: String,
+        #[serde(default)]
+        severity: String,
+        #[serde(default)]
+        nevra: String,
+    }
+    let advisories: Vec<Advisory> = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "dnf updateinfo".to_string(),
+        detail: err.to_string(),
+    })?;
+    let mut findings = Vec::new();
+    for advisory in &advisories {
+        let Some((package, evr)) = split_nevra(&advisory.nevra) else {
+            continue;
+        };
+        let Some(version) = installed.get(package) else {
+            continue;
+        };
+        findings.push(Finding {
+            package: package.to_string(),
+            manager: "dnf".to_string(),
+            installed_version: version.to_string(),
+            id: advisory.name.clone(),
+            summary: format!("security update to {evr} pending"),
+            severity: Severity::parse(&advisory.severity),
+            score: None,
+            fixed_version: Some(evr),
+            source: "fedora".to_string(),
+        });
+    }
+    Ok(findings)
+}
+
+/// Split a `name-version-release.arch` nevra into the name and the
+/// version-release part.
+fn split_nevra(nevra: &str) -> Option<(&str, String)> {
+    let stem = nevra.rsplit_once('.').map_or(nevra, |(stem, _arch)| stem);
+    let (rest, release) = stem.rsplit_once('-')?;
+    let (name, version) = rest.rsplit_once('-')?;
+    Some((name, format!("{version}-{release}")))
+}
+
+/// Check one-line apt sources (`deb [options] url suite ...`) for
+/// `trusted=yes`, which skips verification outright, and for entries
+/// without a pinned `signed-by=` key.
+fn parse_apt_list(file: &str, content: &str) -> Vec<SignatureFinding> {
+    let mut findings = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if !trimmed.starts_with("deb ") && !trimmed.starts_with("deb-src ") {
+            continue;
+        }
+        let options = trimmed
+            .split_once('[')
+            .and_then(|(_, rest)| rest.split_once(']'))
+            .map(|(options, _)| options)
+            .unwrap_or("");
+        let location = format!("{file}:{}", index + 1);
+        if options.contains("trusted=yes") {
+            findings.push(SignatureFinding {
+                severity: Severity::High,
+                detail: "repository is marked trusted=yes; apt installs from it without \
+                         any signature verification"
+                    .to_string(),
+                location,
+            });
+        } else if !options.contains("signed-by=") {
+            findings.push(SignatureFinding {
+                severity: Severity::Medium,
+                detail: "repository has no signed-by= option; any key in the shared \
+                         keyring can sign it"
+                    .to_string(),
+                location,
+            });
+        }
+    }
+    findings
+}
+
+/// Check deb822 `.sources` stanzas for `Trusted: yes` and for missing
+/// `Signed-By:` fields.
+fn parse_apt_deb822(file: &str, content: &str) -> Vec<SignatureFinding> {
+    let mut findings = Vec::new();
+    let mut stanza_start = None;
+    let mut has_signed_by = false;
+    let mut lines: Vec<(usize, &str)> = content.lines().enumerate().collect();
+    // A sentinel blank line flushes the final stanza.
+    lines.push((content.lines().count(), ""));
+    for (index, line) in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(start) = stanza_start.take() {
+                if !has_signed_by {
+                    findings.push(SignatureFinding {
+                        severity: Severity::Medium,
+                        detail: "source stanza has no Signed-By field; any key in the \
+                                 shared keyring can sign it"
+                            .to_string(),
+                        location: format!("{file}:{}", start + 1),
+                    });
+                }
+            }
+            has_signed_by = false;
+            continue;
+        }
+        if trimmed.starts_with("Types:") && stanza_start.is_none() {
+            stanza_start = Some(index);
+        }
+        if trimmed.starts_with("Signed-By:") {
+            has_signed_by = true;
+        }
+        if trimmed.starts_with("Trusted:") && trimmed.ends_with("yes") {
+            findings.push(SignatureFinding {
+                severity: Severity::High,
+                detail: "source stanza is marked Trusted: yes; apt installs from it \
+                         without any signature verification"
+                    .to_string(),
+                location: format!("{file}:{}", index + 1),
+            });
+        }
+    }
+    findings
+}
+
+/// Check dnf repo files for sections that turn `gpgcheck` off.
+fn parse_dnf_repos(file: &str, content: &str) -> Vec<SignatureFinding> {
+    let mut findings = Vec::new();
+    let mut section = String::new();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if key.trim() == "gpgcheck" && value.trim() == "0" {
+            findings.push(SignatureFinding {
+                severity: Severity::High,
+                detail: format!(
+                    "repository [{section}] has gpgcheck=0; its packages install \
+                     without signature verification"
+                ),
+                location: format!("{file}:{}", index + 1),
+            });
+        }
+    }
+    findings
+}
+
+/// Check pacman.conf `SigLevel` settings: `Never` disables verification,
+/// `Optional` and `TrustAll` weaken it.
+fn parse_pacman_conf(file: &str, content: &str) -> Vec<SignatureFinding> {
+    let mut findings = Vec::new();
+    let mut section = "options".to_string();
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.split('#').next().unwrap_or("").trim();
+        if let Some(name) = trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, value)) = trimmed.split_once('=') else {
+            continue;
+        };
+        if key.trim() != "SigLevel" {
+            continue;
+        }
+        let location = format!("{file}:{}", index + 1);
+        // Scoped tokens like DatabaseOptional relax only database
+        // signatures; only the package-affecting ones are flagged.
+        let words: Vec<&str> = value.split_whitespace().collect();
+        let has = |bare: &str, scoped: &str| words.iter().any(|w| *w == bare || *w == scoped);
+        if has("Never", "PackageNever") {
+            findings.push(SignatureFinding {
+                severity: Severity::High,
+                detail: format!("SigLevel Never disables signature checking for [{section}]"),
+                location,
+            });
+        } else if has("Optional", "PackageOptional") {
+            findings.push(SignatureFinding {
+                severity: Severity::Medium,
+                detail: format!(
+                    "SigLevel Optional for [{section}]: unsigned packages are accepted"
+                ),
+                location,
+            });
+        } else if has("TrustAll", "PackageTrustAll") {
+            findings.push(SignatureFinding {
+                severity: Severity::Medium,
+                detail: format!(
+                    "SigLevel TrustAll for [{section}]: signatures from unknown keys count"
+                ),
+                location,
+            });
+        }
+    }
+    findings
+}
+
+/// Check a gpg key listing (`apt-key list`, `pacman-key --list-keys`)
+/// for expired keys and keys expiring within `KEY_EXPIRY_WARN_DAYS`.
+fn parse_key_listing(keyring: &str, output: &str, today: NaiveDate) -> Vec<SignatureFinding> {
+    // A `pub` line carries the expiry; the following `uid` line names
+    // the key. Keys without an expiry tag never produce a finding.
+    let mut pending: Option<(bool, NaiveDate)> = None;
+    let mut findings = Vec::new();
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("pub") {
+            pending = extract_key_expiry(trimmed);
+            continue;
+        }
+        let Some(rest) = trimmed.strip_prefix("uid") else {
+            continue;
+        };
+        let Some((expired, date)) = pending.take() else {
+            continue;
+        };
+        // gpg prints a validity tag like "[ unknown]" before the name.
+        let rest = rest.trim();
+        let name = match rest.strip_prefix('[') {
+            Some(tagged) => tagged.split_once(']').map(|(_, name)| name).unwrap_or(""),
+            None => rest,
+        }
+        .trim();
+        if expired {
+            findings.push(SignatureFinding {
+                severity: Severity::High,
+                detail: format!("signing key \"{name}\" expired on {date}"),
+                location: keyring.to_string(),
+            });
+        } else if (date - today).num_days() <= KEY_EXPIRY_WARN_DAYS {
+            findings.push(SignatureFinding {
+                severity: Severity::Medium,
+                detail: format!("signing key \"{name}\" expires on {date}"),
+                location: keyring.to_string(),
+            });
+        }
+    }
+    findings
+}
+
+/// The `[expired: date]` or `[expires: date]` tag of a gpg `pub` line,
+/// as (already expired, date).
+fn extract_key_expiry(line: &str) -> Option<(bool, NaiveDate)> {
+    for (tag, expired) in [("[expired: ", true), ("[expires: ", false)] {
+        if let Some(rest) = line.split(tag).nth(1) {
+            let date = rest.split(']').next()?.trim();
+            if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                return Some((expired, date));
+            }
+        }
+    }
+    None
+}
+
+/// POST a JSON body to the OSV API through curl, which follows the
+/// proxy environment the rest of the backends already use.
+/// Read the repository configuration files for `manager`, as
+/// (path, content) pairs; unreadable files are simply absent.
+fn repo_files(manager: &str) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    let mut push = |path: String| {
+        if let Ok(content) = crate::utils::host::read_file(&path) {
+            files.push((path, content));
+        }
+    };
+    match manager {
+        "apt" => {
+            push("/etc/apt/sources.list".to_string());
+            for entry in std::fs::read_dir("/etc/apt/sources.list.d")
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let path = entry.path();
+                if matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("list") | Some("sources")
+                ) {
+                    push(path.display().to_string());
+                }
+            }
+        }
+        "dnf" => {
+            for entry in std::fs::read_dir("/etc/yum.repos.d")
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                let path = entry.path();
+                if path.extension().and_then(|ext| ext.to_str()) == Some("repo") {
+                    push(path.display().to_string());
+                }
+            }
+        }
+        "pacman" => push("/etc/pacman.conf".to_string()),
+        _ => {}
+    }
+    files
+}
+
+/// Weak-configuration notes for one third-party source. pacman and dnf
+/// origins name a `[section]` in their config files, so the checks are
+/// scoped to that section; apt origins are suites, so only lines that
+/// mention the suite are considered. The AUR has no repo file at all —
+/// its one flag states the inherent condition.
+fn origin_flags(manager: &str, origin: &str, files: &[(String, String)]) -> Vec<String> {
+    let mut flags = Vec::new();
+    match manager {
+        "pacman" if origin.eq_ignore_ascii_case("aur") => {
+            flags.push("built outside any repository; no distro signing or review".to_string());
+        }
+        "pacman" | "dnf" => {
+            for (path, content) in files {
+                let mut in_section = false;
+                for (index, line) in content.lines().enumerate() {
+                    let trimmed = line.split('#').next().unwrap_or("").trim();
+                    if let Some(name) = trimmed
+                        .strip_prefix('[')
+                        .and_then(|rest| rest.strip_suffix(']'))
+                    {
+                        in_section = name.eq_ignore_ascii_case(origin);
+                        continue;
+                    }
+                    if !in_section {
+                        continue;
+                    }
+                    let Some((key, value)) = trimmed.split_once('=') else {
+                        continue;
+                    };
+                    let (key, value) = (key.trim(), value.trim());
+                    let at = format!("{path}:{}", index + 1);
+                    if key == "gpgcheck" && value == "0" {
+                        flags.push(format!("signature checking disabled ({at})"));
+                    }
+                    if key == "SigLevel"
+                        && value.split_whitespace().any(|word| {
+                            matches!(
+                                word,
+                                "Never" | "PackageNever" | "Optional" | "PackageOptional"
+                            )
+                        })
+                    {
+                        flags.push(format!("signature checking weakened ({at})"));
+                    }
+                    if matches!(key, "baseurl" | "metalink" | "mirrorlist" | "Server")
+                        && value.starts_with("http://")
+                    {
+                        flags.push(format!("plain-http URL ({at})"));
+                    }
+                }
+            }
+        }
+        _ => {
+            let needle = origin.to_lowercase();
+            for (path, content) in files {
+                for (index, line) in content.lines().enumerate() {
+                    let trimmed = line.trim();
+                    if !trimmed.starts_with("deb ") && !trimmed.starts_with("deb-src ") {
+                        continue;
+                    }
+                    if !trimmed.to_lowercase().contains(&needle) {
+                        continue;
+                    }
+                    let at = format!("{path}:{}", index + 1);
+                    if trimmed.contains("http://") {
+                        flags.push(format!("plain-http URL ({at})"));
+                    }
+                    if trimmed.contains("trusted=yes") {
+                        flags.push(format!("signature checking disabled ({at})"));
+                    } else if !trimmed.contains("signed-by=") {
+                        flags.push(format!("no pinned signing key ({at})"));
+                    }
+                }
+            }
+        }
+    }
+    flags
+}
+
+/// The versions the AUR currently carries for `names`, via one info RPC
+/// call; any failure means an empty map and no comparison.
+/// Where setuid binaries and file capabilities are expected to live.
+const SETUID_DIRS: [&str; 6] = [
+    "/usr/bin",
+    "/usr/sbin",
+    "/bin",
+    "/sbin",
+    "/usr/local/bin",
+    "/usr/local/sbin",
+];
+
+/// Package-name tokens whose setuid bits are business as usual, across
+/// the distros' different package names (shadow vs shadow-utils,
+/// policykit-1 vs polkit, ...).
+const SETUID_EXPECTED: [&str; 14] = [
+    "sudo",
+    "passwd",
+    "shadow",
+    "util-linux",
+    "mount",
+    "su",
+    "openssh",
+    "iputils",
+    "fuse",
+    "polkit",
+    "policykit",
+    "dbus",
+    "cron",
+    "at",
+];
+
+/// Whether a privileged binary deserves a finding, and how urgent: a
+/// setuid file no package accounts for is the worst case; anything
+/// owned by an allowlisted package is normal and reports nothing.
+fn classify_privileged(kind: &str, package: Option<&str>) -> Option<Severity> {
+    let Some(package) = package else {
+        return Some(if kind == "setuid" {
+            Severity::Critical
+        } else {
+            Severity::High
+        });
+    };
+    let lower = package.to_lowercase();
+    if SETUID_EXPECTED.iter().any(|token| lower.contains(token)) {
+        return None;
+    }
+    Some(match kind {
+        "setuid" => Severity::High,
+        _ => Severity::Medium,
+    })
+}
+
+/// Parse `getcap -r` output into (path, capability set) pairs. Both the
+/// newer `path cap_x=ep` and the older `path = cap_x+ep` framings occur.
+fn parse_getcap(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (path, caps) = match line.split_once(" = ") {
+                Some(split) => split,
+                None => line.rsplit_once(' ')?,
+            };
+            let (path, caps) = (path.trim(), caps.trim());
+            (path.starts_with('/') && caps.starts_with("cap_"))
+                .then(|| (path.to_string(), caps.to_string()))
+        })
+        .collect()
+}
+
+/// On-disk cache of owner lookups for the privileged-binary walk,
+/// keyed by path and guarded by mtime.
+#[derive(Serialize, Deserialize)]
+struct SetuidCacheEntry {
+    mtime: i64,
+    package: Option<String>,
+}
+
+fn setuid_cache_path() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("setuid.json")
+}
+
+fn load_setuid_cache() -> BTreeMap<String, SetuidCacheEntry> {
+    std::fs::read_to_string(setuid_cache_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn save_setuid_cache(cache: &BTreeMap<String, SetuidCacheEntry>) {
+    let path = setuid_cache_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_string(cache) {
+        let _ = std::fs::write(&path, data);
+    }
+}
+
+/// Run a short local survey command; `command` is split on whitespace.
+async fn run_local(command: &str) -> Result<String> {
+    let argv: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+    crate::package_managers::run_backend("security", &argv).await
+}
+
+/// Snap interfaces broad enough to deserve a flag regardless of app.
+const SNAP_RISKY: [&str; 3] = ["home", "removable-media", "network-control"];
+
+/// Flatten `flatpak info --show-permissions` keyfile output into one
+/// string per grant: `key=value` for [Context] lists, and
+/// `system-bus talk <name>` style entries for the bus policy sections.
+fn parse_flatpak_permissions(output: &str) -> Vec<String> {
+    let mut permissions = Vec::new();
+    let mut section = String::new();
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = name.to_string();
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match section.as_str() {
+            "Context" => {
+                for item in value.split(';').filter(|item| !item.is_empty()) {
+                    permissions.push(format!("{key}={item}"));
+                }
+            }
+            "Session Bus Policy" => permissions.push(format!("session-bus {value} {key}")),
+            "System Bus Policy" => permissions.push(format!("system-bus {value} {key}")),
+            _ => {}
+        }
+    }
+    permissions
+}
+
+/// The subset of flatpak grants worth flagging: host filesystem
+/// access, every device, and any name on the system bus.
+fn flag_flatpak_permissions(permissions: &[String]) -> Vec<String> {
+    permissions
+        .iter()
+        .filter(|grant| {
+            matches!(
+                grant.as_str(),
+                "filesystems=host" | "filesystems=host-os" | "filesystems=host-etc"
+            ) || grant.as_str() == "devices=all"
+                || grant.starts_with("system-bus talk ")
+                || grant.starts_with("system-bus own ")
+        })
+        .cloned()
+        .collect()
+}
+
+/// Group `snap connections` rows by snap: the plug column is
+/// `snap:plug`, the interface column names the permission. Rows whose
+/// slot is "-" are disconnected and do not count.
+fn parse_snap_connections(output: &str) -> BTreeMap<String, Vec<String>> {
+    let mut by_snap: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for line in output.lines().skip(1) {
+        let mut words = line.split_whitespace();
+        let (Some(interface), Some(plug), Some(slot)) =
+            (words.next(), words.next(), words.next())
+        else {
+            continue;
+        };
+        if slot == "-" {
+            continue;
+        }
+        let Some((snap, _)) = plug.split_once(':') else {
+            continue;
+        };
+        if snap.is_empty() {
+            continue;
+        }
+        let interfaces = by_snap.entry(snap.to_string()).or_default();
+        if !interfaces.iter().any(|known| known == interface) {
+            interfaces.push(interface.to_string());
+        }
+    }
+    by_snap
+}
+
+async fn aur_versions(names: &[&str]) -> BTreeMap<String, String> {
+    if names.is_empty() {
+        return BTreeMap::new();
+    }
+    let mut url = format!("{AUR_RPC}?v=5&type=info");
+    for name in names {
+        url.push_str("&arg[]=");
+        url.push_str(name);
+    }
+    match get_json(&url).await {
+        Ok(output) => parse_aur_info(&output),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+/// Parse an AUR info RPC response into name -> version. Packages the
+/// AUR does not know are simply absent from the results array.
+fn parse_aur_info(output: &str) -> BTreeMap<String, String> {
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(default)]
+        results: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Version")]
+        version: String,
+    }
+    serde_json::from_str::<Response>(output)
+        .map(|response| {
+            response
+                .results
+                .into_iter()
+                .map(|entry| (entry.name, entry.version))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn post_json(url: &str, body: &str) -> Result<String> {
+    let argv: Vec<String> = [
+        "curl",
+        "-sS",
+        "--fail",
+        "--max-time",
+        "30",
+        "-H",
+        "Content-Type: application/json",
+        "-d",
+        body,
+        url,
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    crate::package_managers::run_backend("security", &argv).await
+}
+
+async fn get_json(url: &str) -> Result<String> {
+    let argv: Vec<String> = ["curl", "-sS", "--fail", "--max-time", "30", url]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    crate::package_managers::run_backend("security", &argv).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_responses_keep_query_order() {
+        let output = r#"{"results":[
+            {"vulns":[{"id":"CVE-2024-1111","modified":"2024-01-06T00:00:00Z"},
+                      {"id":"GHSA-xxxx","modified":"2024-01-07T00:00:00Z"}]},
+            {},
+            {"vulns":[{"id":"CVE-2024-2222","modified":"2024-01-08T00:00:00Z"}]}
+        ]}"#;
+        let results = parse_batch_response(output).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], vec!["CVE-2024-1111", "GHSA-xxxx"]);
+        assert!(results[1].is_empty());
+        assert_eq!(results[2], vec!["CVE-2024-2222"]);
+    }
+
+    #[test]
+    fn advisories_yield_severity_and_fixed_versions() {
+        let output = r#"{
+            "id":"GHSA-xxxx",
+            "summary":"Buffer overflow in frobnicator",
+            "severity":[{"type":"CVSS_V3","score":"CVSS:3.1/AV:N/AC:L"}],
+            "database_specific":{"severity":"HIGH"},
+            "affected":[{
+                "package":{"ecosystem":"Debian","name":"frob"},
+                "ranges":[{"type":"ECOSYSTEM","events":[
+                    {"introduced":"0"},{"fixed":"1.2.3-4"}
+                ]}]
+            }]
+        }"#;
+        let detail = parse_vuln(output).unwrap();
+        assert_eq!(detail.severity, Severity::High);
+        assert_eq!(detail.fixed_for("Debian", "frob"), Some("1.2.3-4".to_string()));
+        assert_eq!(detail.fixed_for("Debian", "other"), None);
+        // A bare CVSS vector never maps onto a bucket by accident.
+        let vector_only = r#"{"id":"X","severity":[{"score":"CVSS:3.1/AV:N"}]}"#;
+        assert_eq!(parse_vuln(vector_only).unwrap().severity, Severity::Unknown);
+    }
+
+    #[test]
+    fn cvss_vectors_score_per_the_spec() {
+        // Reference scores from published advisories.
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some(9.8)
+        );
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H"),
+            Some(9.9)
+        );
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.0/AV:L/AC:H/PR:L/UI:R/S:U/C:L/I:N/A:N"),
+            Some(2.2)
+        );
+        // No impact at all scores zero; truncated vectors score nothing.
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(cvss_v3_base_score("CVSS:3.1/AV:N/AC:L"), None);
+        assert_eq!(cvss_v3_base_score("AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), None);
+    }
+
+    #[test]
+    fn a_complete_vector_outranks_the_qualitative_word() {
+        let output = r#"{
+            "id":"GHSA-yyyy",
+            "severity":[{"type":"CVSS_V3","score":"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+            "database_specific":{"severity":"LOW"}
+        }"#;
+        let detail = parse_vuln(output).unwrap();
+        assert_eq!(detail.score, Some(9.8));
+        assert_eq!(detail.severity, Severity::Critical);
+    }
+
+    #[test]
+    fn ignores_expire_on_their_until_date() {
+        let mut list = IgnoreList {
+            path: std::env::temp_dir().join("pkgtool-test-ignores.json"),
+            entries: Vec::new(),
+        };
+        let expiring = IgnoreEntry {
+            id: "CVE-2024-1111".to_string(),
+            reason: "not reachable in our deployment".to_string(),
+            until: NaiveDate::from_ymd_opt(2026, 8, 31),
+            added: Utc::now(),
+        };
+        let indefinite = IgnoreEntry {
+            id: "CVE-2024-2222".to_string(),
+            reason: "accepted risk".to_string(),
+            until: None,
+            added: Utc::now(),
+        };
+        list.entries = vec![expiring, indefinite];
+        let before = NaiveDate::from_ymd_opt(2026, 8, 26).unwrap();
+        let after = NaiveDate::from_ymd_opt(2026, 9, 1).unwrap();
+        assert!(list.is_ignored("CVE-2024-1111", before));
+        assert!(!list.is_ignored("CVE-2024-1111", after));
+        assert!(list.is_ignored("CVE-2024-2222", after));
+        assert!(!list.is_ignored("CVE-2020-9999", before));
+    }
+
+    #[test]
+    fn debian_tracker_reports_open_issues_for_installed_packages() {
+        let output = r#"{
+            "frob": {
+                "CVE-2024-1111": {
+                    "description": "Buffer overflow in frobnicator",
+                    "releases": {
+                        "bookworm": {"status":"open","urgency":"medium"},
+                        "sid": {"status":"resolved","fixed_version":"1.2.3-4","urgency":"medium"}
+                    }
+                },
+                "CVE-2020-9999": {
+                    "description": "Long fixed",
+                    "releases": {
+                        "bookworm": {"status":"resolved","fixed_version":"1.0-1","urgency":"low"}
+                    }
+                }
+            },
+            "notinstalled": {
+                "CVE-2024-2222": {
+                    "description": "Elsewhere",
+                    "releases": {"bookworm": {"status":"open","urgency":"high"}}
+                }
+            }
+        }"#;
+        let installed = BTreeMap::from([("frob", "1.2.3-1")]);
+        let findings = parse_debian_tracker(output, &installed).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].id, "CVE-2024-1111");
+        assert_eq!(findings[0].severity, Severity::Medium);
+        assert_eq!(findings[0].fixed_version, Some("1.2.3-4".to_string()));
+        assert_eq!(findings[0].source, "debian");
+    }
+
+    #[test]
+    fn arch_groups_expand_to_one_finding_per_cve() {
+        let output = r#"[
+            {"name":"AVG-2824","packages":["linux","linux-lts"],"status":"Vulnerable",
+             "severity":"High","type":"arbitrary code execution","fixed":"6.2-1",
+             "issues":["CVE-2024-1111","CVE-2024-2222"]},
+            {"name":"AVG-1000","packages":["linux"],"status":"Fixed",
+             "severity":"Low","type":"denial of service","fixed":"5.0-1",
+             "issues":["CVE-2020-9999"]}
+        ]"#;
+        let installed = BTreeMap::from([("linux", "6.1-1")]);
+        let findings = parse_arch_advisories(output, &installed).unwrap();
+        // Fixed groups and uninstalled packages drop out; each CVE of a
+        // vulnerable group stays addressable for the dedup.
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].id, "CVE-2024-1111");
+        assert_eq!(findings[0].summary, "arbitrary code execution (AVG-2824)");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[1].id, "CVE-2024-2222");
+    }
+
+    #[test]
+    fn arch_audit_output_is_accepted_in_both_framings() {
+        let bare = r#"[
+            {"name":"openssl","cves":["CVE-2024-1111","CVE-2024-2222"],
+             "severity":"High","type":"information disclosure","fixed":"3.2.1-1"},
+            {"name":"notinstalled","cves":["CVE-2024-3333"],"severity":"Low"}
+        ]"#;
+        let wrapped = r#"{"packages":[
+            {"pkgname":"openssl","issues":["CVE-2024-1111"],"severity":"Medium"}
+        ]}"#;
+        let installed = BTreeMap::from([("openssl", "3.2.0-1")]);
+        let findings = parse_arch_audit(bare, &installed).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].id, "CVE-2024-1111");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].fixed_version, Some("3.2.1-1".to_string()));
+        assert_eq!(findings[0].source, "arch-audit");
+        let findings = parse_arch_audit(wrapped, &installed).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Medium);
+    }
+
+    #[test]
+    fn debsecan_lines_carry_the_urgency_and_skip_noise() {
+        let output = "CVE-2024-1111 libfrob2 (remotely exploitable, high urgency)\n\
+                      CVE-2024-2222 libfrob2 (low urgency)\n\
+                      CVE-2024-3333 otherpkg (medium urgency)\n\
+                      obsolete packages: none\n";
+        let installed = BTreeMap::from([("libfrob2", "2.1-3")]);
+        let findings = parse_debsecan(output, &installed);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].id, "CVE-2024-1111");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].summary, "remotely exploitable, high urgency");
+        assert_eq!(findings[1].severity, Severity::Low);
+        assert_eq!(findings[1].source, "debsecan");
+    }
+
+    #[test]
+    fn flatpak_permissions_flatten_and_flag_the_broad_grants() {
+        let output = "[Context]\n\
+                      shares=network;ipc;\n\
+                      devices=all;\n\
+                      filesystems=host;xdg-download;\n\
+                      \n\
+                      [Session Bus Policy]\n\
+                      org.freedesktop.Flatpak=talk\n\
+                      \n\
+                      [System Bus Policy]\n\
+                      org.freedesktop.NetworkManager=talk\n";
+        let permissions = parse_flatpak_permissions(output);
+        assert!(permissions.contains(&"shares=network".to_string()));
+        assert!(permissions.contains(&"filesystems=xdg-download".to_string()));
+        let flagged = flag_flatpak_permissions(&permissions);
+        assert_eq!(
+            flagged,
+            vec![
+                "devices=all".to_string(),
+                "filesystems=host".to_string(),
+                "system-bus talk org.freedesktop.NetworkManager".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn snap_connections_group_by_snap_and_skip_disconnected() {
+        let output = "Interface        Plug                 Slot              Notes\n\
+                      home             firefox:home         :home             -\n\
+                      network          firefox:network      :network          -\n\
+                      network-control  vlc:network-control  -                 -\n\
+                      home             firefox:home         :home             manual\n";
+        let by_snap = parse_snap_connections(output);
+        // The disconnected vlc plug drops out; the duplicate firefox
+        // home row does not double-count.
+        assert_eq!(by_snap.len(), 1);
+        assert_eq!(by_snap["firefox"], vec!["home", "network"]);
+    }
+
+    #[test]
+    fn getcap_output_parses_in_both_framings() {
+        let output = "/usr/bin/ping cap_net_raw=ep\n\
+                      /usr/bin/gnome-keyring-daemon = cap_ipc_lock+ep\n\
+                      noise without a path\n";
+        let caps = parse_getcap(output);
+        assert_eq!(caps.len(), 2);
+        assert_eq!(caps[0], ("/usr/bin/ping".to_string(), "cap_net_raw=ep".to_string()));
+        assert_eq!(caps[1].1, "cap_ipc_lock+ep");
+    }
+
+    #[test]
+    fn privileged_binaries_classify_by_owner_and_kind() {
+        // Unowned setuid is the worst case; allowlisted owners report
+        // nothing regardless of the distro's package-name flavour.
+        assert_eq!(classify_privileged("setuid", None), Some(Severity::Critical));
+        assert_eq!(classify_privileged("cap_net_raw=ep", None), Some(Severity::High));
+        assert_eq!(classify_privileged("setuid", Some("shadow-utils")), None);
+        assert_eq!(classify_privileged("setuid", Some("policykit-1")), None);
+        assert_eq!(
+            classify_privileged("setuid", Some("somegame")),
+            Some(Severity::High)
+        );
+        assert_eq!(
+            classify_privileged("setgid", Some("somegame")),
+            Some(Severity::Medium)
+        );
+    }
+
+    #[test]
+    fn fedora_updateinfo_maps_nevras_onto_installed_packages() {
+        let output = r#"[
+            {"name":"FEDORA-2024-abcd","type":"security","severity":"Important",
+             "nevra":"frob-utils-1.2.3-4.fc40.x86_64"},
+            {"name":"FEDORA-2024-efgh","type":"security","severity":"Moderate",
+             "nevra":"other-2.0-1.fc40.noarch"}
+        ]"#;
+        let installed = BTreeMap::from([("frob-utils", "1.2.3-1")]);
+        let findings = parse_fedora_updateinfo(output, &installed).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].package, "frob-utils");
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].fixed_version, Some("1.2.3-4.fc40".to_string()));
+    }
+
+    #[test]
+    fn apt_sources_flag_trusted_and_unpinned_entries() {
+        let list = "# comment\n\
+                    deb [trusted=yes] https://example.com/repo stable main\n\
+                    deb [arch=amd64 signed-by=/usr/share/keyrings/x.gpg] https://ok.example stable main\n\
+                    deb https://bare.example stable main\n";
+        let findings = parse_apt_list("/etc/apt/sources.list", list);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].location, "/etc/apt/sources.list:2");
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert_eq!(findings[1].location, "/etc/apt/sources.list:4");
+
+        let deb822 = "Types: deb\n\
+                      URIs: https://example.com/repo\n\
+                      Suites: stable\n\
+                      Trusted: yes\n\
+                      \n\
+                      Types: deb\n\
+                      URIs: https://ok.example\n\
+                      Signed-By: /usr/share/keyrings/x.gpg\n";
+        let findings = parse_apt_deb822("/etc/apt/sources.list.d/x.sources", deb822);
+        // The first stanza is both Trusted: yes and missing Signed-By.
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().any(|f| f.severity == Severity::High
+            && f.location == "/etc/apt/sources.list.d/x.sources:4"));
+    }
+
+    #[test]
+    fn dnf_and_pacman_configs_flag_disabled_verification() {
+        let repo = "[fedora]\nname=Fedora\ngpgcheck=1\n\n[sketchy]\nname=Sketchy\ngpgcheck=0\n";
+        let findings = parse_dnf_repos("/etc/yum.repos.d/sketchy.repo", repo);
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].detail.contains("[sketchy]"));
+        assert_eq!(findings[0].location, "/etc/yum.repos.d/sketchy.repo:7");
+
+        let conf = "[options]\nSigLevel = Required DatabaseOptional\n\
+                    [custom]\nSigLevel = Never # local builds\n\
+                    [aur-cache]\nSigLevel = Optional TrustAll\n";
+        let findings = parse_pacman_conf("/etc/pacman.conf", conf);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert!(findings[0].detail.contains("[custom]"));
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert!(findings[1].detail.contains("[aur-cache]"));
+    }
+
+    #[test]
+    fn key_listings_flag_expired_and_expiring_keys() {
+        let output = "\
+/etc/pacman.d/gnupg/pubring.gpg\n\
+-------------------------------\n\
+pub   rsa4096 2017-05-17 [SC] [expired: 2024-01-01]\n\
+      ABCDEF1234567890\n\
+uid           [ expired] Old Distro Signing Key <old@example.org>\n\
+\n\
+pub   rsa4096 2020-01-01 [SC] [expires: 2026-09-10]\n\
+      1234567890ABCDEF\n\
+uid           [ unknown] Soon Key <soon@example.org>\n\
+\n\
+pub   rsa4096 2022-01-01 [SC] [expires: 2030-01-01]\n\
+      0000000011111111\n\
+uid           [  full  ] Healthy Key <ok@example.org>\n";
+        let today = NaiveDate::from_ymd_opt(2026, 8, 26).unwrap();
+        let findings = parse_key_listing("pacman keyring", output, today);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert!(findings[0].detail.contains("Old Distro Signing Key"));
+        assert_eq!(findings[1].severity, Severity::Medium);
+        assert!(findings[1].detail.contains("expires on 2026-09-10"));
+    }
+
+    #[test]
+    fn the_fingerprint_tracks_versions_not_order() {
+        let package = |name: &str, version: &str| PackageInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            manager: "apt".to_string(),
+            installed: true,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        };
+        let unordered = [package("zsh", "5.9-1"), package("bash", "5.2-1")];
+        let ordered = [package("bash", "5.2-1"), package("zsh", "5.9-1")];
+        assert_eq!(fingerprint(&unordered), fingerprint(&ordered));
+        let upgraded = [package("bash", "5.2-2"), package("zsh", "5.9-1")];
+        assert_ne!(fingerprint(&ordered), fingerprint(&upgraded));
+    }
+
+    #[test]
+    fn advisory_detail_keeps_vector_date_and_advisory_first_references() {
+        let output = r#"{
+            "id":"GHSA-yyyy",
+            "summary":"Use after free",
+            "published":"2024-03-01T09:30:00Z",
+            "severity":[{"type":"CVSS_V3","score":"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+            "references":[
+                {"type":"WEB","url":"https://example.com/writeup"},
+                {"type":"ADVISORY","url":"https://example.com/advisory"}
+            ],
+            "affected":[]
+        }"#;
+        let detail = parse_vuln(output).unwrap();
+        assert_eq!(
+            detail.vector.as_deref(),
+            Some("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H")
+        );
+        assert_eq!(detail.published.as_deref(), Some("2024-03-01T09:30:00Z"));
+        assert_eq!(
+            detail.references,
+            ["https://example.com/advisory", "https://example.com/writeup"]
+        );
+    }
+
+    #[test]
+    fn origin_flags_are_scoped_to_the_named_section() {
+        let repo = "[fedora]\ngpgcheck=1\n\n[vendor]\ngpgcheck=0\nbaseurl=http://vendor.example/repo\n";
+        let files = vec![("/etc/yum.repos.d/vendor.repo".to_string(), repo.to_string())];
+        let flags = origin_flags("dnf", "vendor", &files);
+        assert_eq!(flags.len(), 2);
+        assert!(flags[0].contains("signature checking disabled"));
+        assert!(flags[0].contains("vendor.repo:5"));
+        assert!(flags[1].contains("plain-http URL"));
+        assert!(origin_flags("dnf", "fedora", &files).is_empty());
+    }
+
+    #[test]
+    fn pacman_siglevel_and_apt_lines_are_flagged() {
+        let conf = "[core]\nInclude = /etc/pacman.d/mirrorlist\n\n[sideload]\nSigLevel = Optional TrustAll\nServer = http://sideload.example/$arch\n";
+        let flags = origin_flags(
+            "pacman",
+            "sideload",
+            &[("/etc/pacman.conf".to_string(), conf.to_string())],
+        );
+        assert_eq!(flags.len(), 2);
+        assert!(flags[0].contains("weakened"));
+
+        let list = "deb http://ppa.example/team/ubuntu jammy main\n";
+        let flags = origin_flags(
+            "apt",
+            "jammy",
+            &[("/etc/apt/sources.list.d/team.list".to_string(), list.to_string())],
+        );
+        assert!(flags.iter().any(|flag| flag.contains("plain-http URL")));
+        assert!(flags.iter().any(|flag| flag.contains("no pinned signing key")));
+
+        let aur = origin_flags("pacman", "AUR", &[]);
+        assert_eq!(aur.len(), 1);
+        assert!(aur[0].contains("outside any repository"));
+    }
+
+    #[test]
+    fn aur_info_responses_map_names_to_versions() {
+        let output = r#"{"resultcount":2,"results":[
+            {"Name":"yay","Version":"12.3.5-1","Description":"Pacman wrapper"},
+            {"Name":"paru-bin","Version":"2.0.4-1"}
+        ],"type":"multiinfo","version":5}"#;
+        let versions = parse_aur_info(output);
+        assert_eq!(versions.get("yay").map(String::as_str), Some("12.3.5-1"));
+        assert_eq!(versions.len(), 2);
+        assert!(parse_aur_info("not json").is_empty());
+    }
+
+    fn export_fixture() -> SecurityReportExport {
+        let report = VulnReport {
+            findings: vec![Finding {
+                package: "frob".to_string(),
+                manager: "apt".to_string(),
+                installed_version: "1.2.3-1".to_string(),
+                id: "CVE-2024-1111".to_string(),
+                summary: "Buffer overflow in <handler>".to_string(),
+                severity: Severity::High,
+                score: Some(8.1),
+                fixed_version: Some("1.2.3-4".to_string()),
+                source: "debian".to_string(),
+            }],
+            scanned: 10,
+            skipped: 2,
+            ..VulnReport::default()
+        };
+        let system = SystemInfo {
+            hostname: "build-host".to_string(),
+            distro: "Debian GNU/Linux 12".to_string(),
+            kernel: "6.1.0-18-amd64".to_string(),
+        };
+        SecurityReportExport::new(&report, system)
+    }
+
+    #[test]
+    fn the_export_json_schema_stays_stable() {
+        // Scripts parse this; field renames and removals are breaking.
+        let json: serde_json::Value =
+            serde_json::from_str(&export_fixture().to_json().unwrap()).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["system"]["hostname"], "build-host");
+        assert_eq!(json["summary"]["high"], 1);
+        assert_eq!(json["summary"]["scanned"], 10);
+        let finding = &json["findings"][0];
+        for field in [
+            "package",
+            "manager",
+            "installed_version",
+            "id",
+            "severity",
+            "score",
+            "summary",
+            "fixed_version",
+            "source",
+            "advisory_url",
+        ] {
+            assert!(finding.get(field).is_some(), "missing field {field}");
+        }
+        assert_eq!(finding["severity"], "high");
+        assert_eq!(
+            finding["advisory_url"],
+            "https://www.cve.org/CVERecord?id=CVE-2024-1111"
+        );
+    }
+
+    #[test]
+    fn html_export_is_escaped_and_self_contained() {
+        let html = export_fixture().to_html();
+        assert!(html.contains("<style>"), "styling must be inline");
+        assert!(!html.contains("http-equiv") && !html.contains("src="), "no external assets");
+        assert!(html.contains("class=\"high\""));
+        assert!(html.contains("Buffer overflow in &lt;handler&gt;"));
+        assert!(!html.contains("<handler>"));
+    }
+
+    #[test]
+    fn advisory_urls_route_to_the_issuing_database() {
+        assert!(advisory_url("CVE-2024-1111").contains("cve.org"));
+        assert!(advisory_url("DSA-5678-1").contains("security-tracker.debian.org"));
+        assert!(advisory_url("AVG-2824").contains("security.archlinux.org"));
+        assert!(advisory_url("FEDORA-2024-abcd").contains("bodhi.fedoraproject.org"));
+        assert!(advisory_url("GHSA-xxxx").contains("osv.dev"));
+    }
+
+    #[test]
+    fn ecosystems_cover_distros_and_language_managers() {
+        assert_eq!(ecosystem("apt"), Some("Debian"));
+        assert_eq!(ecosystem("pacman"), Some("Arch"));
+        assert_eq!(ecosystem("cargo"), Some("crates.io"));
+        assert_eq!(ecosystem("brew"), None);
+    }
+}
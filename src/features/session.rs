@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Restorable view state, written to `state.json` in the platform state
+/// directory on exit and periodically while running, so a crash loses at
+/// most one save interval.
+///
+/// Unknown or stale values degrade gracefully on restore: a vanished tab
+/// falls back to the configured default and an out-of-range selection is
+/// clamped to the nearest row once data arrives.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Title of the tab that was active on exit.
+    pub selected_tab: String,
+    /// Installed-list order: "name" or "recently installed".
+    pub sort_mode: String,
+    /// Origin the installed list was filtered to, if any.
+    pub origin_filter: Option<String>,
+    pub show_held_only: bool,
+    pub split_ratio: u16,
+    /// Selected row per tab, keyed by tab title.
+    pub selections: HashMap<String, usize>,
+    /// Commands entered in the input bar, oldest first.
+    pub command_history: Vec<String>,
+}
+
+impl SessionState {
+    /// Load the previous session, or start fresh when the file is absent
+    /// or unreadable.
+    pub fn load() -> Self {
+        std::fs::read_to_string(state_path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = state_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(self)?;
+        std::fs::write(&path, data)?;
+        Ok(())
+    }
+}
+
+fn state_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("state.json")
+}
@@ -0,0 +1,2082 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PkgError, Result};
+use crate::package_managers::PackageInfo;
+use crate::utils::privilege::PrivilegeRunner;
+
+/// A filesystem or package-state snapshot known to pkgtool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub created: DateTime<Utc>,
+    /// What prompted the snapshot ("pre-update", "manual", ...).
+    pub trigger: String,
+    /// "pre" or "post" for backends that pair snapshots around a
+    /// transaction (snapper); empty for plain snapshots.
+    #[serde(default)]
+    pub kind: String,
+    /// For a post snapshot, the id of the pre snapshot it pairs with.
+    #[serde(default)]
+    pub pre: Option<String>,
+    /// How full a fixed-size snapshot's copy-on-write space is (LVM);
+    /// `None` for backends whose snapshots cannot fill up.
+    #[serde(default)]
+    pub usage_percent: Option<f64>,
+    /// Space the snapshot occupies, where the backend reports it (ZFS);
+    /// pruning uses it to say how much was reclaimed.
+    #[serde(default)]
+    pub size_bytes: Option<u64>,
+    /// Free-text note attached after the fact ("before trying the
+    /// nvidia driver"); stored natively where the backend can, in a
+    /// sidecar file otherwise.
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// How full a fixed-size snapshot may get before the UI warns. LVM
+/// silently invalidates a snapshot whose copy-on-write space fills, so
+/// creeping usage needs to be visible well before 100%.
+pub const USAGE_WARN_PERCENT: f64 = 80.0;
+
+/// Whether a restore took effect immediately or only arms a change that
+/// the next boot completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestoreOutcome {
+    Complete,
+    RebootRequired,
+}
+
+/// Aggregate storage numbers for the volume a backend's snapshots live
+/// on. Every field is optional: each backend fills in what its tools
+/// expose and leaves the rest `None` rather than guessing.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotUsage {
+    /// Total space the snapshots occupy.
+    pub used_bytes: Option<u64>,
+    /// Free space left on the snapshot volume.
+    pub free_bytes: Option<u64>,
+    /// Exclusive usage per snapshot id — the space freed by deleting
+    /// just that one — where the backend can tell snapshots apart.
+    pub per_snapshot: Vec<(String, u64)>,
+}
+
+/// Where snapshots are taken from and stored, from the `[snapshots]`
+/// config section. The btrfs backend uses the locations, the LVM
+/// backend only the size; Timeshift and snapper manage their own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Subvolume to snapshot; the root filesystem by default.
+    pub subvolume: String,
+    /// Directory read-only snapshots are created in. Must live on the
+    /// same btrfs filesystem as the subvolume.
+    pub directory: String,
+    /// Size of LVM snapshots: a percentage of the origin volume like
+    /// "20%ORIGIN", or an absolute size like "5G".
+    pub lvm_size: String,
+    /// When to snapshot automatically before a mutating operation:
+    /// "always", "upgrades-only" (system updates), "over-threshold"
+    /// (transactions touching more than `threshold` packages) or
+    /// "never". The `builtin:snapshot` hook works independently.
+    pub auto: String,
+    /// Package-count cutoff for the "over-threshold" policy.
+    pub threshold: usize,
+    /// What a failed automatic snapshot does to the operation: "abort"
+    /// it, or "warn" and continue without the snapshot.
+    pub on_failure: String,
+    /// How many automatic snapshots to keep; older ones become prune
+    /// candidates. 0 disables the count rule.
+    pub keep_last: usize,
+    /// Age cutoff in days for automatic snapshots, regardless of count.
+    /// 0 disables the age rule. Manual snapshots are never pruned.
+    pub keep_days: u64,
+    /// Warn before an automatic snapshot when the snapshot volume has
+    /// less than this many megabytes free. 0 disables the check.
+    pub min_free_mb: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        SnapshotConfig {
+            subvolume: "/".to_string(),
+            directory: "/.pkgtool-snapshots".to_string(),
+            lvm_size: "20%ORIGIN".to_string(),
+            auto: "never".to_string(),
+            threshold: 10,
+            on_failure: "abort".to_string(),
+            keep_last: 10,
+            keep_days: 0,
+            min_free_mb: 512,
+        }
+    }
+}
+
+/// Common interface over the snapshot tools; backends are interchangeable
+/// behind `SnapshotManager`, which picks one at startup.
+#[async_trait]
+pub trait SnapshotBackend: Send + Sync {
+    /// Short identifier shown in the UI ("btrfs", "timeshift", "snapper").
+    fn id(&self) -> &str;
+    async fn create(&self, trigger: &str) -> Result<Snapshot>;
+    async fn list(&self) -> Result<Vec<Snapshot>>;
+    async fn delete(&self, id: &str) -> Result<()>;
+
+    /// Take the "before" snapshot of a transaction and return it, so the
+    /// caller can record its id. The default takes a plain "pre-..."
+    /// snapshot; pairing backends return one with `kind` set to "pre".
+    async fn pre_transaction(&self, description: &str) -> Result<Snapshot> {
+        self.create(&format!("pre-{description}")).await
+    }
+
+    /// Close the pair opened by `pre_transaction`. Only meaningful for
+    /// pairing backends; the default is a no-op.
+    async fn post_transaction(&self, _pre: &Snapshot, _description: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Storage the snapshots occupy and the room left for more. The
+    /// default reports nothing, which renders as no usage section at
+    /// all rather than a row of zeros.
+    async fn usage(&self) -> Result<SnapshotUsage> {
+        Ok(SnapshotUsage::default())
+    }
+
+    /// Attach a free-text note to snapshot `id`, replacing any earlier
+    /// one; an empty note removes it. The default keeps a sidecar file
+    /// in the state directory for backends without native descriptions;
+    /// snapper stores notes in its own metadata instead.
+    async fn set_note(&self, id: &str, note: &str) -> Result<()> {
+        set_sidecar_note(id, note)
+    }
+
+    /// Whether the automatic pre-transaction snapshot policy applies.
+    /// rpm-ostree deployments are created by the transaction itself,
+    /// so snapshotting ahead of one would be redundant there.
+    fn snapshots_before_transactions(&self) -> bool {
+        true
+    }
+
+    /// Pin or unpin snapshot `id` so cleanup keeps it. Only rpm-ostree
+    /// has a notion of pinning; everywhere else this is unsupported.
+    async fn toggle_pin(&self, _id: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: "snapshots".to_string(),
+            operation: "only rpm-ostree deployments can be pinned".to_string(),
+        })
+    }
+
+    /// One sentence on what restoring means for this backend — every
+    /// tool restores differently and most need a reboot, so the
+    /// confirmation dialog quotes this rather than a generic warning.
+    fn restore_explanation(&self) -> &'static str;
+
+    /// Restore the system to snapshot `id`. `RebootRequired` means the
+    /// running system is untouched until the next boot.
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome>;
+}
+
+/// Manages system snapshots taken before risky package operations.
+///
+/// On image-based ostree systems the deployments themselves stand in
+/// for snapshots. Otherwise, when snapper or Timeshift is installed and
+/// configured it is used, so systems that already snapshot through one
+/// of them do not grow a second, parallel snapshot tree; failing all
+/// that, pkgtool drives btrfs — or LVM, for roots on a logical volume —
+/// directly.
+pub struct SnapshotManager {
+    backend: Box<dyn SnapshotBackend>,
+}
+
+impl SnapshotManager {
+    pub fn new(config: SnapshotConfig, runner: PrivilegeRunner) -> Self {
+        let backend: Box<dyn SnapshotBackend> = if OstreeBackend::present() {
+            Box::new(OstreeBackend { runner })
+        } else if SnapperBackend::present() {
+            Box::new(SnapperBackend { runner })
+        } else if TimeshiftBackend::present() {
+            Box::new(TimeshiftBackend { runner })
+        } else if ZfsBackend::present() {
+            Box::new(ZfsBackend { runner })
+        } else if LvmBackend::present() {
+            Box::new(LvmBackend { config, runner })
+        } else {
+            Box::new(BtrfsBackend { config, runner })
+        };
+        SnapshotManager { backend }
+    }
+
+    /// Take the pre snapshot of a transaction; the returned snapshot
+    /// must come back through [`post_transaction`] once the transaction
+    /// finishes, successful or not, so pairing backends can close it.
+    ///
+    /// [`post_transaction`]: SnapshotManager::post_transaction
+    pub async fn pre_transaction(&self, description: &str) -> Result<Snapshot> {
+        self.backend.pre_transaction(description).await
+    }
+
+    pub async fn post_transaction(&self, pre: &Snapshot, description: &str) -> Result<()> {
+        self.backend.post_transaction(pre, description).await
+    }
+
+    /// The active backend's identifier, for display.
+    pub fn backend_id(&self) -> &str {
+        self.backend.id()
+    }
+
+    pub async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        self.backend.create(trigger).await
+    }
+
+    pub async fn list(&self) -> Result<Vec<Snapshot>> {
+        self.backend.list().await
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        self.backend.delete(id).await?;
+        // Keep the sidecar notes in sync; a missing entry is a no-op.
+        let _ = set_sidecar_note(id, "");
+        Ok(())
+    }
+
+    pub async fn set_note(&self, id: &str, note: &str) -> Result<()> {
+        self.backend.set_note(id, note).await
+    }
+
+    pub async fn snapshot_usage(&self) -> Result<SnapshotUsage> {
+        self.backend.usage().await
+    }
+
+    pub fn snapshots_before_transactions(&self) -> bool {
+        self.backend.snapshots_before_transactions()
+    }
+
+    pub async fn toggle_pin(&self, id: &str) -> Result<()> {
+        self.backend.toggle_pin(id).await
+    }
+
+    pub fn restore_explanation(&self) -> &'static str {
+        self.backend.restore_explanation()
+    }
+
+    pub async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        self.backend.restore(id).await
+    }
+}
+
+/// Prefix identifying snapshots pkgtool created; foreign subvolumes in the
+/// snapshots directory are never listed or deleted.
+const SNAPSHOT_PREFIX: &str = "pkgtool-";
+
+/// Timestamp embedded in btrfs snapshot names, chosen to sort chronologically.
+const NAME_TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+/// Make a trigger safe for embedding in an LV or ZFS snapshot name,
+/// neither of which may carry spaces or most punctuation.
+fn sanitize_trigger(trigger: &str) -> String {
+    trigger
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "+_.-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Drives btrfs directly: snapshots are read-only subvolumes named
+/// `pkgtool-<timestamp>-<trigger>` under the configured directory, so the
+/// name alone carries everything `list` reports and nothing needs a
+/// sidecar database. Creation and deletion run `btrfs subvolume` through
+/// privilege escalation; listing reads the directory unprivileged.
+struct BtrfsBackend {
+    config: SnapshotConfig,
+    runner: PrivilegeRunner,
+}
+
+impl BtrfsBackend {
+    /// Fail with a typed error unless the configured subvolume is btrfs.
+    /// `Unsupported` here renders as a clear "not on btrfs" explanation
+    /// instead of a raw findmnt failure.
+    async fn ensure_btrfs(&self) -> Result<()> {
+        let found = run(&[
+            "findmnt",
+            "-t",
+            "btrfs",
+            "-n",
+            "-o",
+            "TARGET",
+            "--target",
+            &self.config.subvolume,
+        ])
+        .await;
+        match found {
+            Ok(output) if !output.trim().is_empty() => Ok(()),
+            _ => Err(PkgError::Unsupported {
+                manager: "snapshots".to_string(),
+                operation: format!("{} is not on a btrfs filesystem", self.config.subvolume),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for BtrfsBackend {
+    fn id(&self) -> &str {
+        "btrfs"
+    }
+
+    async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        self.ensure_btrfs().await?;
+        let created = Utc::now();
+        let id = format!(
+            "{SNAPSHOT_PREFIX}{}-{trigger}",
+            created.format(NAME_TIMESTAMP)
+        );
+        let target = format!("{}/{id}", self.config.directory);
+        run_privileged(&self.runner, &["mkdir", "-p", &self.config.directory]).await?;
+        run_privileged(
+            &self.runner,
+            &[
+                "btrfs",
+                "subvolume",
+                "snapshot",
+                "-r",
+                &self.config.subvolume,
+                &target,
+            ],
+        )
+        .await?;
+        Ok(Snapshot {
+            id,
+            created,
+            trigger: trigger.to_string(),
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        })
+    }
+
+    /// Snapshots pkgtool has created, oldest first. A missing snapshots
+    /// directory just means none were taken yet.
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        self.ensure_btrfs().await?;
+        let listing = match run(&["ls", "-1", &self.config.directory]).await {
+            Ok(listing) => listing,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let mut snapshots: Vec<Snapshot> = listing
+            .lines()
+            .filter_map(parse_snapshot_name)
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.created);
+        Ok(snapshots)
+    }
+
+    /// Delete one snapshot by id. Only names carrying our prefix are
+    /// accepted, so a corrupted id can never point the delete at a
+    /// foreign subvolume.
+    async fn delete(&self, id: &str) -> Result<()> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let target = format!("{}/{id}", self.config.directory);
+        run_privileged(&self.runner, &["btrfs", "subvolume", "delete", &target]).await?;
+        Ok(())
+    }
+
+    /// Exclusive usage per snapshot via `btrfs filesystem du`, free
+    /// space via plain `df` on the snapshots directory. The du pass is
+    /// best effort — it needs root and quota-less filesystems report
+    /// slowly — so a failure only drops the per-snapshot numbers.
+    async fn usage(&self) -> Result<SnapshotUsage> {
+        let snapshots = self.list().await?;
+        let mut usage = SnapshotUsage::default();
+        if !snapshots.is_empty() {
+            let paths: Vec<String> = snapshots
+                .iter()
+                .map(|snapshot| format!("{}/{}", self.config.directory, snapshot.id))
+                .collect();
+            let mut args = vec!["btrfs", "filesystem", "du", "-s", "--raw"];
+            args.extend(paths.iter().map(String::as_str));
+            if let Ok(output) = run_privileged(&self.runner, &args).await {
+                usage.per_snapshot = parse_btrfs_du(&output);
+                usage.used_bytes = Some(usage.per_snapshot.iter().map(|(_, bytes)| bytes).sum());
+            }
+        }
+        if let Ok(output) = run(&["df", "-B1", "--output=avail", &self.config.directory]).await {
+            usage.free_bytes = parse_df_avail(&output);
+        }
+        Ok(usage)
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "a writable copy of the snapshot becomes the default subvolume; \
+         the running system is untouched until you reboot"
+    }
+
+    /// Our snapshots are read-only, so restore clones one into a
+    /// writable subvolume and makes that the default for the next boot.
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let source = format!("{}/{id}", self.config.directory);
+        let target = format!("{}/{id}-rw", self.config.directory);
+        run_privileged(
+            &self.runner,
+            &["btrfs", "subvolume", "snapshot", &source, &target],
+        )
+        .await?;
+        run_privileged(&self.runner, &["btrfs", "subvolume", "set-default", &target]).await?;
+        Ok(RestoreOutcome::RebootRequired)
+    }
+}
+
+/// Parse `btrfs filesystem du -s --raw`: a header naming the columns,
+/// then "total exclusive set_shared filename" per path. Exclusive is
+/// the number that matters — the space freed by deleting just that
+/// snapshot. The header fails the numeric parse and drops out.
+fn parse_btrfs_du(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _total: u64 = fields.next()?.parse().ok()?;
+            let exclusive: u64 = fields.next()?.parse().ok()?;
+            let _shared = fields.next()?;
+            let id = fields.next()?.rsplit('/').next()?;
+            Some((id.to_string(), exclusive))
+        })
+        .collect()
+}
+
+/// Parse `df -B1 --output=avail`: a header line, then the byte count.
+fn parse_df_avail(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| line.trim().parse().ok())
+}
+
+/// Drives an existing Timeshift installation, so its snapshots and
+/// pkgtool's appear in one place. Every timeshift invocation needs root,
+/// including `--list`.
+struct TimeshiftBackend {
+    runner: PrivilegeRunner,
+}
+
+impl TimeshiftBackend {
+    /// Whether Timeshift is installed and has been set up. The config file
+    /// only exists after its first-run wizard, so a merely-installed
+    /// Timeshift does not capture snapshot duty from the btrfs backend.
+    fn present() -> bool {
+        crate::package_managers::binary_exists("timeshift")
+            && crate::utils::host::read_file("/etc/timeshift/timeshift.json").is_ok()
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for TimeshiftBackend {
+    fn id(&self) -> &str {
+        "timeshift"
+    }
+
+    async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        run_privileged(
+            &self.runner,
+            &[
+                "timeshift",
+                "--create",
+                "--comments",
+                &format!("pkgtool {trigger}"),
+                "--tags",
+                "O",
+            ],
+        )
+        .await?;
+        // Timeshift names the snapshot itself; the newest entry is ours.
+        self.list().await?.pop().ok_or_else(|| PkgError::Parse {
+            source_desc: "timeshift --list".to_string(),
+            detail: "created snapshot missing from listing".to_string(),
+        })
+    }
+
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        let output = run_privileged(&self.runner, &["timeshift", "--list"]).await?;
+        Ok(parse_timeshift_list(&output))
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        run_privileged(
+            &self.runner,
+            &["timeshift", "--delete", "--snapshot", id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "timeshift rewrites the target system from the snapshot in \
+         place; reboot once it reports success"
+    }
+
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        run_privileged(
+            &self.runner,
+            &["timeshift", "--restore", "--snapshot", id, "--yes"],
+        )
+        .await?;
+        Ok(RestoreOutcome::RebootRequired)
+    }
+}
+
+/// Drives an existing snapper installation (openSUSE and friends).
+/// Besides plain snapshots, snapper pairs a pre and a post snapshot
+/// around each transaction, which is what its comparison tools expect —
+/// so `pre_transaction` takes a numbered pre snapshot and
+/// `post_transaction` closes the pair, successful transaction or not.
+struct SnapperBackend {
+    runner: PrivilegeRunner,
+}
+
+impl SnapperBackend {
+    /// Whether snapper is installed and has a root config. Like Timeshift,
+    /// a merely-installed snapper without `snapper create-config` having
+    /// run does not capture snapshot duty from the btrfs backend.
+    fn present() -> bool {
+        crate::package_managers::binary_exists("snapper")
+            && crate::utils::host::read_file("/etc/snapper/configs/root").is_ok()
+    }
+
+    async fn create_numbered(&self, args: &[&str]) -> Result<u32> {
+        let output = run_privileged(&self.runner, args).await?;
+        output.trim().parse().map_err(|_| PkgError::Parse {
+            source_desc: "snapper create --print-number".to_string(),
+            detail: format!("expected a snapshot number, got {:?}", output.trim()),
+        })
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for SnapperBackend {
+    fn id(&self) -> &str {
+        "snapper"
+    }
+
+    async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        let number = self
+            .create_numbered(&[
+                "snapper",
+                "create",
+                "--print-number",
+                "--description",
+                &format!("pkgtool: {trigger}"),
+            ])
+            .await?;
+        Ok(Snapshot {
+            id: number.to_string(),
+            created: Utc::now(),
+            trigger: trigger.to_string(),
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        })
+    }
+
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        let output =
+            run_privileged(&self.runner, &["snapper", "--machine-readable", "csv", "list"])
+                .await?;
+        Ok(parse_snapper_csv(&output))
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        run_privileged(&self.runner, &["snapper", "delete", id]).await?;
+        Ok(())
+    }
+
+    /// snapper carries notes natively, as userdata next to the
+    /// description pkgtool already writes; `note=` with nothing after
+    /// it clears the entry.
+    async fn set_note(&self, id: &str, note: &str) -> Result<()> {
+        run_privileged(
+            &self.runner,
+            &["snapper", "modify", "--userdata", &format!("note={note}"), id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "snapper rollback makes a new writable copy of the snapshot the \
+         default subvolume; reboot to start using it"
+    }
+
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        run_privileged(&self.runner, &["snapper", "rollback", id]).await?;
+        Ok(RestoreOutcome::RebootRequired)
+    }
+
+    async fn pre_transaction(&self, description: &str) -> Result<Snapshot> {
+        let number = self
+            .create_numbered(&[
+                "snapper",
+                "create",
+                "--type",
+                "pre",
+                "--print-number",
+                "--description",
+                &format!("pkgtool: {description}"),
+            ])
+            .await?;
+        Ok(Snapshot {
+            id: number.to_string(),
+            created: Utc::now(),
+            trigger: description.to_string(),
+            kind: "pre".to_string(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        })
+    }
+
+    async fn post_transaction(&self, pre: &Snapshot, description: &str) -> Result<()> {
+        run_privileged(
+            &self.runner,
+            &[
+                "snapper",
+                "create",
+                "--type",
+                "post",
+                "--pre-number",
+                &pre.id,
+                "--description",
+                &format!("pkgtool: {description}"),
+            ],
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// Parse `snapper --machine-readable csv list`: a header row naming the
+/// columns, then one row per snapshot. Columns are located by header name
+/// so the parser survives snapper versions adding or reordering them.
+/// Fields are split on plain commas; the descriptions pkgtool writes
+/// never contain one. Snapshot 0 ("current", no date) is skipped.
+fn parse_snapper_csv(output: &str) -> Vec<Snapshot> {
+    let mut lines = output.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let column = |name: &str| header.split(',').position(|column| column == name);
+    let (Some(number), Some(date)) = (column("number"), column("date")) else {
+        return Vec::new();
+    };
+    let kind = column("type");
+    let pre_number = column("pre-number");
+    let description = column("description");
+    let userdata = column("userdata");
+
+    let mut snapshots = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').collect();
+        let at = |index: Option<usize>| index.and_then(|i| fields.get(i)).copied().unwrap_or("");
+        if at(Some(number)).is_empty() || at(Some(number)) == "0" {
+            continue;
+        }
+        let Ok(created) = NaiveDateTime::parse_from_str(at(Some(date)), "%Y-%m-%d %H:%M:%S")
+        else {
+            continue;
+        };
+        let full_description = at(description);
+        let trigger = full_description
+            .strip_prefix("pkgtool: ")
+            .unwrap_or(full_description)
+            .to_string();
+        snapshots.push(Snapshot {
+            id: at(Some(number)).to_string(),
+            created: created.and_utc(),
+            trigger,
+            kind: match at(kind) {
+                "single" => String::new(),
+                other => other.to_string(),
+            },
+            pre: Some(at(pre_number))
+                .filter(|pre| !pre.is_empty())
+                .map(str::to_string),
+            usage_percent: None,
+            size_bytes: None,
+            // pkgtool writes a single `note=` entry; notes containing
+            // a comma would not round-trip through the csv anyway.
+            note: at(userdata)
+                .strip_prefix("note=")
+                .filter(|note| !note.is_empty())
+                .map(str::to_string),
+        });
+    }
+    snapshots
+}
+
+/// Drives LVM for systems whose root sits on a logical volume without
+/// btrfs: snapshots are copy-on-write LVs next to the root LV, named
+/// the same way as the btrfs backend's subvolumes. Unlike btrfs
+/// snapshots they have a fixed size and LVM silently invalidates them
+/// when the copy-on-write space fills, so `list` carries the usage
+/// percentage for the UI to surface. Every LVM tool needs root.
+struct LvmBackend {
+    config: SnapshotConfig,
+    runner: PrivilegeRunner,
+}
+
+impl LvmBackend {
+    /// Whether the root filesystem sits on a device-mapper volume with
+    /// the LVM tools installed. A btrfs root stays with the btrfs
+    /// backend even when LVM is underneath it.
+    fn present() -> bool {
+        if !crate::package_managers::binary_exists("lvs") {
+            return false;
+        }
+        let Ok(mounts) = crate::utils::host::read_file("/proc/mounts") else {
+            return false;
+        };
+        mounts.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            let device = fields.next().unwrap_or("");
+            let target = fields.next();
+            let fstype = fields.next().unwrap_or("");
+            target == Some("/") && fstype != "btrfs" && device.starts_with("/dev/mapper/")
+        })
+    }
+
+    /// The volume group and logical volume holding the root filesystem.
+    async fn origin(&self) -> Result<(String, String)> {
+        let device = run(&["findmnt", "-n", "-o", "SOURCE", "/"]).await?;
+        let output = run_privileged(
+            &self.runner,
+            &[
+                "lvs",
+                "--reportformat",
+                "json",
+                "-o",
+                "vg_name,lv_name",
+                device.trim(),
+            ],
+        )
+        .await?;
+        let row = parse_lvs_json(&output)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| PkgError::Parse {
+                source_desc: "lvs --reportformat json".to_string(),
+                detail: format!("{} is not a logical volume", device.trim()),
+            })?;
+        Ok((row.vg_name, row.lv_name))
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for LvmBackend {
+    fn id(&self) -> &str {
+        "lvm"
+    }
+
+    async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        let (vg, lv) = self.origin().await?;
+        let created = Utc::now();
+        let trigger = sanitize_trigger(trigger);
+        let id = format!(
+            "{SNAPSHOT_PREFIX}{}-{trigger}",
+            created.format(NAME_TIMESTAMP)
+        );
+        let size = &self.config.lvm_size;
+        // Percentages are extent counts to lvcreate, absolute sizes bytes.
+        let size_flag = if size.contains('%') { "-l" } else { "-L" };
+        let result = run_privileged(
+            &self.runner,
+            &[
+                "lvcreate",
+                "-s",
+                size_flag,
+                size,
+                "-n",
+                &id,
+                &format!("{vg}/{lv}"),
+            ],
+        )
+        .await;
+        if let Err(PkgError::CommandFailed { stderr, .. }) = &result {
+            let stderr = stderr.to_lowercase();
+            // lvcreate's wording varies by version; both phrasings mean
+            // the volume group is out of room.
+            if stderr.contains("insufficient free space") || stderr.contains("free extents") {
+                return Err(PkgError::Unsupported {
+                    manager: "snapshots".to_string(),
+                    operation: format!(
+                        "volume group {vg} lacks free extents for a {size} snapshot — \
+                         free space in the VG or lower snapshots.lvm_size"
+                    ),
+                });
+            }
+        }
+        result?;
+        Ok(Snapshot {
+            id,
+            created,
+            trigger,
+            kind: String::new(),
+            pre: None,
+            usage_percent: Some(0.0),
+            size_bytes: None,
+            note: None,
+        })
+    }
+
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        let (vg, _) = self.origin().await?;
+        let output = run_privileged(
+            &self.runner,
+            &[
+                "lvs",
+                "--reportformat",
+                "json",
+                "-o",
+                "lv_name,origin,data_percent",
+                &vg,
+            ],
+        )
+        .await?;
+        let mut snapshots: Vec<Snapshot> = parse_lvs_json(&output)?
+            .into_iter()
+            .filter(|row| !row.origin.is_empty())
+            .filter_map(|row| {
+                let mut snapshot = parse_snapshot_name(&row.lv_name)?;
+                snapshot.usage_percent = row.data_percent.trim().parse().ok();
+                Some(snapshot)
+            })
+            .collect();
+        snapshots.sort_by_key(|snapshot| snapshot.created);
+        Ok(snapshots)
+    }
+
+    /// Delete one snapshot LV by name, with the same prefix guard as the
+    /// btrfs backend so a corrupted id can never remove a foreign LV.
+    async fn delete(&self, id: &str) -> Result<()> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let (vg, _) = self.origin().await?;
+        run_privileged(&self.runner, &["lvremove", "-y", &format!("{vg}/{id}")]).await?;
+        Ok(())
+    }
+
+    /// Copy-on-write usage per snapshot from the lvs size and data%
+    /// columns, free space from what is left unallocated in the volume
+    /// group — the room `lvcreate` has for further snapshots.
+    async fn usage(&self) -> Result<SnapshotUsage> {
+        let (vg, _) = self.origin().await?;
+        let output = run_privileged(
+            &self.runner,
+            &[
+                "lvs",
+                "--reportformat",
+                "json",
+                "--units",
+                "b",
+                "--nosuffix",
+                "-o",
+                "lv_name,origin,data_percent,lv_size",
+                &vg,
+            ],
+        )
+        .await?;
+        let mut usage = SnapshotUsage::default();
+        for row in parse_lvs_json(&output)? {
+            if row.origin.is_empty() || parse_snapshot_name(&row.lv_name).is_none() {
+                continue;
+            }
+            if let Some(used) = cow_used_bytes(&row.lv_size, &row.data_percent) {
+                usage.per_snapshot.push((row.lv_name, used));
+            }
+        }
+        usage.used_bytes = Some(usage.per_snapshot.iter().map(|(_, bytes)| bytes).sum());
+        let free = run_privileged(
+            &self.runner,
+            &[
+                "vgs",
+                "--units",
+                "b",
+                "--nosuffix",
+                "--noheadings",
+                "-o",
+                "vg_free",
+                &vg,
+            ],
+        )
+        .await;
+        if let Ok(output) = free {
+            usage.free_bytes = output.trim().parse::<f64>().ok().map(|bytes| bytes as u64);
+        }
+        Ok(usage)
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "the snapshot is merged back into the origin volume; the merge \
+         of the root volume only runs on the next activation, so reboot \
+         to complete it"
+    }
+
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let (vg, _) = self.origin().await?;
+        run_privileged(&self.runner, &["lvconvert", "--merge", &format!("{vg}/{id}")]).await?;
+        Ok(RestoreOutcome::RebootRequired)
+    }
+}
+
+/// Drives ZFS for root-on-ZFS systems: snapshots live on the root
+/// dataset under the same `pkgtool-<timestamp>-<trigger>` names as the
+/// other backends. ZFS is the one backend that can also restore in
+/// place, via `zfs rollback` — which destroys every snapshot between
+/// the target and now, so the UI confirms it very explicitly.
+struct ZfsBackend {
+    runner: PrivilegeRunner,
+}
+
+impl ZfsBackend {
+    /// Whether the root filesystem is a ZFS dataset with the tools
+    /// installed.
+    fn present() -> bool {
+        if !crate::package_managers::binary_exists("zfs") {
+            return false;
+        }
+        let Ok(mounts) = crate::utils::host::read_file("/proc/mounts") else {
+            return false;
+        };
+        mounts.lines().any(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next();
+            let target = fields.next();
+            let fstype = fields.next().unwrap_or("");
+            target == Some("/") && fstype == "zfs"
+        })
+    }
+
+    /// The dataset mounted at `/`, e.g. "rpool/ROOT/default".
+    async fn root_dataset(&self) -> Result<String> {
+        let source = run(&["findmnt", "-t", "zfs", "-n", "-o", "SOURCE", "--target", "/"]).await?;
+        let dataset = source.trim();
+        if dataset.is_empty() {
+            return Err(PkgError::Unsupported {
+                manager: "snapshots".to_string(),
+                operation: "/ is not on a ZFS dataset".to_string(),
+            });
+        }
+        Ok(dataset.to_string())
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for ZfsBackend {
+    fn id(&self) -> &str {
+        "zfs"
+    }
+
+    async fn create(&self, trigger: &str) -> Result<Snapshot> {
+        let dataset = self.root_dataset().await?;
+        let created = Utc::now();
+        let trigger = sanitize_trigger(trigger);
+        let id = format!(
+            "{SNAPSHOT_PREFIX}{}-{trigger}",
+            created.format(NAME_TIMESTAMP)
+        );
+        run_privileged(
+            &self.runner,
+            &["zfs", "snapshot", &format!("{dataset}@{id}")],
+        )
+        .await?;
+        Ok(Snapshot {
+            id,
+            created,
+            trigger,
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        })
+    }
+
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        let dataset = self.root_dataset().await?;
+        let output = run(&[
+            "zfs",
+            "list",
+            "-t",
+            "snapshot",
+            "-H",
+            "-p",
+            "-o",
+            "name,creation,used",
+            &dataset,
+        ])
+        .await?;
+        Ok(parse_zfs_list(&output))
+    }
+
+    /// Destroy one snapshot by name, behind the usual prefix guard.
+    async fn delete(&self, id: &str) -> Result<()> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let dataset = self.root_dataset().await?;
+        run_privileged(
+            &self.runner,
+            &["zfs", "destroy", &format!("{dataset}@{id}")],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// The `used` column `list` already carries is a snapshot's
+    /// exclusive usage in ZFS terms, so this only sums it and asks the
+    /// dataset how much room is left.
+    async fn usage(&self) -> Result<SnapshotUsage> {
+        let snapshots = self.list().await?;
+        let per_snapshot: Vec<(String, u64)> = snapshots
+            .iter()
+            .filter_map(|snapshot| Some((snapshot.id.clone(), snapshot.size_bytes?)))
+            .collect();
+        let mut usage = SnapshotUsage {
+            used_bytes: Some(per_snapshot.iter().map(|(_, bytes)| bytes).sum()),
+            free_bytes: None,
+            per_snapshot,
+        };
+        let dataset = self.root_dataset().await?;
+        if let Ok(output) = run(&["zfs", "list", "-H", "-p", "-o", "avail", &dataset]).await {
+            usage.free_bytes = output.trim().parse().ok();
+        }
+        Ok(usage)
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "zfs rollback restores the dataset immediately and destroys \
+         every snapshot newer than the target"
+    }
+
+    /// `zfs rollback -r` — the `-r` destroys every snapshot newer than
+    /// the target, which is why the caller must confirm explicitly.
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        if parse_snapshot_name(id).is_none() {
+            return Err(PkgError::NotFound(id.to_string()));
+        }
+        let dataset = self.root_dataset().await?;
+        run_privileged(
+            &self.runner,
+            &["zfs", "rollback", "-r", &format!("{dataset}@{id}")],
+        )
+        .await?;
+        Ok(RestoreOutcome::Complete)
+    }
+}
+
+/// Parse `zfs list -t snapshot -H -p -o name,creation,used`: one
+/// tab-separated row per snapshot, the name as `dataset@snapshot` and
+/// the creation time in epoch seconds (`-p`). Snapshots without our
+/// name prefix are someone else's and are skipped.
+fn parse_zfs_list(output: &str) -> Vec<Snapshot> {
+    let mut snapshots: Vec<Snapshot> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let name = fields.next()?;
+            let creation = fields.next()?;
+            let used = fields.next()?;
+            let (_, short_name) = name.split_once('@')?;
+            let mut snapshot = parse_snapshot_name(short_name)?;
+            // The epoch column is authoritative; the name only sorts.
+            snapshot.created = DateTime::from_timestamp(creation.trim().parse().ok()?, 0)?;
+            snapshot.size_bytes = used.trim().parse().ok();
+            Some(snapshot)
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| snapshot.created);
+    snapshots
+}
+
+/// Drives rpm-ostree on image-based systems (Silverblue, Kinoite and
+/// friends), where the deployments themselves are the snapshots: each
+/// package transaction creates one, so there is nothing to snapshot by
+/// hand and nothing to take before a transaction. Restore is
+/// `rpm-ostree rollback`; pinning protects a deployment from cleanup.
+struct OstreeBackend {
+    runner: PrivilegeRunner,
+}
+
+impl OstreeBackend {
+    /// Whether this is an ostree-managed host. The marker file exists
+    /// on every booted ostree system, so a merely-installed rpm-ostree
+    /// does not capture snapshot duty.
+    fn present() -> bool {
+        crate::package_managers::binary_exists("rpm-ostree")
+            && crate::utils::host::read_file("/run/ostree-booted").is_ok()
+    }
+
+    async fn deployments(&self) -> Result<Vec<OstreeDeployment>> {
+        let output = run(&["rpm-ostree", "status", "--json"]).await?;
+        parse_rpm_ostree_status(&output)
+    }
+}
+
+#[async_trait]
+impl SnapshotBackend for OstreeBackend {
+    fn id(&self) -> &str {
+        "rpm-ostree"
+    }
+
+    async fn create(&self, _trigger: &str) -> Result<Snapshot> {
+        Err(PkgError::Unsupported {
+            manager: "snapshots".to_string(),
+            operation: "rpm-ostree creates a deployment with every transaction; \
+                        there is nothing to snapshot by hand"
+                .to_string(),
+        })
+    }
+
+    /// Every deployment in boot order — the pending one first when a
+    /// transaction is staged, then the booted one.
+    async fn list(&self) -> Result<Vec<Snapshot>> {
+        Ok(self
+            .deployments()
+            .await?
+            .iter()
+            .map(OstreeDeployment::as_snapshot)
+            .collect())
+    }
+
+    async fn delete(&self, _id: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: "snapshots".to_string(),
+            operation: "rpm-ostree garbage-collects deployments on its own; \
+                        pin the ones that must survive"
+                .to_string(),
+        })
+    }
+
+    fn snapshots_before_transactions(&self) -> bool {
+        false
+    }
+
+    /// `ostree admin pin` addresses deployments by boot-order index,
+    /// so the checksum is resolved against a fresh listing first.
+    async fn toggle_pin(&self, id: &str) -> Result<()> {
+        let deployments = self.deployments().await?;
+        let (index, deployment) = deployments
+            .iter()
+            .enumerate()
+            .find(|(_, deployment)| deployment.checksum == id)
+            .ok_or_else(|| PkgError::NotFound(id.to_string()))?;
+        let index = index.to_string();
+        let mut args = vec!["ostree", "admin", "pin"];
+        if deployment.pinned {
+            args.push("--unpin");
+        }
+        args.push(&index);
+        run_privileged(&self.runner, &args).await?;
+        Ok(())
+    }
+
+    fn restore_explanation(&self) -> &'static str {
+        "rpm-ostree rollback moves the previous deployment first in \
+         the boot order; reboot to start it"
+    }
+
+    async fn restore(&self, id: &str) -> Result<RestoreOutcome> {
+        let deployments = self.deployments().await?;
+        let target = deployments
+            .iter()
+            .find(|deployment| deployment.checksum == id)
+            .ok_or_else(|| PkgError::NotFound(id.to_string()))?;
+        if target.booted {
+            return Err(PkgError::Unsupported {
+                manager: "snapshots".to_string(),
+                operation: "that deployment is booted right now".to_string(),
+            });
+        }
+        run_privileged(&self.runner, &["rpm-ostree", "rollback"]).await?;
+        Ok(RestoreOutcome::RebootRequired)
+    }
+}
+
+/// One entry of the deployments array in `rpm-ostree status --json`;
+/// only the fields pkgtool reads.
+#[derive(Debug, Default, Deserialize)]
+struct OstreeDeployment {
+    #[serde(default)]
+    checksum: String,
+    #[serde(default)]
+    version: String,
+    /// Commit time in epoch seconds.
+    #[serde(default)]
+    timestamp: i64,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    booted: bool,
+    /// Staged by a finished transaction, waiting for the next boot.
+    #[serde(default)]
+    staged: bool,
+}
+
+impl OstreeDeployment {
+    /// Present the deployment in snapshot terms; its state markers ride
+    /// along in the trigger text since nothing else maps onto them.
+    fn as_snapshot(&self) -> Snapshot {
+        let mut notes = Vec::new();
+        if self.staged {
+            notes.push("pending");
+        }
+        if self.booted {
+            notes.push("booted");
+        }
+        if self.pinned {
+            notes.push("pinned");
+        }
+        let suffix = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        Snapshot {
+            id: self.checksum.clone(),
+            created: DateTime::from_timestamp(self.timestamp, 0).unwrap_or_default(),
+            trigger: format!("deployment {}{suffix}", self.version),
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        }
+    }
+}
+
+/// Parse `rpm-ostree status --json`, keeping the deployments in the
+/// boot order the tool reports — the index doubles as the argument to
+/// `ostree admin pin`.
+fn parse_rpm_ostree_status(output: &str) -> Result<Vec<OstreeDeployment>> {
+    #[derive(Deserialize)]
+    struct Status {
+        #[serde(default)]
+        deployments: Vec<OstreeDeployment>,
+    }
+    let status: Status = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "rpm-ostree status --json".to_string(),
+        detail: err.to_string(),
+    })?;
+    Ok(status.deployments)
+}
+
+/// One row of `lvs --reportformat json`; only requested columns are filled.
+#[derive(Debug, Default, Deserialize)]
+struct LvsRow {
+    #[serde(default)]
+    lv_name: String,
+    #[serde(default)]
+    vg_name: String,
+    /// The LV this one is a snapshot of; empty for ordinary volumes.
+    #[serde(default)]
+    origin: String,
+    /// Copy-on-write usage like "47.30"; empty for ordinary volumes.
+    #[serde(default)]
+    data_percent: String,
+    /// Volume size in bytes when requested with `--units b --nosuffix`.
+    #[serde(default)]
+    lv_size: String,
+}
+
+/// Bytes a copy-on-write snapshot has filled: its size scaled by the
+/// data% column. `None` when lvs left either column empty.
+fn cow_used_bytes(lv_size: &str, data_percent: &str) -> Option<u64> {
+    let size: f64 = lv_size.trim().parse().ok()?;
+    let percent: f64 = data_percent.trim().parse().ok()?;
+    Some((size * percent / 100.0) as u64)
+}
+
+/// Flatten the `{"report": [{"lv": [...]}]}` envelope of lvs json output.
+fn parse_lvs_json(output: &str) -> Result<Vec<LvsRow>> {
+    #[derive(Deserialize)]
+    struct Report {
+        #[serde(default)]
+        report: Vec<Entry>,
+    }
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(default)]
+        lv: Vec<LvsRow>,
+    }
+    let report: Report = serde_json::from_str(output).map_err(|err| PkgError::Parse {
+        source_desc: "lvs --reportformat json".to_string(),
+        detail: err.to_string(),
+    })?;
+    Ok(report.report.into_iter().flat_map(|entry| entry.lv).collect())
+}
+
+/// Recover a btrfs snapshot's metadata from its name,
+/// e.g. "pkgtool-20240106-152755-pre-update". Foreign names yield `None`.
+fn parse_snapshot_name(name: &str) -> Option<Snapshot> {
+    let rest = name.strip_prefix(SNAPSHOT_PREFIX)?;
+    // The timestamp is two fixed-width dash-joined fields; the trigger
+    // (which may itself contain dashes) is everything after.
+    let (stamp, trigger) = rest.split_at_checked(15)?;
+    let trigger = trigger.strip_prefix('-').filter(|t| !t.is_empty())?;
+    let created = NaiveDateTime::parse_from_str(stamp, NAME_TIMESTAMP).ok()?;
+    Some(Snapshot {
+        id: name.to_string(),
+        created: created.and_utc(),
+        trigger: trigger.to_string(),
+        kind: String::new(),
+        pre: None,
+        usage_percent: None,
+        size_bytes: None,
+        note: None,
+    })
+}
+
+/// Parse the snapshot table of `timeshift --list`: header lines, a dashed
+/// separator, then "num  >  name  tags  description" rows. The name
+/// doubles as the creation time; a "pkgtool ..." comment becomes the
+/// trigger, anything else is reported verbatim.
+fn parse_timeshift_list(output: &str) -> Vec<Snapshot> {
+    let mut snapshots = Vec::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace().peekable();
+        // Rows start with the snapshot number.
+        if parts.next().is_none_or(|n| n.parse::<u32>().is_err()) {
+            continue;
+        }
+        if parts.peek() == Some(&">") {
+            parts.next();
+        }
+        let Some(name) = parts.next() else { continue };
+        let Ok(created) = NaiveDateTime::parse_from_str(name, "%Y-%m-%d_%H-%M-%S") else {
+            continue;
+        };
+        let _tags = parts.next();
+        let description = parts.collect::<Vec<_>>().join(" ");
+        let trigger = description
+            .strip_prefix("pkgtool ")
+            .unwrap_or(&description)
+            .to_string();
+        snapshots.push(Snapshot {
+            id: name.to_string(),
+            created: created.and_utc(),
+            trigger,
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        });
+    }
+    snapshots
+}
+
+/// A snapshot the retention policy would delete, with the rule that
+/// condemned it — shown verbatim in the prune confirmation.
+#[derive(Debug, Clone)]
+pub struct PruneCandidate {
+    pub snapshot: Snapshot,
+    pub reason: String,
+}
+
+/// Whether a snapshot was taken automatically around a transaction.
+/// Manual snapshots — anything a user named — are never pruned.
+fn is_automatic(snapshot: &Snapshot) -> bool {
+    snapshot.kind == "pre"
+        || snapshot.kind == "post"
+        || snapshot.trigger.starts_with("pre-")
+        || snapshot.trigger.starts_with("post-")
+}
+
+/// Apply the retention policy to a backend listing and return what a
+/// prune pass would delete. `protected` is the snapshot referenced by
+/// the most recent transaction in history, which is never a candidate:
+/// it is the one most likely to be restored.
+pub fn prune_candidates(
+    config: &SnapshotConfig,
+    snapshots: &[Snapshot],
+    protected: Option<&str>,
+    now: DateTime<Utc>,
+) -> Vec<PruneCandidate> {
+    let mut automatic: Vec<&Snapshot> = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.kind != "packages")
+        .filter(|snapshot| is_automatic(snapshot))
+        .filter(|snapshot| Some(snapshot.id.as_str()) != protected)
+        .collect();
+    // Newest first, so the keep-last window is a prefix.
+    automatic.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created));
+    let mut candidates = Vec::new();
+    for (index, snapshot) in automatic.iter().enumerate() {
+        let age_days = (now - snapshot.created).num_days();
+        if config.keep_days > 0 && age_days > config.keep_days as i64 {
+            candidates.push(PruneCandidate {
+                snapshot: (*snapshot).clone(),
+                reason: format!("{age_days} days old (limit {})", config.keep_days),
+            });
+        } else if config.keep_last > 0 && index >= config.keep_last {
+            candidates.push(PruneCandidate {
+                snapshot: (*snapshot).clone(),
+                reason: format!("beyond the last {}", config.keep_last),
+            });
+        }
+    }
+    candidates
+}
+
+/// One installed package inside a [`PackageSet`]: just enough to
+/// reconstruct the state later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageRecord {
+    pub name: String,
+    pub version: String,
+    /// Whether the package was installed explicitly; `None` when the
+    /// backend could not tell at capture time.
+    #[serde(default)]
+    pub explicit: Option<bool>,
+}
+
+/// The complete installed state at a point in time, per manager.
+///
+/// Unlike the filesystem backends above, a package set works on every
+/// system: it is a plain JSON file in the state directory, written by
+/// `snapshot-packages` and automatically before each system update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSet {
+    pub label: String,
+    pub created: DateTime<Utc>,
+    /// Manager id -> its installed packages at capture time.
+    pub managers: BTreeMap<String, Vec<PackageRecord>>,
+}
+
+impl PackageSet {
+    /// Total number of recorded packages across all managers.
+    pub fn total(&self) -> usize {
+        self.managers.values().map(Vec::len).sum()
+    }
+
+    /// Present the set as a list entry next to filesystem snapshots; the
+    /// "packages" kind is how the tab's keys tell the two apart.
+    pub fn as_snapshot(&self) -> Snapshot {
+        Snapshot {
+            id: self.label.clone(),
+            created: self.created,
+            trigger: format!("package set ({} packages)", self.total()),
+            kind: "packages".to_string(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        }
+    }
+}
+
+/// Capture a package set from an already-listed installed view.
+pub fn capture_package_set(label: &str, installed: &[PackageInfo]) -> PackageSet {
+    let mut managers: BTreeMap<String, Vec<PackageRecord>> = BTreeMap::new();
+    for package in installed {
+        managers
+            .entry(package.manager.clone())
+            .or_default()
+            .push(PackageRecord {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                explicit: package.explicit,
+            });
+    }
+    PackageSet {
+        label: label.to_string(),
+        created: Utc::now(),
+        managers,
+    }
+}
+
+/// One manager's share of the work to converge the system back onto a
+/// package set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreStep {
+    pub manager: String,
+    /// Recorded but no longer installed.
+    pub install: Vec<String>,
+    /// Installed but absent from the record.
+    pub remove: Vec<String>,
+    /// Installed at a different version; (name, recorded version).
+    pub version_changes: Vec<(String, String)>,
+}
+
+impl RestoreStep {
+    pub fn is_empty(&self) -> bool {
+        self.install.is_empty() && self.remove.is_empty() && self.version_changes.is_empty()
+    }
+}
+
+/// Diff a recorded package set against the current installed view. Steps
+/// come out in manager order; managers with nothing to do are dropped.
+pub fn diff_package_set(set: &PackageSet, installed: &[PackageInfo]) -> Vec<RestoreStep> {
+    let mut current: BTreeMap<&str, BTreeMap<&str, &str>> = BTreeMap::new();
+    for package in installed {
+        current
+            .entry(package.manager.as_str())
+            .or_default()
+            .insert(package.name.as_str(), package.version.as_str());
+    }
+    let mut steps = Vec::new();
+    for (manager, records) in &set.managers {
+        let now = current.remove(manager.as_str()).unwrap_or_default();
+        let mut step = RestoreStep {
+            manager: manager.clone(),
+            ..RestoreStep::default()
+        };
+        let recorded: BTreeMap<&str, &str> = records
+            .iter()
+            .map(|record| (record.name.as_str(), record.version.as_str()))
+            .collect();
+        for (name, version) in &recorded {
+            match now.get(name) {
+                None => step.install.push(name.to_string()),
+                Some(installed_version) if installed_version != version => step
+                    .version_changes
+                    .push((name.to_string(), version.to_string())),
+                Some(_) => {}
+            }
+        }
+        step.remove.extend(
+            now.keys()
+                .filter(|name| !recorded.contains_key(*name))
+                .map(|name| name.to_string()),
+        );
+        if !step.is_empty() {
+            steps.push(step);
+        }
+    }
+    steps
+}
+
+fn package_sets_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("package-sets")
+}
+
+fn package_set_path(label: &str) -> PathBuf {
+    package_sets_dir().join(format!("{}.json", sanitize_trigger(label)))
+}
+
+/// Write a package set to the state directory, one JSON file per label.
+/// A later capture with the same label replaces the earlier one.
+pub fn save_package_set(set: &PackageSet) -> Result<PathBuf> {
+    std::fs::create_dir_all(package_sets_dir())?;
+    let path = package_set_path(&set.label);
+    std::fs::write(&path, serde_json::to_string_pretty(set)?)?;
+    Ok(path)
+}
+
+/// Read a package set back by label.
+pub fn load_package_set(label: &str) -> Result<PackageSet> {
+    let path = package_set_path(label);
+    let data = std::fs::read_to_string(&path)
+        .map_err(|_| PkgError::NotFound(format!("no package set named {label}")))?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+/// Delete a saved package set by label.
+pub fn delete_package_set(label: &str) -> Result<()> {
+    std::fs::remove_file(package_set_path(label))?;
+    Ok(())
+}
+
+/// Every saved package set, oldest first. Files that do not parse are
+/// skipped rather than failing the listing; a missing directory just
+/// means nothing has been captured yet.
+pub fn list_package_sets() -> Vec<PackageSet> {
+    let Ok(entries) = std::fs::read_dir(package_sets_dir()) else {
+        return Vec::new();
+    };
+    let mut sets: Vec<PackageSet> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let data = std::fs::read_to_string(entry.path()).ok()?;
+            serde_json::from_str(&data).ok()
+        })
+        .collect();
+    sets.sort_by_key(|set| set.created);
+    sets
+}
+
+fn notes_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("snapshot-notes.json")
+}
+
+/// The sidecar notes, one JSON map of snapshot id to note. A missing or
+/// unreadable file just means nothing has been annotated yet.
+pub fn sidecar_notes() -> BTreeMap<String, String> {
+    std::fs::read_to_string(notes_path())
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+/// Set, replace or (with an empty note) drop the sidecar note for a
+/// snapshot id. Also called when a snapshot is deleted, so the sidecar
+/// never accumulates entries for snapshots that no longer exist.
+pub fn set_sidecar_note(id: &str, note: &str) -> Result<()> {
+    let mut notes = sidecar_notes();
+    if note.is_empty() {
+        notes.remove(id);
+    } else {
+        notes.insert(id.to_string(), note.to_string());
+    }
+    let path = notes_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&notes)?)?;
+    Ok(())
+}
+
+/// What happened to one package between two package sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+    /// The version stayed put but the explicit/dependency mark flipped.
+    MarkChanged,
+}
+
+/// One line of a package-set diff; `from`/`to` hold versions (or marks,
+/// for [`ChangeKind::MarkChanged`]), empty on the side that has none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageChange {
+    pub manager: String,
+    pub name: String,
+    pub kind: ChangeKind,
+    pub from: String,
+    pub to: String,
+}
+
+fn mark_label(explicit: bool) -> &'static str {
+    if explicit {
+        "explicit"
+    } else {
+        "dependency"
+    }
+}
+
+/// Diff two package sets, grouped by manager and keyed on manager+name.
+/// dpkg reports multiarch packages as `name:arch`, so the architecture
+/// takes part in the key where it matters. Version movement goes through
+/// the distro-aware comparator to tell upgrades from downgrades.
+pub fn diff_package_sets(older: &PackageSet, newer: &PackageSet) -> Vec<PackageChange> {
+    use std::collections::BTreeSet;
+
+    let mut changes = Vec::new();
+    let managers: BTreeSet<&String> = older.managers.keys().chain(newer.managers.keys()).collect();
+    let by_name = |set: &'_ PackageSet, manager: &str| -> BTreeMap<String, PackageRecord> {
+        set.managers
+            .get(manager)
+            .map(|records| {
+                records
+                    .iter()
+                    .map(|record| (record.name.clone(), record.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    for manager in managers {
+        let before = by_name(older, manager);
+        let after = by_name(newer, manager);
+        let change = |name: &str, kind, from: &str, to: &str| PackageChange {
+            manager: manager.clone(),
+            name: name.to_string(),
+            kind,
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        for (name, record) in &after {
+            let Some(old) = before.get(name) else {
+                changes.push(change(name, ChangeKind::Added, "", &record.version));
+                continue;
+            };
+            if old.version != record.version {
+                let kind = match crate::utils::version::compare(
+                    crate::utils::version::VersionScheme::for_manager(manager),
+                    &old.version,
+                    &record.version,
+                ) {
+                    std::cmp::Ordering::Less => ChangeKind::Upgraded,
+                    _ => ChangeKind::Downgraded,
+                };
+                changes.push(change(name, kind, &old.version, &record.version));
+            }
+            if let (Some(was), Some(is)) = (old.explicit, record.explicit) {
+                if was != is {
+                    changes.push(change(
+                        name,
+                        ChangeKind::MarkChanged,
+                        mark_label(was),
+                        mark_label(is),
+                    ));
+                }
+            }
+        }
+        for (name, record) in &before {
+            if !after.contains_key(name) {
+                changes.push(change(name, ChangeKind::Removed, &record.version, ""));
+            }
+        }
+    }
+    changes
+}
+
+/// Render a diff as Markdown — one section per manager, one bullet per
+/// change — ready for pasting into an incident report.
+pub fn diff_markdown(from: &str, to: &str, changes: &[PackageChange]) -> String {
+    let mut out = format!("# Package changes: {from} -> {to}\n");
+    let mut current_manager = "";
+    for change in changes {
+        if change.manager != current_manager {
+            out.push_str(&format!("\n## {}\n\n", change.manager));
+            current_manager = &change.manager;
+        }
+        let line = match change.kind {
+            ChangeKind::Added => format!("- added `{}` {}", change.name, change.to),
+            ChangeKind::Removed => format!("- removed `{}` (was {})", change.name, change.from),
+            ChangeKind::Upgraded => {
+                format!("- upgraded `{}` {} -> {}", change.name, change.from, change.to)
+            }
+            ChangeKind::Downgraded => {
+                format!("- downgraded `{}` {} -> {}", change.name, change.from, change.to)
+            }
+            ChangeKind::MarkChanged => {
+                format!("- `{}` mark: {} -> {}", change.name, change.from, change.to)
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Run an unprivileged probe command on the managed host.
+async fn run(args: &[&str]) -> Result<String> {
+    let argv: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    crate::package_managers::run_backend("snapshots", &argv).await
+}
+
+/// Run a snapshot mutation through privilege escalation.
+async fn run_privileged(runner: &PrivilegeRunner, args: &[&str]) -> Result<String> {
+    crate::package_managers::run_backend("snapshots", &runner.wrap(args)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip_through_the_parser() {
+        let snapshot = parse_snapshot_name("pkgtool-20240106-152755-pre-update").unwrap();
+        assert_eq!(snapshot.trigger, "pre-update");
+        assert_eq!(
+            snapshot.created.format(NAME_TIMESTAMP).to_string(),
+            "20240106-152755"
+        );
+        assert_eq!(snapshot.id, "pkgtool-20240106-152755-pre-update");
+    }
+
+    #[test]
+    fn foreign_subvolumes_are_ignored() {
+        assert!(parse_snapshot_name("timeshift-2024-01-06").is_none());
+        assert!(parse_snapshot_name("pkgtool-garbage-manual").is_none());
+        assert!(parse_snapshot_name("pkgtool-20240106-152755-").is_none());
+        assert!(parse_snapshot_name("pkgtool-20240106-152755").is_none());
+    }
+
+    #[test]
+    fn lvs_json_rows_become_snapshots_with_usage() {
+        let output = r#"{"report":[{"lv":[
+            {"lv_name":"root","vg_name":"vg0","origin":"","data_percent":""},
+            {"lv_name":"pkgtool-20240106-152755-pre-update","vg_name":"vg0","origin":"root","data_percent":"47.30"},
+            {"lv_name":"homebackup","vg_name":"vg0","origin":"home","data_percent":"12.00"}
+        ]}]}"#;
+        let rows = parse_lvs_json(output).unwrap();
+        assert_eq!(rows.len(), 3);
+        // Only snapshot rows with our name prefix become snapshots.
+        let snapshots: Vec<Snapshot> = rows
+            .into_iter()
+            .filter(|row| !row.origin.is_empty())
+            .filter_map(|row| {
+                let mut snapshot = parse_snapshot_name(&row.lv_name)?;
+                snapshot.usage_percent = row.data_percent.trim().parse().ok();
+                Some(snapshot)
+            })
+            .collect();
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].trigger, "pre-update");
+        assert_eq!(snapshots[0].usage_percent, Some(47.3));
+    }
+
+    #[test]
+    fn btrfs_du_rows_report_exclusive_bytes_per_snapshot() {
+        let output = "\
+     Total   Exclusive  Set shared  Filename
+  10485760     1048576     9437184  /.pkgtool-snapshots/pkgtool-20240106-152755-pre-update
+   8388608           0     8388608  /.pkgtool-snapshots/pkgtool-20240112-080000-manual
+";
+        let rows = parse_btrfs_du(output);
+        assert_eq!(
+            rows,
+            vec![
+                ("pkgtool-20240106-152755-pre-update".to_string(), 1048576),
+                ("pkgtool-20240112-080000-manual".to_string(), 0),
+            ]
+        );
+        assert_eq!(parse_df_avail("     Avail\n42949672960\n"), Some(42949672960));
+    }
+
+    #[test]
+    fn cow_usage_scales_the_snapshot_size() {
+        assert_eq!(cow_used_bytes("1000000000", "50.00"), Some(500_000_000));
+        // lvs leaves both columns empty for ordinary volumes.
+        assert_eq!(cow_used_bytes("", "47.30"), None);
+        assert_eq!(cow_used_bytes("1000000000", ""), None);
+    }
+
+    #[test]
+    fn zfs_tab_rows_parse_with_epoch_timestamps() {
+        let output = "\
+rpool/ROOT/default@pkgtool-20240106-152755-pre-update\t1704554875\t1048576
+rpool/ROOT/default@zfs-auto-snap_daily-2024-01-07\t1704614400\t0
+rpool/ROOT/default@pkgtool-20240112-080000-manual\t1705046400\t8192
+";
+        let snapshots = parse_zfs_list(output);
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].id, "pkgtool-20240106-152755-pre-update");
+        assert_eq!(snapshots[0].trigger, "pre-update");
+        // The creation epoch wins over the timestamp in the name.
+        assert_eq!(snapshots[0].created.timestamp(), 1704554875);
+        assert_eq!(snapshots[1].trigger, "manual");
+    }
+
+    #[test]
+    fn rpm_ostree_deployments_parse_with_state_markers() {
+        let output = r#"{"deployments":[
+            {"checksum":"aaa111","version":"40.20240112.0","timestamp":1705046400,
+             "staged":true,"booted":false,"pinned":false},
+            {"checksum":"bbb222","version":"40.20240106.0","timestamp":1704554875,
+             "booted":true,"pinned":true}
+        ]}"#;
+        let deployments = parse_rpm_ostree_status(output).unwrap();
+        assert_eq!(deployments.len(), 2);
+        let snapshots: Vec<Snapshot> = deployments
+            .iter()
+            .map(OstreeDeployment::as_snapshot)
+            .collect();
+        // Boot order is preserved: the pending deployment stays first.
+        assert_eq!(snapshots[0].id, "aaa111");
+        assert_eq!(snapshots[0].trigger, "deployment 40.20240112.0 (pending)");
+        assert_eq!(snapshots[1].trigger, "deployment 40.20240106.0 (booted, pinned)");
+        assert_eq!(snapshots[1].created.timestamp(), 1704554875);
+    }
+
+    #[test]
+    fn snapper_csv_rows_parse_with_pair_links() {
+        let output = "\
+config,subvolume,number,default,active,date,user,cleanup,type,pre-number,description,userdata
+root,/,0,yes,yes,,root,,single,,current,
+root,/,41,no,no,2024-01-06 15:27:55,root,number,pre,,pkgtool: install htop,note=went fine
+root,/,42,no,no,2024-01-06 15:28:10,root,number,post,41,pkgtool: install htop,
+root,/,43,no,no,2024-01-07 08:00:00,root,,single,,zypper rollback point,
+";
+        let snapshots = parse_snapper_csv(output);
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].id, "41");
+        assert_eq!(snapshots[0].kind, "pre");
+        assert_eq!(snapshots[0].trigger, "install htop");
+        assert_eq!(snapshots[0].note.as_deref(), Some("went fine"));
+        assert_eq!(snapshots[1].kind, "post");
+        assert_eq!(snapshots[1].note, None);
+        assert_eq!(snapshots[1].pre.as_deref(), Some("41"));
+        // Foreign and single snapshots stay plain.
+        assert_eq!(snapshots[2].kind, "");
+        assert_eq!(snapshots[2].trigger, "zypper rollback point");
+    }
+
+    #[test]
+    fn timeshift_table_rows_parse_into_snapshots() {
+        let output = "\
+Device : /dev/sda2
+UUID : 1234
+Mounted at : /run/timeshift/backup
+------------------------------------------------------------------------------
+Num     Name                 Tags  Description
+------------------------------------------------------------------------------
+0    >  2024-01-06_15-27-55  O     pkgtool pre-update
+1    >  2024-01-10_09-00-01  D
+2    >  2024-01-12_12-30-00  O     before kernel test
+";
+        let snapshots = parse_timeshift_list(output);
+        assert_eq!(snapshots.len(), 3);
+        assert_eq!(snapshots[0].id, "2024-01-06_15-27-55");
+        assert_eq!(snapshots[0].trigger, "pre-update");
+        assert_eq!(snapshots[1].trigger, "");
+        assert_eq!(snapshots[2].trigger, "before kernel test");
+    }
+
+    fn automatic(id: &str, days_ago: i64) -> Snapshot {
+        Snapshot {
+            id: id.to_string(),
+            created: Utc::now() - chrono::Duration::days(days_ago),
+            trigger: "pre-update".to_string(),
+            kind: String::new(),
+            pre: None,
+            usage_percent: None,
+            size_bytes: None,
+            note: None,
+        }
+    }
+
+    #[test]
+    fn retention_keeps_recent_manual_and_protected_snapshots() {
+        let config = SnapshotConfig {
+            keep_last: 2,
+            keep_days: 30,
+            ..SnapshotConfig::default()
+        };
+        let mut manual = automatic("kept-manual", 90);
+        manual.trigger = "before kernel test".to_string();
+        let snapshots = vec![
+            automatic("newest", 1),
+            automatic("recent", 2),
+            automatic("third", 3),
+            automatic("ancient", 45),
+            automatic("protected", 60),
+            manual,
+        ];
+        let candidates = prune_candidates(&config, &snapshots, Some("protected"), Utc::now());
+        let ids: Vec<&str> = candidates
+            .iter()
+            .map(|candidate| candidate.snapshot.id.as_str())
+            .collect();
+        // "third" falls out of the keep-last window, "ancient" to the age
+        // rule; the manual and protected snapshots survive both.
+        assert_eq!(ids, vec!["third", "ancient"]);
+        assert!(candidates[0].reason.contains("last 2"));
+        assert!(candidates[1].reason.contains("days old"));
+    }
+
+    fn installed(name: &str, version: &str, manager: &str) -> PackageInfo {
+        PackageInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: String::new(),
+            manager: manager.to_string(),
+            installed: true,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        }
+    }
+
+    #[test]
+    fn package_set_diff_covers_installs_removes_and_versions() {
+        let recorded = capture_package_set(
+            "before",
+            &[
+                installed("htop", "3.0", "apt"),
+                installed("tmux", "3.4", "apt"),
+                installed("ripgrep", "14.1.0-1", "pacman"),
+            ],
+        );
+        let now = [
+            installed("tmux", "3.5", "apt"),
+            installed("zsh", "5.9", "apt"),
+            installed("ripgrep", "14.1.0-1", "pacman"),
+        ];
+        let steps = diff_package_set(&recorded, &now);
+        // pacman already matches, so only apt has work to do.
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].manager, "apt");
+        assert_eq!(steps[0].install, vec!["htop"]);
+        assert_eq!(steps[0].remove, vec!["zsh"]);
+        assert_eq!(
+            steps[0].version_changes,
+            vec![("tmux".to_string(), "3.4".to_string())]
+        );
+    }
+
+    #[test]
+    fn set_to_set_diff_classifies_every_change_kind() {
+        let mut before = capture_package_set(
+            "before",
+            &[
+                installed("bash", "5.2", "apt"),
+                installed("vim", "2:9.0", "apt"),
+                installed("curl", "8.5", "apt"),
+                installed("htop", "3.0", "apt"),
+            ],
+        );
+        before.managers.get_mut("apt").unwrap()[3].explicit = Some(true);
+        let mut after = capture_package_set(
+            "after",
+            &[
+                installed("bash", "5.2", "apt"),
+                installed("vim", "2:9.1", "apt"),
+                installed("wget", "1.21", "apt"),
+                installed("htop", "3.0", "apt"),
+            ],
+        );
+        after.managers.get_mut("apt").unwrap()[3].explicit = Some(false);
+        let changes = diff_package_sets(&before, &after);
+        let kind_of = |name: &str| {
+            changes
+                .iter()
+                .find(|change| change.name == name)
+                .map(|change| change.kind)
+        };
+        assert_eq!(kind_of("wget"), Some(ChangeKind::Added));
+        assert_eq!(kind_of("curl"), Some(ChangeKind::Removed));
+        assert_eq!(kind_of("vim"), Some(ChangeKind::Upgraded));
+        assert_eq!(kind_of("htop"), Some(ChangeKind::MarkChanged));
+        assert!(kind_of("bash").is_none());
+        let markdown = diff_markdown("before", "after", &changes);
+        assert!(markdown.contains("## apt"));
+        assert!(markdown.contains("- upgraded `vim` 2:9.0 -> 2:9.1"));
+    }
+
+    #[test]
+    fn matching_package_set_diffs_to_nothing() {
+        let state = [installed("htop", "3.0", "apt")];
+        let recorded = capture_package_set("idle", &state);
+        assert!(diff_package_set(&recorded, &state).is_empty());
+    }
+}
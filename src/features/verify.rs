@@ -0,0 +1,246 @@
+//! Checksum verification of locally downloaded package files.
+//!
+//! Before a file from outside the manager's own download path gets
+//! installed, its digest is compared against what the repositories claim:
+//! apt's `SHA256` metadata field, a detached `.sig` next to a pacman
+//! package, or the digests embedded in an rpm header (`rpm -K`). The
+//! result is deliberately three-valued — a file that cannot be checked is
+//! reported as exactly that, never silently treated as fine.
+
+use std::path::Path;
+
+use crate::error::{PkgError, Result};
+use crate::package_managers::{binary_exists, run_backend};
+
+/// The outcome of checking one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// The digest or signature matches; `source` says what vouched for it.
+    Matched { source: String },
+    /// The file does not match the repository's expectation.
+    Mismatched { source: String, detail: String },
+    /// Nothing to compare against; the reason says why.
+    Unverifiable(String),
+}
+
+/// Verify a package file against repository metadata, dispatching on the
+/// package format.
+pub async fn verify_file(path: &str) -> Result<Verification> {
+    if !Path::new(path).is_file() {
+        return Err(PkgError::NotFound(path.to_string()));
+    }
+    if path.ends_with(".deb") {
+        verify_deb(path).await
+    } else if path.ends_with(".rpm") {
+        verify_rpm(path).await
+    } else if path.contains(".pkg.tar") {
+        verify_pacman(path).await
+    } else {
+        Ok(Verification::Unverifiable(
+            "unrecognized package format; expected .deb, .rpm or .pkg.tar.*".to_string(),
+        ))
+    }
+}
+
+/// SHA256 of a file, via the coreutils the backends already rely on.
+async fn sha256(path: &str) -> Result<String> {
+    let argv: Vec<String> = ["sha256sum", path].iter().map(|arg| arg.to_string()).collect();
+    let output = run_backend("verify", &argv).await?;
+    output
+        .split_whitespace()
+        .next()
+        .map(str::to_string)
+        .ok_or_else(|| PkgError::Parse {
+            source_desc: "sha256sum".to_string(),
+            detail: "empty output".to_string(),
+        })
+}
+
+/// Compare a .deb against the `SHA256` field apt's metadata records for
+/// that name and version. The filename (`name_version_arch.deb`) supplies
+/// both; an epoch's `:` arrives urlencoded as `%3a`.
+async fn verify_deb(path: &str) -> Result<Verification> {
+    let Some((name, version)) = deb_name_version(path) else {
+        return Ok(Verification::Unverifiable(
+            "filename does not follow name_version_arch.deb".to_string(),
+        ));
+    };
+    let argv: Vec<String> = ["apt-cache", "show", &name]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let output = match run_backend("verify", &argv).await {
+        Ok(output) => output,
+        Err(_) => {
+            return Ok(Verification::Unverifiable(format!(
+                "apt metadata has no entry for {name}"
+            )))
+        }
+    };
+    let Some(expected) = apt_sha256_for_version(&output, &version) else {
+        return Ok(Verification::Unverifiable(format!(
+            "apt metadata lists no SHA256 for {name} {version}"
+        )));
+    };
+    let actual = sha256(path).await?;
+    if actual.eq_ignore_ascii_case(&expected) {
+        Ok(Verification::Matched {
+            source: "apt repository metadata (SHA256)".to_string(),
+        })
+    } else {
+        Ok(Verification::Mismatched {
+            source: "apt repository metadata".to_string(),
+            detail: format!("expected sha256 {expected}, file has {actual}"),
+        })
+    }
+}
+
+/// `name` and decoded `version` from a `name_version_arch.deb` filename.
+fn deb_name_version(path: &str) -> Option<(String, String)> {
+    let file = Path::new(path).file_name()?.to_str()?;
+    let stem = file.strip_suffix(".deb")?;
+    let mut parts = stem.split('_');
+    let name = parts.next()?.to_string();
+    let version = parts.next()?.replace("%3a", ":").replace("%3A", ":");
+    if name.is_empty() || version.is_empty() {
+        return None;
+    }
+    Some((name, version))
+}
+
+/// The `SHA256` field of the `apt-cache show` paragraph whose `Version`
+/// matches.
+fn apt_sha256_for_version(output: &str, version: &str) -> Option<String> {
+    let mut version_matches = false;
+    let mut sha256 = None;
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if version_matches {
+                return sha256;
+            }
+            version_matches = false;
+            sha256 = None;
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Version:") {
+            version_matches = value.trim() == version;
+        } else if let Some(value) = line.strip_prefix("SHA256:") {
+            sha256 = Some(value.trim().to_string());
+        }
+    }
+    if version_matches {
+        sha256
+    } else {
+        None
+    }
+}
+
+/// Verify a pacman package through the detached `.sig` mirrors publish
+/// next to every package.
+async fn verify_pacman(path: &str) -> Result<Verification> {
+    let signature = format!("{path}.sig");
+    if !Path::new(&signature).is_file() {
+        return Ok(Verification::Unverifiable(
+            "no detached .sig next to the file; download it from the same mirror".to_string(),
+        ));
+    }
+    if !binary_exists("pacman-key") {
+        return Ok(Verification::Unverifiable(
+            "pacman-key is not installed".to_string(),
+        ));
+    }
+    let argv: Vec<String> = ["pacman-key", "--verify", &signature, path]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    match run_backend("verify", &argv).await {
+        Ok(_) => Ok(Verification::Matched {
+            source: "detached signature (pacman-key)".to_string(),
+        }),
+        Err(PkgError::CommandFailed { stderr, .. }) => Ok(Verification::Mismatched {
+            source: "detached signature".to_string(),
+            detail: stderr.lines().last().unwrap_or("bad signature").to_string(),
+        }),
+        Err(err) => Err(err),
+    }
+}
+
+/// Verify an rpm against the digests embedded in its own header.
+async fn verify_rpm(path: &str) -> Result<Verification> {
+    if !binary_exists("rpm") {
+        return Ok(Verification::Unverifiable("rpm is not installed".to_string()));
+    }
+    let argv: Vec<String> = ["rpm", "-K", path].iter().map(|arg| arg.to_string()).collect();
+    match run_backend("verify", &argv).await {
+        Ok(output) => Ok(parse_rpm_check(&output)),
+        // rpm -K exits non-zero on a failed check, with the verdict on
+        // stdout captured into the error's stderr field by run_backend.
+        Err(PkgError::CommandFailed { stderr, .. }) => Ok(Verification::Mismatched {
+            source: "rpm header digests".to_string(),
+            detail: stderr.lines().last().unwrap_or("digests NOT OK").to_string(),
+        }),
+        Err(err) => Err(err),
+    }
+}
+
+/// Interpret the `rpm -K` verdict line.
+fn parse_rpm_check(output: &str) -> Verification {
+    let verdict = output.lines().next().unwrap_or("").to_lowercase();
+    if verdict.contains("not ok") {
+        Verification::Mismatched {
+            source: "rpm header digests".to_string(),
+            detail: output.lines().next().unwrap_or("").trim().to_string(),
+        }
+    } else if verdict.contains("ok") {
+        Verification::Matched {
+            source: "rpm header digests".to_string(),
+        }
+    } else {
+        Verification::Unverifiable("rpm -K produced no verdict".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deb_filenames_decode_name_and_version() {
+        assert_eq!(
+            deb_name_version("/tmp/htop_3.2.2-2_amd64.deb"),
+            Some(("htop".to_string(), "3.2.2-2".to_string()))
+        );
+        assert_eq!(
+            deb_name_version("vim_2%3a9.0.1378-2_amd64.deb"),
+            Some(("vim".to_string(), "2:9.0.1378-2".to_string()))
+        );
+        assert_eq!(deb_name_version("notapackage.tar.gz"), None);
+    }
+
+    #[test]
+    fn apt_metadata_yields_the_hash_of_the_matching_version() {
+        let output = "Package: htop\nVersion: 3.2.1-1\nSHA256: aaaa\n\n\
+                      Package: htop\nVersion: 3.2.2-2\nSHA256: bbbb\n";
+        assert_eq!(
+            apt_sha256_for_version(output, "3.2.2-2"),
+            Some("bbbb".to_string())
+        );
+        assert_eq!(apt_sha256_for_version(output, "9.9.9"), None);
+    }
+
+    #[test]
+    fn rpm_verdicts_map_onto_the_three_states() {
+        assert!(matches!(
+            parse_rpm_check("pkg.rpm: digests signatures OK"),
+            Verification::Matched { .. }
+        ));
+        assert!(matches!(
+            parse_rpm_check("pkg.rpm: DIGESTS SIGNATURES NOT OK"),
+            Verification::Mismatched { .. }
+        ));
+        assert!(matches!(
+            parse_rpm_check(""),
+            Verification::Unverifiable(_)
+        ));
+    }
+}
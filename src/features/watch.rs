@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::package_managers::PackageManager;
+
+/// Interval for `pkgtool watch` when auto_refresh_secs is 0: disabling the
+/// TUI's background checks should not make an explicit watcher a no-op.
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// A refresh lock older than this is presumed left over from a crash.
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// The watcher's last result, written to the state directory after every
+/// successful check so an interactive instance can show it without
+/// re-querying the backends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchState {
+    pub timestamp: DateTime<Utc>,
+    pub total: usize,
+    pub security: usize,
+}
+
+/// The check interval the configuration implies.
+pub fn interval(config: &Config) -> Duration {
+    if config.auto_refresh_secs == 0 {
+        DEFAULT_INTERVAL
+    } else {
+        Duration::from_secs(config.auto_refresh_secs)
+    }
+}
+
+fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+}
+
+fn state_path() -> PathBuf {
+    state_dir().join("watch.json")
+}
+
+fn lock_path() -> PathBuf {
+    state_dir().join("refresh.lock")
+}
+
+/// The last written state when it is younger than `max_age`; `None` means
+/// there is no watcher running, or its result is too old to trust.
+pub fn load_fresh(max_age: Duration) -> Option<WatchState> {
+    let data = std::fs::read_to_string(state_path()).ok()?;
+    let state: WatchState = serde_json::from_str(&data).ok()?;
+    let age = Utc::now()
+        .signed_duration_since(state.timestamp)
+        .to_std()
+        .ok()?;
+    (age < max_age).then_some(state)
+}
+
+fn store(state: &WatchState) -> std::io::Result<()> {
+    std::fs::create_dir_all(state_dir())?;
+    let json = serde_json::to_string(state).expect("state serializes");
+    std::fs::write(state_path(), json)
+}
+
+/// Cross-process guard around metadata refreshes, so the watcher and an
+/// interactive instance never hit the backends at the same time. Taken by
+/// creating the lock file exclusively; released by deleting it on drop.
+pub struct RefreshLock {
+    path: PathBuf,
+}
+
+/// Try to take the refresh lock; `None` means another pkgtool process is
+/// refreshing right now and this cycle should be skipped, not queued.
+pub fn try_refresh_lock() -> Option<RefreshLock> {
+    let path = lock_path();
+    if let Ok(meta) = std::fs::metadata(&path) {
+        let stale = meta
+            .modified()
+            .ok()
+            .and_then(|mtime| mtime.elapsed().ok())
+            .is_some_and(|age| age > LOCK_STALE_AFTER);
+        if !stale {
+            return None;
+        }
+        // A crashed process left the lock behind; break it.
+        let _ = std::fs::remove_file(&path);
+    }
+    std::fs::create_dir_all(state_dir()).ok()?;
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .ok()?;
+    use std::io::Write as _;
+    let _ = writeln!(&file, "{}", std::process::id());
+    Some(RefreshLock { path })
+}
+
+impl Drop for RefreshLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+enum Outcome {
+    Checked { total: usize, security: usize },
+    /// Another instance holds the refresh lock; nothing was queried.
+    Skipped,
+    Failed(String),
+}
+
+/// `pkgtool watch`: a daemonless updates watcher. Checks on the configured
+/// interval, writes its result to the state file, and sends a desktop
+/// notification when the counts change. Backs off exponentially while
+/// backends keep failing (a flaky network must not melt into a retry
+/// storm) and exits cleanly on SIGTERM or SIGHUP.
+pub async fn run(managers: &[Arc<dyn PackageManager>], config: &Config) -> i32 {
+    let base = interval(config);
+    let mut backoff = 1u32;
+    // Seed from the previous run so restarting the watcher does not
+    // re-announce counts the user has already seen.
+    let mut announced = load_fresh(base.saturating_mul(2)).map(|state| (state.total, state.security));
+    loop {
+        match check(managers, config.offline).await {
+            Outcome::Checked { total, security } => {
+                backoff = 1;
+                let state = WatchState {
+                    timestamp: Utc::now(),
+                    total,
+                    security,
+                };
+                if let Err(err) = store(&state) {
+                    log::warn!("watch: could not write state file: {err}");
+                }
+                let suppressed = config.notify_security_only && security == 0;
+                if announced != Some((total, security)) && total > 0 && !suppressed {
+                    notify(total, security);
+                }
+                announced = Some((total, security));
+            }
+            Outcome::Skipped => {}
+            Outcome::Failed(err) => {
+                log::warn!("watch: check failed: {err}");
+                backoff = (backoff * 2).min(8);
+            }
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(base.saturating_mul(backoff)) => {}
+            _ = crate::terminal::shutdown_signal() => return 0,
+        }
+    }
+}
+
+async fn check(managers: &[Arc<dyn PackageManager>], offline: bool) -> Outcome {
+    let Some(_lock) = try_refresh_lock() else {
+        return Outcome::Skipped;
+    };
+    let mut total = 0;
+    let mut security = 0;
+    for manager in managers {
+        if !offline && manager.network_operations().contains(&"refresh") {
+            if let Err(err) = manager.refresh_metadata().await {
+                return Outcome::Failed(format!("{}: {err}", manager.id()));
+            }
+        }
+        match manager.list_updates().await {
+            Ok(updates) => {
+                total += updates.len();
+                security += updates.iter().filter(|update| update.security).count();
+            }
+            Err(err) => return Outcome::Failed(format!("{}: {err}", manager.id())),
+        }
+    }
+    Outcome::Checked { total, security }
+}
+
+/// Send the desktop notification; a missing notification daemon only logs,
+/// since the state file still carries the result.
+fn notify(total: usize, security: usize) {
+    let body = if security > 0 {
+        format!("{total} updates available, {security} security")
+    } else {
+        format!("{total} updates available")
+    };
+    if let Err(err) = notify_rust::Notification::new()
+        .summary("pkgtool")
+        .body(&body)
+        .show()
+    {
+        log::warn!("watch: notification failed: {err}");
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// A bookmarked package, identified across sessions by manager and name.
+///
+/// The package does not have to be installed — watching a package that is
+/// still waiting for a version bump is the main use case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub manager: String,
+    pub name: String,
+}
+
+/// Persistent set of bookmarked packages.
+pub struct Watchlist {
+    path: PathBuf,
+    entries: Vec<WatchEntry>,
+}
+
+impl Watchlist {
+    /// Load the existing watchlist, or start empty when the file is absent
+    /// or unreadable.
+    pub fn load() -> Self {
+        let path = watchlist_path();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Watchlist { path, entries }
+    }
+
+    pub fn contains(&self, manager: &str, name: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.manager == manager && entry.name == name)
+    }
+
+    /// Add or remove a bookmark; returns true when the package is now watched.
+    pub fn toggle(&mut self, manager: &str, name: &str) -> Result<bool> {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.manager == manager && entry.name == name);
+        let watched = match position {
+            Some(index) => {
+                self.entries.remove(index);
+                false
+            }
+            None => {
+                self.entries.push(WatchEntry {
+                    manager: manager.to_string(),
+                    name: name.to_string(),
+                });
+                true
+            }
+        };
+        self.save()?;
+        Ok(watched)
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(&self.entries)?;
+        std::fs::write(&self.path, data)?;
+        Ok(())
+    }
+}
+
+fn watchlist_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".pkg_tool")
+        .join("watchlist.json")
+}
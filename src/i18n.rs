@@ -0,0 +1,151 @@
+use std::sync::OnceLock;
+
+/// English table; the source of truth. Every key used anywhere in the UI
+/// must appear here, which `tests::spanish_is_a_subset_of_english` together
+/// with the fallback in `tr` turns into "missing translations show English,
+/// never a raw key".
+static ENGLISH: &[(&str, &str)] = &[
+    ("tab.overview", "Overview"),
+    ("tab.packages", "Packages"),
+    ("tab.updates", "Updates"),
+    ("tab.search", "Search"),
+    ("tab.log", "Log"),
+    ("tab.snapshots", "Snapshots"),
+    ("tab.security", "Security"),
+    ("quit.wait", "Wait for completion"),
+    ("quit.detach", "Detach and quit (operation continues)"),
+    ("quit.abort", "Abort operation and quit"),
+    ("cancel.keep", "Keep running"),
+    ("cancel.cancel", "Cancel it (SIGINT; unsafe mid-transaction)"),
+    ("confirm.proceed", "Proceed"),
+    ("confirm.abort", "Abort"),
+    ("stall.wait", "Keep waiting"),
+    ("stall.abort", "Abort the operation"),
+    ("setup.step.managers", "Package managers"),
+    ("setup.step.theme", "Theme"),
+    ("setup.step.confirm", "Confirmations"),
+    ("setup.step.refresh", "Auto-refresh"),
+    ("setup.step.escalation", "Privilege escalation"),
+    ("setup.confirm.ask", "Ask before install/remove/update"),
+    ("setup.confirm.destructive", "Ask before removals only"),
+    ("setup.confirm.never", "Never ask"),
+    ("setup.refresh.auto", "Check for updates periodically"),
+    ("setup.refresh.manual", "Only check manually"),
+    ("setup.keys.managers", "Space:toggle  Enter:next  Esc:accept defaults"),
+    ("setup.keys.mid", "Enter:next  Left:back  Esc:accept defaults"),
+    ("setup.keys.last", "Enter:finish  Left:back  Esc:accept defaults"),
+    ("setup.saved", "setup saved"),
+    ("status.updated", "system updated"),
+    ("bar.hints", "q:quit  Tab:switch  /:search  ::command  ?:help"),
+];
+
+/// Spanish. Keys left out fall back to English at lookup time.
+static SPANISH: &[(&str, &str)] = &[
+    ("tab.overview", "Resumen"),
+    ("tab.packages", "Paquetes"),
+    ("tab.updates", "Actualizaciones"),
+    ("tab.search", "Búsqueda"),
+    ("tab.log", "Registro"),
+    ("tab.snapshots", "Instantáneas"),
+    ("tab.security", "Seguridad"),
+    ("quit.wait", "Esperar a que termine"),
+    ("quit.detach", "Desacoplar y salir (la operación continúa)"),
+    ("quit.abort", "Abortar la operación y salir"),
+    ("cancel.keep", "Seguir ejecutando"),
+    ("cancel.cancel", "Cancelarla (SIGINT; inseguro a mitad de transacción)"),
+    ("confirm.proceed", "Continuar"),
+    ("confirm.abort", "Abortar"),
+    ("stall.wait", "Seguir esperando"),
+    ("stall.abort", "Abortar la operación"),
+    ("setup.step.managers", "Gestores de paquetes"),
+    ("setup.step.theme", "Tema"),
+    ("setup.step.confirm", "Confirmaciones"),
+    ("setup.step.refresh", "Actualización automática"),
+    ("setup.step.escalation", "Escalada de privilegios"),
+    ("setup.confirm.ask", "Preguntar antes de instalar/eliminar/actualizar"),
+    ("setup.confirm.destructive", "Preguntar solo antes de eliminar"),
+    ("setup.confirm.never", "No preguntar nunca"),
+    ("setup.refresh.auto", "Buscar actualizaciones periódicamente"),
+    ("setup.refresh.manual", "Buscar solo manualmente"),
+    ("setup.keys.managers", "Espacio:marcar  Intro:siguiente  Esc:aceptar valores"),
+    ("setup.keys.mid", "Intro:siguiente  Izq:atrás  Esc:aceptar valores"),
+    ("setup.keys.last", "Intro:terminar  Izq:atrás  Esc:aceptar valores"),
+    ("setup.saved", "configuración guardada"),
+    ("status.updated", "sistema actualizado"),
+    ("bar.hints", "q:salir  Tab:cambiar  /:buscar  ::comando  ?:ayuda"),
+];
+
+/// The table for the selected locale; `ENGLISH` until `init` runs.
+static ACTIVE: OnceLock<&'static [(&'static str, &'static str)]> = OnceLock::new();
+
+/// Select the locale: an explicit tag from the config ("en", "es") or
+/// "auto" to read LC_ALL/LC_MESSAGES/LANG, in glibc precedence order.
+pub fn init(preference: &str) {
+    let tag = if preference.is_empty() || preference == "auto" {
+        detected_locale()
+    } else {
+        preference.to_ascii_lowercase()
+    };
+    let _ = ACTIVE.set(table_for(&tag));
+}
+
+fn table_for(tag: &str) -> &'static [(&'static str, &'static str)] {
+    match tag {
+        "es" => SPANISH,
+        _ => ENGLISH,
+    }
+}
+
+/// Language part of the first locale variable that is set, e.g. "es" from
+/// `LANG=es_ES.UTF-8`.
+fn detected_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .map(|value| {
+            value
+                .split(['_', '.', '@'])
+                .next()
+                .unwrap_or("en")
+                .to_ascii_lowercase()
+        })
+        .unwrap_or_else(|| "en".to_string())
+}
+
+fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == key)
+        .map(|(_, text)| *text)
+}
+
+/// Look up a user-visible string in the active locale, falling back to
+/// English for untranslated keys. Keys are literals, so the English table
+/// missing one is a programming error; the key itself is the last resort.
+pub fn tr(key: &'static str) -> &'static str {
+    let active = ACTIVE.get().copied().unwrap_or(ENGLISH);
+    lookup(active, key)
+        .or_else(|| lookup(ENGLISH, key))
+        .unwrap_or(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spanish_is_a_subset_of_english() {
+        for (key, _) in SPANISH {
+            assert!(
+                lookup(ENGLISH, key).is_some(),
+                "spanish key {key} missing from the english table"
+            );
+        }
+    }
+
+    #[test]
+    fn untranslated_keys_fall_back_to_english() {
+        assert_eq!(lookup(ENGLISH, "tab.log"), Some("Log"));
+        assert!(lookup(&ENGLISH[..1], "tab.log").is_none());
+    }
+}
@@ -0,0 +1,19 @@
+//! pkgtool: a terminal UI and CLI over the system package managers.
+//!
+//! The binary in `main.rs` is a thin wrapper over this library; the split
+//! exists so benchmarks and integration tests can reach the pure pieces
+//! (parsers, scorers) without spawning processes.
+
+pub mod actions;
+pub mod app;
+pub mod cli;
+pub mod config;
+pub mod error;
+pub mod features;
+pub mod i18n;
+pub mod logging;
+pub mod package_managers;
+pub mod terminal;
+pub mod theme;
+pub mod ui;
+pub mod utils;
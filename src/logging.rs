@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use log::{LevelFilter, Metadata, Record};
+
+/// Rotate once the log file grows past this size.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// Upper bound on entries kept in memory for the Log tab.
+const RECENT_CAP: usize = 500;
+
+static LOGGER: OnceLock<FileLogger> = OnceLock::new();
+
+/// File logger for the whole process.
+///
+/// Logging to stderr fights with the alternate screen and is lost when the
+/// terminal closes, so everything goes to `pkgtool.log` in the platform state
+/// directory instead. The file rotates once (`pkgtool.log.1`) at a size cap,
+/// and a bounded in-memory buffer keeps recent entries so the Log tab can
+/// show them without re-reading the file.
+struct FileLogger {
+    path: PathBuf,
+    /// Open handle to the log file; `None` until first use or after an
+    /// unwritable disk, so logging failures never take the app down.
+    file: Mutex<Option<File>>,
+    /// Entries not yet drained by the Log tab.
+    recent: Mutex<VecDeque<String>>,
+}
+
+impl FileLogger {
+    fn open(&self) -> Option<File> {
+        let parent = self.path.parent()?;
+        std::fs::create_dir_all(parent).ok()?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .ok()
+    }
+
+    /// Rename the file to its `.1` sibling once it passes the size cap, so
+    /// the log never grows without bound but one generation stays around.
+    fn rotate_if_needed(&self, file: &mut Option<File>) {
+        let over_cap = file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .is_some_and(|meta| meta.len() > MAX_LOG_SIZE);
+        if !over_cap {
+            return;
+        }
+        let _ = std::fs::rename(&self.path, self.path.with_extension("log.1"));
+        *file = self.open();
+    }
+}
+
+impl log::Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_entry(record);
+        {
+            let mut recent = self.recent.lock().unwrap();
+            if recent.len() == RECENT_CAP {
+                recent.pop_front();
+            }
+            recent.push_back(line.clone());
+        }
+        let mut file = self.file.lock().unwrap();
+        if file.is_none() {
+            *file = self.open();
+        }
+        if let Some(f) = file.as_mut() {
+            if writeln!(f, "{line}").is_err() {
+                *file = None;
+                return;
+            }
+        }
+        self.rotate_if_needed(&mut file);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().unwrap().as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// One formatted log line: timestamp, level, target, message.
+fn format_entry(record: &Record) -> String {
+    format!(
+        "{} {:<5} {} {}",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+/// Install the file logger at `level`. Safe to call once per process;
+/// repeated calls only adjust the level.
+pub fn init(level: LevelFilter) {
+    let logger = LOGGER.get_or_init(|| FileLogger {
+        path: log_path(),
+        file: Mutex::new(None),
+        recent: Mutex::new(VecDeque::new()),
+    });
+    let _ = log::set_logger(logger);
+    log::set_max_level(level);
+}
+
+/// Path of the active log file, shown in the UI for bug reports.
+pub fn log_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pkgtool")
+        .join("pkgtool.log")
+}
+
+/// Entries written since the last call, for the Log tab.
+pub fn drain_recent() -> Vec<String> {
+    LOGGER
+        .get()
+        .map(|logger| logger.recent.lock().unwrap().drain(..).collect())
+        .unwrap_or_default()
+}
+
+/// Record one backend command with structured fields, so slow or failing
+/// invocations can be traced from the file afterwards.
+pub fn invocation(manager: &str, command: &str, duration: Duration, status: i32) {
+    log::info!(
+        target: "pkgtool::backend",
+        "manager={manager} command=\"{command}\" duration_ms={} status={status}",
+        duration.as_millis()
+    );
+}
@@ -0,0 +1,107 @@
+use clap::Parser;
+
+use pkgtool::app::{self, App};
+use pkgtool::cli::{self, Cli};
+use pkgtool::config::Config;
+use pkgtool::terminal::{self, TerminalGuard};
+use pkgtool::{i18n, logging, package_managers, utils};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Cli::parse();
+    // `sudo pkgtool` would scatter root-owned files through /root, so the
+    // XDG directories are resolved against the invoking user's home before
+    // the config is read — the config consulted below is then that user's
+    // own. A plain root login (no SUDO_USER, e.g. a container) is left
+    // alone; escalation is skipped either way.
+    let as_root = utils::privilege::running_as_root();
+    let invoker = utils::privilege::invoking_user();
+    let adopted = match &invoker {
+        Some(user) if as_root => utils::privilege::adopt_user_dirs(user),
+        _ => Vec::new(),
+    };
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("pkgtool: {err}");
+            std::process::exit(1);
+        }
+    };
+    if !adopted.is_empty() && config.root_behavior != "adapt" {
+        // This user opted out of adoption; put root's own paths back.
+        utils::privilege::undo_adoption(&adopted);
+    }
+    let warn_root = as_root && invoker.is_some() && config.root_behavior == "warn";
+    if args.offline {
+        config.offline = true;
+    }
+    if args.dry_run {
+        config.dry_run = true;
+    }
+    // Point every backend command and file read at the remote host before
+    // anything probes for managers, so detection sees the server's system.
+    if let Some(target) = args.host.clone().or_else(|| config.remote.target()) {
+        utils::host::init(target, config.remote.ssh_options.clone());
+    }
+    // Bad plugin definitions fail fast like a malformed config file would;
+    // the error names the offending `plugins.<id>.<field>` key.
+    if let Err(err) = package_managers::plugin::validate(&config.plugins) {
+        eprintln!("pkgtool: {err}");
+        std::process::exit(1);
+    }
+    // Children inherit the merged proxy settings from our environment.
+    utils::proxy::resolve(&config.proxy).export();
+    // stderr is unusable under the alternate screen, so logs go to a file;
+    // --debug overrides the configured level for one-off bug hunts.
+    logging::init(if args.debug {
+        log::LevelFilter::Trace
+    } else {
+        config.log_level.parse().unwrap_or(log::LevelFilter::Info)
+    });
+    i18n::init(&config.locale);
+    // `setup` forces the wizard but otherwise starts the TUI as usual.
+    let force_setup = matches!(args.command, Some(cli::Command::Setup));
+    if args.command.is_some() && !force_setup {
+        if warn_root {
+            eprintln!("pkgtool: running as root; config and cache resolve under /root");
+        }
+        std::process::exit(cli::run(args, config).await);
+    }
+
+    let first_run = !Config::exists();
+    let mut app = App::new(config);
+    if args.profile {
+        app.debug_overlay = true;
+    }
+    if app.package_managers.is_empty() {
+        eprintln!("pkgtool: no supported package manager detected");
+        std::process::exit(1);
+    }
+    if force_setup || first_run {
+        app.open_setup_wizard();
+    }
+    if warn_root {
+        app.message_dialog = Some(app::MessageDialog {
+            title: "Running as root".to_string(),
+            lines: vec![
+                "pkgtool was launched through sudo, so its config, cache and".to_string(),
+                "history will be written under /root.".to_string(),
+                String::new(),
+                "Set root_behavior = \"adapt\" in the config to use your own".to_string(),
+                "directories instead, or \"allow\" to silence this notice.".to_string(),
+            ],
+        });
+    }
+
+    let mut guard = TerminalGuard::new()?;
+    let result = tokio::select! {
+        result = app.run(&mut guard.terminal) => result,
+        // Closing the terminal emulator sends SIGHUP/SIGTERM; stop the loop
+        // so running children are interrupted and the screen is restored.
+        _ = terminal::shutdown_signal() => Ok(()),
+    };
+    app.shutdown().await;
+    drop(guard);
+
+    result
+}
@@ -0,0 +1,621 @@
+use std::collections::{HashMap, HashSet};
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::process::Command;
+
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+    binary_exists, common, OutputLine, PackageDetails, PackageInfo, PackageManager, PackageUpdate,
+};
+use crate::error::{PkgError, Result};
+use crate::utils::privilege::PrivilegeRunner;
+
+/// Best-effort install dates scraped from the dpkg log.
+///
+/// dpkg does not store install times in its status database, so scan
+/// /var/log/dpkg.log (and its first rotation) for "install" entries. Packages
+/// installed before the log rotated away simply get no date.
+async fn dpkg_install_dates() -> HashMap<String, DateTime<Utc>> {
+    let mut dates = HashMap::new();
+    for log in ["/var/log/dpkg.log.1", "/var/log/dpkg.log"] {
+        let Ok(contents) = crate::utils::host::read_file(log) else {
+            continue;
+        };
+        dates.extend(common::parse_dpkg_log(&contents));
+    }
+    dates
+}
+
+/// Backend for Debian/Ubuntu systems driving apt, apt-get and dpkg.
+pub struct AptManager {
+    runner: PrivilegeRunner,
+}
+
+impl AptManager {
+    pub fn new(runner: PrivilegeRunner) -> Self {
+        AptManager { runner }
+    }
+
+    async fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        let mut argv = vec![program.to_string()];
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        super::run_backend(self.id(), &argv).await
+    }
+
+    async fn run_privileged(&self, args: &[&str]) -> Result<String> {
+        let argv = self.runner.wrap(args);
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+        self.run(argv[0], &argv[1..]).await
+    }
+
+    /// Run a privileged command with stdout/stderr forwarded line by line.
+    ///
+    /// When `cancel` fires, the child gets SIGINT first (apt/dpkg roll back
+    /// cleanly where possible) and SIGKILL after a grace period.
+    async fn stream_privileged(
+        &self,
+        args: &[&str],
+        output: &UnboundedSender<OutputLine>,
+        answers: Arc<Mutex<UnboundedReceiver<String>>>,
+        cancel: CancellationToken,
+    ) -> Result<()> {
+        let started = std::time::Instant::now();
+        let argv = crate::utils::host::routed(&self.runner.wrap(args));
+        let mut child = Command::new(&argv[0])
+            .args(&argv[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // Forward any answers from the UI to the child while it runs.
+        let mut stdin = child.stdin.take().expect("stdin piped");
+        let answer_writer = tokio::spawn(async move {
+            let mut answers = answers.lock().await;
+            while let Some(answer) = answers.recv().await {
+                if stdin.write_all(format!("{answer}\n").as_bytes()).await.is_err() {
+                    break;
+                }
+                let _ = stdin.flush().await;
+            }
+        });
+
+        let stdout = child.stdout.take().expect("stdout piped");
+        let stderr = child.stderr.take().expect("stderr piped");
+        let out_tx = output.clone();
+        let err_tx = output.clone();
+        let stderr_tail = tokio::spawn(async move {
+            let mut tail = String::new();
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                tail.push_str(&line);
+                tail.push('\n');
+                let _ = err_tx.send(OutputLine {
+                    text: line,
+                    stderr: true,
+                });
+            }
+            tail
+        });
+        let mut lines = BufReader::new(stdout).lines();
+        let mut cancelled = false;
+        loop {
+            tokio::select! {
+                line = lines.next_line() => {
+                    let Ok(Some(line)) = line else { break };
+                    let _ = out_tx.send(OutputLine {
+                        text: line,
+                        stderr: false,
+                    });
+                }
+                _ = cancel.cancelled() => {
+                    cancelled = true;
+                    if let Some(pid) = child.id() {
+                        let _ = Command::new("kill")
+                            .args(["-INT", &pid.to_string()])
+                            .status()
+                            .await;
+                    }
+                    // Grace period for a clean rollback, then force it.
+                    if tokio::time::timeout(std::time::Duration::from_secs(5), child.wait())
+                        .await
+                        .is_err()
+                    {
+                        let _ = child.start_kill();
+                    }
+                    break;
+                }
+            }
+        }
+
+        if cancelled {
+            answer_writer.abort();
+            stderr_tail.abort();
+            let _ = child.wait().await;
+            return Err(PkgError::Cancelled);
+        }
+        let status = child.wait().await?;
+        answer_writer.abort();
+        let stderr = stderr_tail.await.unwrap_or_default();
+        crate::logging::invocation(
+            self.id(),
+            &argv.join(" "),
+            started.elapsed(),
+            status.code().unwrap_or(-1),
+        );
+        if status.success() {
+            Ok(())
+        } else {
+            Err(PkgError::CommandFailed {
+                command: argv.join(" "),
+                status: status.code().unwrap_or(-1),
+                stderr,
+            })
+        }
+    }
+
+    /// Best-effort map of package name to the suite it was installed from,
+    /// e.g. "jammy-updates" or a PPA suite.
+    async fn installed_origins(&self) -> HashMap<String, String> {
+        match self.run("apt", &["list", "--installed"]).await {
+            Ok(output) => common::parse_apt_installed_origins(&output),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    /// Names of manually installed packages, per `apt-mark showmanual`;
+    /// everything else was pulled in as a dependency.
+    async fn manual_marks(&self) -> Option<HashSet<String>> {
+        let output = self.run("apt-mark", &["showmanual"]).await.ok()?;
+        Some(output.lines().map(|line| line.trim().to_string()).collect())
+    }
+}
+
+#[async_trait]
+impl PackageManager for AptManager {
+    fn id(&self) -> &str {
+        "apt"
+    }
+
+    fn display_name(&self) -> &str {
+        "APT"
+    }
+
+    fn is_available(&self) -> bool {
+        binary_exists("apt-get") && binary_exists("dpkg-query")
+    }
+
+    /// `apt search` runs against the local index, so it stays usable offline.
+    fn network_operations(&self) -> &[&str] {
+        &["refresh", "update", "install"]
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        let output = self
+            .run(
+                "dpkg-query",
+                &[
+                    "-W",
+                    "-f",
+                    "${Package}\t${Version}\t${Installed-Size}\t${binary:Summary}\n",
+                ],
+            )
+            .await?;
+        let install_dates = dpkg_install_dates().await;
+        let origins = self.installed_origins().await;
+        let manual = self.manual_marks().await;
+
+        let mut packages = common::parse_dpkg_query(&output);
+        for package in &mut packages {
+            package.install_date = install_dates.get(&package.name).copied();
+            package.origin = origins.get(&package.name).cloned();
+            package.explicit = manual.as_ref().map(|marks| marks.contains(&package.name));
+        }
+        Ok(packages)
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run("apt-cache", &["search", query]).await?;
+        Ok(common::parse_apt_search(&output))
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            // `--dry-run` resolves the whole transaction without root.
+            let mut args = vec!["install", "-y", "--dry-run"];
+            args.extend(packages.iter().map(String::as_str));
+            self.run("apt-get", &args).await?;
+            return Ok(());
+        }
+        let mut args = vec!["apt-get", "install", "-y"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    /// apt pins versions with `name=version`; downgrades additionally need
+    /// `--allow-downgrades` to get past the resolver.
+    async fn install_version(&self, package: &str, version: &str, dry_run: bool) -> Result<()> {
+        let pinned = format!("{package}={version}");
+        if dry_run {
+            self.run(
+                "apt-get",
+                &["install", "-y", "--allow-downgrades", "--dry-run", &pinned],
+            )
+            .await?;
+            return Ok(());
+        }
+        self.run_privileged(&["apt-get", "install", "-y", "--allow-downgrades", &pinned])
+            .await?;
+        Ok(())
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            let mut args = vec!["remove", "-y", "--dry-run"];
+            args.extend(packages.iter().map(String::as_str));
+            self.run("apt-get", &args).await?;
+            return Ok(());
+        }
+        let mut args = vec!["apt-get", "remove", "-y"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    async fn refresh_metadata(&self) -> Result<()> {
+        self.run_privileged(&["apt-get", "update"]).await?;
+        Ok(())
+    }
+
+    async fn update_system(&self, dry_run: bool) -> Result<()> {
+        if dry_run {
+            // Simulate against the current index; refreshing it would write.
+            self.run("apt-get", &["upgrade", "-y", "--dry-run"]).await?;
+            return Ok(());
+        }
+        self.run_privileged(&["apt-get", "update"]).await?;
+        self.run_privileged(&["apt-get", "upgrade", "-y"]).await?;
+        Ok(())
+    }
+
+    async fn update_system_streaming(
+        &self,
+        output: UnboundedSender<OutputLine>,
+        answers: Arc<Mutex<UnboundedReceiver<String>>>,
+        cancel: CancellationToken,
+        dry_run: bool,
+    ) -> Result<()> {
+        if dry_run {
+            // The plan is short and the simulation quick, so stream it as
+            // one block instead of wiring up the whole child plumbing.
+            let plan = self.run("apt-get", &["upgrade", "-y", "--dry-run"]).await?;
+            for line in plan.lines() {
+                let _ = output.send(OutputLine {
+                    text: line.to_string(),
+                    stderr: false,
+                });
+            }
+            return Ok(());
+        }
+        self.stream_privileged(&["apt-get", "update"], &output, answers.clone(), cancel.clone())
+            .await?;
+        self.stream_privileged(&["apt-get", "upgrade", "-y"], &output, answers, cancel)
+            .await?;
+        Ok(())
+    }
+
+    /// `--only-upgrade` limits the transaction to packages that are
+    /// already installed, so the security subset upgrades without
+    /// pulling anything new in.
+    async fn update_security(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            let mut args = vec!["install", "-y", "--only-upgrade", "--dry-run"];
+            args.extend(packages.iter().map(String::as_str));
+            self.run("apt-get", &args).await?;
+            return Ok(());
+        }
+        let mut args = vec!["apt-get", "install", "-y", "--only-upgrade"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        let output = self.run("apt", &["list", "--upgradable"]).await?;
+        Ok(common::parse_apt_upgradable(&output))
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        self.run_privileged(&["apt-get", "autoremove", "-y"]).await?;
+        self.run_privileged(&["apt-get", "clean"]).await?;
+        Ok(())
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        let output = self.run("apt-cache", &["show", package]).await?;
+        let mut details = PackageDetails {
+            name: package.to_string(),
+            manager: self.id().to_string(),
+            ..Default::default()
+        };
+        let mut in_description = false;
+        for line in output.lines() {
+            if line.is_empty() {
+                break; // only the first stanza
+            }
+            if let Some(rest) = line.strip_prefix(' ') {
+                if in_description {
+                    details.description.push('\n');
+                    details.description.push_str(rest);
+                }
+                continue;
+            }
+            in_description = false;
+            let Some((key, value)) = line.split_once(": ") else {
+                continue;
+            };
+            match key {
+                "Version" => details.version = value.to_string(),
+                "Description" | "Description-en" => {
+                    details.description = value.to_string();
+                    in_description = true;
+                }
+                "Installed-Size" => {
+                    details.size = value.parse::<u64>().ok().map(|kib| kib * 1024);
+                }
+                "Homepage" => details.url = Some(value.to_string()),
+                "Maintainer" => details.packager = Some(value.to_string()),
+                "Depends" => {
+                    details.depends = value
+                        .split(", ")
+                        .map(|d| d.split_whitespace().next().unwrap_or(d).to_string())
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        if details.version.is_empty() {
+            return Err(PkgError::NotFound(package.to_string()));
+        }
+        Ok(details)
+    }
+
+    async fn dependencies(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run("apt-cache", &["depends", package]).await?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("Depends: "))
+            .map(|dep| dep.trim_start_matches('<').trim_end_matches('>').to_string())
+            .collect())
+    }
+
+    /// `showpkg` answers both halves at once: whether the name is a
+    /// real package (it provides itself) and its reverse provides. A
+    /// names-only search fills in the providers' descriptions.
+    async fn providers(&self, name: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run("apt-cache", &["showpkg", name]).await?;
+        let (real, mut names) = common::parse_showpkg(&output);
+        if real {
+            names.insert(0, name.to_string());
+            names.dedup();
+        }
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pattern = format!("^({})$", names.join("|"));
+        let search = self
+            .run("apt-cache", &["search", "--names-only", &pattern])
+            .await
+            .unwrap_or_default();
+        let described: std::collections::HashMap<String, PackageInfo> =
+            common::parse_apt_search(&search)
+                .into_iter()
+                .map(|package| (package.name.clone(), package))
+                .collect();
+        Ok(names
+            .into_iter()
+            .map(|name| {
+                described.get(&name).cloned().unwrap_or_else(|| PackageInfo {
+                    name,
+                    version: String::new(),
+                    description: String::new(),
+                    manager: self.id().to_string(),
+                    installed: false,
+                    size: None,
+                    install_date: None,
+                    origin: None,
+                    explicit: None,
+                })
+            })
+            .collect())
+    }
+
+    async fn optional_dependencies(&self, package: &str) -> Result<Vec<super::OptionalDep>> {
+        let output = self.run("apt-cache", &["depends", package]).await?;
+        Ok(common::parse_apt_weak_deps(&output))
+    }
+
+    /// `apt-cache depends pkg=version` addresses any version the lists
+    /// carry, installed or candidate alike; a failure here means the
+    /// lists do not know that version yet.
+    async fn version_dependencies(&self, package: &str, version: &str) -> Result<Vec<String>> {
+        let spec = format!("{package}={version}");
+        match self.run("apt-cache", &["depends", &spec]).await {
+            Ok(output) => Ok(common::parse_apt_depends(&output)),
+            Err(PkgError::CommandFailed { .. }) => Err(PkgError::NotFound(format!(
+                "{spec} is not in the package lists"
+            ))),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// `apt-get --simulate` resolves the transaction without root and
+    /// prints one "Inst" line per package it would add; sizes come from
+    /// an `apt-cache show` pass over that plan.
+    async fn install_footprint(&self, packages: &[String]) -> Result<super::InstallFootprint> {
+        let mut args = vec!["install", "--simulate", "-qq"];
+        args.extend(packages.iter().map(String::as_str));
+        let plan = self.run("apt-get", &args).await?;
+        let mut footprint = super::InstallFootprint {
+            packages: common::parse_apt_simulate(&plan),
+            ..Default::default()
+        };
+        if !footprint.packages.is_empty() {
+            let mut args = vec!["show"];
+            args.extend(footprint.packages.iter().map(String::as_str));
+            if let Ok(output) = self.run("apt-cache", &args).await {
+                let (download, installed) =
+                    common::parse_apt_show_sizes(&output, &footprint.packages);
+                footprint.download_bytes = download;
+                footprint.installed_bytes = installed;
+            }
+        }
+        Ok(footprint)
+    }
+
+    /// One dpkg-query table covers the whole installed set.
+    async fn all_dependency_edges(
+        &self,
+    ) -> Result<std::collections::HashMap<String, Vec<super::DepEdge>>> {
+        let output = self
+            .run("dpkg-query", &["-W", "-f=${Package}\t${Depends}\n"])
+            .await?;
+        Ok(common::parse_depends_table(&output))
+    }
+
+    async fn essential_packages(&self) -> Result<Vec<String>> {
+        let output = self
+            .run("dpkg-query", &["-W", "-f=${Package}\t${Essential}\n"])
+            .await?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .filter(|(_, essential)| essential.trim() == "yes")
+            .map(|(name, _)| name.to_string())
+            .collect())
+    }
+
+    async fn dependents(&self, package: &str) -> Result<Vec<String>> {
+        let output = self
+            .run("apt-cache", &["rdepends", "--installed", package])
+            .await?;
+        Ok(common::parse_rdepends(&output))
+    }
+
+    async fn installed_files(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run("dpkg", &["-L", package]).await?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('/'))
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn predict_conflicts(&self, packages: &[String]) -> Result<super::ConflictReport> {
+        let mut report = super::ConflictReport::default();
+        let installed: std::collections::HashSet<String> = self
+            .run("dpkg-query", &["-W", "-f=${Package}\n"])
+            .await?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        for package in packages {
+            let Ok(output) = self.run("apt-cache", &["show", package]).await else {
+                continue;
+            };
+            for field in ["Conflicts", "Replaces"] {
+                for name in common::parse_apt_relations(&output, field) {
+                    if installed.contains(&name) && !packages.contains(&name) {
+                        report.conflicts.push(super::Conflict {
+                            package: package.clone(),
+                            with: name,
+                            path: None,
+                        });
+                    }
+                }
+            }
+        }
+        if !binary_exists("apt-file") {
+            report
+                .notes
+                .push("apt-file not installed; file conflict check skipped".to_string());
+            return Ok(report);
+        }
+        if crate::utils::host::is_remote() {
+            report
+                .notes
+                .push("remote host; file conflict check skipped".to_string());
+            return Ok(report);
+        }
+        for package in packages {
+            let Ok(listing) = self.run("apt-file", &["list", package]).await else {
+                continue;
+            };
+            // Only files already on disk can clash, which keeps the
+            // per-path owner queries to a handful at most.
+            for path in common::parse_apt_file_list(&listing) {
+                if !std::path::Path::new(&path).is_file() {
+                    continue;
+                }
+                let Ok(owner_line) = self.run("dpkg-query", &["-S", &path]).await else {
+                    continue;
+                };
+                let owner = owner_line
+                    .lines()
+                    .next()
+                    .and_then(|line| line.split([':', ','].as_ref()).next())
+                    .unwrap_or("")
+                    .trim()
+                    .to_string();
+                if !owner.is_empty() && owner != *package && !packages.contains(&owner) {
+                    report.conflicts.push(super::Conflict {
+                        package: package.clone(),
+                        with: owner,
+                        path: Some(path),
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Packages dpkg considers half-installed or inconsistent; apt's
+    /// one-size-fits-all repair applies to all of them.
+    async fn dependency_problems(&self) -> Result<Vec<super::DepProblem>> {
+        let output = self.run("dpkg", &["--audit"]).await?;
+        Ok(common::parse_dpkg_audit(&output)
+            .into_iter()
+            .map(|(package, detail)| super::DepProblem {
+                manager: self.id().to_string(),
+                package,
+                detail,
+                fix: Some("apt-get --fix-broken install".to_string()),
+            })
+            .collect())
+    }
+
+    async fn hold(&self, package: &str) -> Result<()> {
+        self.run_privileged(&["apt-mark", "hold", package]).await?;
+        Ok(())
+    }
+
+    async fn list_held(&self) -> Result<Vec<String>> {
+        let output = self.run("apt-mark", &["showhold"]).await?;
+        Ok(output.lines().map(str::to_string).collect())
+    }
+
+    async fn unhold(&self, package: &str) -> Result<()> {
+        self.run_privileged(&["apt-mark", "unhold", package]).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+// Not registered by initialize_package_managers yet; kept warning-free until
+// backend detection lands.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+
+use super::{binary_exists, common, PackageDetails, PackageInfo, PackageManager, PackageUpdate};
+use crate::error::{PkgError, Result};
+
+/// Backend for macOS (and Linuxbrew) systems driving Homebrew.
+pub struct BrewManager;
+
+impl BrewManager {
+    pub fn new() -> Self {
+        BrewManager
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String> {
+        let mut argv = vec!["brew".to_string()];
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        super::run_backend(self.id(), &argv).await
+    }
+}
+
+#[async_trait]
+impl PackageManager for BrewManager {
+    fn id(&self) -> &str {
+        "brew"
+    }
+
+    fn display_name(&self) -> &str {
+        "Homebrew"
+    }
+
+    fn is_available(&self) -> bool {
+        binary_exists("brew")
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        let output = self.run(&["list", "--versions"]).await?;
+        Ok(common::parse_brew_list(&output))
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run(&["search", query]).await?;
+        Ok(output
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with("==>"))
+            .map(|name| PackageInfo {
+                name: name.trim().to_string(),
+                version: String::new(),
+                description: String::new(),
+                manager: self.id().to_string(),
+                installed: false,
+                size: None,
+                install_date: None,
+                origin: None,
+                explicit: None,
+            })
+            .collect())
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        let mut args = vec!["install"];
+        if dry_run {
+            args.push("--dry-run");
+        }
+        args.extend(packages.iter().map(String::as_str));
+        self.run(&args).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            // `brew uninstall` has no dry-run flag; record what would run
+            // instead of running anything.
+            log::info!("dry-run: would run brew uninstall {}", packages.join(" "));
+            return Ok(());
+        }
+        let mut args = vec!["uninstall"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run(&args).await?;
+        Ok(())
+    }
+
+    async fn refresh_metadata(&self) -> Result<()> {
+        self.run(&["update"]).await?;
+        Ok(())
+    }
+
+    async fn update_system(&self, dry_run: bool) -> Result<()> {
+        if dry_run {
+            self.run(&["upgrade", "--dry-run"]).await?;
+            return Ok(());
+        }
+        self.run(&["update"]).await?;
+        self.run(&["upgrade"]).await?;
+        Ok(())
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        let output = self.run(&["outdated", "--verbose"]).await?;
+        Ok(common::parse_brew_outdated(&output))
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        self.run(&["cleanup"]).await?;
+        Ok(())
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        let output = self.run(&["info", "--json=v2", package]).await?;
+        let value: serde_json::Value = serde_json::from_str(&output)?;
+        let formula = value
+            .get("formulae")
+            .and_then(|f| f.get(0))
+            .ok_or_else(|| PkgError::NotFound(package.to_string()))?;
+        Ok(PackageDetails {
+            name: package.to_string(),
+            version: formula
+                .pointer("/versions/stable")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            description: formula
+                .get("desc")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string(),
+            manager: self.id().to_string(),
+            size: None,
+            depends: formula
+                .get("dependencies")
+                .and_then(|v| v.as_array())
+                .map(|deps| {
+                    deps.iter()
+                        .filter_map(|d| d.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            url: formula
+                .get("homepage")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+            licenses: formula
+                .get("license")
+                .and_then(|v| v.as_str())
+                .map(|l| vec![l.to_string()])
+                .unwrap_or_default(),
+            packager: None,
+            validation: None,
+        })
+    }
+
+    async fn dependencies(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run(&["deps", package]).await?;
+        Ok(output.lines().map(str::to_string).collect())
+    }
+
+    async fn hold(&self, package: &str) -> Result<()> {
+        self.run(&["pin", package]).await?;
+        Ok(())
+    }
+
+    async fn unhold(&self, package: &str) -> Result<()> {
+        self.run(&["unpin", package]).await?;
+        Ok(())
+    }
+}
+
+impl Default for BrewManager {
+    fn default() -> Self {
+        BrewManager::new()
+    }
+}
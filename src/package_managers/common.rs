@@ -0,0 +1,1236 @@
+//! Pure parsers for backend command output.
+//!
+//! Each backend shells out, then hands the captured text to one of these
+//! functions. Keeping the parsing free of process plumbing means it can be
+//! unit-tested against fixture captures and benchmarked (see
+//! `benches/parsers.rs`) without a package manager installed.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use super::{PackageInfo, PackageUpdate};
+
+/// Parse `dpkg-query -W -f '${Package}\t${Version}\t${Installed-Size}\t${binary:Summary}\n'`.
+/// Install dates and origins come from separate sources; the caller fills
+/// them in afterwards.
+pub fn parse_dpkg_query(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(name), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let size = fields
+            .next()
+            .and_then(|s| s.trim().parse::<u64>().ok())
+            .map(|kib| kib * 1024);
+        let description = fields.next().unwrap_or("").to_string();
+        packages.push(PackageInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            description,
+            manager: "apt".to_string(),
+            installed: true,
+            size,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse a dpkg log for "install" entries, mapping package name to the
+/// install time. Later entries win, matching reinstalls.
+pub fn parse_dpkg_log(contents: &str) -> HashMap<String, DateTime<Utc>> {
+    let mut dates = HashMap::new();
+    for line in contents.lines() {
+        // "2024-05-01 12:34:56 install ripgrep:amd64 <none> 14.1.0-1"
+        let mut parts = line.split_whitespace();
+        let (Some(date), Some(time), Some(action), Some(package)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        if action != "install" {
+            continue;
+        }
+        let Ok(naive) =
+            NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y-%m-%d %H:%M:%S")
+        else {
+            continue;
+        };
+        let name = package.split(':').next().unwrap_or(package);
+        dates.insert(name.to_string(), naive.and_utc());
+    }
+    dates
+}
+
+/// Parse `apt list --installed` into a map of package name to the suite it
+/// was installed from, e.g. "jammy-updates" or a PPA suite.
+pub fn parse_apt_installed_origins(output: &str) -> HashMap<String, String> {
+    let mut origins = HashMap::new();
+    for line in output.lines() {
+        // "vim/jammy-updates,now 2:8.2.3995-1ubuntu2.15 amd64 [installed]"
+        let Some((name, rest)) = line.split_once('/') else {
+            continue;
+        };
+        let suite = rest.split([',', ' ']).next().unwrap_or("");
+        if !suite.is_empty() {
+            origins.insert(name.to_string(), suite.to_string());
+        }
+    }
+    origins
+}
+
+/// Parse `apt-cache search` output: one "name - description" per line.
+pub fn parse_apt_search(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        let Some((name, description)) = line.split_once(" - ") else {
+            continue;
+        };
+        packages.push(PackageInfo {
+            name: name.trim().to_string(),
+            version: String::new(),
+            description: description.trim().to_string(),
+            manager: "apt".to_string(),
+            installed: false,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse `apt list --upgradable` output.
+pub fn parse_apt_upgradable(output: &str) -> Vec<PackageUpdate> {
+    let mut updates = Vec::new();
+    for line in output.lines() {
+        // "vim/jammy-updates 2:8.2.3995-1ubuntu2.15 amd64 [upgradable from: 2:8.2.3995-1ubuntu2.13]"
+        let Some((name, rest)) = line.split_once('/') else {
+            continue;
+        };
+        let mut parts = rest.split_whitespace();
+        let suite = parts.next().unwrap_or("");
+        let Some(new_version) = parts.next() else {
+            continue;
+        };
+        let current_version = line
+            .rsplit_once("upgradable from: ")
+            .map(|(_, v)| v.trim_end_matches(']').to_string())
+            .unwrap_or_default();
+        updates.push(PackageUpdate {
+            name: name.to_string(),
+            current_version,
+            new_version: new_version.to_string(),
+            manager: "apt".to_string(),
+            // e.g. "jammy-security"; Debian spells it "bookworm-security".
+            security: suite.ends_with("-security"),
+        });
+    }
+    updates
+}
+
+/// Parse `rpm -qa --qf '%{NAME}\t%{VERSION}-%{RELEASE}\t%{SIZE}\t%{INSTALLTIME}\t%{SUMMARY}\n'`.
+pub fn parse_rpm_query(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let (Some(name), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let size = fields.next().and_then(|s| s.parse().ok());
+        let install_date = fields
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .and_then(|secs| DateTime::from_timestamp(secs, 0));
+        let description = fields.next().unwrap_or("").to_string();
+        packages.push(PackageInfo {
+            name: name.to_string(),
+            version: version.to_string(),
+            description,
+            manager: "dnf".to_string(),
+            installed: true,
+            size,
+            install_date,
+            origin: None,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse `dnf search` output: "name.arch : summary" lines.
+pub fn parse_dnf_search(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        // "ripgrep.x86_64 : Line-oriented search tool"
+        let Some((name_arch, description)) = line.split_once(" : ") else {
+            continue;
+        };
+        let name = name_arch.rsplit_once('.').map(|(n, _)| n).unwrap_or(name_arch);
+        packages.push(PackageInfo {
+            name: name.to_string(),
+            version: String::new(),
+            description: description.trim().to_string(),
+            manager: "dnf".to_string(),
+            installed: false,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse `dnf repoquery --upgrades --qf '%{name}\t%{evr}\n'` output.
+pub fn parse_dnf_upgrades(output: &str) -> Vec<PackageUpdate> {
+    let mut updates = Vec::new();
+    for line in output.lines() {
+        let Some((name, new_version)) = line.split_once('\t') else {
+            continue;
+        };
+        updates.push(PackageUpdate {
+            name: name.to_string(),
+            current_version: String::new(),
+            new_version: new_version.trim().to_string(),
+            manager: "dnf".to_string(),
+            security: false,
+        });
+    }
+    updates
+}
+
+/// Extract the package name from a versionlock entry like
+/// "httpd-0:2.4.58-1.fc39.*": everything before the first dash that is
+/// followed by a digit (the epoch or version).
+pub fn parse_versionlock_name(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let bytes = line.as_bytes();
+    for (i, window) in bytes.windows(2).enumerate() {
+        if window[0] == b'-' && window[1].is_ascii_digit() {
+            return Some(line[..i].to_string());
+        }
+    }
+    None
+}
+
+/// Parse dnf's human-readable size strings like "4.2 M" or "123 k".
+pub fn parse_dnf_size(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let number: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()? {
+        "k" => 1024.0,
+        "M" => 1024.0 * 1024.0,
+        "G" => 1024.0 * 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// Parse `pacman -Qi` over the whole database: one stanza per package,
+/// which also yields the description, installed size, install date and
+/// install reason.
+/// Origins come from the sync databases; the caller fills them in.
+pub fn parse_pacman_query(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    let mut current: Option<PackageInfo> = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Name" => {
+                packages.extend(current.take());
+                current = Some(PackageInfo {
+                    name: value.to_string(),
+                    version: String::new(),
+                    description: String::new(),
+                    manager: "pacman".to_string(),
+                    installed: true,
+                    size: None,
+                    install_date: None,
+                    origin: None,
+                    explicit: None,
+                });
+            }
+            "Version" => {
+                if let Some(package) = current.as_mut() {
+                    package.version = value.to_string();
+                }
+            }
+            "Description" => {
+                if let Some(package) = current.as_mut() {
+                    package.description = value.to_string();
+                }
+            }
+            "Installed Size" => {
+                if let Some(package) = current.as_mut() {
+                    package.size = parse_pacman_size(value);
+                }
+            }
+            "Install Date" => {
+                if let Some(package) = current.as_mut() {
+                    package.install_date = parse_pacman_date(value);
+                }
+            }
+            "Install Reason" => {
+                if let Some(package) = current.as_mut() {
+                    package.explicit = Some(value.starts_with("Explicitly"));
+                }
+            }
+            _ => {}
+        }
+    }
+    packages.extend(current);
+    packages
+}
+
+/// Parse `pacman -Ss` output: a "repo/name version [installed]" header
+/// line followed by an indented description.
+pub fn parse_pacman_search(output: &str) -> Vec<PackageInfo> {
+    let mut packages = Vec::new();
+    let mut lines = output.lines().peekable();
+    while let Some(line) = lines.next() {
+        // "core/bash 5.2.026-2 [installed]" followed by an indented description
+        if line.starts_with(' ') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let Some(repo_name) = parts.next() else {
+            continue;
+        };
+        let Some(name) = repo_name.split('/').nth(1) else {
+            continue;
+        };
+        let version = parts.next().unwrap_or("").to_string();
+        let repo = repo_name.split('/').next().map(str::to_string);
+        let installed = line.contains("[installed");
+        let description = lines
+            .peek()
+            .filter(|next| next.starts_with(' '))
+            .map(|next| next.trim().to_string())
+            .unwrap_or_default();
+        packages.push(PackageInfo {
+            name: name.to_string(),
+            version,
+            description,
+            manager: "pacman".to_string(),
+            installed,
+            size: None,
+            install_date: None,
+            origin: repo,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse `checkupdates` output: "name current -> new" lines.
+pub fn parse_checkupdates(output: &str) -> Vec<PackageUpdate> {
+    let mut updates = Vec::new();
+    for line in output.lines() {
+        // "linux 6.6.8.arch1-1 -> 6.6.9.arch1-1"
+        let mut parts = line.split_whitespace();
+        let (Some(name), Some(current), Some(_arrow), Some(new)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        updates.push(PackageUpdate {
+            name: name.to_string(),
+            current_version: current.to_string(),
+            new_version: new.to_string(),
+            manager: "pacman".to_string(),
+            security: false,
+        });
+    }
+    updates
+}
+
+/// Extract package names from IgnorePkg lines in a pacman.conf.
+pub fn parse_ignore_pkg(config: &str) -> Vec<String> {
+    config
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim() == "IgnorePkg")
+        .flat_map(|(_, value)| value.split_whitespace().map(str::to_string))
+        .collect()
+}
+
+/// Parse pacman's install date strings, e.g. "Thu 14 Dec 2023 10:44:31 PM CET".
+///
+/// The timezone abbreviation is not reliably parseable, so drop it and treat
+/// the timestamp as local-enough; the rendered relative age does not need
+/// second precision.
+pub fn parse_pacman_date(value: &str) -> Option<DateTime<Utc>> {
+    let without_zone = value.rsplit_once(' ').map(|(rest, _)| rest).unwrap_or(value);
+    for format in ["%a %d %b %Y %I:%M:%S %p", "%a %d %b %Y %H:%M:%S"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(without_zone, format) {
+            return Some(naive.and_utc());
+        }
+    }
+    None
+}
+
+/// Parse pacman's human-readable size strings like "34.42 MiB".
+pub fn parse_pacman_size(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let number: f64 = parts.next()?.parse().ok()?;
+    let multiplier = match parts.next()? {
+        "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+/// Parse `brew list --versions` output: "name version..." with the
+/// highest version winning when several are installed, rather than
+/// trusting brew's output order.
+pub fn parse_brew_list(output: &str) -> Vec<PackageInfo> {
+    use crate::utils::version::{compare, VersionScheme};
+    let mut packages = Vec::new();
+    for line in output.lines() {
+        let mut parts = line.split_whitespace();
+        let Some(name) = parts.next() else { continue };
+        let version = parts
+            .max_by(|a, b| compare(VersionScheme::Semver, a, b))
+            .unwrap_or("")
+            .to_string();
+        packages.push(PackageInfo {
+            name: name.to_string(),
+            version,
+            description: String::new(),
+            manager: "brew".to_string(),
+            installed: true,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        });
+    }
+    packages
+}
+
+/// Parse `brew outdated --verbose` output: "name (current) < new" lines.
+pub fn parse_brew_outdated(output: &str) -> Vec<PackageUpdate> {
+    let mut updates = Vec::new();
+    for line in output.lines() {
+        // "ffmpeg (6.1) < 6.1.1"
+        let mut parts = line.split_whitespace();
+        let Some(name) = parts.next() else { continue };
+        let current = parts
+            .next()
+            .map(|v| v.trim_matches(['(', ')']).to_string())
+            .unwrap_or_default();
+        let new_version = parts.last().unwrap_or("").to_string();
+        updates.push(PackageUpdate {
+            name: name.to_string(),
+            current_version: current,
+            new_version,
+            manager: "brew".to_string(),
+            security: false,
+        });
+    }
+    updates
+}
+
+/// Parse one package's `pacman -Qi` output into typed dependency
+/// edges: Depends On as required, Optional Deps (with their trailing
+/// descriptions and continuation lines) as optional, Provides (minus
+/// any version suffix) as provides.
+pub fn parse_qi_edges(output: &str) -> Vec<super::DepEdge> {
+    use super::{DepEdge, DepKind};
+    let mut edges = Vec::new();
+    let mut in_optional = false;
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once(" : ") {
+            let value = value.trim();
+            in_optional = false;
+            match key.trim() {
+                "Depends On" if value != "None" => {
+                    edges.extend(value.split_whitespace().map(|name| DepEdge {
+                        name: name.to_string(),
+                        kind: DepKind::Required,
+                    }));
+                }
+                "Provides" if value != "None" => {
+                    edges.extend(value.split_whitespace().map(|name| DepEdge {
+                        name: name.split('=').next().unwrap_or(name).to_string(),
+                        kind: DepKind::Provides,
+                    }));
+                }
+                "Optional Deps" if value != "None" => {
+                    in_optional = true;
+                    if let Some(name) = value.split(':').next() {
+                        edges.push(DepEdge {
+                            name: name.trim().to_string(),
+                            kind: DepKind::Optional,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        } else if in_optional {
+            // Continuation lines of Optional Deps are indented
+            // `name: description` pairs.
+            if let Some(name) = line.trim().split(':').next().filter(|name| !name.is_empty()) {
+                edges.push(DepEdge {
+                    name: name.trim().to_string(),
+                    kind: DepKind::Optional,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// Parse `apt-cache showpkg`: whether the name has real versions of its
+/// own, and the packages listed under "Reverse Provides:".
+pub fn parse_showpkg(output: &str) -> (bool, Vec<String>) {
+    let mut real = false;
+    let mut providers = Vec::new();
+    let mut section = "";
+    for line in output.lines() {
+        let header = line.trim_end();
+        if header.ends_with(':') && !line.starts_with(' ') {
+            section = header.trim_end_matches(':');
+            continue;
+        }
+        match section {
+            "Versions" if !line.trim().is_empty() => real = true,
+            "Reverse Provides" => {
+                if let Some(name) = line.split_whitespace().next() {
+                    providers.push(name.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    providers.sort();
+    providers.dedup();
+    (real, providers)
+}
+
+/// Optional Deps entries of a `pacman -Qi`/`-Si` stanza as name plus
+/// annotation, continuation lines included. The trailing "[installed]"
+/// marker is dropped; callers check the installed set themselves.
+pub fn parse_optional_deps(output: &str) -> Vec<super::OptionalDep> {
+    fn entry(line: &str) -> Option<super::OptionalDep> {
+        let line = line.trim().trim_end_matches("[installed]").trim_end();
+        if line.is_empty() || line == "None" {
+            return None;
+        }
+        let (name, note) = match line.split_once(':') {
+            Some((name, note)) => (
+                name.trim(),
+                Some(note.trim().to_string()).filter(|note| !note.is_empty()),
+            ),
+            None => (line, None),
+        };
+        Some(super::OptionalDep {
+            name: name.to_string(),
+            note,
+        })
+    }
+    let mut deps = Vec::new();
+    let mut in_optional = false;
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once(" : ") {
+            in_optional = key.trim() == "Optional Deps";
+            if in_optional {
+                deps.extend(entry(value));
+            }
+        } else if in_optional {
+            deps.extend(entry(line));
+        }
+    }
+    deps
+}
+
+/// Recommends/Suggests lines of `apt-cache depends`; alternatives are
+/// prefixed with `|` and virtual targets wrapped in angle brackets.
+pub fn parse_apt_weak_deps(output: &str) -> Vec<super::OptionalDep> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches('|');
+            line.strip_prefix("Recommends: ")
+                .or_else(|| line.strip_prefix("Suggests: "))
+        })
+        .map(|name| name.trim().trim_start_matches('<').trim_end_matches('>'))
+        .filter(|name| !name.is_empty())
+        .map(|name| super::OptionalDep {
+            name: name.to_string(),
+            note: None,
+        })
+        .collect()
+}
+
+/// Depends/Pre-Depends lines of `apt-cache depends`, reduced to sorted
+/// bare names; alternatives are prefixed with `|` and virtual targets
+/// wrapped in angle brackets, like the weak-dependency lines above.
+pub fn parse_apt_depends(output: &str) -> Vec<String> {
+    let mut deps: Vec<String> = output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches('|');
+            line.strip_prefix("Depends: ")
+                .or_else(|| line.strip_prefix("PreDepends: "))
+        })
+        .map(|name| name.trim().trim_start_matches('<').trim_end_matches('>'))
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect();
+    deps.sort();
+    deps.dedup();
+    deps
+}
+
+/// Cumulative (download, installed) bytes over a multi-stanza
+/// `pacman -Si` dump; a total is None when no stanza carried the field.
+pub fn parse_si_size_totals(output: &str) -> (Option<u64>, Option<u64>) {
+    let mut download = None;
+    let mut installed = None;
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        let total = match key.trim() {
+            "Download Size" => &mut download,
+            "Installed Size" => &mut installed,
+            _ => continue,
+        };
+        if let Some(bytes) = parse_pacman_size(value.trim()) {
+            *total = Some(total.unwrap_or(0) + bytes);
+        }
+    }
+    (download, installed)
+}
+
+/// Packages an `apt-get install --simulate -qq` plan would install, one
+/// "Inst name (version ...)" line each.
+pub fn parse_apt_simulate(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Inst "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Cumulative (download, installed) bytes over `apt-cache show`
+/// stanzas: Size is in bytes, Installed-Size in KiB. Only each
+/// package's first stanza counts; later ones are older versions.
+pub fn parse_apt_show_sizes(output: &str, packages: &[String]) -> (Option<u64>, Option<u64>) {
+    let mut download = None;
+    let mut installed = None;
+    let mut pending: std::collections::HashSet<&str> =
+        packages.iter().map(String::as_str).collect();
+    let mut counting = false;
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("Package: ") {
+            counting = pending.remove(name.trim());
+            continue;
+        }
+        if !counting {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Size: ") {
+            if let Ok(bytes) = value.trim().parse::<u64>() {
+                download = Some(download.unwrap_or(0) + bytes);
+            }
+        } else if let Some(value) = line.strip_prefix("Installed-Size: ") {
+            if let Ok(kib) = value.trim().parse::<u64>() {
+                installed = Some(installed.unwrap_or(0) + kib * 1024);
+            }
+        }
+    }
+    (download, installed)
+}
+
+/// Split a whole `pacman -Qi` dump into per-package edge lists. Stanzas
+/// are blank-line separated; each is keyed by its Name field and parsed
+/// with [`parse_qi_edges`].
+pub fn parse_qi_edge_table(
+    output: &str,
+) -> std::collections::HashMap<String, Vec<super::DepEdge>> {
+    let mut table = std::collections::HashMap::new();
+    for stanza in output.split("\n\n") {
+        let name = stanza.lines().find_map(|line| {
+            let (key, value) = line.split_once(" : ")?;
+            (key.trim() == "Name").then(|| value.trim().to_string())
+        });
+        if let Some(name) = name {
+            table.insert(name, parse_qi_edges(stanza));
+        }
+    }
+    table
+}
+
+/// Per-package required edges from `dpkg-query -W -f='${Package}\t${Depends}\n'`.
+/// Dependency entries are comma-separated with `|` alternatives and
+/// parenthesised version constraints; every alternative becomes an edge
+/// so any satisfying package keeps the graph connected.
+pub fn parse_depends_table(
+    output: &str,
+) -> std::collections::HashMap<String, Vec<super::DepEdge>> {
+    use super::{DepEdge, DepKind};
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(name, depends)| {
+            let edges = depends
+                .split([',', '|'])
+                .filter_map(|entry| entry.split_whitespace().next())
+                .map(|dep| DepEdge {
+                    // "libc6:amd64 (>= 2.34)" names the bare package.
+                    name: dep.split(':').next().unwrap_or(dep).to_string(),
+                    kind: DepKind::Required,
+                })
+                .collect();
+            (name.to_string(), edges)
+        })
+        .collect()
+}
+
+/// Per-package required edges from
+/// `rpm -qa --qf '%{NAME}\t[%{REQUIRENAME} ]\n'`. REQUIRENAME lists
+/// capabilities, so rpmlib() and config() markers and file paths are
+/// dropped; soname capabilities stay and simply dangle when no package
+/// node carries that name.
+pub fn parse_requires_table(
+    output: &str,
+) -> std::collections::HashMap<String, Vec<super::DepEdge>> {
+    use super::{DepEdge, DepKind};
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(name, requires)| {
+            let edges = requires
+                .split_whitespace()
+                .filter(|cap| !cap.contains('(') && !cap.starts_with('/'))
+                .map(|cap| DepEdge {
+                    name: cap.to_string(),
+                    kind: DepKind::Required,
+                })
+                .collect();
+            (name.to_string(), edges)
+        })
+        .collect()
+}
+
+/// Value list of one whitespace-separated field in a `pacman -Si`/`-Qi`
+/// stanza, e.g. "Conflicts With". "None" yields the empty list and
+/// version constraints are stripped from each name.
+pub fn parse_qi_field(output: &str, field: &str) -> Vec<String> {
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        if key.trim() != field {
+            continue;
+        }
+        let value = value.trim();
+        if value == "None" {
+            return Vec::new();
+        }
+        return value
+            .split_whitespace()
+            .map(|name| {
+                name.split(['=', '<', '>'])
+                    .next()
+                    .unwrap_or(name)
+                    .to_string()
+            })
+            .collect();
+    }
+    Vec::new()
+}
+
+/// Comma-separated relationship values of an `apt-cache show` field
+/// ("Conflicts", "Replaces"), version constraints stripped.
+pub fn parse_apt_relations(output: &str, field: &str) -> Vec<String> {
+    let prefix = format!("{field}: ");
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix(&prefix))
+        .flat_map(|value| value.split(','))
+        .filter_map(|entry| entry.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Absolute paths from `pacman -Fl` output, whose "pkg usr/bin/x" lines
+/// carry the path relative to the filesystem root.
+pub fn parse_fl_files(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(_, path)| format!("/{}", path.trim()))
+        .collect()
+}
+
+/// Paths from `apt-file list` output: "pkg: /path" lines.
+pub fn parse_apt_file_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .map(|(_, path)| path.trim().to_string())
+        .filter(|path| path.starts_with('/'))
+        .collect()
+}
+
+/// Map of absolute path to owning package from "owner path" lines, the
+/// shape of `pacman -Ql` and `rpm -qa --qf '[%{NAME} %{FILENAMES}\n]'`.
+pub fn parse_file_owners(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (owner, path) = line.split_once(' ')?;
+            let path = path.trim();
+            path.starts_with('/')
+                .then(|| (path.to_string(), owner.to_string()))
+        })
+        .collect()
+}
+
+/// Parse `dpkg --audit`: prose section headers (possibly spanning
+/// several lines, the last ending with ':') followed by indented
+/// " package  description" rows. Each row becomes (package, section).
+pub fn parse_dpkg_audit(output: &str) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+    let mut header: Vec<&str> = Vec::new();
+    let mut in_entries = false;
+    for line in output.lines() {
+        if line.starts_with(' ') {
+            in_entries = true;
+            if let Some(name) = line.split_whitespace().next() {
+                let section = header.join(" ");
+                problems.push((
+                    name.to_string(),
+                    section.trim_end_matches(':').to_string(),
+                ));
+            }
+        } else if !line.trim().is_empty() {
+            if in_entries {
+                header.clear();
+                in_entries = false;
+            }
+            header.push(line.trim());
+        }
+    }
+    problems
+}
+
+/// Parse `pacman -Dk` errors of the form
+/// "error: missing 'dep' dependency for 'pkg'" into (pkg, dep) pairs.
+pub fn parse_pacman_dk(output: &str) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+    for line in output.lines() {
+        let line = line.trim().trim_start_matches("error:").trim();
+        let Some(rest) = line.strip_prefix("missing '") else {
+            continue;
+        };
+        let Some((dep, rest)) = rest.split_once('\'') else {
+            continue;
+        };
+        let Some(package) = rest
+            .trim()
+            .strip_prefix("dependency for '")
+            .and_then(|rest| rest.strip_suffix('\''))
+        else {
+            continue;
+        };
+        problems.push((package.to_string(), dep.to_string()));
+    }
+    problems
+}
+
+/// Parse `dnf repoquery --unsatisfied` "pkg requires dep" lines into
+/// (pkg, requirement) pairs, tolerating a leading "package " and a
+/// trailing version in the package spec.
+pub fn parse_unsatisfied(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .trim_start_matches("package ")
+                .split_once(" requires ")
+        })
+        .map(|(package, requirement)| {
+            let name = package.split_whitespace().next().unwrap_or(package);
+            (name.to_string(), requirement.trim().to_string())
+        })
+        .collect()
+}
+
+/// Parse `apt-cache rdepends --installed`: the package and a "Reverse
+/// Depends:" header, then one indented dependent per line, some marked
+/// with a leading `|` for or-dependencies.
+pub fn parse_rdepends(output: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for line in output
+        .lines()
+        .skip_while(|line| !line.trim().eq_ignore_ascii_case("Reverse Depends:"))
+        .skip(1)
+    {
+        let name = line.trim().trim_start_matches('|').trim();
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Walk `pacman -Qi` stanzas collecting Name, Packager and Validated By,
+/// flagging each completed stanza through `provenance_flag`.
+pub fn parse_pacman_provenance(output: &str) -> HashMap<String, String> {
+    let mut flagged = HashMap::new();
+    let (mut name, mut packager, mut validation) = (None, None, None);
+    for line in output.lines().chain(std::iter::once("")) {
+        if line.trim().is_empty() {
+            if let (Some(name), Some(reason)) = (
+                name.take(),
+                super::provenance_flag("pacman", packager.take(), validation.take()),
+            ) {
+                flagged.insert(name, reason);
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(" : ") else {
+            continue;
+        };
+        match key.trim() {
+            "Name" => name = Some(value.trim().to_string()),
+            "Packager" => packager = Some(value.trim()),
+            "Validated By" => validation = Some(value.trim()),
+            _ => {}
+        }
+    }
+    flagged
+}
+
+/// Flag tab-separated `name<TAB>packager[<TAB>signature]` survey lines.
+pub fn parse_tabular_provenance(manager: &str, output: &str) -> HashMap<String, String> {
+    let mut flagged = HashMap::new();
+    for line in output.lines() {
+        let mut fields = line.split('\t');
+        let Some(name) = fields.next().filter(|name| !name.is_empty()) else {
+            continue;
+        };
+        let packager = fields.next();
+        let validation = fields.next();
+        if let Some(reason) = super::provenance_flag(manager, packager, validation) {
+            flagged.insert(name.to_string(), reason);
+        }
+    }
+    flagged
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dpkg_query_lines_parse_into_packages() {
+        let output = "vim\t2:8.2.3995-1ubuntu2.15\t3664\tVi IMproved\nbroken line\n";
+        let packages = parse_dpkg_query(output);
+        assert_eq!(packages.len(), 1); // the tab-less line is skipped
+        assert_eq!(packages[0].name, "vim");
+        assert_eq!(packages[0].size, Some(3664 * 1024));
+        assert_eq!(packages[0].description, "Vi IMproved");
+    }
+
+    #[test]
+    fn apt_upgradable_flags_security_suites() {
+        let output = "openssl/jammy-security 3.0.2-0ubuntu1.15 amd64 [upgradable from: 3.0.2-0ubuntu1.14]\n\
+                      vim/jammy-updates 2:8.2.3995-1ubuntu2.15 amd64 [upgradable from: 2:8.2.3995-1ubuntu2.13]\n";
+        let updates = parse_apt_upgradable(output);
+        assert_eq!(updates.len(), 2);
+        assert!(updates[0].security);
+        assert!(!updates[1].security);
+        assert_eq!(updates[1].current_version, "2:8.2.3995-1ubuntu2.13");
+    }
+
+    #[test]
+    fn qi_fields_and_apt_relations_strip_version_constraints() {
+        let stanza = "Name            : pipewire\nConflicts With  : pulseaudio jack2<2.0\nReplaces        : None\n";
+        assert_eq!(parse_qi_field(stanza, "Conflicts With"), vec!["pulseaudio", "jack2"]);
+        assert!(parse_qi_field(stanza, "Replaces").is_empty());
+        let show = "Package: mariadb-server\nConflicts: mysql-server (<< 8.0), mydumper\n";
+        assert_eq!(
+            parse_apt_relations(show, "Conflicts"),
+            vec!["mysql-server", "mydumper"]
+        );
+    }
+
+    #[test]
+    fn file_listings_normalize_to_absolute_paths_with_owners() {
+        let fl = "ripgrep usr/bin/rg\nripgrep usr/share/man/man1/rg.1.gz\n";
+        assert_eq!(parse_fl_files(fl)[0], "/usr/bin/rg");
+        let ql = "fd /usr/bin/fd\nnoise without path\n";
+        assert_eq!(
+            parse_file_owners(ql).get("/usr/bin/fd"),
+            Some(&"fd".to_string())
+        );
+        let apt_file = "bat: /usr/bin/batcat\n";
+        assert_eq!(parse_apt_file_list(apt_file), vec!["/usr/bin/batcat"]);
+    }
+
+    #[test]
+    fn dpkg_audit_sections_attach_to_their_packages() {
+        let output = "The following packages are missing the list control file in the\n\
+                      database, they need to be reinstalled:\n\
+                      \x20libfoo1              shared foo library\n\
+                      \n\
+                      The following packages are only half configured:\n\
+                      \x20bar                  a bar tool\n";
+        let problems = parse_dpkg_audit(output);
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].0, "libfoo1");
+        assert!(problems[0].1.ends_with("they need to be reinstalled"));
+        assert_eq!(problems[1], ("bar".to_string(), "The following packages are only half configured".to_string()));
+    }
+
+    #[test]
+    fn pacman_dk_errors_yield_package_and_dependency() {
+        let output = "error: missing 'libalpm.so=13-64' dependency for 'pacman-contrib'\n\
+                      some unrelated noise\n";
+        assert_eq!(
+            parse_pacman_dk(output),
+            vec![("pacman-contrib".to_string(), "libalpm.so=13-64".to_string())]
+        );
+    }
+
+    #[test]
+    fn unsatisfied_lines_strip_the_package_spec() {
+        let output = "package foo-1.0-1.fc39.x86_64 requires libbar.so.1()(64bit)\n\
+                      baz requires qux >= 2.0\n";
+        let problems = parse_unsatisfied(output);
+        assert_eq!(problems[0], ("foo-1.0-1.fc39.x86_64".to_string(), "libbar.so.1()(64bit)".to_string()));
+        assert_eq!(problems[1], ("baz".to_string(), "qux >= 2.0".to_string()));
+    }
+
+    #[test]
+    fn rdepends_skips_the_header_and_alternation_bars() {
+        let output = "libssl3\nReverse Depends:\n  openssl\n |wget\n  openssl\n";
+        assert_eq!(parse_rdepends(output), vec!["openssl", "wget"]);
+    }
+
+    #[test]
+    fn showpkg_separates_real_versions_from_reverse_provides() {
+        let virtual_only = "Package: mail-transport-agent\n\
+                            Versions: \n\
+                            \n\
+                            Reverse Depends: \n\
+                            Dependencies: \n\
+                            Provides: \n\
+                            Reverse Provides: \n\
+                            postfix 3.7.11-0\n\
+                            exim4-daemon-light 4.96-15\n";
+        let (real, providers) = parse_showpkg(virtual_only);
+        assert!(!real);
+        assert_eq!(providers, vec!["exim4-daemon-light", "postfix"]);
+        let real_package = "Package: postfix\n\
+                            Versions: \n\
+                            3.7.11-0 (/var/lib/apt/lists/x)\n\
+                            \n\
+                            Reverse Provides: \n";
+        assert!(parse_showpkg(real_package).0);
+    }
+
+    #[test]
+    fn optional_deps_keep_annotations_and_drop_installed_markers() {
+        let qi = "Name            : gimp\n\
+                  Optional Deps   : cups: printing support [installed]\n\
+                    ghostscript: PostScript support\n\
+                    gutenprint\n";
+        let deps = parse_optional_deps(qi);
+        let rows: Vec<(&str, Option<&str>)> = deps
+            .iter()
+            .map(|dep| (dep.name.as_str(), dep.note.as_deref()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("cups", Some("printing support")),
+                ("ghostscript", Some("PostScript support")),
+                ("gutenprint", None),
+            ]
+        );
+        assert!(parse_optional_deps("Optional Deps   : None\n").is_empty());
+        let depends = "curl\n  Depends: libc6\n  Recommends: ca-certificates\n \
+                       |Suggests: <www-browser>\n";
+        let weak: Vec<String> = parse_apt_weak_deps(depends)
+            .into_iter()
+            .map(|dep| dep.name)
+            .collect();
+        assert_eq!(weak, vec!["ca-certificates", "www-browser"]);
+    }
+
+    #[test]
+    fn apt_depends_keep_hard_edges_and_drop_weak_ones() {
+        let depends = "curl\n  PreDepends: init-system-helpers\n  Depends: libc6\n \
+                       |Depends: <mail-transport-agent>\n  Depends: libcurl4\n \
+                       Recommends: ca-certificates\n  Depends: libc6\n";
+        assert_eq!(
+            parse_apt_depends(depends),
+            vec![
+                "init-system-helpers",
+                "libc6",
+                "libcurl4",
+                "mail-transport-agent",
+            ]
+        );
+    }
+
+    #[test]
+    fn footprint_parsers_total_plan_sizes() {
+        let si = "Name            : ffmpeg\n\
+                  Download Size   : 10.00 MiB\n\
+                  Installed Size  : 30.00 MiB\n\
+                  \n\
+                  Name            : x264\n\
+                  Download Size   : 1.00 MiB\n\
+                  Installed Size  : 2.00 MiB\n";
+        assert_eq!(
+            parse_si_size_totals(si),
+            (Some(11 * 1024 * 1024), Some(32 * 1024 * 1024))
+        );
+        let plan = "Inst libx264 (0.164 Debian:12/stable [amd64])\n\
+                    Inst ffmpeg (7:5.1 Debian:12/stable [amd64])\n\
+                    Conf ffmpeg (7:5.1 Debian:12/stable [amd64])\n";
+        let names = parse_apt_simulate(plan);
+        assert_eq!(names, vec!["libx264", "ffmpeg"]);
+        // ffmpeg's second stanza is an older version and must not count.
+        let show = "Package: ffmpeg\n\
+                    Installed-Size: 2048\n\
+                    Size: 1000000\n\
+                    \n\
+                    Package: ffmpeg\n\
+                    Installed-Size: 9999\n\
+                    Size: 9999999\n\
+                    \n\
+                    Package: libx264\n\
+                    Installed-Size: 1024\n\
+                    Size: 500000\n";
+        assert_eq!(
+            parse_apt_show_sizes(show, &names),
+            (Some(1_500_000), Some(3072 * 1024))
+        );
+    }
+
+    #[test]
+    fn bulk_edge_tables_key_by_package_and_drop_capabilities() {
+        use super::super::DepKind;
+        let qi = "Name            : a\n\
+                  Depends On      : b\n\
+                  \n\
+                  Name            : b\n\
+                  Depends On      : None\n";
+        let table = parse_qi_edge_table(qi);
+        assert_eq!(table.len(), 2);
+        assert_eq!(table["a"][0].name, "b");
+        assert!(table["b"].is_empty());
+        let dpkg = "wget\tlibc6:amd64 (>= 2.34), libssl3 | libssl1.1\n";
+        let edges = &parse_depends_table(dpkg)["wget"];
+        let names: Vec<&str> = edges.iter().map(|edge| edge.name.as_str()).collect();
+        assert_eq!(names, vec!["libc6", "libssl3", "libssl1.1"]);
+        assert!(edges.iter().all(|edge| edge.kind == DepKind::Required));
+        let rpm = "curl\tlibcurl.so.4()(64bit) rpmlib(PayloadIsZstd) /bin/sh bash \n";
+        let names: Vec<String> = parse_requires_table(rpm)["curl"]
+            .iter()
+            .map(|edge| edge.name.clone())
+            .collect();
+        assert_eq!(names, vec!["bash"]);
+    }
+
+    #[test]
+    fn qi_edges_type_the_three_dependency_kinds() {
+        use super::super::DepKind;
+        let output = "Name            : openssl\n\
+                      Depends On      : glibc  zlib\n\
+                      Optional Deps   : ca-certificates: trust store [installed]\n\
+                        perl: for the tools\n\
+                      Provides        : libcrypto.so=3-64\n";
+        let edges = parse_qi_edges(output);
+        let kinds: Vec<(&str, DepKind)> = edges
+            .iter()
+            .map(|edge| (edge.name.as_str(), edge.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("glibc", DepKind::Required),
+                ("zlib", DepKind::Required),
+                ("ca-certificates", DepKind::Optional),
+                ("perl", DepKind::Optional),
+                ("libcrypto.so", DepKind::Provides),
+            ]
+        );
+    }
+
+    #[test]
+    fn pacman_provenance_flags_local_builds_and_unvalidated_installs() {
+        let output = "Name            : mytool\n\
+                      Packager        : Unknown Packager\n\
+                      Validated By    : None\n\
+                      \n\
+                      Name            : bash\n\
+                      Packager        : Arch Linux <builder@archlinux.org>\n\
+                      Validated By    : Signature\n\
+                      \n\
+                      Name            : sidegrade\n\
+                      Packager        : Someone <x@example.com>\n\
+                      Validated By    : None\n";
+        let flagged = parse_pacman_provenance(output);
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged["mytool"], "locally built (Unknown Packager)");
+        assert_eq!(flagged["sidegrade"], "installed without signature validation");
+    }
+
+    #[test]
+    fn tabular_provenance_flags_unsigned_rpms() {
+        let output = "bash\tFedora Project\tRSA/SHA256, Key ID abc\n\
+                      homebuilt\tMe\t(none)\n";
+        let flagged = parse_tabular_provenance("dnf", output);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged["homebuilt"], "unsigned rpm");
+    }
+
+    #[test]
+    fn pacman_stanzas_parse_into_packages() {
+        let output = "Name            : bash\nVersion         : 5.2.026-2\n\
+                      Description     : The GNU Bourne Again shell\n\
+                      Installed Size  : 9.17 MiB\n\n\
+                      Name            : ripgrep\nVersion         : 14.1.0-1\n";
+        let packages = parse_pacman_query(output);
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].size, Some((9.17 * 1024.0 * 1024.0) as u64));
+        assert_eq!(packages[1].version, "14.1.0-1");
+    }
+
+    #[test]
+    fn checkupdates_lines_carry_both_versions() {
+        let updates = parse_checkupdates("linux 6.6.8.arch1-1 -> 6.6.9.arch1-1\n");
+        assert_eq!(updates[0].current_version, "6.6.8.arch1-1");
+        assert_eq!(updates[0].new_version, "6.6.9.arch1-1");
+    }
+
+}
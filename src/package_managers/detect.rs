@@ -0,0 +1,150 @@
+use super::binary_exists;
+use crate::config::Config;
+
+/// Every backend this build knows, with the binaries detection probes for.
+pub const KNOWN: [(&str, &str, &[&str]); 4] = [
+    ("apt", "APT", &["apt-get", "dpkg-query"]),
+    ("dnf", "DNF", &["dnf"]),
+    ("pacman", "Pacman", &["pacman"]),
+    ("brew", "Homebrew", &["brew"]),
+];
+
+/// One backend's detection verdict, with the reasoning spelled out so the
+/// Settings tab and the debug log can show why a manager is or is not used.
+pub struct Candidate {
+    pub id: &'static str,
+    #[allow(dead_code)] // shown once the Settings tab exists
+    pub display_name: &'static str,
+    pub included: bool,
+    /// Why the candidate was included or skipped.
+    pub reason: String,
+}
+
+/// Decide which backends to use on this system, in priority order.
+///
+/// The distro's native manager (from /etc/os-release ID and ID_LIKE) comes
+/// first, then managers that are not tied to a distro (Homebrew). A manager
+/// whose binary merely exists — dnf installed inside a toolbox on an Ubuntu
+/// host, say — is skipped rather than queried against the wrong system.
+/// `force_managers` and `disabled_managers` in the config override either
+/// direction.
+pub fn detect(config: &Config) -> Vec<Candidate> {
+    let ids = os_release_ids();
+    let native = native_manager(&ids);
+    let mut included = Vec::new();
+    let mut skipped = Vec::new();
+    for (id, display_name, binaries) in KNOWN {
+        let missing: Vec<&str> = binaries
+            .iter()
+            .copied()
+            .filter(|binary| !binary_exists(binary))
+            .collect();
+        let candidate = |included: bool, reason: String| Candidate {
+            id,
+            display_name,
+            included,
+            reason,
+        };
+        if config.disabled_managers.iter().any(|m| m == id) {
+            skipped.push(candidate(false, "disabled in config".to_string()));
+        } else if !missing.is_empty() {
+            skipped.push(candidate(
+                false,
+                format!("binary not found: {}", missing.join(", ")),
+            ));
+        } else if config.force_managers.iter().any(|m| m == id) {
+            included.push(candidate(true, "force-enabled in config".to_string()));
+        } else if native == Some(id) {
+            // The native manager leads the ordering.
+            included.insert(0, candidate(true, native_reason(&ids)));
+        } else if id == "brew" {
+            included.push(candidate(true, "user-space manager present".to_string()));
+        } else {
+            skipped.push(candidate(
+                false,
+                match native {
+                    Some(native) => {
+                        format!("present but not the system's native manager ({native} is)")
+                    }
+                    None => "present but the distro is unrecognized".to_string(),
+                },
+            ));
+        }
+    }
+    included.extend(skipped);
+    included
+}
+
+fn native_reason(ids: &[String]) -> String {
+    match ids.first() {
+        Some(id) => format!("native manager for {id}"),
+        None => "native manager".to_string(),
+    }
+}
+
+/// ID and ID_LIKE tokens from the managed host's /etc/os-release, most
+/// specific first. Missing file (macOS, containers without it) yields an
+/// empty list.
+pub fn os_release_ids() -> Vec<String> {
+    let Ok(contents) = crate::utils::host::read_file("/etc/os-release") else {
+        return Vec::new();
+    };
+    parse_os_release_ids(&contents)
+}
+
+fn parse_os_release_ids(contents: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+    for key in ["ID", "ID_LIKE"] {
+        for line in contents.lines() {
+            if let Some(value) = line.strip_prefix(&format!("{key}=")) {
+                let value = value.trim_matches('"');
+                ids.extend(value.split_whitespace().map(|id| id.to_lowercase()));
+            }
+        }
+    }
+    ids
+}
+
+/// The manager a distro family installs its system with, if recognized.
+fn native_manager(ids: &[String]) -> Option<&'static str> {
+    for id in ids {
+        let native = match id.as_str() {
+            "debian" | "ubuntu" | "linuxmint" | "pop" | "raspbian" => Some("apt"),
+            "fedora" | "rhel" | "centos" | "rocky" | "almalinux" => Some("dnf"),
+            "arch" | "manjaro" | "endeavouros" => Some("pacman"),
+            _ => None,
+        };
+        if native.is_some() {
+            return native;
+        }
+    }
+    // No os-release at all usually means macOS, where Homebrew is native.
+    if ids.is_empty() && binary_exists("brew") {
+        return Some("brew");
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn os_release_prefers_id_over_id_like() {
+        let ids = parse_os_release_ids("ID=ubuntu\nID_LIKE=debian\nNAME=\"Ubuntu\"\n");
+        assert_eq!(ids, ["ubuntu", "debian"]);
+        assert_eq!(native_manager(&ids), Some("apt"));
+    }
+
+    #[test]
+    fn id_like_resolves_derivatives() {
+        let ids = parse_os_release_ids("ID=neon\nID_LIKE=\"ubuntu debian\"\n");
+        assert_eq!(native_manager(&ids), Some("apt"));
+    }
+
+    #[test]
+    fn unknown_distros_have_no_native_manager() {
+        let ids = parse_os_release_ids("ID=nixos\n");
+        assert_eq!(native_manager(&ids), None);
+    }
+}
@@ -0,0 +1,486 @@
+// Not registered by initialize_package_managers yet; kept warning-free until
+// backend detection lands.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+
+use super::{binary_exists, common, PackageDetails, PackageInfo, PackageManager, PackageUpdate};
+use crate::error::{PkgError, Result};
+use crate::utils::privilege::PrivilegeRunner;
+
+/// Backend for Fedora/RHEL systems driving dnf and rpm.
+pub struct DnfManager {
+    runner: PrivilegeRunner,
+}
+
+impl DnfManager {
+    pub fn new(runner: PrivilegeRunner) -> Self {
+        DnfManager { runner }
+    }
+
+    async fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        let mut argv = vec![program.to_string()];
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        super::run_backend(self.id(), &argv).await
+    }
+
+    async fn run_privileged(&self, args: &[&str]) -> Result<String> {
+        let argv = self.runner.wrap(args);
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+        self.run(argv[0], &argv[1..]).await
+    }
+
+    /// Resolve a transaction without running it. dnf has no dry-run flag,
+    /// but `--assumeno` prints the full plan and then declines it, which it
+    /// reports as exit code 1 with no error output; nothing needs root.
+    async fn simulate(&self, args: &[&str]) -> Result<()> {
+        match self.run("dnf", args).await {
+            Ok(_) => Ok(()),
+            Err(PkgError::CommandFailed { status: 1, stderr, .. }) if stderr.trim().is_empty() => {
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[async_trait]
+impl PackageManager for DnfManager {
+    fn id(&self) -> &str {
+        "dnf"
+    }
+
+    fn display_name(&self) -> &str {
+        "DNF"
+    }
+
+    fn is_available(&self) -> bool {
+        binary_exists("dnf")
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        let output = self
+            .run(
+                "rpm",
+                &[
+                    "-qa",
+                    "--qf",
+                    "%{NAME}\t%{VERSION}-%{RELEASE}\t%{SIZE}\t%{INSTALLTIME}\t%{SUMMARY}\n",
+                ],
+            )
+            .await?;
+        let mut packages = common::parse_rpm_query(&output);
+        // from_repo needs the dnf history db; skip origins when unavailable.
+        if let Ok(output) = self
+            .run(
+                "dnf",
+                &["repoquery", "--installed", "--qf", "%{name}\t%{from_repo}\n"],
+            )
+            .await
+        {
+            let mut repos = std::collections::HashMap::new();
+            for line in output.lines() {
+                if let Some((name, repo)) = line.split_once('\t') {
+                    repos.insert(name.to_string(), repo.trim().to_string());
+                }
+            }
+            for package in &mut packages {
+                package.origin = repos.get(&package.name).cloned().filter(|r| !r.is_empty());
+            }
+        }
+        Ok(packages)
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run("dnf", &["-q", "search", query]).await?;
+        Ok(common::parse_dnf_search(&output))
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            let mut args = vec!["install", "--assumeno"];
+            args.extend(packages.iter().map(String::as_str));
+            return self.simulate(&args).await;
+        }
+        let mut args = vec!["dnf", "install", "-y"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    /// dnf pins versions with `name-version`; `--allowerasing` lets the
+    /// resolver swap out the newer build on a downgrade.
+    async fn install_version(&self, package: &str, version: &str, dry_run: bool) -> Result<()> {
+        let pinned = format!("{package}-{version}");
+        if dry_run {
+            return self.simulate(&["install", "--assumeno", &pinned]).await;
+        }
+        self.run_privileged(&["dnf", "install", "-y", "--allowerasing", &pinned])
+            .await?;
+        Ok(())
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            let mut args = vec!["remove", "--assumeno"];
+            args.extend(packages.iter().map(String::as_str));
+            return self.simulate(&args).await;
+        }
+        let mut args = vec!["dnf", "remove", "-y"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    async fn refresh_metadata(&self) -> Result<()> {
+        self.run_privileged(&["dnf", "makecache"]).await?;
+        Ok(())
+    }
+
+    async fn update_system(&self, dry_run: bool) -> Result<()> {
+        if dry_run {
+            return self.simulate(&["upgrade", "--assumeno"]).await;
+        }
+        self.run_privileged(&["dnf", "upgrade", "-y"]).await?;
+        Ok(())
+    }
+
+    /// dnf resolves the security subset itself from updateinfo, so the
+    /// named packages are advisory only.
+    async fn update_security(&self, _packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            return self.simulate(&["upgrade", "--security", "--assumeno"]).await;
+        }
+        self.run_privileged(&["dnf", "upgrade", "--security", "-y"]).await?;
+        Ok(())
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        // check-update exits 100 when updates are available.
+        let output = match self.run("dnf", &["-q", "check-update"]).await {
+            Ok(_) => return Ok(Vec::new()),
+            Err(PkgError::CommandFailed { status: 100, .. }) => {
+                self.run("dnf", &["-q", "repoquery", "--upgrades", "--qf", "%{name}\t%{evr}\n"])
+                    .await?
+            }
+            Err(err) => return Err(err),
+        };
+        let mut updates = common::parse_dnf_upgrades(&output);
+        // Flag the security subset via updateinfo; best effort, since the
+        // listing is useful without it.
+        if let Ok(advisories) = self.run("dnf", &["-q", "updateinfo", "list", "--security"]).await {
+            for update in &mut updates {
+                let nevra = format!("{}-{}", update.name, update.new_version);
+                update.security = advisories.lines().any(|line| {
+                    line.split_whitespace()
+                        .last()
+                        .is_some_and(|column| column.starts_with(&nevra))
+                });
+            }
+        }
+        Ok(updates)
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        self.run_privileged(&["dnf", "clean", "all"]).await?;
+        self.run_privileged(&["dnf", "autoremove", "-y"]).await?;
+        Ok(())
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        let output = self.run("dnf", &["-q", "info", package]).await?;
+        let mut details = PackageDetails {
+            name: package.to_string(),
+            manager: self.id().to_string(),
+            ..Default::default()
+        };
+        let mut in_description = false;
+        for line in output.lines() {
+            if let Some((key, value)) = line.split_once(" : ") {
+                let value = value.trim();
+                match key.trim() {
+                    "Version" => details.version = value.to_string(),
+                    "Size" => details.size = common::parse_dnf_size(value),
+                    "URL" => details.url = Some(value.to_string()),
+                    "License" => details.licenses = vec![value.to_string()],
+                    "Description" => {
+                        details.description = value.to_string();
+                        in_description = true;
+                    }
+                    _ => in_description = false,
+                }
+            } else if in_description {
+                let cont = line.trim_start_matches([' ', ':']).trim();
+                if !cont.is_empty() {
+                    details.description.push('\n');
+                    details.description.push_str(cont);
+                }
+            }
+        }
+        if details.version.is_empty() {
+            return Err(PkgError::NotFound(package.to_string()));
+        }
+        // dnf info omits the builder; rpm has it. Best-effort: a failure
+        // just leaves the provenance fields empty.
+        if let Ok(output) = self.run("rpm", &["-qi", package]).await {
+            for line in output.lines() {
+                let Some((key, value)) = line.split_once(':') else {
+                    continue;
+                };
+                match key.trim() {
+                    "Packager" => details.packager = Some(value.trim().to_string()),
+                    "Signature" => details.validation = Some(value.trim().to_string()),
+                    _ => {}
+                }
+            }
+        }
+        Ok(details)
+    }
+
+    async fn dependencies(&self, package: &str) -> Result<Vec<String>> {
+        let output = self
+            .run("dnf", &["-q", "repoquery", "--requires", "--resolve", "--qf", "%{name}\n", package])
+            .await?;
+        let mut deps: Vec<String> = output.lines().map(str::to_string).collect();
+        deps.sort();
+        deps.dedup();
+        Ok(deps)
+    }
+
+    /// `--whatprovides` answers for real and virtual names alike; a
+    /// real package appears in its own provider list.
+    async fn providers(&self, name: &str) -> Result<Vec<PackageInfo>> {
+        let output = self
+            .run(
+                "dnf",
+                &["-q", "repoquery", "--whatprovides", name, "--qf", "%{name}\t%{version}\t%{summary}\n"],
+            )
+            .await?;
+        let mut seen = std::collections::HashSet::new();
+        Ok(output
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let name = parts.next()?.trim();
+                if name.is_empty() || !seen.insert(name.to_string()) {
+                    return None;
+                }
+                Some(PackageInfo {
+                    name: name.to_string(),
+                    version: parts.next().unwrap_or("").trim().to_string(),
+                    description: parts.next().unwrap_or("").trim().to_string(),
+                    manager: self.id().to_string(),
+                    installed: false,
+                    size: None,
+                    install_date: None,
+                    origin: None,
+                    explicit: None,
+                })
+            })
+            .collect())
+    }
+
+    /// Weak dependencies resolved to package names; rpm metadata has no
+    /// annotation strings, so the notes stay empty.
+    async fn optional_dependencies(&self, package: &str) -> Result<Vec<super::OptionalDep>> {
+        let mut names = Vec::new();
+        for flag in ["--recommends", "--suggests"] {
+            let output = self
+                .run("dnf", &["-q", "repoquery", flag, "--resolve", "--qf", "%{name}\n", package])
+                .await?;
+            names.extend(output.lines().map(str::to_string));
+        }
+        names.sort();
+        names.dedup();
+        Ok(names
+            .into_iter()
+            .map(|name| super::OptionalDep { name, note: None })
+            .collect())
+    }
+
+    /// repoquery addresses an exact version as `name-version`. The
+    /// installed copy may already be gone from the repositories, so the
+    /// installed database is asked first, then the enabled repos.
+    async fn version_dependencies(&self, package: &str, version: &str) -> Result<Vec<String>> {
+        let spec = format!("{package}-{version}");
+        for scope in [&["--installed"][..], &[][..]] {
+            let mut args =
+                vec!["-q", "repoquery", "--requires", "--resolve", "--qf", "%{name}\n"];
+            args.extend_from_slice(scope);
+            args.push(&spec);
+            let Ok(output) = self.run("dnf", &args).await else {
+                continue;
+            };
+            let mut deps: Vec<String> = output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            if deps.is_empty() {
+                continue;
+            }
+            deps.sort();
+            deps.dedup();
+            return Ok(deps);
+        }
+        Err(PkgError::NotFound(format!(
+            "{spec} is in neither the installed set nor the enabled repositories"
+        )))
+    }
+
+    /// One rpm query covers the whole installed set; edges name
+    /// capabilities rather than packages, which is close enough for a
+    /// whole-graph pass.
+    async fn all_dependency_edges(
+        &self,
+    ) -> Result<std::collections::HashMap<String, Vec<super::DepEdge>>> {
+        let output = self
+            .run("rpm", &["-qa", "--qf", "%{NAME}\t[%{REQUIRENAME} ]\n"])
+            .await?;
+        Ok(common::parse_requires_table(&output))
+    }
+
+    /// dnf's protected packages: the lists under /etc/dnf/protected.d
+    /// plus dnf itself, which is always protected.
+    async fn essential_packages(&self) -> Result<Vec<String>> {
+        let mut names = vec!["dnf".to_string()];
+        for entry in std::fs::read_dir("/etc/dnf/protected.d")
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let Ok(content) = crate::utils::host::read_file(&entry.path().to_string_lossy())
+            else {
+                continue;
+            };
+            names.extend(
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string),
+            );
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    async fn installed_files(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run("rpm", &["-ql", package]).await?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with('/'))
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn predict_conflicts(&self, packages: &[String]) -> Result<super::ConflictReport> {
+        let mut report = super::ConflictReport::default();
+        let installed: std::collections::HashSet<String> = self
+            .run("rpm", &["-qa", "--qf", "%{NAME}\n"])
+            .await?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        for package in packages {
+            if let Ok(output) = self
+                .run("dnf", &["-q", "repoquery", "--conflicts", package])
+                .await
+            {
+                for line in output.lines() {
+                    // Capabilities may carry a version constraint
+                    // ("foo < 2.0"); the name is the first token.
+                    let Some(name) = line.split_whitespace().next() else {
+                        continue;
+                    };
+                    if installed.contains(name) && !packages.iter().any(|p| p == name) {
+                        report.conflicts.push(super::Conflict {
+                            package: package.clone(),
+                            with: name.to_string(),
+                            path: None,
+                        });
+                    }
+                }
+            }
+        }
+        if crate::utils::host::is_remote() {
+            report
+                .notes
+                .push("remote host; file conflict check skipped".to_string());
+            return Ok(report);
+        }
+        for package in packages {
+            let Ok(listing) = self.run("dnf", &["-q", "repoquery", "-l", package]).await else {
+                continue;
+            };
+            // Only files already on disk can clash, which keeps the
+            // per-path owner queries to a handful at most.
+            for path in listing.lines().map(str::trim) {
+                if !path.starts_with('/') || !std::path::Path::new(path).is_file() {
+                    continue;
+                }
+                let Ok(owner) = self
+                    .run("rpm", &["-qf", "--qf", "%{NAME}", path])
+                    .await
+                else {
+                    continue;
+                };
+                let owner = owner.trim().to_string();
+                if !owner.is_empty() && owner != *package && !packages.contains(&owner) {
+                    report.conflicts.push(super::Conflict {
+                        package: package.clone(),
+                        with: owner,
+                        path: Some(path.to_string()),
+                    });
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    async fn dependency_problems(&self) -> Result<Vec<super::DepProblem>> {
+        let output = self
+            .run("dnf", &["-q", "repoquery", "--unsatisfied"])
+            .await?;
+        Ok(common::parse_unsatisfied(&output)
+            .into_iter()
+            .map(|(package, requirement)| super::DepProblem {
+                manager: self.id().to_string(),
+                package: package.clone(),
+                detail: format!("requires {requirement}"),
+                fix: Some(format!("dnf distro-sync {package}")),
+            })
+            .collect())
+    }
+
+    async fn dependents(&self, package: &str) -> Result<Vec<String>> {
+        let output = self
+            .run("dnf", &["-q", "repoquery", "--installed", "--whatrequires", package, "--qf", "%{name}\n"])
+            .await?;
+        let mut names: Vec<String> = output.lines().map(str::to_string).collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    async fn hold(&self, package: &str) -> Result<()> {
+        self.run_privileged(&["dnf", "versionlock", "add", package]).await?;
+        Ok(())
+    }
+
+    async fn unhold(&self, package: &str) -> Result<()> {
+        self.run_privileged(&["dnf", "versionlock", "delete", package]).await?;
+        Ok(())
+    }
+
+    async fn list_held(&self) -> Result<Vec<String>> {
+        let output = self.run("dnf", &["versionlock", "list"]).await?;
+        Ok(output
+            .lines()
+            .filter_map(common::parse_versionlock_name)
+            .collect())
+    }
+}
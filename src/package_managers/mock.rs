@@ -0,0 +1,153 @@
+use async_trait::async_trait;
+
+use super::{PackageDetails, PackageInfo, PackageManager, PackageUpdate};
+use crate::error::{PkgError, Result};
+
+/// Test-only backend injected through the `PKGTOOL_MOCK_MANAGER` environment
+/// variable, so integration tests can drive the CLI against predictable
+/// behavior instead of a real package manager.
+///
+/// The variable holds space-separated `key=value` pairs:
+/// `fail=<pkg,pkg>` packages whose install/remove fails, `updates=<n>`
+/// how many pending updates to report, `security=<n>` how many of those
+/// count as security updates. An empty value is a manager where
+/// everything succeeds and nothing is outdated.
+pub struct MockManager {
+    fail: Vec<String>,
+    updates: usize,
+    security: usize,
+}
+
+impl MockManager {
+    pub fn from_spec(spec: &str) -> Self {
+        let mut fail = Vec::new();
+        let mut updates = 0;
+        let mut security = 0;
+        for pair in spec.split_whitespace() {
+            match pair.split_once('=') {
+                Some(("fail", list)) => {
+                    fail = list.split(',').map(str::to_string).collect();
+                }
+                Some(("updates", count)) => {
+                    updates = count.parse().unwrap_or(0);
+                }
+                Some(("security", count)) => {
+                    security = count.parse().unwrap_or(0);
+                }
+                _ => {}
+            }
+        }
+        MockManager {
+            fail,
+            updates,
+            security,
+        }
+    }
+
+    fn check(&self, operation: &str, packages: &[String]) -> Result<()> {
+        if let Some(package) = packages.iter().find(|p| self.fail.contains(p)) {
+            return Err(PkgError::CommandFailed {
+                command: format!("mock {operation} {package}"),
+                status: 1,
+                stderr: format!("simulated failure for {package}"),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PackageManager for MockManager {
+    fn id(&self) -> &str {
+        "mock"
+    }
+
+    fn display_name(&self) -> &str {
+        "Mock"
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn network_operations(&self) -> &[&str] {
+        &[]
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        Ok(vec![PackageInfo {
+            name: query.to_string(),
+            version: "1.0".to_string(),
+            description: "mock result".to_string(),
+            manager: self.id().to_string(),
+            installed: false,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        }])
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        // A dry run still resolves, so a failing package fails either way.
+        let _ = dry_run;
+        self.check("install", packages)
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        let _ = dry_run;
+        self.check("remove", packages)
+    }
+
+    async fn update_system(&self, _dry_run: bool) -> Result<()> {
+        Ok(())
+    }
+
+    async fn update_security(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        let _ = dry_run;
+        self.check("update-security", packages)
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        // The first `security` updates carry the flag.
+        Ok((0..self.updates)
+            .map(|i| PackageUpdate {
+                name: format!("mockpkg{i}"),
+                current_version: "1.0".to_string(),
+                new_version: "1.1".to_string(),
+                manager: self.id().to_string(),
+                security: i < self.security,
+            })
+            .collect())
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        Err(PkgError::NotFound(package.to_string()))
+    }
+
+    async fn dependencies(&self, _package: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn hold(&self, package: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("hold {package}"),
+        })
+    }
+
+    async fn unhold(&self, package: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("unhold {package}"),
+        })
+    }
+}
@@ -0,0 +1,709 @@
+pub mod apt;
+pub mod brew;
+pub mod common;
+pub mod detect;
+pub mod dnf;
+pub mod mock;
+pub mod pacman;
+pub mod plugin;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::error::{PkgError, Result};
+use crate::utils::privilege::PrivilegeRunner;
+
+/// A single package as reported by a backend, either installed or available.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// Identifier of the backend that reported this package ("apt", "pacman", ...).
+    pub manager: String,
+    pub installed: bool,
+    /// Installed size in bytes, when the backend reports it.
+    pub size: Option<u64>,
+    /// When the package was installed, where the backend can tell.
+    #[serde(default)]
+    pub install_date: Option<DateTime<Utc>>,
+    /// Repository the package came from ("main", "extra", "AUR", ...).
+    #[serde(default)]
+    pub origin: Option<String>,
+    /// Whether the package was installed explicitly rather than pulled in
+    /// as a dependency; `None` when the backend cannot tell.
+    #[serde(default)]
+    pub explicit: Option<bool>,
+}
+
+/// A pending upgrade for an installed package.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageUpdate {
+    pub name: String,
+    pub current_version: String,
+    pub new_version: String,
+    pub manager: String,
+    /// Whether the update comes from a security channel, for backends that
+    /// can tell (apt's `-security` suites); false when unknown.
+    #[serde(default)]
+    pub security: bool,
+}
+
+/// Extended metadata for a single package, shown in the details pane.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PackageDetails {
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub manager: String,
+    pub size: Option<u64>,
+    pub depends: Vec<String>,
+    pub url: Option<String>,
+    pub licenses: Vec<String>,
+    /// Who built the package: pacman's Packager, rpm's Packager, apt's
+    /// Maintainer. None when the backend does not expose it.
+    #[serde(default)]
+    pub packager: Option<String>,
+    /// How the install was validated: pacman's "Validated By", rpm's
+    /// Signature line. None when the backend does not expose it.
+    #[serde(default)]
+    pub validation: Option<String>,
+}
+
+/// How one package needs another in the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepKind {
+    /// A hard dependency; removing the target breaks the package.
+    Required,
+    /// An optional dependency; absent without breaking anything.
+    Optional,
+    /// A virtual name the package provides rather than depends on.
+    Provides,
+}
+
+/// One directed dependency edge as a backend reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepEdge {
+    pub name: String,
+    pub kind: DepKind,
+}
+
+/// One optional or weak dependency: pacman's optdepends, Debian's
+/// Recommends/Suggests, rpm's weak deps. Only pacman annotates what the
+/// extra package is for; the note is absent elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionalDep {
+    pub name: String,
+    /// The backend's annotation, e.g. "printing support".
+    pub note: Option<String>,
+}
+
+/// One predicted install conflict: a file another installed package
+/// owns, or a declared Conflicts:/Replaces: relationship from metadata.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    /// The package about to be installed.
+    pub package: String,
+    /// The installed package it collides with.
+    pub with: String,
+    /// The clashing path for file conflicts; None for declared ones.
+    pub path: Option<String>,
+}
+
+/// What the pre-install conflict check found, with notes for the parts
+/// that could not run (e.g. a missing file database).
+#[derive(Debug, Clone, Default)]
+pub struct ConflictReport {
+    pub conflicts: Vec<Conflict>,
+    pub notes: Vec<String>,
+}
+
+/// The true cost of an install: every package it would newly pull in,
+/// with cumulative sizes. Sizes are None when the backend's metadata
+/// does not expose them.
+#[derive(Debug, Clone, Default)]
+pub struct InstallFootprint {
+    /// The packages the install would add, the targets included.
+    pub packages: Vec<String>,
+    pub download_bytes: Option<u64>,
+    pub installed_bytes: Option<u64>,
+}
+
+impl InstallFootprint {
+    /// The one-line form the details pane and confirmation dialog show,
+    /// e.g. "23 new packages, 410 MiB download, 1.4 GiB installed".
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} new package(s)", self.packages.len())];
+        if let Some(bytes) = self.download_bytes {
+            parts.push(format!("{} download", crate::utils::format_size(bytes)));
+        }
+        if let Some(bytes) = self.installed_bytes {
+            parts.push(format!("{} installed", crate::utils::format_size(bytes)));
+        }
+        parts.join(", ")
+    }
+}
+
+/// One broken or unsatisfied dependency found by a backend's
+/// consistency check, with the repair command the distro suggests.
+#[derive(Debug, Clone)]
+pub struct DepProblem {
+    pub manager: String,
+    /// The package whose dependencies are broken.
+    pub package: String,
+    /// What is wrong, e.g. "missing dependency libfoo.so".
+    pub detail: String,
+    /// A runnable repair command, when the distro has one.
+    pub fix: Option<String>,
+}
+
+/// One line of live output from a running backend command.
+#[derive(Debug, Clone)]
+pub struct OutputLine {
+    pub text: String,
+    /// Whether the line came from the child's stderr.
+    pub stderr: bool,
+}
+
+/// Common interface implemented by every package manager backend.
+#[async_trait]
+pub trait PackageManager: Send + Sync {
+    /// Short identifier used in the UI and registry keys ("apt", "pacman",
+    /// or a plugin's configured id).
+    fn id(&self) -> &str;
+
+    /// Human-readable name shown in the UI.
+    #[allow(dead_code)] // used once manager pickers exist
+    fn display_name(&self) -> &str;
+
+    /// Whether the backend's binary is present on this system.
+    #[allow(dead_code)] // registration goes through detect::detect now
+    fn is_available(&self) -> bool;
+
+    /// Operations that need the network, blocked in offline mode. Backends
+    /// whose search runs against a local index narrow this down.
+    fn network_operations(&self) -> &[&str] {
+        &["refresh", "update", "install", "search"]
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>>;
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>>;
+
+    /// With `dry_run` set, resolve and report the transaction without
+    /// changing the system: backends use their simulate variant where one
+    /// exists and must never spawn a privileged command with write intent.
+    /// Backends without a simulate variant log the command that would have
+    /// run and skip it.
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()>;
+
+    /// See `install` for the `dry_run` contract.
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()>;
+
+    /// Install packages one at a time, reporting a per-package outcome so
+    /// callers can tell a partial failure from a total one. Backends that
+    /// can attribute failures within a single transaction may override this.
+    async fn install_each(&self, packages: &[String], dry_run: bool) -> Vec<(String, Result<()>)> {
+        let mut results = Vec::new();
+        for package in packages {
+            let result = self.install(std::slice::from_ref(package), dry_run).await;
+            results.push((package.clone(), result));
+        }
+        results
+    }
+
+    /// Install a specific version of a package, used when restoring a
+    /// recorded package set. Backends whose CLI cannot pin a version
+    /// report the operation as unsupported.
+    async fn install_version(&self, package: &str, version: &str, dry_run: bool) -> Result<()> {
+        let _ = dry_run;
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("install {package} at version {version}"),
+        })
+    }
+
+    /// Per-package counterpart of `remove`; see `install_each`.
+    async fn remove_each(&self, packages: &[String], dry_run: bool) -> Vec<(String, Result<()>)> {
+        let mut results = Vec::new();
+        for package in packages {
+            let result = self.remove(std::slice::from_ref(package), dry_run).await;
+            results.push((package.clone(), result));
+        }
+        results
+    }
+
+    /// Refresh repository metadata without upgrading anything.
+    async fn refresh_metadata(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Refresh metadata and upgrade all packages. See `install` for the
+    /// `dry_run` contract; a dry run skips the metadata refresh too.
+    async fn update_system(&self, dry_run: bool) -> Result<()>;
+
+    /// Like `update_system`, but streaming live stdout/stderr lines through
+    /// `output`. Lines arriving on `answers` are written to the child's
+    /// stdin, so the UI can answer interactive prompts, and `cancel` firing
+    /// terminates the running command (SIGINT, then SIGKILL). Backends
+    /// without streaming support fall back to the plain update.
+    async fn update_system_streaming(
+        &self,
+        output: tokio::sync::mpsc::UnboundedSender<OutputLine>,
+        answers: Arc<tokio::sync::Mutex<tokio::sync::mpsc::UnboundedReceiver<String>>>,
+        cancel: tokio_util::sync::CancellationToken,
+        dry_run: bool,
+    ) -> Result<()> {
+        let _ = (output, answers, cancel);
+        self.update_system(dry_run).await
+    }
+
+    /// Apply only the pending updates that fix vulnerabilities. Backends
+    /// with native support resolve the subset themselves and ignore
+    /// `packages` (dnf's `--security`); the rest upgrade exactly the named
+    /// packages. The default refuses, which is correct wherever partial
+    /// upgrades are unsupported (pacman).
+    async fn update_security(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        let _ = (packages, dry_run);
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: "security-only update (partial upgrades)".to_string(),
+        })
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>>;
+
+    async fn clean_cache(&self) -> Result<()>;
+
+    async fn details(&self, package: &str) -> Result<PackageDetails>;
+
+    #[allow(dead_code)] // used by DependencyManager once wired up
+    async fn dependencies(&self, package: &str) -> Result<Vec<String>>;
+
+    /// Packages the distro treats as essential or protected, which a
+    /// removal must never sweep up silently: dpkg's Essential flag, the
+    /// pacman base group, dnf's protected packages. The default reports
+    /// none.
+    async fn essential_packages(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Absolute paths of the files an installed package owns. The
+    /// default reports the query as unsupported.
+    async fn installed_files(&self, package: &str) -> Result<Vec<String>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("file list of {package}"),
+        })
+    }
+
+    /// Predict conflicts before installing `packages`: files already
+    /// owned by other installed packages, plus declared Conflicts: and
+    /// Replaces: relationships against the installed set. Backends note
+    /// the checks they had to skip instead of failing the whole
+    /// prediction. The default reports the check as unsupported.
+    async fn predict_conflicts(&self, packages: &[String]) -> Result<ConflictReport> {
+        let _ = packages;
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: "conflict prediction".to_string(),
+        })
+    }
+
+    /// What installing `packages` would really cost, from the backend's
+    /// simulate-install plan: the new packages pulled in and their
+    /// cumulative download and installed sizes. The default reports the
+    /// query as unsupported; callers fall back to the dependency
+    /// closure minus the installed set, which has no sizes.
+    async fn install_footprint(&self, packages: &[String]) -> Result<InstallFootprint> {
+        let _ = packages;
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: "install footprint".to_string(),
+        })
+    }
+
+    /// Broken or unsatisfied dependencies in the installed system, from
+    /// the backend's own consistency check (`dpkg --audit`, `pacman
+    /// -Dk`, `dnf repoquery --unsatisfied`). The default reports the
+    /// check as unsupported.
+    async fn dependency_problems(&self) -> Result<Vec<DepProblem>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: "dependency health check".to_string(),
+        })
+    }
+
+    /// Installed packages that directly require this one, for the
+    /// reverse side of the dependency graph. The default reports the
+    /// query as unsupported.
+    async fn dependents(&self, package: &str) -> Result<Vec<String>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("reverse dependencies of {package}"),
+        })
+    }
+
+    /// Direct dependency edges with their kinds. The default wraps
+    /// `dependencies` as required edges; backends that can tell optional
+    /// dependencies and provides apart override it.
+    async fn dependency_edges(&self, package: &str) -> Result<Vec<DepEdge>> {
+        Ok(self
+            .dependencies(package)
+            .await?
+            .into_iter()
+            .map(|name| DepEdge {
+                name,
+                kind: DepKind::Required,
+            })
+            .collect())
+    }
+
+    /// Packages that provide a (possibly virtual) name: `awk`,
+    /// `java-runtime`, `mail-transport-agent`. A real package provides
+    /// itself and appears in its own list. The default reports the
+    /// query as unsupported.
+    async fn providers(&self, name: &str) -> Result<Vec<PackageInfo>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("resolving providers of {name}"),
+        })
+    }
+
+    /// The package's optional dependencies with their annotations, for
+    /// the optional-dependency browser. The default reports the query
+    /// as unsupported.
+    async fn optional_dependencies(&self, package: &str) -> Result<Vec<OptionalDep>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("optional dependencies of {package}"),
+        })
+    }
+
+    /// One dependency-edge list per installed package, fetched in a
+    /// single backend query. Whole-graph passes like cycle detection
+    /// need this; expanding thousands of packages one query at a time
+    /// is too slow. The default reports the query as unsupported.
+    async fn all_dependency_edges(&self) -> Result<HashMap<String, Vec<DepEdge>>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: "bulk dependency listing".to_string(),
+        })
+    }
+
+    /// Required dependencies of one exact version of a package —
+    /// installed or repository candidate, whichever of the backend's
+    /// databases describes it. `Err(NotFound)` when none does, e.g. a
+    /// candidate the mirrors have not published yet.
+    async fn version_dependencies(&self, package: &str, version: &str) -> Result<Vec<String>> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("dependencies of {package} {version}"),
+        })
+    }
+
+    async fn hold(&self, package: &str) -> Result<()>;
+
+    async fn unhold(&self, package: &str) -> Result<()>;
+
+    /// Names of packages currently held back from upgrades. Backends without
+    /// a hold mechanism report none.
+    async fn list_held(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Build the registry of usable package managers for this system, in
+/// priority order: the distro's native manager first.
+///
+/// Distro detection decides what actually runs (see `detect::detect`); the
+/// outcome of every candidate, included or skipped, goes to the debug log.
+pub fn initialize_package_managers(config: &Config) -> Vec<(String, Arc<dyn PackageManager>)> {
+    // Integration tests substitute a predictable backend for the real ones.
+    if let Ok(spec) = std::env::var("PKGTOOL_MOCK_MANAGER") {
+        return vec![(
+            "mock".to_string(),
+            Arc::new(mock::MockManager::from_spec(&spec)) as Arc<dyn PackageManager>,
+        )];
+    }
+    let runner = PrivilegeRunner::new(&config.escalation);
+    let mut managers: Vec<(String, Arc<dyn PackageManager>)> = Vec::new();
+    for candidate in detect::detect(config) {
+        log::debug!(
+            "detection: {} {} ({})",
+            candidate.id,
+            if candidate.included { "included" } else { "skipped" },
+            candidate.reason
+        );
+        if candidate.included {
+            if let Some(manager) = construct(candidate.id, &runner) {
+                managers.push((candidate.id.to_string(), manager));
+            }
+        }
+    }
+    // Plugin backends come last, in a stable order. Their definitions were
+    // validated at startup; a failure here only drops that one plugin.
+    let mut plugin_ids: Vec<&String> = config.plugins.keys().collect();
+    plugin_ids.sort();
+    for id in plugin_ids {
+        match plugin::CommandPluginManager::from_config(id, &config.plugins[id]) {
+            Ok(manager) => managers.push((id.clone(), Arc::new(manager))),
+            Err(err) => log::error!("plugin {id} disabled: {err}"),
+        }
+    }
+    managers
+}
+
+/// Instantiate the backend for a detected id. brew never escalates, so it
+/// takes no runner.
+fn construct(id: &str, runner: &PrivilegeRunner) -> Option<Arc<dyn PackageManager>> {
+    match id {
+        "apt" => Some(Arc::new(apt::AptManager::new(runner.clone()))),
+        "dnf" => Some(Arc::new(dnf::DnfManager::new(runner.clone()))),
+        "pacman" => Some(Arc::new(pacman::PacmanManager::new(runner.clone()))),
+        "brew" => Some(Arc::new(brew::BrewManager::new())),
+        _ => None,
+    }
+}
+
+/// Detection outcome for one known backend, usable or not.
+pub struct Detection {
+    pub id: &'static str,
+    pub display_name: &'static str,
+    /// `None` when the backend is usable; otherwise why detection failed
+    /// (e.g. which binary is missing), for the setup wizard.
+    pub unavailable: Option<String>,
+}
+
+/// Probe every known backend, including the unusable ones.
+///
+/// Unlike `initialize_package_managers`, this reports what *could* run —
+/// the setup wizard offers any backend whose binaries exist and says why
+/// the others are missing, independent of the distro's native choice.
+pub fn detect_all() -> Vec<Detection> {
+    detect::KNOWN
+        .iter()
+        .map(|(id, display_name, binaries)| {
+            let missing: Vec<&str> = binaries
+                .iter()
+                .copied()
+                .filter(|binary| !binary_exists(binary))
+                .collect();
+            Detection {
+                id,
+                display_name,
+                unavailable: if missing.is_empty() {
+                    None
+                } else {
+                    Some(format!("binary not found: {}", missing.join(", ")))
+                },
+            }
+        })
+        .collect()
+}
+
+/// Whether an origin refers to a third-party source (PPA, COPR, AUR, ...)
+/// rather than a distribution repository.
+pub fn is_third_party_origin(origin: &str) -> bool {
+    let lower = origin.to_lowercase();
+    lower == "aur"
+        || lower.contains("ppa")
+        || lower.contains("copr")
+        || lower.contains("rpmfusion")
+}
+
+/// Whether an origin names an official distribution repository for
+/// `manager`. Stricter than `!is_third_party_origin`: pacman and dnf
+/// origins are repo ids, so anything outside the distro's own set is
+/// third-party even without a telltale name. apt origins are suites,
+/// where a vendor archive is indistinguishable from the distro's own,
+/// so only the pattern check applies there.
+pub fn is_official_origin(manager: &str, origin: &str) -> bool {
+    if is_third_party_origin(origin) {
+        return false;
+    }
+    let lower = origin.to_lowercase();
+    match manager {
+        "pacman" => matches!(
+            lower.as_str(),
+            "core" | "extra" | "community" | "multilib" | "testing"
+                | "core-testing" | "extra-testing" | "multilib-testing"
+        ),
+        "dnf" => {
+            ["fedora", "updates", "rawhide", "baseos", "appstream", "crb"]
+                .iter()
+                .any(|prefix| lower.starts_with(prefix))
+        }
+        _ => true,
+    }
+}
+
+/// Why a package's provenance deserves a second look, or `None` when
+/// the packager and validation data look like a trusted builder's.
+/// pacman stamps locally built packages "Unknown Packager" and records
+/// how the install was validated; rpm shows "(none)" for an unsigned
+/// package. apt has no per-package signature, so only a missing
+/// maintainer stands out there.
+pub fn provenance_flag(
+    manager: &str,
+    packager: Option<&str>,
+    validation: Option<&str>,
+) -> Option<String> {
+    let packager = packager.unwrap_or("").trim();
+    let validation = validation.unwrap_or("").trim();
+    match manager {
+        "pacman" => {
+            if packager == "Unknown Packager" {
+                Some("locally built (Unknown Packager)".to_string())
+            } else if validation == "None" {
+                Some("installed without signature validation".to_string())
+            } else {
+                None
+            }
+        }
+        "dnf" => {
+            if validation.is_empty() || validation == "(none)" {
+                Some("unsigned rpm".to_string())
+            } else {
+                None
+            }
+        }
+        "apt" => {
+            if packager.is_empty() {
+                Some("no maintainer recorded".to_string())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Survey every installed package of one manager for provenance flags,
+/// name to reason, using one bulk query. Managers without the metadata
+/// return an empty map; so does any command failure — the filter built
+/// on this is an audit aid, not a gate.
+pub async fn provenance_survey(manager: &str) -> std::collections::HashMap<String, String> {
+    let argv: Vec<String> = match manager {
+        "pacman" => vec!["pacman".into(), "-Qi".into()],
+        "dnf" => vec![
+            "rpm".into(),
+            "-qa".into(),
+            "--qf".into(),
+            "%{NAME}\\t%{PACKAGER}\\t%{SIGPGP:pgpsig}\\n".into(),
+        ],
+        "apt" => vec![
+            "dpkg-query".into(),
+            "-W".into(),
+            "-f=${Package}\\t${Maintainer}\\n".into(),
+        ],
+        _ => return Default::default(),
+    };
+    let Ok(output) = run_backend(manager, &argv).await else {
+        return Default::default();
+    };
+    match manager {
+        "pacman" => common::parse_pacman_provenance(&output),
+        "dnf" => common::parse_tabular_provenance("dnf", &output),
+        _ => common::parse_tabular_provenance("apt", &output),
+    }
+}
+
+/// Which package owns a file, asked of whichever native manager is
+/// present. `None` when nothing claims the path — which for a
+/// privileged binary is itself a finding — or when no manager here can
+/// answer. The first present manager decides; the native ones do not
+/// coexist.
+pub async fn owner_of(path: &str) -> Option<String> {
+    let probes: [(&str, &[&str]); 3] = [
+        ("pacman", &["pacman", "-Qqo", path]),
+        ("apt", &["dpkg-query", "-S", path]),
+        ("dnf", &["rpm", "-qf", "--qf", "%{NAME}", path]),
+    ];
+    for (manager, argv) in probes {
+        if !binary_exists(argv[0]) {
+            continue;
+        }
+        let argv: Vec<String> = argv.iter().map(|word| word.to_string()).collect();
+        let owner = match run_backend(manager, &argv).await {
+            Ok(output) => {
+                // dpkg answers "pkg1, pkg2: /path"; the others print the
+                // bare name.
+                let first = output.lines().next().unwrap_or("");
+                let name = first
+                    .split(':')
+                    .next()
+                    .unwrap_or("")
+                    .split(',')
+                    .next()
+                    .unwrap_or("")
+                    .trim();
+                (!name.is_empty()).then(|| name.to_string())
+            }
+            Err(_) => None, // "not owned" exits non-zero on all three
+        };
+        return owner;
+    }
+    None
+}
+
+/// Check whether a binary exists on the managed host. Locally the usual
+/// directories are probed; on a remote host the shell resolves it, so
+/// detection at startup sees the server's managers, not this machine's.
+pub(crate) fn binary_exists(name: &str) -> bool {
+    let prefix = crate::utils::host::routed(&[]);
+    if prefix.is_empty() {
+        return ["/usr/bin", "/usr/local/bin", "/opt/homebrew/bin", "/bin"]
+            .iter()
+            .any(|dir| Path::new(dir).join(name).exists());
+    }
+    std::process::Command::new(&prefix[0])
+        .args(&prefix[1..])
+        .args(["command", "-v", name])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Run one backend command to completion on the managed host.
+///
+/// Every non-streaming backend call funnels through here and onto the
+/// shared `CommandRunner`: routing through `utils::host`, the
+/// controlled environment, the timeout with its process-group kill,
+/// and the invocation log all live there. Failure maps onto
+/// `CommandFailed` carrying the original argv so messages never show
+/// the transport. ssh reserves exit 255 for its own failures; that
+/// case is reported as a lost connection and flags cached data as
+/// stale.
+pub(crate) async fn run_backend(manager: &str, argv: &[String]) -> Result<String> {
+    let mut runner = crate::utils::runner::CommandRunner::new(manager);
+    if manager == "apt" {
+        runner = runner.env("DEBIAN_FRONTEND", "noninteractive");
+    }
+    let output = runner.run(argv).await?;
+    if output.status == 0 {
+        return Ok(output.stdout);
+    }
+    let stderr = output.stderr;
+    if output.status == 255 {
+        if let Some(target) = crate::utils::host::remote_target() {
+            crate::utils::host::mark_connection_lost();
+            return Err(PkgError::CommandFailed {
+                command: argv.join(" "),
+                status: output.status,
+                stderr: format!("ssh connection to {target} lost: {}", stderr.trim()),
+            });
+        }
+    }
+    Err(PkgError::CommandFailed {
+        command: argv.join(" "),
+        status: output.status,
+        stderr,
+    })
+}
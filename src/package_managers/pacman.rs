@@ -0,0 +1,444 @@
+// Not registered by initialize_package_managers yet; kept warning-free until
+// backend detection lands.
+#![allow(dead_code)]
+
+use async_trait::async_trait;
+
+use super::{binary_exists, common, PackageDetails, PackageInfo, PackageManager, PackageUpdate};
+use crate::error::{PkgError, Result};
+use crate::utils::privilege::PrivilegeRunner;
+
+/// Backend for Arch Linux systems driving pacman.
+pub struct PacmanManager {
+    runner: PrivilegeRunner,
+}
+
+impl PacmanManager {
+    pub fn new(runner: PrivilegeRunner) -> Self {
+        PacmanManager { runner }
+    }
+
+    async fn run(&self, program: &str, args: &[&str]) -> Result<String> {
+        let mut argv = vec![program.to_string()];
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        super::run_backend(self.id(), &argv).await
+    }
+
+    async fn run_privileged(&self, args: &[&str]) -> Result<String> {
+        let argv = self.runner.wrap(args);
+        let argv: Vec<&str> = argv.iter().map(String::as_str).collect();
+        self.run(argv[0], &argv[1..]).await
+    }
+
+    /// Map of package name to sync repository ("core", "extra", ...), built
+    /// from `pacman -Sl`. Packages missing from every sync db are foreign
+    /// (AUR or local builds).
+    async fn sync_repos(&self) -> std::collections::HashMap<String, String> {
+        let mut repos = std::collections::HashMap::new();
+        let Ok(output) = self.run("pacman", &["-Sl"]).await else {
+            return repos;
+        };
+        for line in output.lines() {
+            let mut parts = line.split_whitespace();
+            if let (Some(repo), Some(name)) = (parts.next(), parts.next()) {
+                repos.insert(name.to_string(), repo.to_string());
+            }
+        }
+        repos
+    }
+}
+
+#[async_trait]
+impl PackageManager for PacmanManager {
+    fn id(&self) -> &str {
+        "pacman"
+    }
+
+    fn display_name(&self) -> &str {
+        "Pacman"
+    }
+
+    fn is_available(&self) -> bool {
+        binary_exists("pacman")
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        let output = self.run("pacman", &["-Qi"]).await?;
+        let mut packages = common::parse_pacman_query(&output);
+        let repos = self.sync_repos().await;
+        for package in &mut packages {
+            package.origin = Some(
+                repos
+                    .get(&package.name)
+                    .cloned()
+                    .unwrap_or_else(|| "AUR".to_string()),
+            );
+        }
+        Ok(packages)
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        let output = self.run("pacman", &["-Ss", query]).await?;
+        Ok(common::parse_pacman_search(&output))
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            // `--print` resolves the targets without touching the system
+            // and needs no root.
+            let mut args = vec!["-S", "--print"];
+            args.extend(packages.iter().map(String::as_str));
+            self.run("pacman", &args).await?;
+            return Ok(());
+        }
+        let mut args = vec!["pacman", "-S", "--noconfirm"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if dry_run {
+            let mut args = vec!["-Rs", "--print"];
+            args.extend(packages.iter().map(String::as_str));
+            self.run("pacman", &args).await?;
+            return Ok(());
+        }
+        let mut args = vec!["pacman", "-Rs", "--noconfirm"];
+        args.extend(packages.iter().map(String::as_str));
+        self.run_privileged(&args).await?;
+        Ok(())
+    }
+
+    async fn refresh_metadata(&self) -> Result<()> {
+        self.run_privileged(&["pacman", "-Sy"]).await?;
+        Ok(())
+    }
+
+    async fn update_system(&self, dry_run: bool) -> Result<()> {
+        if dry_run {
+            // Plan against the current sync dbs; `-y` would write them.
+            self.run("pacman", &["-Su", "--print"]).await?;
+            return Ok(());
+        }
+        self.run_privileged(&["pacman", "-Syu", "--noconfirm"]).await?;
+        Ok(())
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        // checkupdates exits 2 when there is nothing to do, so tolerate that.
+        let output = match self.run("checkupdates", &[]).await {
+            Ok(output) => output,
+            Err(PkgError::CommandFailed { status: 2, .. }) => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+        Ok(common::parse_checkupdates(&output))
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        self.run_privileged(&["pacman", "-Sc", "--noconfirm"]).await?;
+        Ok(())
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        let output = self.run("pacman", &["-Qi", package]).await?;
+        let mut details = PackageDetails {
+            name: package.to_string(),
+            manager: self.id().to_string(),
+            ..Default::default()
+        };
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once(" : ") else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "Version" => details.version = value.to_string(),
+                "Description" => details.description = value.to_string(),
+                "URL" => details.url = Some(value.to_string()),
+                "Licenses" => {
+                    details.licenses = value.split_whitespace().map(str::to_string).collect();
+                }
+                "Depends On" if value != "None" => {
+                    details.depends = value.split_whitespace().map(str::to_string).collect();
+                }
+                "Installed Size" => {
+                    details.size = common::parse_pacman_size(value);
+                }
+                "Packager" => details.packager = Some(value.to_string()),
+                "Validated By" => details.validation = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        if details.version.is_empty() {
+            return Err(PkgError::NotFound(package.to_string()));
+        }
+        Ok(details)
+    }
+
+    async fn dependencies(&self, package: &str) -> Result<Vec<String>> {
+        Ok(self.details(package).await?.depends)
+    }
+
+    async fn dependency_edges(&self, package: &str) -> Result<Vec<super::DepEdge>> {
+        let output = self.run("pacman", &["-Qi", package]).await?;
+        Ok(common::parse_qi_edges(&output))
+    }
+
+    /// A real package provides itself; for a virtual name, search
+    /// candidates are kept when their sync stanza lists the name under
+    /// Provides.
+    async fn providers(&self, name: &str) -> Result<Vec<PackageInfo>> {
+        if let Ok(output) = self.run("pacman", &["-Si", name]).await {
+            let mut own = common::parse_pacman_query(&output);
+            for package in &mut own {
+                package.installed = false;
+            }
+            return Ok(own);
+        }
+        let search = self.run("pacman", &["-Ss", name]).await.unwrap_or_default();
+        let candidates = common::parse_pacman_search(&search);
+        if candidates.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut args = vec!["-Si"];
+        args.extend(candidates.iter().map(|package| package.name.as_str()));
+        let output = self.run("pacman", &args).await.unwrap_or_default();
+        let table = common::parse_qi_edge_table(&output);
+        Ok(candidates
+            .into_iter()
+            .filter(|candidate| {
+                table.get(&candidate.name).is_some_and(|edges| {
+                    edges.iter().any(|edge| {
+                        edge.kind == super::DepKind::Provides && edge.name == name
+                    })
+                })
+            })
+            .collect())
+    }
+
+    /// Installed packages carry their optdepends in `-Qi`; not-yet
+    /// installed ones fall back to the sync database.
+    async fn optional_dependencies(&self, package: &str) -> Result<Vec<super::OptionalDep>> {
+        let output = match self.run("pacman", &["-Qi", package]).await {
+            Ok(output) => output,
+            Err(_) => self.run("pacman", &["-Si", package]).await?,
+        };
+        Ok(common::parse_optional_deps(&output))
+    }
+
+    /// The local database describes the installed version and the sync
+    /// database the repository candidate; pacman has no way to address
+    /// any other version, so anything else is reported as absent.
+    async fn version_dependencies(&self, package: &str, version: &str) -> Result<Vec<String>> {
+        for query in ["-Qi", "-Si"] {
+            let Ok(output) = self.run("pacman", &[query, package]).await else {
+                continue;
+            };
+            if common::parse_qi_field(&output, "Version").first().map(String::as_str)
+                != Some(version)
+            {
+                continue;
+            }
+            let mut deps: Vec<String> = common::parse_qi_edges(&output)
+                .into_iter()
+                .filter(|edge| edge.kind == super::DepKind::Required)
+                .map(|edge| {
+                    // Drop version constraints like "glibc>=2.38".
+                    edge.name
+                        .split(['<', '>', '='])
+                        .next()
+                        .unwrap_or(&edge.name)
+                        .to_string()
+                })
+                .collect();
+            deps.sort();
+            deps.dedup();
+            return Ok(deps);
+        }
+        Err(PkgError::NotFound(format!(
+            "{package} {version} is in neither the local nor the sync database"
+        )))
+    }
+
+    /// One `pacman -Qi` dump covers the whole installed set.
+    async fn all_dependency_edges(
+        &self,
+    ) -> Result<std::collections::HashMap<String, Vec<super::DepEdge>>> {
+        let output = self.run("pacman", &["-Qi"]).await?;
+        Ok(common::parse_qi_edge_table(&output))
+    }
+
+    /// Members of the base group; `pacman -Qg base` prints "base name"
+    /// pairs for the installed ones.
+    async fn essential_packages(&self) -> Result<Vec<String>> {
+        let output = self.run("pacman", &["-Qg", "base"]).await?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(1))
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn installed_files(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run("pacman", &["-Qlq", package]).await?;
+        Ok(output.lines().map(str::to_string).collect())
+    }
+
+    async fn predict_conflicts(&self, packages: &[String]) -> Result<super::ConflictReport> {
+        let mut report = super::ConflictReport::default();
+        let installed: std::collections::HashSet<String> = self
+            .run("pacman", &["-Qq"])
+            .await?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        // Path-to-owner map of the whole local database; fetched once,
+        // and only when a repo file list actually comes back.
+        let mut owners: Option<std::collections::HashMap<String, String>> = None;
+        let mut file_db_missing = false;
+        for package in packages {
+            if let Ok(output) = self.run("pacman", &["-Si", package]).await {
+                for field in ["Conflicts With", "Replaces"] {
+                    for name in common::parse_qi_field(&output, field) {
+                        if installed.contains(&name) && !packages.contains(&name) {
+                            report.conflicts.push(super::Conflict {
+                                package: package.clone(),
+                                with: name,
+                                path: None,
+                            });
+                        }
+                    }
+                }
+            }
+            // -Fl needs the synced file database (`pacman -Fy`).
+            match self.run("pacman", &["-Fl", package]).await {
+                Ok(listing) => {
+                    if owners.is_none() {
+                        let local = self.run("pacman", &["-Ql"]).await.unwrap_or_default();
+                        owners = Some(common::parse_file_owners(&local));
+                    }
+                    let owners = owners.as_ref().expect("filled above");
+                    for path in common::parse_fl_files(&listing) {
+                        let Some(owner) = owners.get(&path) else {
+                            continue;
+                        };
+                        if owner != package && !packages.contains(owner) {
+                            report.conflicts.push(super::Conflict {
+                                package: package.clone(),
+                                with: owner.clone(),
+                                path: Some(path),
+                            });
+                        }
+                    }
+                }
+                Err(_) => file_db_missing = true,
+            }
+        }
+        if file_db_missing {
+            report.notes.push(
+                "file database not available (pacman -Fy); file conflict check skipped"
+                    .to_string(),
+            );
+        }
+        Ok(report)
+    }
+
+    /// `-S --print` resolves the transaction without root and lists
+    /// only the packages that would actually be installed; sizes come
+    /// from a `-Si` pass over that plan.
+    async fn install_footprint(&self, packages: &[String]) -> Result<super::InstallFootprint> {
+        let mut args = vec!["-S", "--print", "--print-format", "%n"];
+        args.extend(packages.iter().map(String::as_str));
+        let plan = self.run("pacman", &args).await?;
+        let mut names: Vec<String> = plan
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        names.dedup();
+        let mut footprint = super::InstallFootprint {
+            packages: names,
+            ..Default::default()
+        };
+        if !footprint.packages.is_empty() {
+            let mut args = vec!["-Si"];
+            args.extend(footprint.packages.iter().map(String::as_str));
+            // Foreign targets have no sync entry; the count still stands.
+            if let Ok(output) = self.run("pacman", &args).await {
+                let (download, installed) = common::parse_si_size_totals(&output);
+                footprint.download_bytes = download;
+                footprint.installed_bytes = installed;
+            }
+        }
+        Ok(footprint)
+    }
+
+    /// `pacman -Dk` exits non-zero and reports on stderr when the local
+    /// database has broken dependencies, so a command failure with
+    /// parseable errors is the interesting case, not a real failure.
+    async fn dependency_problems(&self) -> Result<Vec<super::DepProblem>> {
+        let output = match self.run("pacman", &["-Dk"]).await {
+            Ok(output) => output,
+            Err(PkgError::CommandFailed { stderr, .. }) => stderr,
+            Err(err) => return Err(err),
+        };
+        Ok(common::parse_pacman_dk(&output)
+            .into_iter()
+            .map(|(package, dep)| {
+                // Version constraints and sonames are not installable
+                // names; suggest the bare package.
+                let bare = dep
+                    .split(['=', '<', '>'])
+                    .next()
+                    .unwrap_or(&dep)
+                    .to_string();
+                super::DepProblem {
+                    manager: self.id().to_string(),
+                    package,
+                    detail: format!("missing dependency {dep}"),
+                    fix: Some(format!("pacman -S --asdeps --needed {bare}")),
+                }
+            })
+            .collect())
+    }
+
+    async fn dependents(&self, package: &str) -> Result<Vec<String>> {
+        let output = self.run("pacman", &["-Qi", package]).await?;
+        for line in output.lines() {
+            let Some((key, value)) = line.split_once(" : ") else {
+                continue;
+            };
+            if key.trim() == "Required By" {
+                let value = value.trim();
+                if value == "None" {
+                    return Ok(Vec::new());
+                }
+                return Ok(value.split_whitespace().map(str::to_string).collect());
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    async fn hold(&self, package: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("hold {package} (edit IgnorePkg in pacman.conf)"),
+        })
+    }
+
+    async fn unhold(&self, package: &str) -> Result<()> {
+        Err(PkgError::Unsupported {
+            manager: self.id().to_string(),
+            operation: format!("unhold {package} (edit IgnorePkg in pacman.conf)"),
+        })
+    }
+
+    /// Holds on pacman are the IgnorePkg entries in pacman.conf.
+    async fn list_held(&self) -> Result<Vec<String>> {
+        let config = crate::utils::host::read_file("/etc/pacman.conf")?;
+        Ok(common::parse_ignore_pkg(&config))
+    }
+}
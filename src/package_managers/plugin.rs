@@ -0,0 +1,412 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{PackageDetails, PackageInfo, PackageManager, PackageUpdate};
+use crate::error::{PkgError, Result};
+
+/// Plugin backend declaration, one table per manager under `[plugins.<id>]`
+/// in the config file.
+///
+/// Each operation is an argv template — the first element is the program,
+/// the rest its arguments. `{query}` (search) and `{packages}` (install and
+/// remove) are substituted as real arguments, never through a shell, so no
+/// escaping is needed or possible. An empty template means the plugin does
+/// not support that operation.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    pub search: Vec<String>,
+    pub list_installed: Vec<String>,
+    pub list_updates: Vec<String>,
+    pub install: Vec<String>,
+    pub remove: Vec<String>,
+    /// How query output is parsed: "json", "tsv", or "regex:<pattern>" with
+    /// named captures (`name`, `version`, `description`, `current_version`,
+    /// `new_version`).
+    pub format: String,
+}
+
+/// How a plugin's stdout is adapted into package records.
+#[derive(Debug)]
+enum OutputFormat {
+    /// A JSON array of objects keyed by field name.
+    Json,
+    /// One record per line, fields separated by tabs.
+    Tsv,
+    /// One record per matching line, fields from named captures.
+    Regex(Regex),
+}
+
+/// A backend driven entirely by user-declared command templates, for
+/// package systems that have no native backend here.
+///
+/// Its commands run like any other backend's: a plugin that fails or
+/// crashes yields an error for this manager only and never disturbs the
+/// native backends, because callers already collect results per manager.
+#[derive(Debug)]
+pub struct CommandPluginManager {
+    id: String,
+    config: PluginConfig,
+    format: OutputFormat,
+}
+
+impl CommandPluginManager {
+    /// Validate a declaration and build the manager. Errors name the
+    /// offending config field, e.g. `plugins.mypm.format`.
+    pub fn from_config(id: &str, config: &PluginConfig) -> Result<Self> {
+        let field = |name: &str| format!("plugins.{id}.{name}");
+        let bad = |name: &str, detail: String| PkgError::Config {
+            path: field(name),
+            detail,
+        };
+        let format = match config.format.as_str() {
+            "json" => OutputFormat::Json,
+            "tsv" => OutputFormat::Tsv,
+            other => match other.strip_prefix("regex:") {
+                Some(pattern) => {
+                    let regex = Regex::new(pattern)
+                        .map_err(|err| bad("format", err.to_string()))?;
+                    if !regex.capture_names().flatten().any(|name| name == "name") {
+                        return Err(bad(
+                            "format",
+                            "regex needs a named capture (?P<name>...)".to_string(),
+                        ));
+                    }
+                    OutputFormat::Regex(regex)
+                }
+                None => {
+                    return Err(bad(
+                        "format",
+                        format!("expected \"json\", \"tsv\" or \"regex:<pattern>\", got \"{other}\""),
+                    ))
+                }
+            },
+        };
+        let templates = [
+            ("search", &config.search, "{query}"),
+            ("list_installed", &config.list_installed, ""),
+            ("list_updates", &config.list_updates, ""),
+            ("install", &config.install, "{packages}"),
+            ("remove", &config.remove, "{packages}"),
+        ];
+        if templates.iter().all(|(_, template, _)| template.is_empty()) {
+            return Err(bad("search", "plugin declares no operations".to_string()));
+        }
+        for (name, template, allowed) in templates {
+            if template.is_empty() {
+                continue;
+            }
+            if template[0].contains('{') {
+                return Err(bad(
+                    name,
+                    "the first element is the program and cannot be a placeholder".to_string(),
+                ));
+            }
+            for part in template {
+                for placeholder in ["{query}", "{packages}"] {
+                    if part.contains(placeholder) && placeholder != allowed {
+                        return Err(bad(
+                            name,
+                            format!("{placeholder} is not valid in this template"),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(CommandPluginManager {
+            id: id.to_string(),
+            config: config.clone(),
+            format,
+        })
+    }
+
+    /// Substitute placeholders into a template, building argv directly.
+    /// `{packages}` expands to one argument per package.
+    fn expand(template: &[String], query: &str, packages: &[String]) -> Vec<String> {
+        let mut argv = Vec::new();
+        for part in template {
+            if part == "{packages}" {
+                argv.extend(packages.iter().cloned());
+            } else {
+                argv.push(part.replace("{query}", query));
+            }
+        }
+        argv
+    }
+
+    async fn run(&self, argv: Vec<String>) -> Result<String> {
+        super::run_backend(&self.id, &argv).await
+    }
+
+    /// One field map per record, however the output was formatted.
+    fn parse_records(&self, output: &str) -> Result<Vec<HashMap<String, String>>> {
+        match &self.format {
+            OutputFormat::Json => {
+                let value: serde_json::Value =
+                    serde_json::from_str(output).map_err(|err| PkgError::Parse {
+                        source_desc: self.id.clone(),
+                        detail: err.to_string(),
+                    })?;
+                let Some(array) = value.as_array() else {
+                    return Err(PkgError::Parse {
+                        source_desc: self.id.clone(),
+                        detail: "expected a JSON array of objects".to_string(),
+                    });
+                };
+                Ok(array
+                    .iter()
+                    .filter_map(|item| item.as_object())
+                    .map(|object| {
+                        object
+                            .iter()
+                            .filter_map(|(key, value)| {
+                                value.as_str().map(|v| (key.clone(), v.to_string()))
+                            })
+                            .collect()
+                    })
+                    .collect())
+            }
+            OutputFormat::Tsv => Ok(output
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| {
+                    // Positional columns: name, version, then either a
+                    // description (lists) or a new version (updates).
+                    let mut fields = line.split('\t');
+                    let mut record = HashMap::new();
+                    for key in ["name", "version", "description"] {
+                        if let Some(value) = fields.next() {
+                            record.insert(key.to_string(), value.to_string());
+                        }
+                    }
+                    record
+                })
+                .collect()),
+            OutputFormat::Regex(regex) => Ok(output
+                .lines()
+                .filter_map(|line| regex.captures(line))
+                .map(|captures| {
+                    regex
+                        .capture_names()
+                        .flatten()
+                        .filter_map(|name| {
+                            captures
+                                .name(name)
+                                .map(|m| (name.to_string(), m.as_str().to_string()))
+                        })
+                        .collect()
+                })
+                .collect()),
+        }
+    }
+
+    fn to_package(&self, record: &HashMap<String, String>, installed: bool) -> Option<PackageInfo> {
+        Some(PackageInfo {
+            name: record.get("name")?.clone(),
+            version: record.get("version").cloned().unwrap_or_default(),
+            description: record.get("description").cloned().unwrap_or_default(),
+            manager: self.id.clone(),
+            installed,
+            size: None,
+            install_date: None,
+            origin: None,
+            explicit: None,
+        })
+    }
+
+    fn to_update(&self, record: &HashMap<String, String>) -> Option<PackageUpdate> {
+        Some(PackageUpdate {
+            name: record.get("name")?.clone(),
+            current_version: record
+                .get("current_version")
+                .or_else(|| record.get("version"))
+                .cloned()
+                .unwrap_or_default(),
+            new_version: record
+                .get("new_version")
+                .or_else(|| record.get("description"))
+                .cloned()
+                .unwrap_or_default(),
+            manager: self.id.clone(),
+            security: false,
+        })
+    }
+
+    fn unsupported(&self, operation: &str) -> PkgError {
+        PkgError::Unsupported {
+            manager: self.id.clone(),
+            operation: operation.to_string(),
+        }
+    }
+}
+
+/// Check every plugin declaration, reporting the first invalid one.
+///
+/// Run at startup so a typo fails fast with the offending field named,
+/// exactly like any other malformed config value.
+pub fn validate(plugins: &HashMap<String, PluginConfig>) -> Result<()> {
+    let mut ids: Vec<&String> = plugins.keys().collect();
+    ids.sort();
+    for id in ids {
+        CommandPluginManager::from_config(id, &plugins[id])?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PackageManager for CommandPluginManager {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.id
+    }
+
+    fn is_available(&self) -> bool {
+        // The user declared the plugin; a missing program surfaces as a
+        // normal command error rather than silent deregistration.
+        true
+    }
+
+    async fn list_installed(&self) -> Result<Vec<PackageInfo>> {
+        if self.config.list_installed.is_empty() {
+            return Err(self.unsupported("list installed"));
+        }
+        let argv = Self::expand(&self.config.list_installed, "", &[]);
+        let output = self.run(argv).await?;
+        Ok(self
+            .parse_records(&output)?
+            .iter()
+            .filter_map(|record| self.to_package(record, true))
+            .collect())
+    }
+
+    async fn search(&self, query: &str) -> Result<Vec<PackageInfo>> {
+        if self.config.search.is_empty() {
+            return Err(self.unsupported("search"));
+        }
+        let argv = Self::expand(&self.config.search, query, &[]);
+        let output = self.run(argv).await?;
+        Ok(self
+            .parse_records(&output)?
+            .iter()
+            .filter_map(|record| self.to_package(record, false))
+            .collect())
+    }
+
+    async fn install(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if self.config.install.is_empty() {
+            return Err(self.unsupported("install"));
+        }
+        let argv = Self::expand(&self.config.install, "", packages);
+        if dry_run {
+            // Plugin commands have no simulate contract, so never run them.
+            log::info!("dry-run: would run {}", argv.join(" "));
+            return Ok(());
+        }
+        self.run(argv).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, packages: &[String], dry_run: bool) -> Result<()> {
+        if self.config.remove.is_empty() {
+            return Err(self.unsupported("remove"));
+        }
+        let argv = Self::expand(&self.config.remove, "", packages);
+        if dry_run {
+            log::info!("dry-run: would run {}", argv.join(" "));
+            return Ok(());
+        }
+        self.run(argv).await?;
+        Ok(())
+    }
+
+    async fn update_system(&self, _dry_run: bool) -> Result<()> {
+        Err(self.unsupported("system update"))
+    }
+
+    async fn list_updates(&self) -> Result<Vec<PackageUpdate>> {
+        if self.config.list_updates.is_empty() {
+            return Ok(Vec::new());
+        }
+        let argv = Self::expand(&self.config.list_updates, "", &[]);
+        let output = self.run(argv).await?;
+        Ok(self
+            .parse_records(&output)?
+            .iter()
+            .filter_map(|record| self.to_update(record))
+            .collect())
+    }
+
+    async fn clean_cache(&self) -> Result<()> {
+        Err(self.unsupported("clean cache"))
+    }
+
+    async fn details(&self, package: &str) -> Result<PackageDetails> {
+        Err(PkgError::NotFound(package.to_string()))
+    }
+
+    async fn dependencies(&self, _package: &str) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn hold(&self, _package: &str) -> Result<()> {
+        Err(self.unsupported("hold"))
+    }
+
+    async fn unhold(&self, _package: &str) -> Result<()> {
+        Err(self.unsupported("unhold"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimal() -> PluginConfig {
+        PluginConfig {
+            search: vec!["mypm".into(), "search".into(), "{query}".into()],
+            format: "tsv".into(),
+            ..PluginConfig::default()
+        }
+    }
+
+    #[test]
+    fn validation_names_the_offending_field() {
+        let mut config = minimal();
+        config.format = "csv".into();
+        let err = CommandPluginManager::from_config("mypm", &config).unwrap_err();
+        assert!(err.to_string().contains("plugins.mypm.format"));
+
+        let mut config = minimal();
+        config.install = vec!["mypm".into(), "install".into(), "{query}".into()];
+        let err = CommandPluginManager::from_config("mypm", &config).unwrap_err();
+        assert!(err.to_string().contains("plugins.mypm.install"));
+    }
+
+    #[test]
+    fn placeholders_expand_to_real_arguments() {
+        let argv = CommandPluginManager::expand(
+            &["mypm".into(), "install".into(), "{packages}".into()],
+            "",
+            &["a b".into(), "c".into()],
+        );
+        // "a b" stays one argument: no shell, no escaping problems.
+        assert_eq!(argv, ["mypm", "install", "a b", "c"]);
+    }
+
+    #[test]
+    fn regex_format_maps_named_captures() {
+        let mut config = minimal();
+        config.format = r"regex:^(?P<name>\S+)\s+(?P<version>\S+)$".into();
+        let plugin = CommandPluginManager::from_config("mypm", &config).unwrap();
+        let records = plugin.parse_records("ripgrep 14.1.0\nbad line without version?").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["name"], "ripgrep");
+        assert_eq!(records[0]["version"], "14.1.0");
+    }
+}
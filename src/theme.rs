@@ -0,0 +1,69 @@
+use ratatui::style::{Color, Modifier, Style};
+
+/// Styles used across the UI, loosely mirroring the classic pkgtool colors.
+pub struct Theme {
+    pub header: Style,
+    pub title: Style,
+    pub highlight: Style,
+    pub selection: Style,
+    pub error: Style,
+    pub warning: Style,
+    pub success: Style,
+    pub dim: Style,
+    pub border: Style,
+    pub border_focused: Style,
+}
+
+impl Theme {
+    /// High-contrast accessibility theme: no colors at all, only bold,
+    /// reversed and underlined modifiers, so it works on monochrome
+    /// terminals and respects NO_COLOR.
+    pub fn no_color() -> Self {
+        Theme {
+            header: Style::default().add_modifier(Modifier::BOLD),
+            title: Style::default().add_modifier(Modifier::BOLD),
+            highlight: Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            selection: Style::default().add_modifier(Modifier::REVERSED),
+            error: Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED),
+            warning: Style::default().add_modifier(Modifier::UNDERLINED),
+            success: Style::default().add_modifier(Modifier::BOLD),
+            dim: Style::default().add_modifier(Modifier::DIM),
+            border: Style::default(),
+            border_focused: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+/// Whether color output should be disabled for this run, via the --no-color
+/// flag or a non-empty NO_COLOR environment variable (https://no-color.org).
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+        || std::env::args().any(|arg| arg == "--no-color")
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            header: Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            title: Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            highlight: Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+            selection: Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            error: Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            warning: Style::default().fg(Color::Yellow),
+            success: Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD),
+            dim: Style::default().fg(Color::DarkGray),
+            border: Style::default().fg(Color::Gray),
+            border_focused: Style::default().fg(Color::Cyan),
+        }
+    }
+}
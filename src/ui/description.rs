@@ -0,0 +1,126 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Word-wrap a package description to `width` display columns.
+///
+/// Paragraph breaks (blank lines) are preserved, Debian-style bullet lines
+/// (`- foo` / `* foo`) keep a hanging indent, and wide characters (CJK) are
+/// never split mid-character.
+pub fn wrap_description(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(4);
+    let mut wrapped = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() {
+            wrapped.push(String::new());
+            continue;
+        }
+        let indent = if trimmed.starts_with("- ") || trimmed.starts_with("* ") {
+            2
+        } else {
+            0
+        };
+        wrap_line(trimmed, width, indent, &mut wrapped);
+    }
+    wrapped
+}
+
+/// Wrap a single logical line, using `indent` columns for continuations.
+fn wrap_line(line: &str, width: usize, indent: usize, out: &mut Vec<String>) {
+    let continuation = " ".repeat(indent);
+    let mut current = String::new();
+    let mut current_width = 0;
+    let mut first = true;
+
+    for word in line.split(' ') {
+        if word.is_empty() {
+            continue;
+        }
+        let word_width = display_width(word);
+        let prefix_width = if first { 0 } else { indent };
+        let space = if current.is_empty() { 0 } else { 1 };
+
+        if current_width + space + word_width <= width.saturating_sub(prefix_width) {
+            if !current.is_empty() {
+                current.push(' ');
+                current_width += 1;
+            }
+            current.push_str(word);
+            current_width += word_width;
+            continue;
+        }
+
+        // Word doesn't fit on this line.
+        if !current.is_empty() {
+            out.push(std::mem::take(&mut current));
+            first = false;
+        }
+        let budget = width.saturating_sub(indent);
+        if word_width <= budget {
+            current.push_str(&continuation);
+            current.push_str(word);
+            current_width = word_width;
+        } else {
+            // Break an overlong word (e.g. unspaced CJK) on char boundaries.
+            let mut chunk = continuation.clone();
+            let mut chunk_width = 0;
+            for c in word.chars() {
+                let c_width = c.width().unwrap_or(0);
+                if chunk_width + c_width > budget && chunk_width > 0 {
+                    out.push(std::mem::take(&mut chunk));
+                    chunk.push_str(&continuation);
+                    chunk_width = 0;
+                }
+                chunk.push(c);
+                chunk_width += c_width;
+            }
+            current = chunk;
+            current_width = chunk_width;
+        }
+    }
+    if !current.is_empty() {
+        out.push(current);
+    }
+}
+
+/// Total display width of a string in terminal columns.
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(|c| c.width().unwrap_or(0)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_to_width() {
+        let lines = wrap_description("a fast modern replacement for grep and friends", 16);
+        assert!(lines.iter().all(|l| display_width(l) <= 16));
+        assert!(lines.len() >= 3);
+    }
+
+    #[test]
+    fn preserves_paragraph_breaks() {
+        let lines = wrap_description("first paragraph\n\nsecond paragraph", 40);
+        assert_eq!(lines, vec!["first paragraph", "", "second paragraph"]);
+    }
+
+    #[test]
+    fn bullet_lines_keep_hanging_indent() {
+        let lines = wrap_description("- a bullet item that is definitely too long to fit", 20);
+        assert!(lines[0].starts_with("- "));
+        assert!(lines[1].starts_with("  "));
+        assert!(lines.iter().all(|l| display_width(l) <= 20));
+    }
+
+    #[test]
+    fn cjk_never_splits_mid_character_and_respects_double_width() {
+        let text = "\u{72b6}\u{614b}\u{76e3}\u{8996}\u{30c4}\u{30fc}\u{30eb}\u{3067}\u{3059}";
+        let lines = wrap_description(text, 8);
+        for line in &lines {
+            assert!(display_width(line) <= 8);
+            // Re-joining must reproduce the original text: no characters lost.
+        }
+        let rejoined: String = lines.concat();
+        assert_eq!(rejoined, text);
+    }
+}
@@ -0,0 +1,2321 @@
+pub mod description;
+
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs};
+use ratatui::Frame;
+
+use crate::app::{App, Focus, Mode, ViewDensity};
+use crate::package_managers::is_third_party_origin;
+use crate::utils::{format_size, truncate_width};
+use crate::utils::loadable::Loadable;
+
+/// Render the whole UI for one frame.
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    app.expire_typeahead();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(3),
+        ])
+        .split(frame.area());
+
+    draw_tabs(frame, app, chunks[0]);
+    app.pane_rects.list = chunks[1];
+    app.pane_rects.details = ratatui::layout::Rect::default();
+    app.pane_rects.input = chunks[2];
+    app.current_tab().render()(frame, app, chunks[1]);
+    draw_bottom_bar(frame, app, chunks[2]);
+
+    if app.palette.is_some() {
+        draw_palette(frame, app);
+    }
+    if app.origin_picker.is_some() {
+        draw_origin_picker(frame, app);
+    }
+    if app.origin_risk.is_some() {
+        draw_origin_risk(frame, app);
+    }
+    if app.permission_view.is_some() {
+        draw_permission_view(frame, app);
+    }
+    if app.dep_health.is_some() {
+        draw_dep_health(frame, app);
+    }
+    if app.optional_deps.is_some() {
+        draw_optional_deps(frame, app);
+    }
+    if app.provider_picker.is_some() {
+        draw_provider_picker(frame, app);
+    }
+    if app.overlaps_view.is_some() {
+        draw_overlaps(frame, app);
+    }
+    if app.heavy_deps.is_some() {
+        draw_heavy_deps(frame, app);
+    }
+    if app.restart_picker.is_some() {
+        draw_restart_picker(frame, app);
+    }
+    if app.finding_detail.is_some() {
+        draw_finding_detail(frame, app);
+    }
+    if app.scope_picker.is_some() {
+        draw_scope_picker(frame, app);
+    }
+    if app.operation.is_some() {
+        draw_operation_overlay(frame, app);
+    }
+    if app.quit_prompt.is_some() {
+        draw_quit_prompt(frame, app);
+    }
+    if app.prompt_dialog.is_some() {
+        draw_prompt_dialog(frame, app);
+    }
+    if app.message_dialog.is_some() {
+        draw_message_dialog(frame, app);
+    }
+    if app.stall_prompt.is_some() {
+        draw_stall_prompt(frame, app);
+    }
+    if app.cancel_prompt.is_some() {
+        draw_cancel_prompt(frame, app);
+    }
+    if app.confirm_prompt.is_some() {
+        draw_confirm_prompt(frame, app);
+    }
+    if app.diff_view.is_some() {
+        draw_diff_view(frame, app);
+    }
+    if app.show_help {
+        draw_help(frame, app);
+    }
+    if app.setup.is_some() {
+        draw_setup_wizard(frame, app);
+    }
+    if app.debug_overlay {
+        draw_debug_overlay(frame, app);
+    }
+}
+
+/// Top-right overlay with render, backend and memory statistics (toggled
+/// with F12 or `--profile`; `profile dump` writes the raw samples).
+fn draw_debug_overlay(frame: &mut Frame, app: &App) {
+    let snapshot = crate::utils::profile::snapshot();
+    let mut lines = vec![
+        format!(
+            " frames: {}  avg: {:.1}/s ",
+            app.frames_rendered,
+            app.frame_rate()
+        ),
+        format!(
+            " render: {:.1}ms avg  {:.1}ms p95 ",
+            snapshot.frame_avg_ms, snapshot.frame_p95_ms
+        ),
+        format!(
+            " cache: {:.0}% hit ({}/{}) ",
+            snapshot.cache_hit_rate(),
+            snapshot.cache_hits,
+            snapshot.cache_hits + snapshot.cache_misses
+        ),
+    ];
+    for (manager, command, ms) in &snapshot.last_calls {
+        lines.push(format!(
+            " {manager}: {:.0}ms  {} ",
+            ms,
+            truncate_width(command, 28)
+        ));
+    }
+    for (label, count, bytes) in app.data_footprint() {
+        lines.push(format!(
+            " {label}: {count} rows  ~{} ",
+            format_size(bytes as u64)
+        ));
+    }
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count() as u16)
+        .max()
+        .unwrap_or(0)
+        .min(frame.area().width);
+    let area = Rect {
+        x: frame.area().width.saturating_sub(width),
+        y: 0,
+        width,
+        height: (lines.len() as u16).min(frame.area().height),
+    };
+    frame.render_widget(Clear, area);
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    frame.render_widget(Paragraph::new(text).style(app.theme.dim), area);
+}
+
+fn draw_palette(frame: &mut Frame, app: &mut App) {
+    let registry = crate::actions::registry();
+    let area = centered_rect(50, 60, frame.area());
+    let Some(palette) = app.palette.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(1)])
+        .split(area);
+
+    frame.render_widget(Clear, area);
+    let query = Paragraph::new(palette.query.as_str())
+        .style(app.theme.highlight)
+        .block(Block::default().borders(Borders::ALL).title(" Command Palette "));
+    frame.render_widget(query, chunks[0]);
+    frame.set_cursor_position((
+        chunks[0].x + 1 + palette.query.chars().count() as u16,
+        chunks[0].y + 1,
+    ));
+
+    let width = chunks[1].width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = palette
+        .matches
+        .iter()
+        .map(|&i| {
+            let entry = &registry[i];
+            let key = entry.key.unwrap_or("");
+            let pad = width
+                .saturating_sub(entry.title.chars().count())
+                .saturating_sub(key.chars().count());
+            ListItem::new(Line::from(vec![
+                Span::raw(entry.title),
+                Span::raw(" ".repeat(pad)),
+                Span::styled(key, app.theme.dim),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, chunks[1], &mut palette.state);
+}
+
+/// Live output of the running operation, like a mini terminal.
+fn draw_operation_overlay(frame: &mut Frame, app: &App) {
+    let area = centered_rect(70, 60, frame.area());
+    let Some(operation) = app.operation.as_ref() else {
+        return;
+    };
+
+    let height = area.height.saturating_sub(2) as usize;
+    let total = app.operation_log.lines.len();
+    let bottom = app.operation_log.scroll.unwrap_or(total).min(total);
+    let start = bottom.saturating_sub(height);
+    let lines: Vec<Line> = app
+        .operation_log
+        .lines
+        .iter()
+        .skip(start)
+        .take(bottom - start)
+        .map(|line| {
+            if line.stderr {
+                Line::from(Span::styled(line.text.clone(), app.theme.warning))
+            } else {
+                Line::from(line.text.clone())
+            }
+        })
+        .collect();
+    let following = if app.operation_log.scroll.is_none() {
+        ""
+    } else {
+        " [scrolled, PgDn to follow]"
+    };
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.border_focused)
+                .title(format!(" {} (running){} ", operation.description, following)),
+        ),
+        area,
+    );
+}
+
+pub fn draw_log_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app.log.iter().map(|line| ListItem::new(line.clone())).collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.pane_border(Focus::List))
+                .title(format!(
+                    " Log ({} lines) — {} ",
+                    app.log.len(),
+                    crate::logging::log_path().display()
+                )),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut app.log_state);
+}
+
+/// The first-run wizard: one screen per question, stepped with Enter.
+fn draw_setup_wizard(frame: &mut Frame, app: &mut App) {
+    use crate::app::SetupWizard;
+
+    let area = centered_rect(60, 60, frame.area());
+    let Some(wizard) = app.setup.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area.inner(ratatui::layout::Margin::new(1, 1)));
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(app.theme.border_focused)
+            .title(format!(
+                " Setup ({}/{}): {} ",
+                wizard.step + 1,
+                SetupWizard::STEP_COUNT,
+                SetupWizard::steps()[wizard.step]
+            )),
+        area,
+    );
+
+    match wizard.step {
+        0 => {
+            // Detected managers get checkboxes; undetected ones only show
+            // why they failed detection, dimmed and unselectable.
+            let items: Vec<ListItem> = wizard
+                .detections
+                .iter()
+                .zip(&wizard.enabled)
+                .map(|(detection, &enabled)| match &detection.unavailable {
+                    None => ListItem::new(format!(
+                        "[{}] {}",
+                        if enabled { "x" } else { " " },
+                        detection.display_name
+                    )),
+                    Some(reason) => ListItem::new(Span::styled(
+                        format!("    {} — {}", detection.display_name, reason),
+                        app.theme.dim,
+                    )),
+                })
+                .collect();
+            let list = List::new(items).highlight_style(app.theme.selection);
+            frame.render_stateful_widget(list, chunks[0], &mut wizard.state);
+        }
+        1 => {
+            let items: Vec<ListItem> = SetupWizard::THEMES
+                .iter()
+                .map(|name| ListItem::new(*name))
+                .collect();
+            let mut state = ratatui::widgets::ListState::default();
+            state.select(Some(wizard.theme_choice));
+            let list = List::new(items).highlight_style(app.theme.selection);
+            frame.render_stateful_widget(list, chunks[0], &mut state);
+        }
+        2 => {
+            let options = [
+                crate::i18n::tr("setup.confirm.ask"),
+                crate::i18n::tr("setup.confirm.destructive"),
+                crate::i18n::tr("setup.confirm.never"),
+            ];
+            let items: Vec<ListItem> = options.iter().map(|o| ListItem::new(*o)).collect();
+            let mut state = ratatui::widgets::ListState::default();
+            state.select(Some(wizard.confirm));
+            let list = List::new(items).highlight_style(app.theme.selection);
+            frame.render_stateful_widget(list, chunks[0], &mut state);
+        }
+        3 => {
+            let options = [
+                crate::i18n::tr("setup.refresh.auto"),
+                crate::i18n::tr("setup.refresh.manual"),
+            ];
+            let items: Vec<ListItem> = options.iter().map(|o| ListItem::new(*o)).collect();
+            let mut state = ratatui::widgets::ListState::default();
+            state.select(Some(usize::from(!wizard.auto_refresh)));
+            let list = List::new(items).highlight_style(app.theme.selection);
+            frame.render_stateful_widget(list, chunks[0], &mut state);
+        }
+        _ => {
+            let items: Vec<ListItem> = SetupWizard::ESCALATIONS
+                .iter()
+                .map(|tool| ListItem::new(*tool))
+                .collect();
+            let mut state = ratatui::widgets::ListState::default();
+            state.select(Some(wizard.escalation));
+            let list = List::new(items).highlight_style(app.theme.selection);
+            frame.render_stateful_widget(list, chunks[0], &mut state);
+        }
+    }
+
+    let keys = if wizard.step == 0 {
+        crate::i18n::tr("setup.keys.managers")
+    } else if wizard.step + 1 == SetupWizard::STEP_COUNT {
+        crate::i18n::tr("setup.keys.last")
+    } else {
+        crate::i18n::tr("setup.keys.mid")
+    };
+    frame.render_widget(Paragraph::new(keys).style(app.theme.dim), chunks[1]);
+}
+
+fn draw_quit_prompt(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(50, 30, frame.area());
+    let title = app
+        .operation
+        .as_ref()
+        .map(|operation| format!(" {} is still running ", operation.description))
+        .unwrap_or_else(|| " Operation running ".to_string());
+    let Some(prompt) = app.quit_prompt.as_mut() else {
+        return;
+    };
+
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = crate::app::QuitPrompt::options()
+        .into_iter()
+        .map(|option| ListItem::new(truncate_width(option, width)))
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(title),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut prompt.state);
+}
+
+/// A question detected in the operation output; the chosen answer is sent
+/// to the child's stdin.
+fn draw_message_dialog(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(60, 40, frame.area());
+    let Some(dialog) = app.message_dialog.as_ref() else {
+        return;
+    };
+    frame.render_widget(Clear, area);
+    let text = Paragraph::new(dialog.lines.join("\n"))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(dialog.title.clone()),
+        );
+    frame.render_widget(text, area);
+}
+
+fn draw_prompt_dialog(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(60, 40, frame.area());
+    let Some(dialog) = app.prompt_dialog.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(1)])
+        .split(area);
+
+    frame.render_widget(Clear, area);
+    let question = Paragraph::new(dialog.question.as_str())
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(" Backend is asking "),
+        );
+    frame.render_widget(question, chunks[0]);
+
+    let items: Vec<ListItem> = dialog
+        .options
+        .iter()
+        .map(|option| ListItem::new(option.clone()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Answer "))
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, chunks[1], &mut dialog.state);
+}
+
+/// Confirmation before cancelling the running operation.
+fn draw_cancel_prompt(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(50, 30, frame.area());
+    let Some(prompt) = app.cancel_prompt.as_mut() else {
+        return;
+    };
+
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = crate::app::CancelPrompt::options()
+        .into_iter()
+        .map(|option| ListItem::new(truncate_width(option, width)))
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(" Cancel the running operation? "),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut prompt.state);
+}
+
+/// The confirmation gate: the operation as a question, with the policy
+/// that demanded the dialog named in the title.
+fn draw_confirm_prompt(frame: &mut Frame, app: &mut App) {
+    // Removals show their blast radius and installs their predicted
+    // conflicts inline, which needs more room than the plain yes/no
+    // dialog.
+    let area = if app.removal_impact.is_some() || app.conflict_report.is_some() {
+        centered_rect(60, 60, frame.area())
+    } else {
+        centered_rect(50, 30, frame.area())
+    };
+    let takes_snapshot = app
+        .confirm_prompt
+        .as_ref()
+        .is_some_and(|prompt| app.operation_takes_snapshot(&prompt.operation));
+    // Restores are backend-specific and dangerous; spell out what the
+    // active backend will actually do before asking for confirmation.
+    let restore_explanation = app.confirm_prompt.as_ref().and_then(|prompt| {
+        matches!(
+            prompt.operation,
+            crate::app::PendingOperation::RestoreSnapshot(_)
+        )
+        .then(|| app.snapshots.restore_explanation())
+    });
+    let Some(prompt) = app.confirm_prompt.as_mut() else {
+        return;
+    };
+
+    let constraints = if app.removal_impact.is_some() || app.conflict_report.is_some() {
+        vec![
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(4),
+        ]
+    } else {
+        vec![Constraint::Length(3), Constraint::Min(1)]
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+
+    frame.render_widget(Clear, area);
+    let mut question = prompt.operation.describe();
+    if takes_snapshot {
+        question.push_str(" (a snapshot will be taken first)");
+    }
+    if let Some(explanation) = restore_explanation {
+        question.push(' ');
+        question.push_str(explanation);
+    }
+    let question = Paragraph::new(question)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(format!(
+                    " confirmation required by policy: {} ",
+                    app.config.confirm
+                )),
+        );
+    frame.render_widget(question, chunks[0]);
+
+    let mut options_area = chunks[1];
+    if let Some(report) = &app.conflict_report {
+        options_area = chunks[2];
+        let mut lines: Vec<Line> = Vec::new();
+        if let Some(footprint) = &app.install_footprint {
+            lines.push(Line::from(format!("footprint: {}", footprint.summary())));
+        }
+        if report.conflicts.is_empty() {
+            lines.push(Line::styled("no conflicts predicted", app.theme.dim));
+        }
+        for conflict in &report.conflicts {
+            let text = match &conflict.path {
+                Some(path) => format!(
+                    "{} conflicts with {} over {path}",
+                    conflict.package, conflict.with
+                ),
+                None => format!("{} conflicts with {}", conflict.package, conflict.with),
+            };
+            lines.push(Line::styled(text, app.theme.warning));
+        }
+        for note in &report.notes {
+            lines.push(Line::styled(format!("note: {note}"), app.theme.dim));
+        }
+        let available = chunks[1].height.saturating_sub(2) as usize;
+        lines.truncate(available);
+        let block = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" predicted conflicts "),
+        );
+        frame.render_widget(block, chunks[1]);
+    }
+    if let Some(impact) = &app.removal_impact {
+        options_area = chunks[2];
+        let mut lines: Vec<Line> = Vec::new();
+        lines.push(Line::from(format!(
+            "affects {} dependent package(s), frees {}",
+            impact.affected,
+            format_size(impact.freed_bytes)
+        )));
+        if !impact.essential.is_empty() {
+            lines.push(Line::styled(
+                format!("essential: {}", impact.essential.join(", ")),
+                app.theme.error,
+            ));
+        }
+        if let Some(error) = &impact.simulation_error {
+            lines.push(Line::styled(
+                format!("simulation: {error}"),
+                app.theme.warning,
+            ));
+        }
+        let available = chunks[1].height.saturating_sub(2) as usize;
+        for (depth, name) in impact.tree.iter().take(available.saturating_sub(lines.len())) {
+            lines.push(Line::from(format!("{}{name}", "  ".repeat(*depth))));
+        }
+        let tree = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" removal impact "),
+        );
+        frame.render_widget(tree, chunks[1]);
+    }
+
+    let items: Vec<ListItem> = crate::app::ConfirmPrompt::options()
+        .into_iter()
+        .map(ListItem::new)
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, options_area, &mut prompt.state);
+}
+
+/// The snapshots of the active backend: `n` takes one, `d` deletes the
+/// selected, `R` starts a restore.
+pub fn draw_snapshots_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let mut title = format!(
+        " Snapshots of {} (backend: {}) ",
+        app.config.snapshots.subvolume,
+        app.snapshots.backend_id()
+    );
+    if let Some(usage) = &app.snapshot_usage {
+        let labeled = |label: &str, bytes: Option<u64>| {
+            bytes.map(|bytes| format!("{} {label}", format_size(bytes)))
+        };
+        let parts: Vec<String> = [
+            labeled("used", usage.used_bytes),
+            labeled("free", usage.free_bytes),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !parts.is_empty() {
+            title = format!("{title}— {} ", parts.join(", "));
+        }
+    }
+    if let Some(filter) = &app.snapshot_filter {
+        title = format!("{title}[filter: {filter}] ");
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::List))
+        .title(title);
+    let near_full = |snapshot: &crate::features::snapshots::Snapshot| {
+        snapshot
+            .usage_percent
+            .is_some_and(|percent| percent >= crate::features::snapshots::USAGE_WARN_PERCENT)
+    };
+    let empty = matches!(&app.snapshot_list, Loadable::Loaded(entries) if entries.is_empty());
+    if empty {
+        frame.render_widget(
+            Paragraph::new("no snapshots yet — press n to take one")
+                .style(app.theme.dim)
+                .block(block),
+            chunks[0],
+        );
+    } else if draw_dataset_placeholder(
+        frame,
+        app,
+        &app.snapshot_list,
+        "Listing snapshots...",
+        block.clone(),
+        chunks[0],
+    ) {
+        // placeholder rendered; fall through to the hint line
+    } else if app.filtered_snapshots().is_empty() {
+        frame.render_widget(
+            Paragraph::new("no snapshots match the filter")
+                .style(app.theme.dim)
+                .block(block),
+            chunks[0],
+        );
+    } else {
+        let entries = app.filtered_snapshots();
+        // Pre snapshots whose post exists get bracket markers, so each
+        // pre/post pair reads as one transaction.
+        let paired: std::collections::HashSet<&str> = entries
+            .iter()
+            .filter_map(|snapshot| snapshot.pre.as_deref())
+            .collect();
+        let items: Vec<ListItem> = entries
+            .iter()
+            .map(|snapshot| {
+                let marker = match snapshot.kind.as_str() {
+                    "pre" if paired.contains(snapshot.id.as_str()) => "┌ ",
+                    "pre" => "╶ ",
+                    "post" => "└ ",
+                    "packages" if app.snapshot_marks.contains(&snapshot.id) => "✓ ",
+                    "packages" => "≡ ",
+                    _ => "  ",
+                };
+                // LVM snapshots show how full their fixed space is;
+                // elsewhere the exclusive size is the useful number.
+                let usage = match (snapshot.usage_percent, snapshot.size_bytes) {
+                    (Some(percent), _) => format!("  {percent:.0}% full"),
+                    (None, Some(bytes)) => format!("  {}", format_size(bytes)),
+                    (None, None) => String::new(),
+                };
+                let note = snapshot
+                    .note
+                    .as_deref()
+                    .map(|note| format!("  \u{201c}{note}\u{201d}"))
+                    .unwrap_or_default();
+                let item = ListItem::new(format!(
+                    "{marker}{}  {}{note}  ({}){usage}",
+                    snapshot.created.format("%Y-%m-%d %H:%M"),
+                    snapshot.trigger,
+                    snapshot.id
+                ));
+                if near_full(snapshot) {
+                    item.style(app.theme.warning)
+                } else if app.snapshot_marks.contains(&snapshot.id) {
+                    item.style(app.theme.highlight)
+                } else {
+                    item
+                }
+            })
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(app.theme.selection);
+        frame.render_stateful_widget(list, chunks[0], &mut app.snapshots_state);
+    }
+    // A nearly full LVM snapshot is about to become invalid; that beats
+    // repeating the key hints.
+    let over = app
+        .snapshot_list
+        .value()
+        .map(|entries| entries.iter().filter(|s| near_full(s)).count())
+        .unwrap_or(0);
+    let hints = if over > 0 {
+        Paragraph::new(format!(
+            " {over} snapshot(s) nearly full — LVM drops them at 100%; delete or grow them "
+        ))
+        .style(app.theme.warning)
+    } else {
+        // rpm-ostree deployments are not created or deleted by hand, so
+        // its hint line swaps those keys for pinning.
+        let keys = if app.snapshots.backend_id() == "rpm-ostree" {
+            " R: roll back   p: pin   e: note   /: filter   Space: mark   D: diff "
+        } else {
+            " n: new   e: note   d: delete   R: restore   /: filter   Space: mark   D: diff "
+        };
+        Paragraph::new(keys).style(app.theme.dim)
+    }
+    .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+/// OSV vulnerability findings for the installed packages: `s` runs a
+/// scan, cached responses keep re-scans cheap.
+pub fn draw_security_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    use crate::features::security::Severity;
+
+    // Signature and keyring problems get their own block above the
+    // vulnerability list, followed by one line per third-party package
+    // source; all of it comes from the same scan.
+    let mut signatures: Vec<Line> = app
+        .vulns
+        .value()
+        .map(|report| report.signatures.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .map(|finding| {
+            let style = match finding.severity {
+                Severity::Critical | Severity::High => app.theme.error,
+                Severity::Medium => app.theme.warning,
+                _ => app.theme.dim,
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<8}  ", finding.severity.label()), style),
+                Span::raw(finding.detail.clone()),
+                Span::styled(format!("  ({})", finding.location), app.theme.dim),
+            ])
+        })
+        .collect();
+    if let Some(warning) = &app.eol {
+        let style = if warning.days_left < 0 {
+            app.theme.error
+        } else {
+            app.theme.warning
+        };
+        signatures.insert(
+            0,
+            Line::from(Span::styled(
+                format!("{:<8}  {}", "EOL", warning.describe()),
+                style,
+            )),
+        );
+    }
+    for group in app
+        .vulns
+        .value()
+        .map(|report| report.origins.as_slice())
+        .unwrap_or_default()
+    {
+        let style = if group.flags.is_empty() {
+            app.theme.dim
+        } else {
+            app.theme.warning
+        };
+        let mut text = format!(
+            "3rd-party  {} [{}]  {} package(s)",
+            group.origin,
+            group.manager,
+            group.packages.len()
+        );
+        if !group.behind.is_empty() {
+            text.push_str(&format!(", {} behind the AUR", group.behind.len()));
+        }
+        if let Some(flag) = group.flags.first() {
+            text.push_str(&format!("  — {flag}"));
+        }
+        signatures.push(Line::from(Span::styled(text, style)));
+    }
+    for finding in app
+        .vulns
+        .value()
+        .map(|report| report.setuid.as_slice())
+        .unwrap_or_default()
+    {
+        let style = match finding.severity {
+            Severity::Critical | Severity::High => app.theme.error,
+            Severity::Medium => app.theme.warning,
+            _ => app.theme.dim,
+        };
+        signatures.push(Line::from(vec![
+            Span::styled(format!("{:<8}  ", finding.severity.label()), style),
+            Span::raw(format!("{} {}", finding.kind, finding.path)),
+            Span::styled(
+                match &finding.package {
+                    Some(package) => format!("  (from {package})"),
+                    None => "  (no package owns it)".to_string(),
+                },
+                app.theme.dim,
+            ),
+        ]));
+    }
+    if let Some(report) = app.vulns.value() {
+        let flagged = report
+            .permissions
+            .iter()
+            .filter(|app| !app.flagged.is_empty())
+            .count();
+        if !report.permissions.is_empty() {
+            let text = format!(
+                "sandbox    {} app(s) audited, {flagged} with risky permissions (p)",
+                report.permissions.len()
+            );
+            signatures.push(Line::from(Span::styled(
+                text,
+                if flagged > 0 { app.theme.warning } else { app.theme.dim },
+            )));
+        }
+    }
+    let mut constraints = vec![Constraint::Min(1), Constraint::Length(1)];
+    if !signatures.is_empty() {
+        constraints.insert(0, Constraint::Length(signatures.len().min(8) as u16 + 2));
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(area);
+    let (signature_area, list_area, hint_area) = if signatures.is_empty() {
+        (None, chunks[0], chunks[1])
+    } else {
+        (Some(chunks[0]), chunks[1], chunks[2])
+    };
+    if let Some(area) = signature_area {
+        frame.render_widget(
+            Paragraph::new(signatures).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Signatures & sources "),
+            ),
+            area,
+        );
+    }
+
+    let today = chrono::Utc::now().date_naive();
+    let mut title = " Security (OSV) ".to_string();
+    if let Some(report) = app.vulns.value() {
+        // Reports persisted before engines were recorded have no label.
+        let engine = if report.engine.is_empty() {
+            "OSV"
+        } else {
+            report.engine.as_str()
+        };
+        // Ignored findings leave the counts; the title says how many are
+        // parked so they cannot vanish silently.
+        let ignored = report
+            .findings
+            .iter()
+            .filter(|finding| app.security_ignores.is_ignored(&finding.id, today))
+            .count();
+        title = if app.show_ignored_findings {
+            format!(" Security ({engine}) — {ignored} ignored finding(s); i: back to active ")
+        } else if ignored > 0 {
+            format!(
+                " Security ({engine}) — {} findings ({ignored} ignored), {} scanned, {} skipped ",
+                report.findings.len() - ignored,
+                report.scanned,
+                report.skipped
+            )
+        } else {
+            format!(
+                " Security ({engine}) — {} findings, {} scanned, {} skipped ",
+                report.findings.len(),
+                report.scanned,
+                report.skipped
+            )
+        };
+        title = format!(
+            "{}, scanned {} ",
+            title.trim_end(),
+            crate::utils::relative_age(report.generated)
+        );
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::List))
+        .title(title);
+    if app.vulns.is_not_loaded() {
+        frame.render_widget(
+            Paragraph::new("press s to scan installed packages against OSV.dev")
+                .style(app.theme.dim)
+                .block(block),
+            list_area,
+        );
+    } else if matches!(&app.vulns, Loadable::Loading) {
+        let progress = match app.scan_progress {
+            Some((queried, total)) => {
+                format!("Querying OSV.dev... ({queried}/{total} packages)")
+            }
+            None => "Querying OSV.dev...".to_string(),
+        };
+        frame.render_widget(
+            Paragraph::new(progress).style(app.theme.dim).block(block),
+            list_area,
+        );
+    } else if let Loadable::Failed(err) = &app.vulns {
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(err.clone(), app.theme.error)),
+                Line::from(""),
+                Line::from(Span::styled("press s to retry", app.theme.dim)),
+            ])
+            .block(block),
+            list_area,
+        );
+    } else if matches!(&app.vulns, Loadable::Loaded(_)) && app.visible_findings().is_empty() {
+        let empty = if app.show_ignored_findings {
+            Paragraph::new("no ignored findings (security ignore <id> <reason> parks one)")
+                .style(app.theme.dim)
+        } else if app.vulns.value().is_some_and(|r| !r.findings.is_empty()) {
+            Paragraph::new("all findings are ignored (i shows them)").style(app.theme.dim)
+        } else {
+            Paragraph::new("no known vulnerabilities in the scanned packages")
+                .style(app.theme.success)
+        };
+        frame.render_widget(empty.block(block), list_area);
+    } else if matches!(&app.vulns, Loadable::Loaded(_)) {
+        // One header per severity bucket, then its findings, each led by
+        // the normalized CVSS score where the advisory carried one.
+        let mut items: Vec<ListItem> = Vec::new();
+        for (severity, group) in app.security_buckets() {
+            let style = match severity {
+                Severity::Critical | Severity::High => app.theme.error,
+                Severity::Medium => app.theme.warning,
+                _ => app.theme.dim,
+            };
+            items.push(
+                ListItem::new(format!("── {} ({}) ──", severity.label(), group.len()))
+                    .style(style),
+            );
+            for finding in group {
+                let score = finding
+                    .score
+                    .map(|score| format!("{score:.1}"))
+                    .unwrap_or_else(|| "—".to_string());
+                let fixed = match &finding.fixed_version {
+                    Some(version) => format!("fixed in {version}"),
+                    None => "no fix listed".to_string(),
+                };
+                let triage = app
+                    .show_ignored_findings
+                    .then(|| app.security_ignores.get(&finding.id))
+                    .flatten()
+                    .map(|entry| match entry.until {
+                        Some(until) => format!("  [until {until}: {}]", entry.reason),
+                        None => format!("  [{}]", entry.reason),
+                    })
+                    .unwrap_or_default();
+                let item = ListItem::new(format!(
+                    "  {score:>4}  {} {} [{}]  {} ({})  {fixed}{triage}",
+                    finding.package,
+                    finding.installed_version,
+                    finding.manager,
+                    finding.id,
+                    finding.source
+                ));
+                items.push(match finding.severity {
+                    Severity::Critical | Severity::High => item.style(app.theme.error),
+                    Severity::Medium => item.style(app.theme.warning),
+                    Severity::Low => item,
+                    Severity::Unknown => item.style(app.theme.dim),
+                });
+            }
+        }
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(app.theme.selection);
+        frame.render_stateful_widget(list, list_area, &mut app.security_state);
+    }
+    // A feed that failed leaves a hole in the report; saying so beats
+    // repeating the key hints.
+    let failed_sources = app
+        .vulns
+        .value()
+        .map(|report| report.source_errors.as_slice())
+        .unwrap_or_default();
+    let hints = if let Some(error) = failed_sources.first() {
+        Paragraph::new(format!(" partial report — {error} ")).style(app.theme.warning)
+    } else if app.show_ignored_findings {
+        Paragraph::new(" i: active findings   security unignore <id> revives one ")
+            .style(app.theme.dim)
+    } else {
+        Paragraph::new(" enter: details   s: scan   i: ignored   o: sources   p: permissions   x: export   security ignore <id> <reason> ")
+            .style(app.theme.dim)
+    }
+    .alignment(Alignment::Center);
+    frame.render_widget(hints, hint_area);
+}
+
+/// Scrollable package-set diff, colored by the kind of each change.
+fn draw_diff_view(frame: &mut Frame, app: &mut App) {
+    use crate::features::snapshots::ChangeKind;
+
+    let area = centered_rect(70, 70, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.diff_view.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    frame.render_widget(Clear, area);
+    let items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .map(|(text, kind)| {
+            let item = ListItem::new(text.clone());
+            match kind {
+                None => item.style(theme.highlight),
+                Some(ChangeKind::Added) => item.style(theme.success),
+                Some(ChangeKind::Removed) => item.style(theme.error),
+                Some(ChangeKind::Downgraded) => item.style(theme.warning),
+                Some(ChangeKind::MarkChanged) => item.style(theme.dim),
+                Some(ChangeKind::Upgraded) => item,
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(view.title.clone()),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" j/k: scroll   e: export Markdown   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+/// Shown when the running operation has produced no output for a while,
+/// which usually means it is stuck on a prompt we did not recognize.
+fn draw_stall_prompt(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(50, 30, frame.area());
+    let Some(prompt) = app.stall_prompt.as_mut() else {
+        return;
+    };
+
+    let width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = crate::app::StallPrompt::options()
+        .into_iter()
+        .map(|option| ListItem::new(truncate_width(option, width)))
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.theme.warning)
+                .title(" No output for a while - still working? "),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut prompt.state);
+}
+
+fn draw_scope_picker(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(40, 40, frame.area());
+    let enabled = app.enabled_managers.clone();
+    let Some(picker) = app.scope_picker.as_mut() else {
+        return;
+    };
+
+    let items: Vec<ListItem> = picker
+        .ids
+        .iter()
+        .map(|id| {
+            let mark = if enabled.contains(id) { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{mark} {id}"))
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Managers (space toggles) "),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut picker.state);
+}
+
+fn draw_origin_picker(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(40, 50, frame.area());
+    let Some(picker) = app.origin_picker.as_mut() else {
+        return;
+    };
+
+    let total: usize = picker.entries.iter().map(|(_, count)| count).sum();
+    let mut items = vec![ListItem::new(format!("All origins ({total})"))];
+    for (origin, count) in &picker.entries {
+        let style = if is_third_party_origin(origin) {
+            app.theme.warning
+        } else {
+            ratatui::style::Style::default()
+        };
+        items.push(ListItem::new(Span::styled(
+            format!("{origin} ({count})"),
+            style,
+        )));
+    }
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Origin "))
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut picker.state);
+}
+
+/// Advisory details for one finding (Enter on a Security row). n/p walk
+/// the package's other findings; fields the cached advisory lacks say
+/// "unknown" instead of disappearing.
+fn draw_finding_detail(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 70, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.finding_detail.as_mut() else {
+        return;
+    };
+    let Some(finding) = view.findings.get(view.index) else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+    let unknown = || "unknown".to_string();
+    let severity_style = match finding.severity {
+        crate::features::security::Severity::Critical
+        | crate::features::security::Severity::High => theme.error,
+        crate::features::security::Severity::Medium => theme.warning,
+        _ => theme.dim,
+    };
+    let detail = view.detail.as_ref();
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{label:<11}"), theme.dim),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(finding.id.clone(), theme.highlight),
+            Span::styled(
+                format!("  ({}/{})", view.index + 1, view.findings.len()),
+                theme.dim,
+            ),
+        ]),
+        field(
+            "package",
+            format!(
+                "{} {} [{}]",
+                finding.package, finding.installed_version, finding.manager
+            ),
+        ),
+        Line::from(vec![
+            Span::styled(format!("{:<11}", "severity"), theme.dim),
+            Span::styled(finding.severity.label().to_string(), severity_style),
+            Span::raw(format!(
+                "  score {}",
+                finding
+                    .score
+                    .map(|score| format!("{score:.1}"))
+                    .unwrap_or_else(unknown)
+            )),
+        ]),
+        field(
+            "vector",
+            detail
+                .and_then(|detail| detail.vector.clone())
+                .unwrap_or_else(unknown),
+        ),
+        field(
+            "published",
+            detail
+                .and_then(|detail| detail.published.clone())
+                .unwrap_or_else(unknown),
+        ),
+        field("affected", finding.installed_version.clone()),
+        field(
+            "fixed in",
+            finding.fixed_version.clone().unwrap_or_else(unknown),
+        ),
+        field("source", finding.source.clone()),
+        Line::from(""),
+    ];
+    let summary = detail
+        .map(|detail| detail.summary.clone())
+        .filter(|summary| !summary.is_empty())
+        .unwrap_or_else(|| finding.summary.clone());
+    lines.push(Line::from(if summary.is_empty() {
+        Span::styled("no summary in the cached advisory", theme.dim)
+    } else {
+        Span::raw(summary)
+    }));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("references", theme.dim)));
+    match detail.map(|detail| detail.references.as_slice()) {
+        Some([]) | None => lines.push(Line::from(Span::styled(
+            format!("  {}  (from the id)", crate::features::security::advisory_url(&finding.id)),
+            theme.dim,
+        ))),
+        Some(references) => {
+            for url in references {
+                lines.push(Line::from(format!("  {url}")));
+            }
+        }
+    }
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" {} ", finding.id)),
+            )
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .scroll((view.scroll, 0)),
+        chunks[0],
+    );
+    let hints = Paragraph::new(" j/k: scroll   n/p: other findings   y: copy link   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+/// Services running outdated binaries, with checkboxes; Enter restarts
+/// the marked ones through privilege escalation.
+fn draw_restart_picker(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(50, 50, frame.area());
+    let Some(picker) = app.restart_picker.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+    let items: Vec<ListItem> = picker
+        .services
+        .iter()
+        .enumerate()
+        .map(|(index, service)| {
+            let mark = if picker.selected.contains(&index) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            ListItem::new(format!("{mark} {service}"))
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Services needing restart "),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut picker.state);
+    let hints = Paragraph::new(" space: mark   enter: restart marked   Esc: close ")
+        .style(app.theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+/// Third-party sources from the last scan: one header row per source,
+/// expanded in place to its weak-configuration flags and packages.
+fn draw_origin_risk(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 60, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.origin_risk.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let mut items: Vec<ListItem> = Vec::new();
+    for (index, group) in view.groups.iter().enumerate() {
+        let expanded = view.expanded.contains(&index);
+        let marker = if expanded { "▾" } else { "▸" };
+        let mut header = format!(
+            "{marker} {} [{}]  {} package(s)",
+            group.origin,
+            group.manager,
+            group.packages.len()
+        );
+        if !group.behind.is_empty() {
+            header.push_str(&format!(", {} behind the AUR", group.behind.len()));
+        }
+        items.push(ListItem::new(header).style(if group.flags.is_empty() {
+            ratatui::style::Style::default()
+        } else {
+            theme.warning
+        }));
+        if !expanded {
+            continue;
+        }
+        for flag in &group.flags {
+            items.push(ListItem::new(format!("    ! {flag}")).style(theme.warning));
+        }
+        for (name, version) in &group.packages {
+            let lag = group
+                .behind
+                .iter()
+                .find(|(behind, _, _)| behind == name)
+                .map(|(_, _, current)| format!("  (AUR has {current})"))
+                .unwrap_or_default();
+            let item = ListItem::new(format!("    {name} {version}{lag}"));
+            items.push(if lag.is_empty() {
+                item.style(theme.dim)
+            } else {
+                item
+            });
+        }
+    }
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Third-party sources "),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" enter/space: expand   g: show its packages   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_permission_view(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 60, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.permission_view.as_mut() else {
+        return;
+    };
+
+    // The overrides block below the list only appears when there is
+    // anything to show.
+    let override_rows = if view.overrides.is_empty() {
+        0
+    } else {
+        view.overrides.len().min(6) as u16 + 1
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(override_rows),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    let mut items: Vec<ListItem> = Vec::new();
+    for (index, audited) in view.apps.iter().enumerate() {
+        let expanded = view.expanded.contains(&index);
+        let marker = if expanded { "▾" } else { "▸" };
+        let mut header = format!(
+            "{marker} {} [{}]  {} permission(s)",
+            audited.app,
+            audited.manager,
+            audited.permissions.len()
+        );
+        if !audited.flagged.is_empty() {
+            header.push_str(&format!(", {} flagged", audited.flagged.len()));
+        }
+        items.push(ListItem::new(header).style(if audited.flagged.is_empty() {
+            ratatui::style::Style::default()
+        } else {
+            theme.warning
+        }));
+        if !expanded {
+            continue;
+        }
+        for grant in &audited.permissions {
+            let risky = audited.flagged.iter().any(|flag| flag == grant);
+            items.push(if risky {
+                ListItem::new(format!("    ! {grant}")).style(theme.warning)
+            } else {
+                ListItem::new(format!("    {grant}")).style(theme.dim)
+            });
+        }
+    }
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Sandbox permissions "),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    if override_rows > 0 {
+        let mut lines = vec![Line::from(Span::styled(
+            "flatpak overrides in effect:",
+            theme.highlight,
+        ))];
+        for entry in view.overrides.iter().take(5) {
+            lines.push(Line::from(Span::styled(format!("  {entry}"), theme.dim)));
+        }
+        frame.render_widget(Paragraph::new(lines), chunks[1]);
+    }
+    let hints = Paragraph::new(" enter/space: expand   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[2]);
+}
+
+fn draw_dep_health(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 50, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.dep_health.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let items: Vec<ListItem> = if view.problems.is_empty() {
+        vec![ListItem::new("no problems found").style(theme.dim)]
+    } else {
+        view.problems
+            .iter()
+            .map(|problem| {
+                let mut line = format!(
+                    "{} — {} [{}]",
+                    problem.package, problem.detail, problem.manager
+                );
+                if let Some(fix) = &problem.fix {
+                    line.push_str(&format!("   fix: {fix}"));
+                }
+                ListItem::new(line).style(theme.warning)
+            })
+            .collect()
+    };
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Dependency health "),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" enter: run the suggested fix   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_optional_deps(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 50, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.optional_deps.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(index, (dep, present))| {
+            let marker = if *present {
+                "[installed]"
+            } else if view.marked.contains(&index) {
+                "[*]"
+            } else {
+                "[ ]"
+            };
+            let mut line = format!("{marker} {}", dep.name);
+            if let Some(note) = &dep.note {
+                line.push_str(&format!(" — {note}"));
+            }
+            let item = ListItem::new(line);
+            if *present {
+                item.style(theme.dim)
+            } else {
+                item
+            }
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Optional dependencies of {} ", view.package)),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" space: mark   enter: install marked/selected   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_provider_picker(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 50, frame.area());
+    let theme = &app.theme;
+    let Some(picker) = app.provider_picker.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let items: Vec<ListItem> = picker
+        .providers
+        .iter()
+        .map(|provider| {
+            let mut line = provider.name.clone();
+            if !provider.version.is_empty() {
+                line.push_str(&format!(" {}", provider.version));
+            }
+            if !provider.description.is_empty() {
+                line.push_str(&format!(" — {}", provider.description));
+            }
+            ListItem::new(line)
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " {} is virtual — pick a provider ",
+            picker.name
+        )))
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut picker.state);
+    let hints = Paragraph::new(" enter: install the selected provider   Esc: abort ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_overlaps(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 50, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.overlaps_view.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let items: Vec<ListItem> = view
+        .rows
+        .iter()
+        .map(|row| {
+            let item = ListItem::new(row.text.clone());
+            if row.target.is_none() {
+                item.style(theme.title)
+            } else if row.winner {
+                item.style(theme.success)
+            } else {
+                item
+            }
+        })
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Cross-manager overlaps "),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" enter: remove the selected copy   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_heavy_deps(frame: &mut Frame, app: &mut App) {
+    let area = centered_rect(70, 50, frame.area());
+    let theme = &app.theme;
+    let Some(view) = app.heavy_deps.as_mut() else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(area);
+
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .map(|(_, _, line)| ListItem::new(line.clone()))
+        .collect();
+    frame.render_widget(Clear, area);
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Heavy single-root dependencies "),
+        )
+        .highlight_style(theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut view.state);
+    let hints = Paragraph::new(" enter: why is it installed   Esc: close ")
+        .style(theme.dim)
+        .alignment(Alignment::Center);
+    frame.render_widget(hints, chunks[1]);
+}
+
+fn draw_tabs(frame: &mut Frame, app: &App, area: Rect) {
+    // Translated titles can be long; give each tab an equal share of the
+    // bar and truncate with an ellipsis rather than overflow.
+    let per_tab = (area.width as usize / app.tabs.len().max(1)).saturating_sub(5).max(6);
+    let titles: Vec<Line> = app
+        .tabs
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| Line::from(format!("{} {}", i + 1, truncate_width(tab.title(), per_tab))))
+        .collect();
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled(" pkgtool ", app.theme.header));
+    if app.dry_run() {
+        // Keep the mode impossible to miss while every action only simulates.
+        block = block.title_top(
+            Line::from(Span::styled(" DRY RUN ", app.theme.warning)).right_aligned(),
+        );
+    }
+    // The restart state stays visible until acted on: a reboot cannot be
+    // done from here, and service restarts wait behind `R`.
+    if let Some(report) = &app.restart {
+        if let Some(reason) = &report.reboot {
+            block = block.title_top(
+                Line::from(Span::styled(
+                    format!(" REBOOT REQUIRED: {reason} "),
+                    app.theme.error,
+                ))
+                .right_aligned(),
+            );
+        } else if !report.services.is_empty() {
+            block = block.title_top(
+                Line::from(Span::styled(
+                    format!(" {} service(s) need restart (R) ", report.services.len()),
+                    app.theme.warning,
+                ))
+                .right_aligned(),
+            );
+        }
+    }
+    let tabs = Tabs::new(titles)
+        .select(app.selected_tab)
+        .block(block)
+        .highlight_style(app.theme.highlight);
+    frame.render_widget(tabs, area);
+}
+
+pub fn draw_overview_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    // A dead or dying release undermines everything below it; one
+    // full-width line above the summary is hard to miss.
+    let mut area = area;
+    if let Some(warning) = &app.eol {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(area);
+        let style = if warning.days_left < 0 {
+            app.theme.error
+        } else {
+            app.theme.warning
+        };
+        frame.render_widget(
+            Paragraph::new(format!(" ⚠ {}", warning.describe())).style(style),
+            chunks[0],
+        );
+        area = chunks[1];
+    }
+    let rows = app.overview_rows();
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|(text, target)| {
+            let style = if target.is_some() {
+                ratatui::style::Style::default()
+            } else {
+                app.theme.dim
+            };
+            ListItem::new(Line::from(Span::styled(text.clone(), style)))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(app.pane_border(Focus::List))
+                .title(" Overview "),
+        )
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut app.overview_state);
+}
+
+/// Build a list row for a package at the configured density.
+///
+/// In detailed mode each row carries a second, dimmed line with the summary
+/// and size — but only rows inside the visible window get it formatted;
+/// off-screen rows keep an empty filler line so all rows stay two cells tall.
+fn package_row(
+    app: &App,
+    pkg: &crate::package_managers::PackageInfo,
+    visible: bool,
+    hint: Option<char>,
+) -> ListItem<'static> {
+    let mut spans = Vec::new();
+    if app.hint_mode {
+        spans.push(match hint {
+            Some(c) => Span::styled(format!("{c} "), app.theme.highlight),
+            None => Span::raw("  ".to_string()),
+        });
+    }
+    spans.extend([
+        watch_marker(app, &pkg.manager, &pkg.name),
+        held_marker(app, &pkg.manager, &pkg.name),
+        Span::raw(format!("{:<40}", pkg.name)),
+        Span::styled(format!("{:<24}", pkg.version), app.theme.dim),
+    ]);
+    if let Some(origin) = &pkg.origin {
+        let style = if is_third_party_origin(origin) {
+            app.theme.warning
+        } else {
+            app.theme.dim
+        };
+        spans.push(Span::styled(format!("{:<14}", origin), style));
+    }
+    if let Some(date) = pkg.install_date {
+        spans.push(Span::styled(crate::utils::relative_age(date), app.theme.dim));
+    }
+    let first = Line::from(spans);
+    match app.density {
+        ViewDensity::Compact => ListItem::new(first),
+        ViewDensity::Detailed if !visible => ListItem::new(vec![first, Line::from("")]),
+        ViewDensity::Detailed => {
+            let summary = pkg.description.lines().next().unwrap_or("").to_string();
+            let mut second = format!("  {summary}");
+            if let Some(size) = pkg.size {
+                second.push_str(&format!("  ({})", format_size(size)));
+            }
+            ListItem::new(vec![first, Line::from(Span::styled(second, app.theme.dim))])
+        }
+    }
+}
+
+/// A two-column marker showing whether a package is on the watchlist.
+fn watch_marker(app: &App, manager: &str, name: &str) -> Span<'static> {
+    if app.watchlist.contains(manager, name) {
+        Span::styled("* ".to_string(), app.theme.highlight)
+    } else {
+        Span::raw("  ")
+    }
+}
+
+/// A two-column lock marker showing whether a package is held.
+fn held_marker(app: &App, manager: &str, name: &str) -> Span<'static> {
+    if app.is_held(manager, name) {
+        Span::styled("\u{1f512}".to_string(), app.theme.warning)
+    } else {
+        Span::raw("  ")
+    }
+}
+
+/// Index range of rows that can appear on screen for a list state.
+fn visible_window(offset: usize, area_height: u16, row_height: usize) -> std::ops::Range<usize> {
+    let rows = (area_height.saturating_sub(2) as usize) / row_height.max(1) + 1;
+    offset..offset + rows
+}
+
+/// Render placeholder content when a dataset has nothing to list.
+///
+/// Returns `true` when a placeholder was drawn, so the caller can skip the
+/// usual list rendering. `Loaded` with rows present draws nothing.
+fn draw_dataset_placeholder<T>(
+    frame: &mut Frame,
+    app: &App,
+    data: &Loadable<Vec<T>>,
+    loading: &str,
+    block: Block,
+    area: Rect,
+) -> bool {
+    let placeholder = match data {
+        Loadable::NotLoaded => Paragraph::new("Not loaded yet - press r").style(app.theme.dim),
+        Loadable::Loading => Paragraph::new(loading.to_string()).style(app.theme.dim),
+        Loadable::Failed(err) => Paragraph::new(vec![
+            Line::from(Span::styled(err.clone(), app.theme.error)),
+            Line::from(""),
+            Line::from(Span::styled("press r to retry", app.theme.dim)),
+        ]),
+        Loadable::Loaded(rows) if rows.is_empty() => {
+            Paragraph::new("no results").style(app.theme.dim)
+        }
+        Loadable::Loaded(_) => return false,
+    };
+    frame.render_widget(placeholder.block(block), area);
+    true
+}
+
+pub fn draw_packages_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(app.split_ratio),
+            Constraint::Percentage(100 - app.split_ratio),
+        ])
+        .split(area);
+    app.pane_rects.list = chunks[0];
+    app.pane_rects.details = chunks[1];
+
+    let title_width = chunks[0].width.saturating_sub(4) as usize;
+    let title = format!(" {} ", app.packages_breadcrumb(title_width));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::List))
+        .title(title);
+    if draw_dataset_placeholder(
+        frame,
+        app,
+        &app.packages,
+        "Loading installed packages...",
+        block.clone(),
+        chunks[0],
+    ) {
+        draw_details(frame, app, chunks[1]);
+        return;
+    }
+
+    let row_height = if app.density == ViewDensity::Detailed { 2 } else { 1 };
+    let window = visible_window(app.package_state.offset(), chunks[0].height, row_height);
+    // Hints are assigned here, to the rows this frame actually shows, so the
+    // mapping stays valid however the list is scrolled or filtered.
+    if app.hint_mode {
+        let total = app.installed_visible().len();
+        app.hint_targets = window
+            .clone()
+            .filter(|i| *i < total)
+            .take(crate::app::HINT_CHARS.chars().count())
+            .collect();
+    }
+    let items: Vec<ListItem> = app
+        .installed_visible()
+        .iter()
+        .enumerate()
+        .map(|(i, pkg)| {
+            let hint = if app.hint_mode {
+                app.hint_targets
+                    .iter()
+                    .position(|&target| target == i)
+                    .and_then(|position| crate::app::HINT_CHARS.chars().nth(position))
+            } else {
+                None
+            };
+            package_row(app, pkg, window.contains(&i), hint)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, chunks[0], &mut app.package_state);
+
+    draw_details(frame, app, chunks[1]);
+}
+
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::Details))
+        .title(" Details ");
+    let Some(details) = &app.details else {
+        let placeholder = Paragraph::new("Press Enter on a package to load details")
+            .style(app.theme.dim)
+            .block(block);
+        frame.render_widget(placeholder, area);
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(details.name.clone(), app.theme.title),
+            Span::raw(" "),
+            Span::raw(details.version.clone()),
+        ]),
+        Line::from(Span::styled(
+            format!("manager: {}", details.manager),
+            app.theme.dim,
+        )),
+    ];
+    if app.is_held(&details.manager, &details.name) {
+        lines.push(Line::from(Span::styled(
+            format!("held at version {}", details.version),
+            app.theme.warning,
+        )));
+    }
+    if let Some(size) = details.size {
+        lines.push(Line::from(format!("size: {}", format_size(size))));
+    }
+    if let Some(footprint) = &app.details_footprint {
+        lines.push(Line::from(format!(
+            "install footprint: {}",
+            footprint.summary()
+        )));
+    }
+    if let Some(url) = &details.url {
+        lines.push(Line::from(format!("url: {url}")));
+    }
+    if !details.licenses.is_empty() {
+        lines.push(Line::from(format!("license: {}", details.licenses.join(", "))));
+    }
+    if let Some(packager) = &details.packager {
+        lines.push(Line::from(Span::styled(
+            format!("packager: {packager}"),
+            app.theme.dim,
+        )));
+    }
+    if let Some(validation) = &details.validation {
+        lines.push(Line::from(Span::styled(
+            format!("validated by: {validation}"),
+            app.theme.dim,
+        )));
+    }
+    if let Some(reason) = crate::package_managers::provenance_flag(
+        &details.manager,
+        details.packager.as_deref(),
+        details.validation.as_deref(),
+    ) {
+        lines.push(Line::from(Span::styled(
+            format!("⚠ {reason}"),
+            app.theme.warning,
+        )));
+    }
+    lines.push(Line::from(""));
+
+    // Description: bold summary line, wrapped body, scrollable via J/K.
+    let text_width = area.width.saturating_sub(2) as usize;
+    let mut description_lines = details.description.lines();
+    if let Some(summary) = description_lines.next() {
+        for wrapped in description::wrap_description(summary, text_width) {
+            lines.push(Line::from(Span::styled(
+                wrapped,
+                ratatui::style::Style::default().add_modifier(ratatui::style::Modifier::BOLD),
+            )));
+        }
+    }
+    let body = description_lines.collect::<Vec<_>>().join("
+");
+    for wrapped in description::wrap_description(&body, text_width) {
+        lines.push(Line::from(wrapped));
+    }
+
+    if !details.depends.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Depends:", app.theme.title)));
+        for dep in &details.depends {
+            lines.push(Line::from(format!("  {dep}")));
+        }
+    }
+
+    if let Some(provides) = app.details_provides.as_deref().filter(|names| !names.is_empty()) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Provides:", app.theme.title)));
+        for name in provides {
+            // Dimmed: these are virtual edges, not packages of their own.
+            lines.push(Line::from(Span::styled(
+                format!("  {name} (virtual)"),
+                app.theme.dim,
+            )));
+        }
+    }
+
+    if let Some(optional) = app.details_optional.as_deref().filter(|deps| !deps.is_empty()) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Optional (O to browse/install):",
+            app.theme.title,
+        )));
+        for (dep, present) in optional {
+            let marker = if *present { "[installed]" } else { "[ ]" };
+            let mut text = format!("  {marker} {}", dep.name);
+            if let Some(note) = &dep.note {
+                text.push_str(&format!(" — {note}"));
+            }
+            lines.push(if *present {
+                Line::from(Span::styled(text, app.theme.dim))
+            } else {
+                Line::from(text)
+            });
+        }
+    }
+
+    // Clamp the scroll so the last line stays reachable but visible.
+    let max_scroll = (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+    let scroll = app.details_scroll.min(max_scroll);
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)).block(block), area);
+}
+
+pub fn draw_updates_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    // (manager, name) pairs of the updates that fix vulnerabilities.
+    let security: std::collections::HashSet<(String, String)> = app
+        .security_updates()
+        .iter()
+        .map(|update| (update.manager.clone(), update.name.clone()))
+        .collect();
+    let mut title = format!(" Updates ({}) ", app.pending_updates().len());
+    if !security.is_empty() {
+        title = format!(
+            " Updates ({}, {} security — S applies just those) ",
+            app.pending_updates().len(),
+            security.len()
+        );
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::List))
+        .title(title);
+    if draw_dataset_placeholder(
+        frame,
+        app,
+        &app.updates,
+        "Checking for updates...",
+        block.clone(),
+        area,
+    ) {
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .pending_updates()
+        .iter()
+        .map(|update| {
+            // Held packages will not actually be upgraded, so grey them out.
+            let held = app.is_held(&update.manager, &update.name);
+            let (version_style, new_style) = if held {
+                (app.theme.dim, app.theme.dim)
+            } else {
+                (app.theme.dim, app.theme.success)
+            };
+            let mut spans = vec![
+                watch_marker(app, &update.manager, &update.name),
+                held_marker(app, &update.manager, &update.name),
+                Span::raw(format!("{:<40}", update.name)),
+                Span::styled(update.current_version.clone(), version_style),
+                Span::raw(" -> "),
+                Span::styled(update.new_version.clone(), new_style),
+            ];
+            if held {
+                spans.push(Span::styled(" (held)".to_string(), app.theme.dim));
+            }
+            if security.contains(&(update.manager.clone(), update.name.clone())) {
+                spans.push(Span::styled(" security".to_string(), app.theme.error));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut app.updates_state);
+}
+
+pub fn draw_search_tab(frame: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(app.pane_border(Focus::List))
+        .title(format!(" Results ({}) ", app.search_hits().len()));
+    if draw_dataset_placeholder(frame, app, &app.search_results, "Searching...", block.clone(), area)
+    {
+        return;
+    }
+
+    let items: Vec<ListItem> = app
+        .search_hits()
+        .iter()
+        .map(|pkg| {
+            ListItem::new(Line::from(vec![
+                watch_marker(app, &pkg.manager, &pkg.name),
+                Span::raw(format!("{:<32}", pkg.name)),
+                Span::styled(format!("[{}] ", pkg.manager), app.theme.dim),
+                Span::raw(pkg.description.clone()),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(app.theme.selection);
+    frame.render_stateful_widget(list, area, &mut app.search_state);
+}
+
+fn draw_bottom_bar(frame: &mut Frame, app: &App, area: Rect) {
+    match app.mode {
+        Mode::Editing => {
+            let input = Paragraph::new(app.input.as_str())
+                .style(app.theme.highlight)
+                .block(Block::default().borders(Borders::ALL).title(" Command "));
+            frame.render_widget(input, area);
+            let cursor_cells = app.input[..app.input_cursor].chars().count() as u16;
+            frame.set_cursor_position((area.x + 1 + cursor_cells, area.y + 1));
+        }
+        Mode::Normal => {
+            let bar = if let Some(typeahead) = &app.typeahead {
+                Paragraph::new(Line::from(vec![
+                    Span::styled("jump: ", app.theme.highlight),
+                    Span::raw(typeahead.buffer.clone()),
+                ]))
+                .block(Block::default().borders(Borders::ALL).title(" Status "))
+            } else {
+                let status = app
+                    .status_message
+                    .as_deref()
+                    .unwrap_or(crate::i18n::tr("bar.hints"));
+                let mut spans = Vec::new();
+                if app.reboot_required {
+                    spans.push(Span::styled(
+                        "[reboot to complete restore]  ".to_string(),
+                        app.theme.warning,
+                    ));
+                }
+                spans.push(Span::raw(status.to_string()));
+                let security = app.security_updates().len();
+                if security > 0 {
+                    spans.push(Span::styled(
+                        format!("  [{security} security update(s) pending]"),
+                        app.theme.warning,
+                    ));
+                }
+                let total = app.package_managers.len();
+                if app.enabled_managers.len() < total {
+                    spans.push(Span::styled(
+                        format!("  [{}/{} managers]", app.enabled_managers.len(), total),
+                        app.theme.warning,
+                    ));
+                }
+                Paragraph::new(Line::from(spans))
+                    .block(Block::default().borders(Borders::ALL).title(" Status "))
+            };
+            frame.render_widget(bar, area);
+        }
+    }
+}
+
+fn draw_help(frame: &mut Frame, app: &App) {
+    let area = centered_rect(60, 70, frame.area());
+    let registry = crate::actions::registry();
+    let filter = app.help_filter.as_deref().unwrap_or("");
+
+    // Navigation keys that are not actions live alongside the registry so
+    // the reference stays complete and both halves filter the same way.
+    let navigation: [(&str, &str); 12] = [
+        ("j/k", "Move the selection"),
+        ("g/G", "Jump to top/bottom"),
+        ("Enter", "Load package details"),
+        ("J/K", "Scroll the details pane"),
+        ("C-Le/Ri", "Move focus between panes"),
+        ("< > =", "Resize the list/details split"),
+        ("v", "Toggle compact/detailed rows"),
+        ("s", "Sort by name / recently installed"),
+        ("o", "Filter by origin/repository"),
+        ("H", "Show only held packages"),
+        ("#", "Quick-select a visible row by hint"),
+        ("'", "Type-ahead jump in the list"),
+    ];
+
+    let mut lines = vec![
+        Line::from(Span::styled("Command reference", app.theme.header)),
+        Line::from(""),
+    ];
+    for entry in &registry {
+        let haystack = format!("{} {}", entry.title, entry.key.unwrap_or(""));
+        if !filter.is_empty() && crate::actions::fuzzy_score(filter, &haystack).is_none() {
+            continue;
+        }
+        lines.push(Line::from(format!(
+            "  {:<10} {}",
+            entry.key.unwrap_or(""),
+            entry.title
+        )));
+        if let Some(synopsis) = entry.synopsis {
+            lines.push(Line::from(Span::styled(
+                format!("             {synopsis}"),
+                app.theme.dim,
+            )));
+        }
+        if !filter.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("             {}", entry.description),
+                app.theme.dim,
+            )));
+        }
+    }
+    for (key, title) in navigation {
+        if !filter.is_empty()
+            && crate::actions::fuzzy_score(filter, &format!("{title} {key}")).is_none()
+        {
+            continue;
+        }
+        lines.push(Line::from(format!("  {key:<10} {title}")));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(match &app.help_filter {
+        Some(filter) => Line::from(Span::styled(
+            format!("filter: {filter}_  (Esc to clear)"),
+            app.theme.highlight,
+        )),
+        None => Line::from(Span::styled(
+            "press / to filter, any other key to close",
+            app.theme.dim,
+        )),
+    });
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Left)
+            .block(Block::default().borders(Borders::ALL).title(" Help ")),
+        area,
+    );
+}
+
+/// A centered rectangle occupying the given percentages of the parent area.
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    let horizontal = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1]);
+    horizontal[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::style::Color;
+    use ratatui::Terminal;
+
+    #[test]
+    fn no_color_theme_renders_without_colors() {
+        let mut app = App::new(crate::config::Config::default());
+        app.theme = crate::theme::Theme::no_color();
+        app.status_message = Some("status".to_string());
+
+        let backend = TestBackend::new(80, 24);
+        let mut terminal = Terminal::new(backend).unwrap();
+        terminal.draw(|frame| draw(frame, &mut app)).unwrap();
+
+        for cell in terminal.backend().buffer().content() {
+            assert_eq!(cell.fg, Color::Reset, "colored fg in cell {:?}", cell);
+            assert_eq!(cell.bg, Color::Reset, "colored bg in cell {:?}", cell);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+/// Remote host settings from the `[remote]` config section. Empty `host`
+/// manages the local machine; `--host user@server` overrides the section.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteConfig {
+    /// Hostname or address of the managed machine; empty means local.
+    pub host: String,
+    /// Login user; empty defers to ssh's own configuration for the host.
+    pub user: String,
+    /// Extra ssh arguments, e.g. ["-p", "2222"].
+    pub ssh_options: Vec<String>,
+}
+
+impl RemoteConfig {
+    /// The ssh destination, e.g. "admin@server", when one is configured.
+    pub fn target(&self) -> Option<String> {
+        if self.host.is_empty() {
+            None
+        } else if self.user.is_empty() {
+            Some(self.host.clone())
+        } else {
+            Some(format!("{}@{}", self.user, self.host))
+        }
+    }
+}
+
+/// Where backend commands run and backend files are read: this machine or
+/// a remote host over SSH. Backends never know the difference — they build
+/// argv as usual and the shared runner routes it, and every file they
+/// would read (/etc/os-release, dpkg logs, pacman.conf) goes through
+/// `read_file` instead of the local filesystem.
+pub trait HostIo: Send + Sync {
+    /// Read a file from the managed host.
+    fn read_file(&self, path: &str) -> io::Result<String>;
+    /// argv prefix that routes a command to the managed host; empty when
+    /// commands already run in the right place.
+    fn command_prefix(&self) -> Vec<String>;
+    /// The ssh destination for messages, when the host is remote.
+    fn remote_target(&self) -> Option<String>;
+}
+
+/// The machine pkgtool itself runs on.
+struct LocalHost;
+
+impl HostIo for LocalHost {
+    fn read_file(&self, path: &str) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn command_prefix(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn remote_target(&self) -> Option<String> {
+        None
+    }
+}
+
+/// A machine reached over SSH. BatchMode keeps ssh from prompting for a
+/// password under the TUI's alternate screen: keys must be agent-loaded
+/// or passwordless, and a missing one fails fast with ssh's own message.
+pub struct SshHost {
+    target: String,
+    options: Vec<String>,
+}
+
+impl SshHost {
+    pub fn new(target: String, options: Vec<String>) -> Self {
+        SshHost { target, options }
+    }
+}
+
+impl HostIo for SshHost {
+    fn read_file(&self, path: &str) -> io::Result<String> {
+        let prefix = self.command_prefix();
+        let output = std::process::Command::new(&prefix[0])
+            .args(&prefix[1..])
+            .args(["cat", path])
+            .output()?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+        if output.status.code() == Some(255) {
+            mark_connection_lost();
+        }
+        Err(io::Error::other(format!(
+            "{}: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+
+    fn command_prefix(&self) -> Vec<String> {
+        let mut prefix = vec!["ssh".to_string(), "-o".to_string(), "BatchMode=yes".to_string()];
+        prefix.extend(self.options.iter().cloned());
+        prefix.push(self.target.clone());
+        prefix.push("--".to_string());
+        prefix
+    }
+
+    fn remote_target(&self) -> Option<String> {
+        Some(self.target.clone())
+    }
+}
+
+/// The selected host; `LocalHost` until `init` installs a remote one.
+static HOST: OnceLock<Box<dyn HostIo>> = OnceLock::new();
+
+/// Set once an ssh command fails with the transport's own exit code, so
+/// cached data is treated as stale: it may no longer reflect the host.
+static CONNECTION_LOST: AtomicBool = AtomicBool::new(false);
+
+/// Install the remote host. Called once at startup, before any backend
+/// detection; later calls (and no call at all) leave the host local.
+pub fn init(target: String, options: Vec<String>) {
+    let _ = HOST.set(Box::new(SshHost::new(target, options)));
+}
+
+fn io() -> &'static dyn HostIo {
+    static LOCAL: LocalHost = LocalHost;
+    HOST.get().map(Box::as_ref).unwrap_or(&LOCAL)
+}
+
+/// Read a file from the managed host (a local read or a remote `cat`).
+pub fn read_file(path: &str) -> io::Result<String> {
+    io().read_file(path)
+}
+
+/// Route a command to the managed host: the argv unchanged locally, or
+/// prefixed with `ssh -o BatchMode=yes <target> --` for a remote one.
+pub fn routed(argv: &[String]) -> Vec<String> {
+    let mut routed = io().command_prefix();
+    routed.extend(argv.iter().cloned());
+    routed
+}
+
+/// The ssh destination, when managing a remote host.
+pub fn remote_target() -> Option<String> {
+    io().remote_target()
+}
+
+/// Whether the managed host is reached over the network.
+pub fn is_remote() -> bool {
+    remote_target().is_some()
+}
+
+/// Record that the ssh transport failed mid-operation.
+pub fn mark_connection_lost() {
+    CONNECTION_LOST.store(true, Ordering::Relaxed);
+}
+
+/// Whether the connection was lost at some point this session; the cache
+/// refuses to serve entries as fresh once this is set.
+pub fn connection_severed() -> bool {
+    CONNECTION_LOST.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_target_combines_user_and_host() {
+        let mut config = RemoteConfig {
+            host: "server.example".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.target().as_deref(), Some("server.example"));
+        config.user = "admin".to_string();
+        assert_eq!(config.target().as_deref(), Some("admin@server.example"));
+        config.host.clear();
+        assert_eq!(config.target(), None);
+    }
+
+    #[test]
+    fn ssh_prefix_carries_options_and_separator() {
+        let host = SshHost::new(
+            "admin@server".to_string(),
+            vec!["-p".to_string(), "2222".to_string()],
+        );
+        assert_eq!(
+            host.command_prefix(),
+            ["ssh", "-o", "BatchMode=yes", "-p", "2222", "admin@server", "--"]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Caps how many backend commands run at once, in two lanes: one for
+/// network-heavy operations (refresh, remote search) and one for local
+/// queries. Six backends refreshing together would otherwise saturate a
+/// metered connection, and a small machine's CPU with the local ones.
+///
+/// Cloning is cheap and shares the lanes, so every task querying managers
+/// holds a clone and acquires a permit per manager call.
+#[derive(Clone)]
+pub struct Limiter {
+    network: Arc<Semaphore>,
+    local: Arc<Semaphore>,
+}
+
+impl Limiter {
+    /// A limiter with the given lane widths; zero is read as one, since a
+    /// closed lane would deadlock every operation behind it.
+    pub fn new(network: usize, local: usize) -> Self {
+        Limiter {
+            network: Arc::new(Semaphore::new(network.max(1))),
+            local: Arc::new(Semaphore::new(local.max(1))),
+        }
+    }
+
+    /// Wait for a slot in the matching lane. The permit is held for the
+    /// duration of the backend call; dropping it frees the slot.
+    pub async fn acquire(&self, network_bound: bool) -> OwnedSemaphorePermit {
+        let lane = if network_bound {
+            &self.network
+        } else {
+            &self.local
+        };
+        lane.clone()
+            .acquire_owned()
+            .await
+            .expect("limiter semaphores are never closed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn a_limit_of_one_serializes_the_lane() {
+        let limiter = Limiter::new(1, 4);
+        let spans = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..3 {
+            let limiter = limiter.clone();
+            let spans = spans.clone();
+            tasks.spawn(async move {
+                let _permit = limiter.acquire(true).await;
+                let started = Instant::now();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                spans.lock().await.push((started, Instant::now()));
+            });
+        }
+        while tasks.join_next().await.is_some() {}
+        let mut spans = spans.lock().await.clone();
+        spans.sort();
+        // No span may begin before the previous one has ended.
+        for pair in spans.windows(2) {
+            assert!(pair[1].0 >= pair[0].1, "operations overlapped: {spans:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn the_lanes_are_independent() {
+        let limiter = Limiter::new(1, 1);
+        let _network = limiter.acquire(true).await;
+        // A held network permit must not block local queries.
+        let local = tokio::time::timeout(Duration::from_secs(1), limiter.acquire(false)).await;
+        assert!(local.is_ok());
+    }
+}
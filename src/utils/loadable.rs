@@ -0,0 +1,49 @@
+/// Lifecycle of a dataset fetched from the backends.
+///
+/// Every tab tracks its data through this type so loading, failure, and
+/// empty states render consistently across the UI.
+#[derive(Debug, Clone, Default)]
+pub enum Loadable<T> {
+    #[default]
+    NotLoaded,
+    Loading,
+    Loaded(T),
+    Failed(String),
+}
+
+impl<T> Loadable<T> {
+    /// The loaded value, if any.
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Loadable::Loaded(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        matches!(self, Loadable::Loaded(_))
+    }
+
+    pub fn is_not_loaded(&self) -> bool {
+        matches!(self, Loadable::NotLoaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_is_not_loaded() {
+        let loadable: Loadable<Vec<u8>> = Loadable::default();
+        assert!(loadable.is_not_loaded());
+        assert!(loadable.value().is_none());
+    }
+
+    #[test]
+    fn loaded_exposes_value() {
+        let loadable = Loadable::Loaded(vec![1, 2, 3]);
+        assert!(loadable.is_loaded());
+        assert_eq!(loadable.value(), Some(&vec![1, 2, 3]));
+    }
+}
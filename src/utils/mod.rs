@@ -0,0 +1,132 @@
+pub mod host;
+pub mod limiter;
+pub mod loadable;
+pub mod privilege;
+pub mod profile;
+pub mod proxy;
+pub mod runner;
+pub mod version;
+
+/// Format a byte count as a short human-readable string.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Truncate to at most `width` display columns, ending in an ellipsis when
+/// anything was cut, so translated labels degrade instead of overflowing.
+pub fn truncate_width(text: &str, width: usize) -> String {
+    use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > width.saturating_sub(1) {
+            break;
+        }
+        out.push(c);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// Render how long ago a timestamp was as a short string like "3d ago".
+pub fn relative_age(then: chrono::DateTime<chrono::Utc>) -> String {
+    relative_age_at(then, chrono::Utc::now())
+}
+
+fn relative_age_at(
+    then: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let seconds = (now - then).num_seconds().max(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86_399 => format!("{}h ago", seconds / 3600),
+        86_400..=31_535_999 => format!("{}d ago", seconds / 86_400),
+        _ => format!("{}y ago", seconds / 31_536_000),
+    }
+}
+
+/// Put `text` on the clipboard through the terminal's OSC 52 support.
+/// Works across ssh because the escape travels with the display stream;
+/// terminals without the feature simply ignore it.
+pub fn copy_to_clipboard(text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    stdout.flush()
+}
+
+/// Standard base64 with padding, enough for OSC 52 payloads — not worth
+/// a dependency.
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bits = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+        out.push(ALPHABET[(bits >> 18) as usize & 63] as char);
+        out.push(ALPHABET[(bits >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(bits >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[bits as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn base64_matches_the_reference_vectors() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn truncate_width_is_column_aware() {
+        assert_eq!(truncate_width("Updates", 10), "Updates");
+        assert_eq!(truncate_width("Actualizaciones", 8), "Actuali…");
+        assert_eq!(truncate_width("日本語テスト", 5), "日本…");
+    }
+
+    #[test]
+    fn relative_age_picks_the_right_unit() {
+        let now = Utc::now();
+        assert_eq!(relative_age_at(now, now), "just now");
+        assert_eq!(relative_age_at(now - Duration::minutes(5), now), "5m ago");
+        assert_eq!(relative_age_at(now - Duration::hours(7), now), "7h ago");
+        assert_eq!(relative_age_at(now - Duration::days(3), now), "3d ago");
+        assert_eq!(relative_age_at(now - Duration::days(800), now), "2y ago");
+    }
+}
@@ -0,0 +1,242 @@
+use std::path::PathBuf;
+
+use crate::package_managers::binary_exists;
+
+/// The escalation tools pkgtool knows how to drive, in fallback order.
+const TOOLS: [&str; 4] = ["sudo", "doas", "run0", "pkexec"];
+
+/// Wraps commands that need root with the right escalation prefix, so
+/// backends only say "this needs root" and never name a tool.
+///
+/// The tool comes from the `escalation` config key when that binary exists,
+/// falling back to whichever known tool is installed. sudo and doas get
+/// `-n`: the TUI owns the terminal, so a tty password prompt would be
+/// invisible — credentials must be cached or passwordless, and the in-app
+/// advice for a missing password only applies to these two. pkexec and
+/// run0 authenticate through polkit's own agent instead and need no such
+/// flag. When pkgtool already runs as root the prefix disappears entirely.
+#[derive(Debug, Clone)]
+pub struct PrivilegeRunner {
+    /// `None` when already root: commands run unwrapped.
+    tool: Option<String>,
+}
+
+impl PrivilegeRunner {
+    pub fn new(preference: &str) -> Self {
+        PrivilegeRunner {
+            tool: select(preference, binary_exists, running_as_root()),
+        }
+    }
+
+    /// Prefix `args` with the escalation command, or return them unchanged
+    /// when running as root.
+    pub fn wrap(&self, args: &[&str]) -> Vec<String> {
+        self.wrap_with(args, proxy_vars())
+    }
+
+    fn wrap_with(&self, args: &[&str], proxy_vars: Vec<&str>) -> Vec<String> {
+        let mut argv: Vec<String> = match self.tool.as_deref() {
+            None => Vec::new(),
+            // `--` so a package name can never be read as a sudo flag.
+            // sudo resets the environment, so any proxy variables must be
+            // named to survive the boundary.
+            Some("sudo") => {
+                let mut prefix = vec!["sudo".to_string(), "-n".to_string()];
+                if !proxy_vars.is_empty() {
+                    prefix.push(format!("--preserve-env={}", proxy_vars.join(",")));
+                }
+                prefix.push("--".to_string());
+                prefix
+            }
+            // doas takes no `--` and only keeps env listed in doas.conf.
+            Some("doas") => vec!["doas".to_string(), "-n".to_string()],
+            // run0 starts a fresh session; --setenv forwards current values.
+            Some("run0") => {
+                let mut prefix = vec!["run0".to_string()];
+                prefix.extend(proxy_vars.iter().map(|var| format!("--setenv={var}")));
+                prefix.push("--".to_string());
+                prefix
+            }
+            // pkexec: no separator, no non-interactive flag; polkit prompts.
+            Some(tool) => vec![tool.to_string()],
+        };
+        argv.extend(args.iter().map(|arg| arg.to_string()));
+        argv
+    }
+
+    /// Whether the selected tool asks for its password on the controlling
+    /// tty. pkexec and run0 go through polkit's agent, which prompts
+    /// outside the application, so in-app password advice stays quiet.
+    pub fn prompts_on_tty(&self) -> bool {
+        matches!(self.tool.as_deref(), Some("sudo" | "doas"))
+    }
+}
+
+/// The proxy variables currently set, which escalation must carry across
+/// into the privileged command's environment (see `utils::proxy`).
+fn proxy_vars() -> Vec<&'static str> {
+    [
+        "http_proxy",
+        "https_proxy",
+        "no_proxy",
+        "HTTP_PROXY",
+        "HTTPS_PROXY",
+        "NO_PROXY",
+    ]
+    .into_iter()
+    .filter(|var| std::env::var_os(var).is_some())
+    .collect()
+}
+
+/// The preference when installed, else the first installed known tool, else
+/// the preference regardless so the eventual spawn error names it.
+fn select(preference: &str, installed: impl Fn(&str) -> bool, root: bool) -> Option<String> {
+    if root {
+        return None;
+    }
+    if !preference.is_empty() && installed(preference) {
+        return Some(preference.to_string());
+    }
+    for tool in TOOLS {
+        if installed(tool) {
+            return Some(tool.to_string());
+        }
+    }
+    Some(if preference.is_empty() {
+        "sudo".to_string()
+    } else {
+        preference.to_string()
+    })
+}
+
+/// The unprivileged user who invoked us through sudo or doas, if any.
+/// A plain root login or a root-only container has neither variable set.
+pub fn invoking_user() -> Option<String> {
+    ["SUDO_USER", "DOAS_USER"].iter().find_map(|var| {
+        std::env::var(var)
+            .ok()
+            .filter(|user| !user.is_empty() && user != "root")
+    })
+}
+
+/// Point the unset XDG base directories at `user`'s home, so a
+/// `sudo pkgtool` session reads and writes the invoking user's config,
+/// cache and history instead of scattering root-owned files through
+/// /root. Explicitly exported variables win. Returns the variables that
+/// were set, so a config opting out can undo the adoption.
+pub fn adopt_user_dirs(user: &str) -> Vec<&'static str> {
+    const VARS: [(&str, &str); 4] = [
+        ("XDG_CONFIG_HOME", ".config"),
+        ("XDG_CACHE_HOME", ".cache"),
+        ("XDG_DATA_HOME", ".local/share"),
+        ("XDG_STATE_HOME", ".local/state"),
+    ];
+    let passwd = std::fs::read_to_string("/etc/passwd").unwrap_or_default();
+    let Some(home) = home_in(&passwd, user) else {
+        return Vec::new();
+    };
+    let mut adopted = Vec::new();
+    for (var, dir) in VARS {
+        if std::env::var_os(var).is_none() {
+            std::env::set_var(var, home.join(dir));
+            adopted.push(var);
+        }
+    }
+    adopted
+}
+
+/// Undo `adopt_user_dirs`, restoring root's own directories.
+pub fn undo_adoption(vars: &[&'static str]) {
+    for var in vars {
+        std::env::remove_var(var);
+    }
+}
+
+/// `user`'s home directory according to a passwd file.
+fn home_in(passwd: &str, user: &str) -> Option<PathBuf> {
+    passwd.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        if fields.next()? != user {
+            return None;
+        }
+        let home = fields.nth(4)?;
+        (!home.is_empty()).then(|| PathBuf::from(home))
+    })
+}
+
+/// Whether the current process already has root privileges.
+pub fn running_as_root() -> bool {
+    std::process::Command::new("id")
+        .arg("-u")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_the_configured_tool_when_installed() {
+        let tool = select("doas", |name| name == "doas" || name == "sudo", false);
+        assert_eq!(tool.as_deref(), Some("doas"));
+    }
+
+    #[test]
+    fn falls_back_to_whatever_is_installed() {
+        let tool = select("doas", |name| name == "run0", false);
+        assert_eq!(tool.as_deref(), Some("run0"));
+    }
+
+    #[test]
+    fn root_needs_no_prefix() {
+        let runner = PrivilegeRunner {
+            tool: select("sudo", |_| true, true),
+        };
+        assert_eq!(
+            runner.wrap_with(&["apt-get", "update"], Vec::new()),
+            ["apt-get", "update"]
+        );
+        assert!(!runner.prompts_on_tty());
+    }
+
+    #[test]
+    fn home_comes_from_the_passwd_entry() {
+        let passwd = "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000:Alice:/home/alice:/bin/zsh\n";
+        assert_eq!(home_in(passwd, "alice"), Some(PathBuf::from("/home/alice")));
+        assert_eq!(home_in(passwd, "bob"), None);
+    }
+
+    #[test]
+    fn each_tool_gets_its_own_prefix() {
+        let runner = |tool: &str| PrivilegeRunner {
+            tool: Some(tool.to_string()),
+        };
+        let wrap = |tool: &str| runner(tool).wrap_with(&["dnf", "install"], Vec::new());
+        assert_eq!(wrap("sudo"), ["sudo", "-n", "--", "dnf", "install"]);
+        assert_eq!(wrap("doas"), ["doas", "-n", "dnf", "install"]);
+        assert_eq!(wrap("run0"), ["run0", "--", "dnf", "install"]);
+        assert_eq!(wrap("pkexec"), ["pkexec", "dnf", "install"]);
+        assert!(runner("sudo").prompts_on_tty());
+        assert!(!runner("pkexec").prompts_on_tty());
+    }
+
+    #[test]
+    fn proxy_variables_survive_the_escalation_boundary() {
+        let runner = PrivilegeRunner {
+            tool: Some("sudo".to_string()),
+        };
+        assert_eq!(
+            runner.wrap_with(&["apt-get", "update"], vec!["http_proxy", "no_proxy"]),
+            ["sudo", "-n", "--preserve-env=http_proxy,no_proxy", "--", "apt-get", "update"]
+        );
+        let runner = PrivilegeRunner {
+            tool: Some("run0".to_string()),
+        };
+        assert_eq!(
+            runner.wrap_with(&["apt-get", "update"], vec!["http_proxy"]),
+            ["run0", "--setenv=http_proxy", "--", "apt-get", "update"]
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Samples kept per ring buffer; enough for percentiles without growing.
+const RING: usize = 256;
+
+/// Always-compiled performance sampler behind the F12 overlay and
+/// `profile dump`. Recording is a bounded push under a mutex that is
+/// never contended for long, so the instrumentation stays cheap whether
+/// or not anyone is looking at the numbers.
+static PROFILER: Mutex<Option<Inner>> = Mutex::new(None);
+
+#[derive(Default)]
+struct Inner {
+    /// Render time of recent frames, in milliseconds.
+    frames: VecDeque<f64>,
+    /// Duration of recent backend calls: (manager, milliseconds).
+    backend: VecDeque<(String, f64)>,
+    /// The most recent backend call per manager: (command, milliseconds).
+    last_call: HashMap<String, (String, f64)>,
+    cache_hits: u64,
+    cache_misses: u64,
+}
+
+fn with<R>(f: impl FnOnce(&mut Inner) -> R) -> R {
+    let mut guard = PROFILER.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(guard.get_or_insert_with(Inner::default))
+}
+
+fn push(ring: &mut VecDeque<f64>, value: f64) {
+    if ring.len() == RING {
+        ring.pop_front();
+    }
+    ring.push_back(value);
+}
+
+/// Record one frame of the draw path.
+pub fn record_frame(duration: Duration) {
+    with(|inner| push(&mut inner.frames, duration.as_secs_f64() * 1000.0));
+}
+
+/// Record one backend command, keyed by manager id.
+pub fn record_backend(manager: &str, command: &str, duration: Duration) {
+    let ms = duration.as_secs_f64() * 1000.0;
+    with(|inner| {
+        if inner.backend.len() == RING {
+            inner.backend.pop_front();
+        }
+        inner.backend.push_back((manager.to_string(), ms));
+        inner
+            .last_call
+            .insert(manager.to_string(), (command.to_string(), ms));
+    });
+}
+
+/// Record one metadata-cache lookup.
+pub fn record_cache(hit: bool) {
+    with(|inner| {
+        if hit {
+            inner.cache_hits += 1;
+        } else {
+            inner.cache_misses += 1;
+        }
+    });
+}
+
+/// Everything the overlay shows and `profile dump` writes.
+#[derive(Serialize)]
+pub struct Snapshot {
+    pub frame_avg_ms: f64,
+    pub frame_p95_ms: f64,
+    pub frames_sampled: usize,
+    /// Most recent backend call per manager: (manager, command, ms).
+    pub last_calls: Vec<(String, String, f64)>,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Raw backend samples, oldest first, for offline analysis.
+    pub backend_samples: Vec<(String, f64)>,
+    pub frame_samples: Vec<f64>,
+}
+
+impl Snapshot {
+    /// Cache hit rate in percent; 100 when nothing was looked up yet.
+    pub fn cache_hit_rate(&self) -> f64 {
+        let total = self.cache_hits + self.cache_misses;
+        if total == 0 {
+            100.0
+        } else {
+            self.cache_hits as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+/// A copy of the current samples with the aggregates precomputed.
+pub fn snapshot() -> Snapshot {
+    with(|inner| {
+        let mut sorted: Vec<f64> = inner.frames.iter().copied().collect();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let avg = if sorted.is_empty() {
+            0.0
+        } else {
+            sorted.iter().sum::<f64>() / sorted.len() as f64
+        };
+        let p95 = sorted
+            .get((sorted.len() * 95 / 100).min(sorted.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or(0.0);
+        let mut last_calls: Vec<(String, String, f64)> = inner
+            .last_call
+            .iter()
+            .map(|(manager, (command, ms))| (manager.clone(), command.clone(), *ms))
+            .collect();
+        last_calls.sort_by(|a, b| a.0.cmp(&b.0));
+        Snapshot {
+            frame_avg_ms: avg,
+            frame_p95_ms: p95,
+            frames_sampled: sorted.len(),
+            last_calls,
+            cache_hits: inner.cache_hits,
+            cache_misses: inner.cache_misses,
+            backend_samples: inner.backend.iter().cloned().collect(),
+            frame_samples: inner.frames.iter().copied().collect(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rings_stay_bounded_and_aggregate() {
+        for i in 0..RING + 10 {
+            record_frame(Duration::from_millis(i as u64 % 10));
+            record_backend("apt", "apt list", Duration::from_millis(5));
+        }
+        record_cache(true);
+        record_cache(false);
+        let snapshot = snapshot();
+        assert_eq!(snapshot.frames_sampled, RING);
+        assert_eq!(snapshot.backend_samples.len(), RING);
+        assert_eq!(snapshot.last_calls[0].0, "apt");
+        assert!((snapshot.cache_hit_rate() - 50.0).abs() < f64::EPSILON);
+        assert!(snapshot.frame_p95_ms >= snapshot.frame_avg_ms);
+    }
+}
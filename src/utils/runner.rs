@@ -0,0 +1,327 @@
+//! Shared async command runner for the package-manager backends.
+//!
+//! Every non-interactive backend invocation funnels through
+//! [`CommandRunner`]: argv only (never `sh -c`), a controlled
+//! environment (`LC_ALL=C` so output parsing is locale-stable, the
+//! proxy variables passed through explicitly), a timeout that kills the
+//! child's whole process group on expiry, and stdout/stderr captured
+//! separately under a size cap. Every invocation is recorded in the
+//! backend log, which feeds the Log tab.
+
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::error::{PkgError, Result};
+
+/// Long enough for a full system upgrade on a slow mirror; a wedged
+/// backend (stale lock, dead mirror) still gets reaped eventually.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Per-stream capture cap. `pacman -Qi` over a big system stays well
+/// under this; anything larger is almost certainly runaway output.
+const DEFAULT_CAPTURE_CAP: usize = 4 * 1024 * 1024;
+
+/// The variables `ProxySettings::export` maintains; passed through to
+/// the child explicitly rather than trusted to inheritance.
+const PROXY_VARS: [&str; 6] = [
+    "http_proxy",
+    "https_proxy",
+    "no_proxy",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+];
+
+/// What one finished invocation produced.
+#[derive(Debug)]
+pub struct CommandOutput {
+    /// The exit code, -1 when the process died without one.
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration: Duration,
+}
+
+/// One backend's way of running commands; cheap to build per call.
+pub struct CommandRunner {
+    /// Backend id the invocation is logged under.
+    label: String,
+    timeout: Duration,
+    capture_cap: usize,
+    /// Extra environment on top of the controlled defaults.
+    env: Vec<(String, String)>,
+    /// Where to forward stdout lines as they arrive, if anywhere.
+    stream: Option<UnboundedSender<String>>,
+}
+
+impl CommandRunner {
+    pub fn new(label: &str) -> Self {
+        CommandRunner {
+            label: label.to_string(),
+            timeout: DEFAULT_TIMEOUT,
+            capture_cap: DEFAULT_CAPTURE_CAP,
+            env: Vec::new(),
+            stream: None,
+        }
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn capture_cap(mut self, bytes: usize) -> Self {
+        self.capture_cap = bytes;
+        self
+    }
+
+    /// Add a variable to the child's environment, e.g. apt's
+    /// `DEBIAN_FRONTEND=noninteractive`.
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Forward stdout to `sender` line by line while still capturing it.
+    pub fn stream_to(mut self, sender: UnboundedSender<String>) -> Self {
+        self.stream = Some(sender);
+        self
+    }
+
+    /// Run `argv` to completion on the managed host. The argv is routed
+    /// through `utils::host` (unchanged locally, an ssh prefix for a
+    /// remote host). A non-zero exit is not an error here — callers see
+    /// the status and decide; only spawn failures and the timeout are.
+    pub async fn run(&self, argv: &[String]) -> Result<CommandOutput> {
+        let routed = crate::utils::host::routed(argv);
+        let started = Instant::now();
+        let mut command = Command::new(&routed[0]);
+        command
+            .args(&routed[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .process_group(0)
+            .env("LC_ALL", "C")
+            .env("LANG", "C");
+        for key in PROXY_VARS {
+            if let Ok(value) = std::env::var(key) {
+                command.env(key, value);
+            }
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+        let mut child = command.spawn()?;
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let waited = tokio::time::timeout(self.timeout, async {
+            let (stdout, stderr, status) = tokio::join!(
+                read_capped(stdout, self.capture_cap, self.stream.as_ref()),
+                read_capped(stderr, self.capture_cap, None),
+                child.wait(),
+            );
+            status.map(|status| (stdout, stderr, status))
+        })
+        .await;
+        match waited {
+            Ok(read) => {
+                let (stdout, stderr, status) = read?;
+                let status = status.code().unwrap_or(-1);
+                let duration = started.elapsed();
+                self.log(argv, duration, status);
+                Ok(CommandOutput {
+                    status,
+                    stdout,
+                    stderr,
+                    duration,
+                })
+            }
+            Err(_) => {
+                // Kill the whole process group, not just the immediate
+                // child — the backends fork helpers that would otherwise
+                // outlive their parent and hold the database lock.
+                if let Some(pid) = child.id() {
+                    let _ = Command::new("kill")
+                        .args(["-s", "KILL", "--", &format!("-{pid}")])
+                        .output()
+                        .await;
+                }
+                let _ = child.kill().await;
+                self.log(argv, started.elapsed(), -1);
+                Err(PkgError::CommandFailed {
+                    command: argv.join(" "),
+                    status: -1,
+                    stderr: format!(
+                        "timed out after {}s; process group killed",
+                        self.timeout.as_secs()
+                    ),
+                })
+            }
+        }
+    }
+
+    fn log(&self, argv: &[String], duration: Duration, status: i32) {
+        crate::logging::invocation(&self.label, &argv.join(" "), duration, status);
+        crate::utils::profile::record_backend(&self.label, &argv.join(" "), duration);
+    }
+}
+
+/// Drain a stream into a capped string, optionally forwarding complete
+/// lines as they arrive. Reading bytes rather than lines keeps invalid
+/// UTF-8 from cutting the capture short; conversion is lossy at the end.
+async fn read_capped<R: AsyncRead + Unpin>(
+    reader: Option<R>,
+    cap: usize,
+    stream: Option<&UnboundedSender<String>>,
+) -> String {
+    let Some(mut reader) = reader else {
+        return String::new();
+    };
+    let mut raw: Vec<u8> = Vec::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 8192];
+    let mut truncated = false;
+    loop {
+        let count = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(count) => count,
+        };
+        if let Some(sender) = stream {
+            pending.extend_from_slice(&buf[..count]);
+            while let Some(pos) = pending.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=pos).collect();
+                let _ = sender.send(String::from_utf8_lossy(&line[..pos]).into_owned());
+            }
+        }
+        if raw.len() < cap {
+            let room = cap - raw.len();
+            raw.extend_from_slice(&buf[..count.min(room)]);
+            truncated |= count > room;
+        } else {
+            truncated = true;
+        }
+    }
+    if let Some(sender) = stream {
+        if !pending.is_empty() {
+            let _ = sender.send(String::from_utf8_lossy(&pending).into_owned());
+        }
+    }
+    let mut text = String::from_utf8_lossy(&raw).into_owned();
+    if truncated {
+        text.push_str("\n[output truncated]\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write an executable script to stand in for a backend binary.
+    fn fake_binary(name: &str, script: &str) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = std::env::temp_dir().join(format!(
+            "pkgtool-runner-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn captures_streams_separately_in_a_stable_locale() {
+        let binary = fake_binary(
+            "capture",
+            "#!/bin/sh\necho \"lc=$LC_ALL\"\necho err-line >&2\nexit 3\n",
+        );
+        let argv = vec![binary.to_string_lossy().into_owned()];
+        let output = CommandRunner::new("test").run(&argv).await.unwrap();
+        assert_eq!(output.status, 3);
+        assert_eq!(output.stdout, "lc=C\n");
+        assert_eq!(output.stderr, "err-line\n");
+        // A tight cap truncates instead of buffering without bound.
+        let capped = CommandRunner::new("test")
+            .capture_cap(2)
+            .run(&argv)
+            .await
+            .unwrap();
+        assert!(capped.stdout.ends_with("[output truncated]\n"));
+        let _ = std::fs::remove_file(binary);
+    }
+
+    #[tokio::test]
+    async fn streams_stdout_lines_while_capturing_them() {
+        let binary = fake_binary("stream", "#!/bin/sh\necho one\necho two\n");
+        let argv = vec![binary.to_string_lossy().into_owned()];
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let output = CommandRunner::new("test")
+            .stream_to(sender)
+            .run(&argv)
+            .await
+            .unwrap();
+        let mut lines = Vec::new();
+        while let Ok(line) = receiver.try_recv() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["one", "two"]);
+        assert_eq!(output.stdout, "one\ntwo\n");
+        let _ = std::fs::remove_file(binary);
+    }
+
+    #[tokio::test]
+    async fn timeout_kills_the_whole_process_group() {
+        let binary = fake_binary(
+            "timeout",
+            "#!/bin/sh\nsleep 30 &\necho $! > \"$1\"\nwait\n",
+        );
+        let pid_file = std::env::temp_dir().join(format!(
+            "pkgtool-runner-pid-{}",
+            std::process::id()
+        ));
+        let argv = vec![
+            binary.to_string_lossy().into_owned(),
+            pid_file.to_string_lossy().into_owned(),
+        ];
+        let started = Instant::now();
+        let result = CommandRunner::new("test")
+            .timeout(Duration::from_millis(200))
+            .run(&argv)
+            .await;
+        match result {
+            Err(PkgError::CommandFailed { status, stderr, .. }) => {
+                assert_eq!(status, -1);
+                assert!(stderr.contains("timed out"), "stderr: {stderr}");
+            }
+            other => panic!("expected a timeout failure, got {other:?}"),
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+        // The grandchild `sleep` must die with the group, not linger. A
+        // kill that worked leaves either nothing or an unreaped zombie,
+        // depending on who inherited the orphan.
+        let pid: u32 = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        let dead = |pid: u32| match std::fs::read_to_string(format!("/proc/{pid}/stat")) {
+            Ok(stat) => stat.split_whitespace().nth(2) == Some("Z"),
+            Err(_) => true,
+        };
+        for _ in 0..50 {
+            if dead(pid) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(dead(pid), "grandchild {pid} survived the group kill");
+        let _ = std::fs::remove_file(binary);
+        let _ = std::fs::remove_file(pid_file);
+    }
+}
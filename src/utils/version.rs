@@ -0,0 +1,373 @@
+//! Distro-aware version comparison.
+//!
+//! Package versions are not plain strings: "1.10" is newer than "1.9",
+//! "1:2.0" carries an epoch, and every ecosystem has its own pre-release
+//! convention. Each backend's versions are compared under its own
+//! [`VersionScheme`]: dpkg's algorithm for apt, rpm/pacman vercmp for
+//! dnf and pacman, plain semver for everything else.
+
+use std::cmp::Ordering;
+
+/// Which ecosystem's rules a version string follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionScheme {
+    /// Debian policy `epoch:upstream-revision` with dpkg's ordering:
+    /// `~` sorts before everything including nothing, letters before
+    /// the remaining punctuation.
+    Deb,
+    /// rpm/pacman vercmp: alphanumeric segments where a numeric segment
+    /// beats an alphabetic one, plus rpm's `~` pre-release marker.
+    Rpm,
+    /// Dotted numeric components with a pre-release suffix after `-`;
+    /// the honest default when the ecosystem is unknown.
+    Semver,
+}
+
+impl VersionScheme {
+    /// The scheme a manager's versions follow; plugins and brew get
+    /// semver, which is right often enough to be the default.
+    pub fn for_manager(manager: &str) -> Self {
+        match manager {
+            "apt" => VersionScheme::Deb,
+            "pacman" | "dnf" => VersionScheme::Rpm,
+            _ => VersionScheme::Semver,
+        }
+    }
+}
+
+/// Compare two version strings under `scheme`.
+pub fn compare(scheme: VersionScheme, a: &str, b: &str) -> Ordering {
+    match scheme {
+        VersionScheme::Deb => deb_compare(a, b),
+        VersionScheme::Rpm => rpm_compare(a, b),
+        VersionScheme::Semver => semver_compare(a, b),
+    }
+}
+
+/// Split `epoch:upstream-revision` into its parts: the epoch is the
+/// digits before the first `:` (0 when absent), the revision everything
+/// after the last `-` (empty when absent). Both Debian and rpm/pacman
+/// write versions this way.
+fn split_evr(version: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest))
+            if !epoch.is_empty() && epoch.bytes().all(|c| c.is_ascii_digit()) =>
+        {
+            (epoch.parse().unwrap_or(u64::MAX), rest)
+        }
+        _ => (0, version),
+    };
+    match rest.rsplit_once('-') {
+        Some((upstream, revision)) => (epoch, upstream, revision),
+        None => (epoch, rest, ""),
+    }
+}
+
+fn deb_compare(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_revision) = split_evr(a);
+    let (b_epoch, b_upstream, b_revision) = split_evr(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| deb_fragment(a_upstream, b_upstream))
+        .then_with(|| deb_fragment(a_revision, b_revision))
+}
+
+/// dpkg's character weight: `~` before end-of-string, end-of-string
+/// alongside digits, letters before the remaining punctuation.
+fn deb_order(c: Option<u8>) -> i32 {
+    match c {
+        Some(b'~') => -1,
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// One upstream or revision fragment under dpkg's alternating walk:
+/// compare the non-digit run by character weight, then the digit run
+/// numerically, and repeat.
+fn deb_fragment(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while a.get(i).is_some_and(|c| !c.is_ascii_digit())
+            || b.get(j).is_some_and(|c| !c.is_ascii_digit())
+        {
+            let ordering = deb_order(a.get(i).copied()).cmp(&deb_order(b.get(j).copied()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while a.get(i).is_some_and(u8::is_ascii_digit) && b.get(j).is_some_and(u8::is_ascii_digit)
+        {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if a.get(i).is_some_and(u8::is_ascii_digit) {
+            return Ordering::Greater;
+        }
+        if b.get(j).is_some_and(u8::is_ascii_digit) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+fn rpm_compare(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_release) = split_evr(a);
+    let (b_epoch, b_upstream, b_release) = split_evr(b);
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| rpm_fragment(a_upstream, b_upstream))
+        .then_with(|| {
+            // A missing release matches any release, the way vercmp
+            // treats "1.0" against "1.0-2".
+            if a_release.is_empty() || b_release.is_empty() {
+                Ordering::Equal
+            } else {
+                rpm_fragment(a_release, b_release)
+            }
+        })
+}
+
+/// vercmp's verdict on a leftover fragment against an exhausted one: a
+/// trailing alphabetic segment or pre-release marker loses to nothing
+/// at all ("1.0a" < "1.0"), anything else wins ("1.0.1" > "1.0").
+fn rpm_leftover(c: u8) -> Ordering {
+    if c == b'~' || c.is_ascii_alphabetic() {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// One version fragment under vercmp: walk runs of digits or letters,
+/// treating everything else as weightless separators. rpm's `~` marks a
+/// pre-release and sorts before anything, including the fragment ending.
+fn rpm_fragment(a: &str, b: &str) -> Ordering {
+    let is_separator = |c: &u8| !c.is_ascii_alphanumeric() && *c != b'~';
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    loop {
+        // vercmp judges the remainder as soon as either side runs out
+        // at a segment boundary, before separators are skipped — that
+        // is what puts "1.0" below "1.0.a".
+        match (a.get(i), b.get(j)) {
+            (None, None) => return Ordering::Equal,
+            (Some(&c), None) => return rpm_leftover(c),
+            (None, Some(&c)) => return rpm_leftover(c).reverse(),
+            _ => {}
+        }
+        let (sep_a, sep_b) = (i, j);
+        while a.get(i).is_some_and(is_separator) {
+            i += 1;
+        }
+        while b.get(j).is_some_and(is_separator) {
+            j += 1;
+        }
+        match (a.get(i) == Some(&b'~'), b.get(j) == Some(&b'~')) {
+            (true, true) => {
+                i += 1;
+                j += 1;
+                continue;
+            }
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {}
+        }
+        let (left, right) = match (a.get(i), b.get(j)) {
+            (Some(&left), Some(&right)) => (left, right),
+            (None, None) => return Ordering::Equal,
+            (Some(&c), None) => return rpm_leftover(c),
+            (None, Some(&c)) => return rpm_leftover(c).reverse(),
+        };
+        // Unequal runs of separators decide on their own ("1.0a" is
+        // older than "1.0.a").
+        let separators = (i - sep_a).cmp(&(j - sep_b));
+        if separators != Ordering::Equal {
+            return separators;
+        }
+        let numeric = left.is_ascii_digit();
+        if right.is_ascii_digit() != numeric {
+            // One segment numeric, one alphabetic: the numeric one is
+            // newer ("1.0.1" > "1.0.a").
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+        let run = |s: &[u8], mut k: usize| {
+            while s.get(k).is_some_and(|c| {
+                if numeric {
+                    c.is_ascii_digit()
+                } else {
+                    c.is_ascii_alphabetic()
+                }
+            }) {
+                k += 1;
+            }
+            k
+        };
+        let (a_end, b_end) = (run(a, i), run(b, j));
+        let (left, right) = (&a[i..a_end], &b[j..b_end]);
+        let ordering = if numeric {
+            let strip = |s: &[u8]| s.iter().take_while(|c| **c == b'0').count();
+            let (left, right) = (&left[strip(left)..], &right[strip(right)..]);
+            left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+        } else {
+            left.cmp(right)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        i = a_end;
+        j = b_end;
+    }
+}
+
+/// Plain semver: the dotted numeric core, then an optional pre-release
+/// after `-` (which sorts before the bare version); build metadata
+/// after `+` is ignored.
+fn semver_compare(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (&str, Option<&str>) {
+        let version = version.split('+').next().unwrap_or(version);
+        match version.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (version, None),
+        }
+    }
+    let (a_core, a_pre) = split(a);
+    let (b_core, b_pre) = split(b);
+    dotted(a_core, b_core).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(a_pre), Some(b_pre)) => dotted(a_pre, b_pre),
+    })
+}
+
+/// Compare dot-separated identifiers: numerically when both sides
+/// parse, bytewise otherwise, with a numeric identifier sorting before
+/// an alphanumeric one and more identifiers winning a tie.
+fn dotted(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        let ordering = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(_), None) => return Ordering::Greater,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(l), Some(r)) => match (l.parse::<u64>(), r.parse::<u64>()) {
+                (Ok(l), Ok(r)) => l.cmp(&r),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => l.cmp(r),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Assert `a < b` both ways round, so the comparator is consistent.
+    fn less(scheme: VersionScheme, a: &str, b: &str) {
+        assert_eq!(compare(scheme, a, b), Ordering::Less, "{a} < {b}");
+        assert_eq!(compare(scheme, b, a), Ordering::Greater, "{b} > {a}");
+    }
+
+    #[test]
+    fn deb_follows_the_dpkg_corpus() {
+        use VersionScheme::Deb;
+        assert_eq!(compare(Deb, "0", "0"), Ordering::Equal);
+        assert_eq!(compare(Deb, "1.0", "1.0"), Ordering::Equal);
+        less(Deb, "1.9", "1.10");
+        // Tilde sorts before everything, including the fragment end.
+        less(Deb, "1.0~~", "1.0~~a");
+        less(Deb, "1.0~~a", "1.0~");
+        less(Deb, "1.0~", "1.0");
+        less(Deb, "1.0~rc1", "1.0");
+        less(Deb, "1.0~rc1", "1.0~rc2");
+        // Letters sort before the remaining punctuation.
+        less(Deb, "1.0a", "1.0+");
+        less(Deb, "1.0", "1.0a");
+        // Epoch and revision.
+        less(Deb, "2.0", "1:1.0");
+        less(Deb, "1:9.9", "2:1.0");
+        less(Deb, "1.0-2", "1.0-10");
+        less(Deb, "2.31-13+deb11u5", "2.31-13+deb11u6");
+    }
+
+    #[test]
+    fn rpm_follows_the_pacman_vercmp_examples() {
+        use VersionScheme::Rpm;
+        // The ordered chain from vercmp(8).
+        for pair in [
+            ("1.0a", "1.0b"),
+            ("1.0b", "1.0beta"),
+            ("1.0beta", "1.0p"),
+            ("1.0p", "1.0pre"),
+            ("1.0pre", "1.0rc"),
+            ("1.0rc", "1.0"),
+            ("1.0", "1.0.a"),
+            ("1.0.a", "1.0.1"),
+            ("1", "1.0"),
+            ("1.0", "1.1"),
+            ("1.1", "1.1.1"),
+            ("1.1.1", "1.2"),
+            ("1.2", "2.0"),
+            ("2.0", "3.0.0"),
+        ] {
+            less(Rpm, pair.0, pair.1);
+        }
+        less(Rpm, "1.9", "1.10");
+        // Epoch beats everything; a missing release matches any.
+        less(Rpm, "9.9", "1:1.0");
+        less(Rpm, "1:9.9", "2:1.0");
+        less(Rpm, "1.0-1", "1.0-2");
+        assert_eq!(compare(Rpm, "1.0-1", "1.0"), Ordering::Equal);
+        less(Rpm, "6.6.8.arch1-1", "6.6.9.arch1-1");
+        // rpm's tilde pre-release extension.
+        less(Rpm, "1.0~rc1-1", "1.0-1");
+    }
+
+    #[test]
+    fn semver_orders_core_and_pre_release() {
+        use VersionScheme::Semver;
+        less(Semver, "1.9.0", "1.10.0");
+        less(Semver, "1.0.0-alpha", "1.0.0");
+        less(Semver, "1.0.0-alpha", "1.0.0-alpha.1");
+        less(Semver, "1.0.0-alpha.1", "1.0.0-alpha.beta");
+        less(Semver, "1.0.0-beta.2", "1.0.0-beta.11");
+        assert_eq!(compare(Semver, "1.0.0+build", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn schemes_map_to_their_backends() {
+        assert_eq!(VersionScheme::for_manager("apt"), VersionScheme::Deb);
+        assert_eq!(VersionScheme::for_manager("pacman"), VersionScheme::Rpm);
+        assert_eq!(VersionScheme::for_manager("dnf"), VersionScheme::Rpm);
+        assert_eq!(VersionScheme::for_manager("brew"), VersionScheme::Semver);
+    }
+}
@@ -0,0 +1,233 @@
+//! Exit-code contract of the CLI, driven against the mock backend that
+//! `PKGTOOL_MOCK_MANAGER` injects (see `package_managers::mock`). Each test
+//! gets its own XDG directories so a developer's real config stays out of
+//! the picture.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Run the pkgtool binary with the given mock spec and arguments.
+fn pkgtool(test: &str, mock_spec: &str, args: &[&str]) -> Output {
+    pkgtool_in(&sandbox(test), mock_spec, args)
+}
+
+/// Like `pkgtool`, but against a prepared sandbox (e.g. a seeded cache).
+fn pkgtool_in(home: &std::path::Path, mock_spec: &str, args: &[&str]) -> Output {
+    let mut command = command_in(home);
+    command
+        .args(args)
+        .env("PKGTOOL_MOCK_MANAGER", mock_spec)
+        .output()
+        .expect("pkgtool binary runs")
+}
+
+/// A pkgtool command pointed at the sandbox, without a mock manager; tests
+/// that exercise real registry paths (e.g. plugins) build on this.
+fn command_in(home: &std::path::Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_pkgtool"));
+    command
+        .env_remove("PKGTOOL_MOCK_MANAGER")
+        .env("XDG_CONFIG_HOME", home.join("config"))
+        .env("XDG_DATA_HOME", home.join("data"))
+        .env("XDG_CACHE_HOME", home.join("cache"))
+        .env("XDG_STATE_HOME", home.join("state"));
+    command
+}
+
+/// A fresh per-test directory to stand in for the user's home.
+fn sandbox(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pkgtool-cli-test-{test}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn install_success_exits_zero() {
+    let output = pkgtool("install-ok", "", &["install", "--yes", "htop"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("installed: htop"));
+}
+
+#[test]
+fn partial_batch_failure_exits_four_and_names_the_package() {
+    let output = pkgtool(
+        "install-partial",
+        "fail=broken",
+        &["install", "--yes", "htop", "broken"],
+    );
+    assert_eq!(output.status.code(), Some(4));
+    let summary = stderr(&output);
+    assert!(summary.contains("broken"), "summary was: {summary}");
+    assert!(summary.contains("1 of 2"), "summary was: {summary}");
+}
+
+#[test]
+fn total_batch_failure_exits_one() {
+    let output = pkgtool("install-fail", "fail=broken", &["install", "--yes", "broken"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("simulated failure for broken"));
+}
+
+#[test]
+fn json_reports_each_package_outcome() {
+    let output = pkgtool(
+        "install-json",
+        "fail=broken",
+        &["install", "--yes", "--json", "htop", "broken"],
+    );
+    assert_eq!(output.status.code(), Some(4));
+    let results: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert_eq!(results[0]["status"], "installed");
+    assert_eq!(results[1]["status"], "failed");
+}
+
+#[test]
+fn check_updates_exits_hundred_when_updates_exist() {
+    let output = pkgtool("updates-some", "updates=2", &["check-updates", "--quiet"]);
+    assert_eq!(output.status.code(), Some(100));
+}
+
+#[test]
+fn check_updates_exits_zero_when_current() {
+    let output = pkgtool("updates-none", "updates=0", &["check-updates", "--quiet"]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn check_updates_counts_the_security_subset() {
+    let output = pkgtool(
+        "updates-security",
+        "updates=3 security=1",
+        &["check-updates"],
+    );
+    assert_eq!(output.status.code(), Some(100));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("3 (1 security)"), "stdout was: {stdout}");
+}
+
+#[test]
+fn update_security_applies_only_the_flagged_subset() {
+    let output = pkgtool(
+        "update-security",
+        "updates=3 security=1",
+        &["update", "--security", "--yes"],
+    );
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let none = pkgtool(
+        "update-security-none",
+        "updates=2",
+        &["update", "--security", "--yes"],
+    );
+    assert_eq!(none.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&none.stdout).contains("no pending security updates"));
+}
+
+#[test]
+fn completions_emit_a_script_with_the_dynamic_shim() {
+    let output = pkgtool("completions-bash", "", &["completions", "bash"]);
+    assert_eq!(output.status.code(), Some(0));
+    let script = String::from_utf8_lossy(&output.stdout).into_owned();
+    assert!(script.contains("_pkgtool"), "not a completion script: {script}");
+    assert!(script.contains("__complete"), "dynamic shim missing: {script}");
+}
+
+#[test]
+fn dynamic_completion_reads_only_the_cache() {
+    let home = sandbox("complete-cached");
+    let cache_dir = home.join("cache").join("pkgtool");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::write(
+        cache_dir.join("mock-packages.json"),
+        r#"{"timestamp":"2026-01-01T00:00:00Z","db_mtime":null,
+            "data":[{"name":"htop"},{"name":"helix"},{"name":"tmux"}]}"#,
+    )
+    .unwrap();
+    let output = pkgtool_in(&home, "", &["__complete", "remove", "h"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "helix\nhtop\n");
+}
+
+#[test]
+fn dynamic_completion_degrades_to_nothing_without_a_cache() {
+    let output = pkgtool("complete-empty", "", &["__complete", "install", "h"]);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn dry_run_never_executes_the_backend_command() {
+    let home = sandbox("dry-run");
+    let config_dir = home.join("config").join("pkgtool");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    // A plugin whose install "transaction" is observable as a file, so the
+    // test can prove whether the command ran.
+    std::fs::write(
+        config_dir.join("config.toml"),
+        "[plugins.faketool]\ninstall = [\"touch\", \"{packages}\"]\nformat = \"tsv\"\n",
+    )
+    .unwrap();
+    let marker = home.join("installed-marker");
+    let marker_arg = marker.to_str().unwrap();
+
+    // Confirmation given (--yes), dry-run active: nothing may execute.
+    let dry = command_in(&home)
+        .args(["--manager", "faketool", "--dry-run", "--yes", "install", marker_arg])
+        .output()
+        .unwrap();
+    assert_eq!(dry.status.code(), Some(0), "{}", stderr(&dry));
+    assert!(!marker.exists(), "dry run executed the install command");
+    assert!(String::from_utf8_lossy(&dry.stdout).contains("would-install"));
+
+    // The same invocation without --dry-run does run it.
+    let wet = command_in(&home)
+        .args(["--manager", "faketool", "--yes", "install", marker_arg])
+        .output()
+        .unwrap();
+    assert_eq!(wet.status.code(), Some(0), "{}", stderr(&wet));
+    assert!(marker.exists(), "real install did not run the command");
+}
+
+#[test]
+fn confirmation_policy_never_needs_no_yes() {
+    let home = sandbox("confirm-never");
+    let config_dir = home.join("config").join("pkgtool");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), "confirm = \"never\"\n").unwrap();
+    // No --yes, JSON output: only acceptable because the policy already
+    // settled the question.
+    let output = pkgtool_in(&home, "", &["install", "--json", "htop"]);
+    assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+    let results: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is JSON");
+    assert_eq!(results[0]["status"], "installed");
+}
+
+#[test]
+fn destructive_only_policy_still_asks_before_removals() {
+    let home = sandbox("confirm-destructive");
+    let config_dir = home.join("config").join("pkgtool");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join("config.toml"), "confirm = \"destructive-only\"\n").unwrap();
+    // Installs pass without --yes...
+    let install = pkgtool_in(&home, "", &["install", "htop"]);
+    assert_eq!(install.status.code(), Some(0), "{}", stderr(&install));
+    assert!(String::from_utf8_lossy(&install.stdout).contains("installed: htop"));
+    // ...but a removal prompts; with stdin closed that reads as "no".
+    let remove = pkgtool_in(&home, "", &["remove", "htop"]);
+    assert!(String::from_utf8_lossy(&remove.stdout).contains("aborted"));
+}
+
+#[test]
+fn usage_errors_exit_two() {
+    let parse_error = pkgtool("usage-flag", "", &["install", "--no-such-flag", "htop"]);
+    assert_eq!(parse_error.status.code(), Some(2));
+    let json_without_yes = pkgtool("usage-json", "", &["install", "--json", "htop"]);
+    assert_eq!(json_without_yes.status.code(), Some(2));
+    assert!(stderr(&json_without_yes).contains("--yes"));
+}